@@ -1,11 +1,28 @@
-use tauri::State;
+use crate::core::agent_detection::{AgentRegistry, DetectedAgent, SharedAgentRegistry};
+use crate::core::app_context::{AppContext, Managed};
 
-use crate::core::agent_detection::{DetectedAgent, SharedAgentRegistry};
-
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn agents_list(
-    registry: State<'_, SharedAgentRegistry>,
+    app: AppContext,
+    registry: Managed<SharedAgentRegistry>,
+) -> std::result::Result<Vec<DetectedAgent>, String> {
+    let probe = crate::core::settings::auth_probe(&app);
+    let mut guard = registry.lock().expect("agent registry mutex poisoned");
+    guard.apply_auth(&probe);
+    Ok(guard.list())
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn agents_refresh(
+    app: AppContext,
+    registry: Managed<SharedAgentRegistry>,
 ) -> std::result::Result<Vec<DetectedAgent>, String> {
-    let guard = registry.lock().expect("agent registry mutex poisoned");
+    let settings = crate::core::settings::settings_get(&app).map_err(|e| format!("{e:#}"))?;
+    let overrides = crate::core::settings::agent_path_overrides(&settings);
+    // Probe outside the lock: `--version` calls can be slow.
+    let mut fresh = AgentRegistry::detect_with(&overrides);
+    fresh.apply_auth(&crate::core::settings::auth_probe(&app));
+    let mut guard = registry.lock().expect("agent registry mutex poisoned");
+    *guard = fresh;
     Ok(guard.list())
 }
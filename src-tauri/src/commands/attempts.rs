@@ -0,0 +1,73 @@
+use std::path::Path;
+
+use crate::core::app_context::{AppContext, Managed};
+use crate::core::attempts::{AttemptComparison, AttemptGroup, AttemptsStartArgs};
+use crate::core::review_store::ReviewItem;
+use crate::core::session_manager::SharedSessionManager;
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttemptsListArgs {
+    pub project_path: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttemptsGroupArgs {
+    pub project_path: String,
+    pub group_id: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttemptsPromoteArgs {
+    pub project_path: String,
+    pub group_id: String,
+    pub index: usize,
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn attempts_start(
+    app: AppContext,
+    sessions: Managed<SharedSessionManager>,
+    args: AttemptsStartArgs,
+) -> std::result::Result<AttemptGroup, String> {
+    crate::core::attempts::start_attempts(&sessions, &app, args).map_err(|e| format!("{e:#}"))
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn attempts_list(
+    app: AppContext,
+    args: AttemptsListArgs,
+) -> std::result::Result<Vec<AttemptGroup>, String> {
+    crate::core::attempts::attempt_group_list(&app, Path::new(&args.project_path))
+        .map_err(|e| format!("{e:#}"))
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn attempts_compare(
+    app: AppContext,
+    args: AttemptsGroupArgs,
+) -> std::result::Result<AttemptComparison, String> {
+    let group = crate::core::attempts::attempt_group_get(
+        &app,
+        Path::new(&args.project_path),
+        &args.group_id,
+    )
+    .map_err(|e| format!("{e:#}"))?;
+    crate::core::attempts::compare_attempts(&group, &app).map_err(|e| format!("{e:#}"))
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn attempts_promote(
+    app: AppContext,
+    args: AttemptsPromoteArgs,
+) -> std::result::Result<ReviewItem, String> {
+    crate::core::attempts::promote_attempt(
+        &app,
+        Path::new(&args.project_path),
+        &args.group_id,
+        args.index,
+    )
+    .map_err(|e| format!("{e:#}"))
+}
@@ -0,0 +1,11 @@
+use crate::core::app_context::AppContext;
+use crate::core::audit_log::{self, AuditEntry, AuditQueryArgs};
+
+/// Audit entries for a project (or the app-wide log), newest first.
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn audit_query(
+    app: AppContext,
+    args: AuditQueryArgs,
+) -> std::result::Result<Vec<AuditEntry>, String> {
+    audit_log::query(&app, &args).map_err(|e| format!("{e:#}"))
+}
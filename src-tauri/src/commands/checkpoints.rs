@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use crate::core::app_context::Managed;
+use crate::core::checkpoints::Checkpoint;
+use crate::core::git_manager::FileDiff;
+use crate::core::session_manager::SharedSessionManager;
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointListArgs {
+    pub session_id: usize,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointDiffArgs {
+    pub session_id: usize,
+    pub index: usize,
+    /// Another checkpoint to diff against; defaults to the current worktree.
+    #[serde(default)]
+    pub against: Option<usize>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointRestoreArgs {
+    pub session_id: usize,
+    pub index: usize,
+}
+
+/// The session's worktree and the key its checkpoints are stored under.
+fn session_dir(
+    sessions: &Managed<SharedSessionManager>,
+    session_id: usize,
+) -> std::result::Result<(PathBuf, String), String> {
+    let guard = sessions.lock().expect("session manager mutex poisoned");
+    let info = guard
+        .get_session_info(session_id)
+        .ok_or_else(|| format!("unknown session_id {session_id}"))?;
+    let dir = PathBuf::from(info.working_dir.unwrap_or(info.project_path));
+    Ok((dir, info.session_key))
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn checkpoint_list(
+    sessions: Managed<SharedSessionManager>,
+    args: CheckpointListArgs,
+) -> std::result::Result<Vec<Checkpoint>, String> {
+    let (dir, key) = session_dir(&sessions, args.session_id)?;
+    crate::core::checkpoints::list_checkpoints(&dir, &key).map_err(|e| format!("{e:#}"))
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn checkpoint_create(
+    sessions: Managed<SharedSessionManager>,
+    args: CheckpointListArgs,
+) -> std::result::Result<Option<Checkpoint>, String> {
+    let (dir, key) = session_dir(&sessions, args.session_id)?;
+    crate::core::checkpoints::create_checkpoint(&dir, &key).map_err(|e| format!("{e:#}"))
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn checkpoint_diff(
+    sessions: Managed<SharedSessionManager>,
+    args: CheckpointDiffArgs,
+) -> std::result::Result<Vec<FileDiff>, String> {
+    let (dir, key) = session_dir(&sessions, args.session_id)?;
+    crate::core::checkpoints::diff_checkpoint(&dir, &key, args.index, args.against)
+        .map_err(|e| format!("{e:#}"))
+}
+
+/// Returns the checkpoint taken of the pre-restore state, if it differed.
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn checkpoint_restore(
+    sessions: Managed<SharedSessionManager>,
+    args: CheckpointRestoreArgs,
+) -> std::result::Result<Option<Checkpoint>, String> {
+    let (dir, key) = session_dir(&sessions, args.session_id)?;
+    crate::core::checkpoints::restore_checkpoint(&dir, &key, args.index)
+        .map_err(|e| format!("{e:#}"))
+}
@@ -0,0 +1,318 @@
+use std::path::{Path, PathBuf};
+
+use crate::commands::localhost::LocalhostIdArgs;
+use crate::commands::persistence::SessionSnapshotSaveNamedArgs;
+use crate::commands::review::GitMergeArgs;
+use crate::core::app_context::{AppContext, Managed};
+use crate::core::audit_log::Caller;
+use crate::core::command_bar::{
+    self, BarCommand, CloseResult, CompletionResult, CompletionSources,
+};
+use crate::core::git_manager::{GitManager, MergeResult};
+use crate::core::localhost_runtime::{LocalhostSessionView, SharedLocalhostRuntime};
+use crate::core::merge_queue;
+use crate::core::persistence::{
+    project_session_config_get, project_session_config_set, SessionSnapshotMeta,
+};
+use crate::core::session_broadcast::{BroadcastArgs, BroadcastResult};
+use crate::core::session_manager::{
+    CreateSessionArgs, CreateSessionResponse, SharedSessionManager,
+};
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandCompleteArgs {
+    pub project_path: String,
+    pub input: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandExecuteArgs {
+    pub project_path: String,
+    pub input: String,
+    /// Recorded in snapshots saved with `/snapshot save`.
+    #[serde(default)]
+    pub orchestration_mode: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum CommandOutcome {
+    SessionCreated { session: CreateSessionResponse },
+    Sent { result: BroadcastResult },
+    Closed { result: CloseResult },
+    Merged { result: MergeResult },
+    SnapshotSaved { snapshot: SessionSnapshotMeta },
+    Snapshots { snapshots: Vec<SessionSnapshotMeta> },
+    Localhost { session: LocalhostSessionView },
+}
+
+/// Suggestions for the last word of `args.input`. Sources that fail to load (e.g. a non-git
+/// project has no branches) are just left out.
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn command_complete(
+    app: AppContext,
+    sessions: Managed<SharedSessionManager>,
+    localhost: Managed<SharedLocalhostRuntime>,
+    args: CommandCompleteArgs,
+) -> std::result::Result<CompletionResult, String> {
+    let project_path = PathBuf::from(&args.project_path);
+    let live: Vec<_> = sessions
+        .lock()
+        .expect("session manager mutex poisoned")
+        .list_sessions()
+        .into_iter()
+        .filter(|s| s.project_path == args.project_path)
+        .collect();
+    let sources = CompletionSources {
+        branches: GitManager::for_project(&app, &project_path)
+            .and_then(|gm| gm.list_branches())
+            .unwrap_or_default(),
+        targets: command_bar::session_targets(&live),
+        snapshots: crate::core::persistence::session_snapshot_list(&app, Some(&project_path))
+            .map(|list| {
+                list.into_iter()
+                    .filter(|m| m.kind == "named")
+                    .map(|m| m.name)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        localhost: localhost
+            .lock()
+            .ok()
+            .and_then(|rt| rt.list(&app, &project_path).ok())
+            .map(|list| {
+                list.into_iter()
+                    .map(|v| (v.spec.id, v.spec.source_label))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+    Ok(command_bar::complete(&args.input, &sources))
+}
+
+/// Parses one command-bar line and runs it through the same code paths as the UI. Targets
+/// only match sessions in `args.project_path`.
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn command_execute(
+    app: AppContext,
+    caller: Caller,
+    sessions: Managed<SharedSessionManager>,
+    args: CommandExecuteArgs,
+) -> std::result::Result<CommandOutcome, String> {
+    let command = command_bar::parse(&args.input).map_err(|e| format!("{e:#}"))?;
+    let project_path = args.project_path.clone();
+
+    match command {
+        BarCommand::New { agent, branch } => {
+            let worktree = match branch {
+                Some(branch) => Some(
+                    NewWorktree::create(&app, &project_path, &branch)
+                        .map_err(|e| format!("{e:#}"))?,
+                ),
+                None => None,
+            };
+            let created = sessions
+                .lock()
+                .expect("session manager mutex poisoned")
+                .create_session(
+                    app.clone(),
+                    CreateSessionArgs {
+                        agent_type: agent,
+                        project_path: project_path.clone(),
+                        branch: worktree.as_ref().map(|w| w.branch.clone()),
+                        working_dir: worktree
+                            .as_ref()
+                            .map(|w| w.path.to_string_lossy().to_string()),
+                        model: None,
+                        codex_provider: None,
+                        provider_profile: None,
+                        env: None,
+                        labels: Default::default(),
+                        initial_prompt: None,
+                    },
+                );
+            let session = match created {
+                Ok(session) => session,
+                Err(err) => {
+                    if let Some(w) = &worktree {
+                        w.discard();
+                    }
+                    return Err(format!("{err:#}"));
+                }
+            };
+            if let Some(w) = worktree {
+                remember_worktree_pane(&project_path, session.pane_index, Some(w.branch));
+            }
+            Ok(CommandOutcome::SessionCreated { session })
+        }
+        BarCommand::Send { target, text } => {
+            let result = super::session::session_broadcast(
+                app.clone(),
+                caller.clone(),
+                sessions,
+                BroadcastArgs {
+                    target,
+                    data: format!("{text}\r"),
+                    project_path: Some(project_path),
+                },
+            )?;
+            Ok(CommandOutcome::Sent { result })
+        }
+        BarCommand::Close { target } => {
+            let mut guard = sessions.lock().expect("session manager mutex poisoned");
+            let ids = guard
+                .resolve_targets(&target, Some(&project_path))
+                .map_err(|e| format!("{e:#}"))?;
+            let result = command_bar::close_each(ids, |id| guard.destroy_session(app.clone(), id));
+            Ok(CommandOutcome::Closed { result })
+        }
+        BarCommand::Merge {
+            branch,
+            strategy,
+            base,
+        } => {
+            let strategy = match strategy {
+                Some(s) => s,
+                None => merge_queue::default_strategy(&app),
+            };
+            let base_branch = match base {
+                Some(b) => b,
+                None => GitManager::for_project(&app, Path::new(&project_path))
+                    .and_then(|gm| gm.default_base_branch())
+                    .map_err(|e| format!("{e:#}"))?,
+            };
+            let result = super::review::git_merge(
+                app.clone(),
+                caller.clone(),
+                sessions,
+                GitMergeArgs {
+                    project_path,
+                    branch,
+                    base_branch,
+                    strategy,
+                    interactive: None,
+                },
+            )?;
+            Ok(CommandOutcome::Merged { result })
+        }
+        BarCommand::SnapshotSave { name } => {
+            let snapshot = super::persistence::session_snapshot_save_named(
+                app.clone(),
+                sessions,
+                SessionSnapshotSaveNamedArgs {
+                    project_path,
+                    name,
+                    orchestration_mode: args
+                        .orchestration_mode
+                        .unwrap_or_else(|| "manual".to_string()),
+                },
+            )?;
+            Ok(CommandOutcome::SnapshotSaved { snapshot })
+        }
+        BarCommand::SnapshotList => {
+            let snapshots = crate::core::persistence::session_snapshot_list(
+                &app,
+                Some(Path::new(&project_path)),
+            )
+            .map_err(|e| format!("{e:#}"))?;
+            Ok(CommandOutcome::Snapshots { snapshots })
+        }
+        BarCommand::LocalhostStart { spec } => {
+            let id = localhost_spec_id(&app, &project_path, &spec)?;
+            let runtime = app.state::<SharedLocalhostRuntime>();
+            let session = super::localhost::localhost_session_start(
+                app.clone(),
+                runtime,
+                LocalhostIdArgs { project_path, id },
+            )?;
+            Ok(CommandOutcome::Localhost { session })
+        }
+        BarCommand::LocalhostStop { spec } => {
+            let id = localhost_spec_id(&app, &project_path, &spec)?;
+            let runtime = app.state::<SharedLocalhostRuntime>();
+            let session = super::localhost::localhost_session_stop(
+                app.clone(),
+                runtime,
+                LocalhostIdArgs { project_path, id },
+            )?;
+            Ok(CommandOutcome::Localhost { session })
+        }
+    }
+}
+
+/// Worktree checked out for `/new @branch`.
+struct NewWorktree {
+    gm: GitManager,
+    path: PathBuf,
+    branch: String,
+    /// Neither the branch nor its worktree existed before, so both are ours to remove.
+    fresh: bool,
+}
+
+impl NewWorktree {
+    fn create(app: &AppContext, project_path: &str, branch: &str) -> anyhow::Result<Self> {
+        let gm = GitManager::for_project(app, Path::new(project_path))?;
+        let base = gm.default_base_branch()?;
+        let branch = gm.normalize_branch(branch)?;
+        let fresh = gm.unused_branch_name(&branch)? == branch;
+        let (path, branch) = gm.create_worktree(&branch, &base)?;
+        Ok(Self {
+            gm,
+            path,
+            branch,
+            fresh,
+        })
+    }
+
+    /// Undoes `create` when the session didn't start; an existing branch is left alone.
+    fn discard(&self) {
+        if self.fresh {
+            let _ = self.gm.remove_worktree(&self.branch);
+        }
+    }
+}
+
+/// Accepts a localhost spec id or its label (case-insensitive).
+fn localhost_spec_id(
+    app: &AppContext,
+    project_path: &str,
+    spec: &str,
+) -> std::result::Result<String, String> {
+    let runtime = app.state::<SharedLocalhostRuntime>();
+    let list = runtime
+        .lock()
+        .map_err(|_| "mutex poisoned".to_string())?
+        .list(app, Path::new(project_path))
+        .map_err(|e| format!("{e:#}"))?;
+    list.iter()
+        .find(|v| v.spec.id == spec)
+        .or_else(|| {
+            list.iter()
+                .find(|v| v.spec.source_label.eq_ignore_ascii_case(spec))
+        })
+        .map(|v| v.spec.id.clone())
+        .ok_or_else(|| format!("no localhost session {spec:?} in this project"))
+}
+
+/// Records the pane's branch so the session config panel shows it as worktree-isolated.
+fn remember_worktree_pane(project_path: &str, pane_index: usize, branch: Option<String>) {
+    let path = Path::new(project_path);
+    let mut cfg = match project_session_config_get(path, pane_index) {
+        Ok(cfg) => cfg.unwrap_or_default(),
+        Err(err) => {
+            eprintln!("command_execute: read pane config: {err:#}");
+            return;
+        }
+    };
+    cfg.branch = branch;
+    cfg.worktree_isolation = Some(true);
+    if let Err(err) = project_session_config_set(path, pane_index, cfg.into()) {
+        eprintln!("command_execute: save pane config: {err:#}");
+    }
+}
@@ -0,0 +1,15 @@
+use crate::core::app_context::Managed;
+use crate::core::event_bus::{EventsSinceArgs, EventsSinceResult, SharedEventBus};
+
+/// Buffered events after `seq`, for views that mount after the events were published.
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn events_since(
+    bus: Managed<SharedEventBus>,
+    args: EventsSinceArgs,
+) -> std::result::Result<EventsSinceResult, String> {
+    let guard = bus
+        .inner()
+        .lock()
+        .map_err(|_| "mutex poisoned".to_string())?;
+    Ok(guard.since(&args))
+}
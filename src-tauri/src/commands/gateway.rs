@@ -0,0 +1,56 @@
+use crate::core::app_context::Managed;
+use crate::core::llm_gateway::{GatewaySessionUsage, GatewayStatus, SharedLlmGateway};
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayUsageArgs {
+    pub session_id: Option<usize>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayRateCapArgs {
+    pub session_id: usize,
+    /// `None` = fall back to the settings default, `Some(0)` = unlimited.
+    pub max_requests_per_minute: Option<u32>,
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn gateway_status(
+    gateway: Managed<SharedLlmGateway>,
+) -> std::result::Result<GatewayStatus, String> {
+    let guard = gateway
+        .inner()
+        .lock()
+        .map_err(|_| "mutex poisoned".to_string())?;
+    Ok(guard.status())
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn gateway_usage(
+    gateway: Managed<SharedLlmGateway>,
+    args: GatewayUsageArgs,
+) -> std::result::Result<Vec<GatewaySessionUsage>, String> {
+    let guard = gateway
+        .inner()
+        .lock()
+        .map_err(|_| "mutex poisoned".to_string())?;
+    Ok(match args.session_id {
+        Some(id) => guard.usage(id).into_iter().collect(),
+        None => guard.usage_all(),
+    })
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn gateway_set_rate_cap(
+    gateway: Managed<SharedLlmGateway>,
+    args: GatewayRateCapArgs,
+) -> std::result::Result<GatewaySessionUsage, String> {
+    let guard = gateway
+        .inner()
+        .lock()
+        .map_err(|_| "mutex poisoned".to_string())?;
+    guard
+        .set_session_rate_cap(args.session_id, args.max_requests_per_minute)
+        .map_err(|e| format!("{e:#}"))
+}
@@ -2,11 +2,13 @@ use std::collections::HashSet;
 use std::path::PathBuf;
 use std::time::Duration;
 
-use tauri::State;
+use serde_json::json;
 
+use crate::core::app_context::{AppContext, Managed};
+use crate::core::audit_log::{self, Caller};
+use crate::core::commit_graph::{CommitFileChange, CommitLogPage, CommitRefs};
 use crate::core::git_manager::{GitManager, OrphanWorktree, WorktreeInfo};
 use crate::core::session_manager::SharedSessionManager;
-use crate::core::settings as core_settings;
 
 #[derive(Debug, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -58,6 +60,8 @@ pub struct GitDetectOrphansArgs {
 pub struct GitCleanupOrphansResponse {
     pub removed: Vec<String>,
     pub failed: Vec<String>,
+    /// Branches of the removed worktrees, deleted along with them.
+    pub deleted_branches: Vec<String>,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -75,17 +79,33 @@ pub struct GitEnsureWorktreeArgs {
     pub base_branch: Option<String>,
 }
 
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitCommitLogArgs {
+    pub project_path: String,
+    /// Every branch, remote and tag when omitted.
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// `nextCursor` from the previous page.
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitCommitArgs {
+    pub project_path: String,
+    /// Commit hash or any revision git understands.
+    pub commit: String,
+}
+
 fn make_manager(
-    app: &tauri::AppHandle,
+    app: &AppContext,
     project_path: PathBuf,
 ) -> std::result::Result<GitManager, String> {
-    let settings = core_settings::settings_get(app).map_err(|e| format!("{e:#}"))?;
-    GitManager::new(
-        project_path,
-        &settings.git.worktree_base_path,
-        &settings.git.branch_prefix,
-    )
-    .map_err(|e| format!("{e:#}"))
+    GitManager::for_project(app, &project_path).map_err(|e| format!("{e:#}"))
 }
 
 fn active_worktrees_for_project(
@@ -107,10 +127,10 @@ fn active_worktrees_for_project(
     out
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn git_create_worktree(
-    app: tauri::AppHandle,
-    sessions: State<'_, SharedSessionManager>,
+    app: AppContext,
+    sessions: Managed<SharedSessionManager>,
     args: GitCreateWorktreeArgs,
 ) -> std::result::Result<GitCreateWorktreeResponse, String> {
     let mut guard = sessions.lock().expect("session manager mutex poisoned");
@@ -148,9 +168,9 @@ pub fn git_create_worktree(
     })
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn git_ensure_worktree(
-    app: tauri::AppHandle,
+    app: AppContext,
     args: GitEnsureWorktreeArgs,
 ) -> std::result::Result<GitCreateWorktreeResponse, String> {
     let gm = make_manager(&app, PathBuf::from(&args.project_path))?;
@@ -167,10 +187,11 @@ pub fn git_ensure_worktree(
     })
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn git_remove_worktree(
-    app: tauri::AppHandle,
-    sessions: State<'_, SharedSessionManager>,
+    app: AppContext,
+    caller: Caller,
+    sessions: Managed<SharedSessionManager>,
     args: GitRemoveWorktreeArgs,
 ) -> std::result::Result<GitRemoveWorktreeResponse, String> {
     let mut guard = sessions.lock().expect("session manager mutex poisoned");
@@ -185,7 +206,16 @@ pub fn git_remove_worktree(
         .ok_or_else(|| "session has no branch".to_string())?;
     let project_path = PathBuf::from(&info.project_path);
     let gm = make_manager(&app, project_path.clone())?;
-    gm.remove_worktree(&branch).map_err(|e| format!("{e:#}"))?;
+    let removed = gm.remove_worktree(&branch).map_err(|e| format!("{e:#}"));
+    audit_log::record(
+        &app,
+        Some(&info.project_path),
+        &caller,
+        "git_remove_worktree",
+        json!({ "sessionId": args.session_id, "branch": branch }),
+        &removed,
+    );
+    removed?;
 
     // Only auto-move the *current session* back to root when removing its *current branch*,
     // and only for plain terminal sessions. Agent CLIs treat `cd` as input, not shell.
@@ -207,28 +237,29 @@ pub fn git_remove_worktree(
 }
 
 // Alias to match the (older) command name in the spec reference.
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn git_delete_worktree(
-    app: tauri::AppHandle,
-    sessions: State<'_, SharedSessionManager>,
+    app: AppContext,
+    caller: Caller,
+    sessions: Managed<SharedSessionManager>,
     args: GitRemoveWorktreeArgs,
 ) -> std::result::Result<GitRemoveWorktreeResponse, String> {
-    git_remove_worktree(app, sessions, args)
+    git_remove_worktree(app, caller, sessions, args)
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn git_list_worktrees(
-    app: tauri::AppHandle,
+    app: AppContext,
     args: GitListWorktreesArgs,
 ) -> std::result::Result<Vec<WorktreeInfo>, String> {
     let gm = make_manager(&app, PathBuf::from(args.project_path))?;
     gm.list_worktrees().map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn git_detect_orphans(
-    app: tauri::AppHandle,
-    sessions: State<'_, SharedSessionManager>,
+    app: AppContext,
+    sessions: Managed<SharedSessionManager>,
     args: GitDetectOrphansArgs,
 ) -> std::result::Result<Vec<OrphanWorktree>, String> {
     let project_path = PathBuf::from(&args.project_path);
@@ -243,10 +274,11 @@ pub fn git_detect_orphans(
         .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn git_cleanup_orphans(
-    app: tauri::AppHandle,
-    sessions: State<'_, SharedSessionManager>,
+    app: AppContext,
+    caller: Caller,
+    sessions: Managed<SharedSessionManager>,
     args: GitDetectOrphansArgs,
 ) -> std::result::Result<GitCleanupOrphansResponse, String> {
     let project_path = PathBuf::from(&args.project_path);
@@ -263,22 +295,68 @@ pub fn git_cleanup_orphans(
 
     let mut removed = Vec::new();
     let mut failed = Vec::new();
+    let mut deleted_branches = Vec::new();
     for o in orphans {
         let p = o.info.path.clone();
         match gm.cleanup_orphan(&o) {
-            Ok(()) => removed.push(p),
+            Ok(()) => {
+                removed.push(p);
+                deleted_branches.extend(o.info.branch.clone());
+            }
             Err(_) => failed.push(p),
         }
     }
 
-    Ok(GitCleanupOrphansResponse { removed, failed })
+    let res = Ok(GitCleanupOrphansResponse {
+        removed,
+        failed,
+        deleted_branches,
+    });
+    audit_log::record(
+        &app,
+        Some(&args.project_path),
+        &caller,
+        "git_cleanup_orphans",
+        json!({ "projectPath": args.project_path, "minAgeSeconds": min_age.as_secs() }),
+        &res,
+    );
+    res
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn git_branches(
-    app: tauri::AppHandle,
+    app: AppContext,
     args: GitBranchesArgs,
 ) -> std::result::Result<Vec<String>, String> {
     let gm = make_manager(&app, PathBuf::from(args.project_path))?;
     gm.list_branches().map_err(|e| format!("{e:#}"))
 }
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn git_commit_log(
+    app: AppContext,
+    args: GitCommitLogArgs,
+) -> std::result::Result<CommitLogPage, String> {
+    let gm = make_manager(&app, PathBuf::from(args.project_path))?;
+    gm.commit_log(args.branch.as_deref(), args.cursor.as_deref(), args.limit)
+        .map_err(|e| format!("{e:#}"))
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn git_commit_files(
+    app: AppContext,
+    args: GitCommitArgs,
+) -> std::result::Result<Vec<CommitFileChange>, String> {
+    let gm = make_manager(&app, PathBuf::from(args.project_path))?;
+    gm.commit_files(&args.commit).map_err(|e| format!("{e:#}"))
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn git_refs_for_commit(
+    app: AppContext,
+    args: GitCommitArgs,
+) -> std::result::Result<CommitRefs, String> {
+    let gm = make_manager(&app, PathBuf::from(args.project_path))?;
+    gm.refs_for_commit(&args.commit)
+        .map_err(|e| format!("{e:#}"))
+}
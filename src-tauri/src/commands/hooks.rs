@@ -0,0 +1,26 @@
+use crate::core::app_context::AppContext;
+use crate::core::hooks::{self, HookDeliveriesArgs, HookDelivery};
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HooksTestArgs {
+    pub hook_id: String,
+}
+
+/// Logged hook deliveries, newest first.
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn hooks_deliveries(
+    app: AppContext,
+    args: HookDeliveriesArgs,
+) -> std::result::Result<Vec<HookDelivery>, String> {
+    hooks::deliveries(&app, &args).map_err(|e| format!("{e:#}"))
+}
+
+/// Sends a test payload to a saved hook once. A failed delivery is returned, not an error.
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn hooks_test(
+    app: AppContext,
+    args: HooksTestArgs,
+) -> std::result::Result<HookDelivery, String> {
+    hooks::send_test(&app, &args.hook_id).map_err(|e| format!("{e:#}"))
+}
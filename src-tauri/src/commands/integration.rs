@@ -0,0 +1,161 @@
+use std::path::Path;
+
+use serde_json::json;
+
+use crate::core::app_context::{AppContext, Managed};
+use crate::core::audit_log::{self, Caller};
+use crate::core::git_events::publish_merge_event;
+use crate::core::git_manager::{GitManager, MergeResult};
+use crate::core::integration::{self, Integration, IntegrationDetail, Resolution};
+use crate::core::session_manager::SharedSessionManager;
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrationListArgs {
+    pub project_path: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrationGetArgs {
+    pub project_path: String,
+    pub id: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrationResolveArgs {
+    pub project_path: String,
+    pub id: String,
+    pub path: String,
+    /// Resolve a single conflict hunk; the whole file otherwise.
+    #[serde(default)]
+    pub hunk: Option<usize>,
+    pub resolution: Resolution,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrationFinishArgs {
+    pub project_path: String,
+    pub id: String,
+}
+
+fn manager(app: &AppContext, project_path: &str) -> std::result::Result<GitManager, String> {
+    GitManager::for_project(app, Path::new(project_path)).map_err(|e| format!("{e:#}"))
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn integration_list(
+    app: AppContext,
+    args: IntegrationListArgs,
+) -> std::result::Result<Vec<Integration>, String> {
+    let gm = manager(&app, &args.project_path)?;
+    integration::list(&gm).map_err(|e| format!("{e:#}"))
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn integration_get(
+    app: AppContext,
+    args: IntegrationGetArgs,
+) -> std::result::Result<IntegrationDetail, String> {
+    let gm = manager(&app, &args.project_path)?;
+    integration::detail(&gm, &args.id).map_err(|e| format!("{e:#}"))
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn integration_resolve(
+    app: AppContext,
+    caller: Caller,
+    args: IntegrationResolveArgs,
+) -> std::result::Result<IntegrationDetail, String> {
+    let gm = manager(&app, &args.project_path)?;
+    let res = integration::resolve(&gm, &args.id, &args.path, args.hunk, &args.resolution)
+        .map_err(|e| format!("{e:#}"));
+    let resolution = match &args.resolution {
+        Resolution::Ours => "ours",
+        Resolution::Theirs => "theirs",
+        Resolution::Both => "both",
+        Resolution::Edited { .. } => "edited",
+    };
+    audit_log::record(
+        &app,
+        Some(&args.project_path),
+        &caller,
+        "integration_resolve",
+        json!({
+            "projectPath": args.project_path,
+            "id": args.id,
+            "path": args.path,
+            "hunk": args.hunk,
+            "resolution": resolution,
+        }),
+        &res.as_ref().map(|_| ()).map_err(String::clone),
+    );
+    res
+}
+
+/// Commits the resolved merge and moves the base branch to it. Returns the new base commit.
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn integration_continue(
+    app: AppContext,
+    caller: Caller,
+    sessions: Managed<SharedSessionManager>,
+    args: IntegrationFinishArgs,
+) -> std::result::Result<String, String> {
+    let gm = manager(&app, &args.project_path)?;
+    let it = integration::load(&gm, &args.id).map_err(|e| format!("{e:#}"));
+    let res = it.clone().and_then(|it| {
+        let hash = integration::finish(&gm, &it.id).map_err(|e| format!("{e:#}"))?;
+        publish_merge_event(
+            &app,
+            &sessions,
+            &args.project_path,
+            &it.branch,
+            &it.base_branch,
+            it.strategy,
+            &MergeResult {
+                success: true,
+                conflict_files: None,
+                integration: None,
+            },
+        );
+        Ok(hash)
+    });
+    audit_log::record(
+        &app,
+        Some(&args.project_path),
+        &caller,
+        "integration_continue",
+        json!({
+            "projectPath": args.project_path,
+            "id": args.id,
+            "branch": it.as_ref().ok().map(|it| &it.branch),
+            "baseBranch": it.as_ref().ok().map(|it| &it.base_branch),
+        }),
+        &res,
+    );
+    res
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn integration_abort(
+    app: AppContext,
+    caller: Caller,
+    args: IntegrationFinishArgs,
+) -> std::result::Result<(), String> {
+    let gm = manager(&app, &args.project_path)?;
+    let res = integration::abort(&gm, &args.id).map_err(|e| format!("{e:#}"));
+    audit_log::record(
+        &app,
+        Some(&args.project_path),
+        &caller,
+        "integration_abort",
+        json!({
+            "projectPath": args.project_path,
+            "id": args.id,
+        }),
+        &res,
+    );
+    res
+}
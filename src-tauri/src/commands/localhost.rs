@@ -1,5 +1,6 @@
 use std::path::Path;
 
+use crate::core::app_context::{AppContext, Managed};
 use crate::core::localhost_runtime::{
     LocalhostPortMode, LocalhostRuntime, LocalhostSessionSpec, LocalhostSessionType,
     LocalhostSessionView, SharedLocalhostRuntime,
@@ -60,10 +61,10 @@ impl From<LocalhostSessionSpecInput> for LocalhostSessionSpec {
     }
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn localhost_session_list(
-    app: tauri::AppHandle,
-    runtime: tauri::State<'_, SharedLocalhostRuntime>,
+    app: AppContext,
+    runtime: Managed<SharedLocalhostRuntime>,
     args: LocalhostListArgs,
 ) -> std::result::Result<Vec<LocalhostSessionView>, String> {
     let project_path = Path::new(&args.project_path);
@@ -74,10 +75,10 @@ pub fn localhost_session_list(
     guard.list(&app, project_path).map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn localhost_session_upsert(
-    app: tauri::AppHandle,
-    runtime: tauri::State<'_, SharedLocalhostRuntime>,
+    app: AppContext,
+    runtime: Managed<SharedLocalhostRuntime>,
     args: LocalhostUpsertArgs,
 ) -> std::result::Result<Vec<LocalhostSessionView>, String> {
     let mut spec: LocalhostSessionSpec = args.spec.into();
@@ -100,10 +101,10 @@ pub fn localhost_session_upsert(
         .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn localhost_session_delete(
-    app: tauri::AppHandle,
-    runtime: tauri::State<'_, SharedLocalhostRuntime>,
+    app: AppContext,
+    runtime: Managed<SharedLocalhostRuntime>,
     args: LocalhostDeleteArgs,
 ) -> std::result::Result<Vec<LocalhostSessionView>, String> {
     let project_path = Path::new(&args.project_path);
@@ -121,10 +122,10 @@ pub fn localhost_session_delete(
         .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn localhost_session_start(
-    app: tauri::AppHandle,
-    runtime: tauri::State<'_, SharedLocalhostRuntime>,
+    app: AppContext,
+    runtime: Managed<SharedLocalhostRuntime>,
     args: LocalhostIdArgs,
 ) -> std::result::Result<LocalhostSessionView, String> {
     let project_path = Path::new(&args.project_path);
@@ -145,10 +146,10 @@ pub fn localhost_session_start(
         .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn localhost_session_stop(
-    app: tauri::AppHandle,
-    runtime: tauri::State<'_, SharedLocalhostRuntime>,
+    app: AppContext,
+    runtime: Managed<SharedLocalhostRuntime>,
     args: LocalhostIdArgs,
 ) -> std::result::Result<LocalhostSessionView, String> {
     let project_path = Path::new(&args.project_path);
@@ -186,10 +187,10 @@ pub fn localhost_session_stop(
     })
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn localhost_session_restart(
-    app: tauri::AppHandle,
-    runtime: tauri::State<'_, SharedLocalhostRuntime>,
+    app: AppContext,
+    runtime: Managed<SharedLocalhostRuntime>,
     args: LocalhostIdArgs,
 ) -> std::result::Result<LocalhostSessionView, String> {
     // Stop is best-effort.
@@ -203,10 +204,10 @@ pub fn localhost_session_restart(
     localhost_session_start(app, runtime, args)
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn localhost_session_logs(
-    _app: tauri::AppHandle,
-    runtime: tauri::State<'_, SharedLocalhostRuntime>,
+    _app: AppContext,
+    runtime: Managed<SharedLocalhostRuntime>,
     args: LocalhostIdArgs,
 ) -> std::result::Result<Vec<String>, String> {
     let guard = runtime
@@ -1,10 +1,13 @@
 use std::path::PathBuf;
 
-use tauri::State;
+use serde_json::json;
 
 use crate::core::agent_detection::AgentType;
+use crate::core::app_context::{AppContext, Managed};
+use crate::core::audit_log::{self, Caller};
 use crate::core::mcp_discovery::{self, McpDiscoveryResult};
 use crate::core::mcp_server::SharedMcpRuntime;
+use crate::core::secrets::{self, SecretStore};
 
 #[derive(Debug, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -25,9 +28,9 @@ pub struct McpSetEnabledArgs {
     pub agent_type: Option<AgentType>,
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn mcp_discover(
-    runtime: State<'_, SharedMcpRuntime>,
+    runtime: Managed<SharedMcpRuntime>,
     args: McpDiscoverArgs,
 ) -> std::result::Result<McpDiscoveryResult, String> {
     let project_path = args
@@ -51,10 +54,38 @@ pub fn mcp_discover(
     Ok(out)
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn mcp_set_enabled(
-    runtime: State<'_, SharedMcpRuntime>,
+    app: AppContext,
+    caller: Caller,
+    runtime: Managed<SharedMcpRuntime>,
     args: McpSetEnabledArgs,
+) -> std::result::Result<(), String> {
+    let res = set_enabled(&app, &runtime, &args);
+    let project_path = args
+        .project_path
+        .as_deref()
+        .filter(|s| !s.trim().is_empty());
+    audit_log::record(
+        &app,
+        project_path,
+        &caller,
+        "mcp_set_enabled",
+        json!({
+            "projectPath": project_path,
+            "name": args.name,
+            "enabled": args.enabled,
+            "scope": args.scope,
+        }),
+        &res,
+    );
+    res
+}
+
+fn set_enabled(
+    app: &AppContext,
+    runtime: &SharedMcpRuntime,
+    args: &McpSetEnabledArgs,
 ) -> std::result::Result<(), String> {
     let agent_type = args.agent_type.unwrap_or(AgentType::ClaudeCode);
     if agent_type != AgentType::ClaudeCode {
@@ -91,9 +122,17 @@ pub fn mcp_set_enabled(
             ));
         };
 
+        // Config files may reference the secrets store instead of holding values inline.
+        let env = if secrets::has_refs(&info.env) {
+            let store = SecretStore::open(app).map_err(|e| format!("{e:#}"))?;
+            secrets::resolve_refs(&store, &info.env).map_err(|e| format!("{e:#}"))?
+        } else {
+            info.env.clone()
+        };
+
         let mut guard = runtime.lock().expect("mcp runtime mutex poisoned");
         guard
-            .start_server(&args.name, cmd, &info.args, &info.env)
+            .start_server(&args.name, cmd, &info.args, &env)
             .map_err(|e| format!("{e:#}"))?;
     } else {
         // If we didn't start it, fall back to best-effort stop by discovered pid.
@@ -0,0 +1,130 @@
+use std::path::Path;
+
+use serde_json::json;
+
+use crate::core::app_context::{AppContext, Managed};
+use crate::core::audit_log::{self, Caller};
+use crate::core::merge_queue::{self, MergeQueue, SharedMergeQueues};
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeQueueGetArgs {
+    pub project_path: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeQueueEntryArgs {
+    pub project_path: String,
+    pub review_id: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeQueuePauseArgs {
+    pub project_path: String,
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn merge_queue_get(
+    app: AppContext,
+    queues: Managed<SharedMergeQueues>,
+    args: MergeQueueGetArgs,
+) -> std::result::Result<MergeQueue, String> {
+    merge_queue::get(&queues, &app, Path::new(&args.project_path)).map_err(|e| format!("{e:#}"))
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn merge_queue_enqueue(
+    app: AppContext,
+    caller: Caller,
+    queues: Managed<SharedMergeQueues>,
+    args: MergeQueueEntryArgs,
+) -> std::result::Result<MergeQueue, String> {
+    let res = merge_queue::enqueue(
+        &queues,
+        &app,
+        Path::new(&args.project_path),
+        &args.review_id,
+    )
+    .map_err(|e| format!("{e:#}"));
+    audit_log::record(
+        &app,
+        Some(&args.project_path),
+        &caller,
+        "merge_queue_enqueue",
+        json!({ "projectPath": args.project_path, "reviewId": args.review_id }),
+        &res,
+    );
+    res
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn merge_queue_remove(
+    app: AppContext,
+    caller: Caller,
+    queues: Managed<SharedMergeQueues>,
+    args: MergeQueueEntryArgs,
+) -> std::result::Result<MergeQueue, String> {
+    let res = merge_queue::remove(
+        &queues,
+        &app,
+        Path::new(&args.project_path),
+        &args.review_id,
+    )
+    .map_err(|e| format!("{e:#}"));
+    audit_log::record(
+        &app,
+        Some(&args.project_path),
+        &caller,
+        "merge_queue_remove",
+        json!({ "projectPath": args.project_path, "reviewId": args.review_id }),
+        &res,
+    );
+    res
+}
+
+fn set_paused(
+    app: &AppContext,
+    caller: &Caller,
+    queues: &SharedMergeQueues,
+    args: &MergeQueuePauseArgs,
+    paused: bool,
+) -> std::result::Result<MergeQueue, String> {
+    let res = merge_queue::set_paused(queues, app, Path::new(&args.project_path), paused)
+        .map_err(|e| format!("{e:#}"));
+    audit_log::record(
+        app,
+        Some(&args.project_path),
+        caller,
+        if paused {
+            "merge_queue_pause"
+        } else {
+            "merge_queue_resume"
+        },
+        json!({ "projectPath": args.project_path }),
+        &res,
+    );
+    res
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn merge_queue_pause(
+    app: AppContext,
+    caller: Caller,
+    queues: Managed<SharedMergeQueues>,
+    args: MergeQueuePauseArgs,
+) -> std::result::Result<MergeQueue, String> {
+    set_paused(&app, &caller, &queues, &args, true)
+}
+
+/// Clears the last failure and picks up the next entry.
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn merge_queue_resume(
+    app: AppContext,
+    caller: Caller,
+    queues: Managed<SharedMergeQueues>,
+    args: MergeQueuePauseArgs,
+) -> std::result::Result<MergeQueue, String> {
+    set_paused(&app, &caller, &queues, &args, false)
+}
@@ -3,12 +3,24 @@ pub mod session;
 // Placeholders for future phases (kept to match documented file structure).
 pub mod agents;
 pub mod ai_provider;
+pub mod attempts;
+pub mod audit;
+pub mod checkpoints;
+pub mod command_bar;
+pub mod events;
+pub mod gateway;
 pub mod git;
+pub mod hooks;
+pub mod integration;
 pub mod localhost;
 pub mod mcp;
+pub mod merge_queue;
+pub mod notifications;
 pub mod onboarding;
 pub mod orchestrator;
 pub mod persistence;
+pub mod remote;
 pub mod review;
+pub mod secrets;
 pub mod settings;
 pub mod skills;
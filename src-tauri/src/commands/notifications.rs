@@ -0,0 +1,38 @@
+use crate::core::app_context::Managed;
+use crate::core::notifications::{
+    NotificationList, NotificationListArgs, SharedNotificationCenter,
+};
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationsMarkReadArgs {
+    /// Every notification when omitted.
+    #[serde(default)]
+    pub ids: Option<Vec<u64>>,
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn notifications_list(
+    center: Managed<SharedNotificationCenter>,
+    args: NotificationListArgs,
+) -> std::result::Result<NotificationList, String> {
+    let guard = center.lock().expect("notification mutex poisoned");
+    Ok(guard.list(&args))
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn notifications_mark_read(
+    center: Managed<SharedNotificationCenter>,
+    args: NotificationsMarkReadArgs,
+) -> std::result::Result<usize, String> {
+    let mut guard = center.lock().expect("notification mutex poisoned");
+    Ok(guard.mark_read(args.ids.as_deref()))
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn notifications_clear(
+    center: Managed<SharedNotificationCenter>,
+) -> std::result::Result<(), String> {
+    center.lock().expect("notification mutex poisoned").clear();
+    Ok(())
+}
@@ -4,18 +4,11 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use serde::Serialize;
-use tauri::path::BaseDirectory;
-use tauri::Manager;
 
 use crate::core::agent_detection::{AgentRegistry, DetectedAgent};
+use crate::core::app_context::AppContext;
 use crate::core::settings::SettingsView;
 
-fn config_dir(app: &tauri::AppHandle) -> anyhow::Result<PathBuf> {
-    app.path()
-        .resolve("synk", BaseDirectory::Config)
-        .map_err(|e| anyhow::anyhow!("{e}"))
-}
-
 fn ensure_dir(path: &Path) -> anyhow::Result<()> {
     fs::create_dir_all(path).map_err(|e| anyhow::anyhow!("create dir {}: {e}", path.display()))?;
     Ok(())
@@ -74,21 +67,21 @@ pub struct OnboardingScanResult {
     pub gastown_workspace_found: bool,
 }
 
-#[tauri::command]
-pub fn onboarding_is_first_run(app: tauri::AppHandle) -> std::result::Result<bool, String> {
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn onboarding_is_first_run(app: AppContext) -> std::result::Result<bool, String> {
     // Note: Some environments may create the config directory as a side effect of path resolution
     // or other early calls (e.g. "list recent projects"). Treat "first run" as "no sentinel
     // files exist yet", which matches the intent of "never launched / not initialized".
-    let dir = config_dir(&app).map_err(|e| format!("{e:#}"))?;
+    let dir = app.config_dir().map_err(|e| format!("{e:#}"))?;
     let has_settings = dir.join("settings.json").exists();
     let has_projects = dir.join("projects.json").exists();
     let has_pricing = dir.join("pricing.json").exists();
     Ok(!(has_settings || has_projects || has_pricing))
 }
 
-#[tauri::command]
-pub fn onboarding_initialize(app: tauri::AppHandle) -> std::result::Result<(), String> {
-    let dir = config_dir(&app).map_err(|e| format!("{e:#}"))?;
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn onboarding_initialize(app: AppContext) -> std::result::Result<(), String> {
+    let dir = app.config_dir().map_err(|e| format!("{e:#}"))?;
     ensure_dir(&dir).map_err(|e| format!("{e:#}"))?;
 
     // Directory skeleton (§29.2).
@@ -171,13 +164,16 @@ pub fn onboarding_initialize(app: tauri::AppHandle) -> std::result::Result<(), S
     Ok(())
 }
 
-#[tauri::command]
-pub fn onboarding_scan(app: tauri::AppHandle) -> std::result::Result<OnboardingScanResult, String> {
-    let agents = AgentRegistry::detect().list();
-    let gt_path = which_like("gt");
-
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn onboarding_scan(app: AppContext) -> std::result::Result<OnboardingScanResult, String> {
     // Workspace check is best-effort; default path is in settings defaults.
     let settings = crate::core::settings::settings_get(&app).unwrap_or_default();
+    let mut registry =
+        AgentRegistry::detect_with(&crate::core::settings::agent_path_overrides(&settings));
+    registry.apply_auth(&crate::core::settings::auth_probe(&app));
+    let agents = registry.list();
+    let gt_path = which_like("gt");
+
     let workspace_raw = settings.gastown.workspace_path.clone();
     let workspace_path = expand_home(&workspace_raw);
     let workspace_found = fs::metadata(&workspace_path)
@@ -1,12 +1,12 @@
 use std::path::PathBuf;
 
-use tauri::State;
-
 use crate::core::agent_detection::AgentType;
+use crate::core::app_context::{AppContext, Managed};
 use crate::core::persistence::{
     ProjectConfigView, RecentProject, SessionConfigDisk, SessionConfigView, SessionSnapshot,
     SessionSnapshotMeta,
 };
+use crate::core::session_labels::SessionLabels;
 use crate::core::session_manager::SharedSessionManager;
 
 #[derive(Debug, serde::Deserialize)]
@@ -38,6 +38,15 @@ pub struct ProjectSessionConfigSetArgs {
     pub worktree_isolation: Option<bool>,
     pub skills: Vec<String>,
     pub mcp_servers: Vec<String>,
+    /// Label fields left out keep their saved values; send an empty value to clear one.
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub group: Option<String>,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -74,23 +83,21 @@ pub struct SessionSnapshotAutosaveMetaArgs {
     pub project_path: String,
 }
 
-#[tauri::command]
-pub fn list_recent_projects(
-    app: tauri::AppHandle,
-) -> std::result::Result<Vec<RecentProject>, String> {
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn list_recent_projects(app: AppContext) -> std::result::Result<Vec<RecentProject>, String> {
     crate::core::persistence::list_recent_projects(&app).map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn open_project(
-    app: tauri::AppHandle,
+    app: AppContext,
     args: OpenProjectArgs,
 ) -> std::result::Result<RecentProject, String> {
     let path = PathBuf::from(args.path);
     crate::core::persistence::open_project(&app, &path).map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn project_config_get(
     args: ProjectConfigGetArgs,
 ) -> std::result::Result<ProjectConfigView, String> {
@@ -98,7 +105,7 @@ pub fn project_config_get(
     crate::core::persistence::project_config_get(&path).map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn project_session_config_get(
     args: ProjectSessionConfigGetArgs,
 ) -> std::result::Result<Option<SessionConfigView>, String> {
@@ -107,26 +114,42 @@ pub fn project_session_config_get(
         .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn project_session_config_set(
     args: ProjectSessionConfigSetArgs,
 ) -> std::result::Result<(), String> {
     let path = PathBuf::from(args.project_path);
+    let saved = crate::core::persistence::project_session_config_get(&path, args.session_id)
+        .map_err(|e| format!("{e:#}"))?
+        .map(|c| c.labels())
+        .unwrap_or_default();
+    let labels = SessionLabels {
+        name: args.name.or(saved.name),
+        color: args.color.or(saved.color),
+        tags: args.tags.unwrap_or(saved.tags),
+        group: args.group.or(saved.group),
+    }
+    .normalized()
+    .map_err(|e| format!("{e:#}"))?;
     let cfg = SessionConfigDisk {
         agent_type: args.agent_type,
         branch: args.branch,
         worktree_isolation: args.worktree_isolation,
         skills: args.skills,
         mcp_servers: args.mcp_servers,
+        name: labels.name,
+        color: labels.color,
+        tags: labels.tags,
+        group: labels.group,
     };
     crate::core::persistence::project_session_config_set(&path, args.session_id, cfg)
         .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn session_snapshot_save_named(
-    app: tauri::AppHandle,
-    manager: State<'_, SharedSessionManager>,
+    app: AppContext,
+    manager: Managed<SharedSessionManager>,
     args: SessionSnapshotSaveNamedArgs,
 ) -> std::result::Result<SessionSnapshotMeta, String> {
     let project_path = PathBuf::from(&args.project_path);
@@ -146,10 +169,10 @@ pub fn session_snapshot_save_named(
     .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn session_snapshot_save_autosave(
-    app: tauri::AppHandle,
-    manager: State<'_, SharedSessionManager>,
+    app: AppContext,
+    manager: Managed<SharedSessionManager>,
     args: SessionSnapshotSaveAutosaveArgs,
 ) -> std::result::Result<SessionSnapshotMeta, String> {
     let project_path = PathBuf::from(&args.project_path);
@@ -168,9 +191,9 @@ pub fn session_snapshot_save_autosave(
     .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn session_snapshot_list(
-    app: tauri::AppHandle,
+    app: AppContext,
     args: SessionSnapshotListArgs,
 ) -> std::result::Result<Vec<SessionSnapshotMeta>, String> {
     let project_path = args.project_path.map(PathBuf::from);
@@ -181,17 +204,17 @@ pub fn session_snapshot_list(
     .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn session_snapshot_load(
-    app: tauri::AppHandle,
+    app: AppContext,
     args: SessionSnapshotLoadArgs,
 ) -> std::result::Result<SessionSnapshot, String> {
     crate::core::persistence::session_snapshot_load(&app, &args.id).map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn session_snapshot_autosave_meta(
-    app: tauri::AppHandle,
+    app: AppContext,
     args: SessionSnapshotAutosaveMetaArgs,
 ) -> std::result::Result<Option<SessionSnapshotMeta>, String> {
     let project_path = PathBuf::from(&args.project_path);
@@ -0,0 +1,417 @@
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use super::{
+    agents, attempts, audit, checkpoints, command_bar, events, gateway, git, hooks, integration,
+    localhost, mcp, merge_queue, notifications, onboarding, persistence, review, secrets, session,
+    settings, skills,
+};
+use crate::core::app_context::AppContext;
+#[cfg(feature = "desktop")]
+use crate::core::app_context::Managed;
+use crate::core::audit_log::{AuditActor, Caller};
+use crate::core::control_socket::CommandHandler;
+#[cfg(feature = "desktop")]
+use crate::core::remote_api::RemoteApiStatus;
+use crate::core::remote_api::{self, SharedRemoteApi};
+use crate::core::settings::RemoteApiView;
+
+/// Deserializes `$args` into whatever the handler expects and serializes its result.
+macro_rules! call {
+    ($args:ident, |$a:ident| $call:expr) => {{
+        let $a = serde_json::from_value($args).map_err(|e| format!("invalid args: {e}"))?;
+        serde_json::to_value($call?).map_err(|e| format!("serialize result: {e}"))
+    }};
+    ($call:expr) => {{
+        serde_json::to_value($call?).map_err(|e| format!("serialize result: {e}"))
+    }};
+}
+
+/// Runs a command by name for clients outside the webview (the `synk` CLI and remote API
+/// clients, in the app or under `synk serve`). `args` is what the frontend passes as `args`; the result is the
+/// command's JSON. The remote API's own commands are left out so clients can't read the token.
+/// `actor` is who the transport says is calling; audited commands record it.
+pub fn dispatch(
+    app: &AppContext,
+    actor: &AuditActor,
+    command: &str,
+    args: Value,
+) -> Result<Value, String> {
+    let app = app.clone();
+    let caller = Caller(actor.clone());
+    match command {
+        "agents_list" => call!(agents::agents_list(app.clone(), app.state())),
+        "agents_refresh" => call!(agents::agents_refresh(app.clone(), app.state())),
+        "onboarding_is_first_run" => call!(onboarding::onboarding_is_first_run(app.clone())),
+        "onboarding_initialize" => call!(onboarding::onboarding_initialize(app.clone())),
+        "onboarding_scan" => call!(onboarding::onboarding_scan(app.clone())),
+        "list_recent_projects" => call!(persistence::list_recent_projects(app.clone())),
+        "open_project" => call!(args, |a| persistence::open_project(app.clone(), a)),
+        "project_config_get" => call!(args, |a| persistence::project_config_get(a)),
+        "project_session_config_get" => {
+            call!(args, |a| persistence::project_session_config_get(a))
+        }
+        "project_session_config_set" => {
+            call!(args, |a| persistence::project_session_config_set(a))
+        }
+        "session_snapshot_save_autosave" => call!(args, |a| {
+            persistence::session_snapshot_save_autosave(app.clone(), app.state(), a)
+        }),
+        "session_snapshot_load" => {
+            call!(args, |a| persistence::session_snapshot_load(app.clone(), a))
+        }
+        "session_snapshot_autosave_meta" => {
+            call!(args, |a| persistence::session_snapshot_autosave_meta(
+                app.clone(),
+                a
+            ))
+        }
+        "settings_get" => call!(settings::settings_get(app.clone())),
+        "settings_set" => {
+            call!(args, |a| settings::settings_set(
+                app.clone(),
+                caller.clone(),
+                app.state(),
+                app.state(),
+                a
+            ))
+        }
+        "settings_validate_provider_key" => {
+            call!(args, |a| settings::settings_validate_provider_key(
+                app.clone(),
+                a
+            ))
+        }
+        "settings_validate_provider_profile" => {
+            call!(args, |a| settings::settings_validate_provider_profile(
+                app.clone(),
+                a
+            ))
+        }
+        "settings_list_provider_models" => {
+            call!(args, |a| settings::settings_list_provider_models(
+                app.clone(),
+                a
+            ))
+        }
+        "settings_ollama_pull_model" => {
+            call!(args, |a| settings::settings_ollama_pull_model(a))
+        }
+        "skills_discover" => call!(args, |a| skills::skills_discover(a)),
+        "skills_set_enabled" => call!(args, |a| skills::skills_set_enabled(a)),
+        "mcp_discover" => call!(args, |a| mcp::mcp_discover(app.state(), a)),
+        "mcp_set_enabled" => call!(args, |a| mcp::mcp_set_enabled(
+            app.clone(),
+            caller.clone(),
+            app.state(),
+            a
+        )),
+        "events_since" => call!(args, |a| events::events_since(app.state(), a)),
+        "audit_query" => call!(args, |a| audit::audit_query(app.clone(), a)),
+        "notifications_list" => {
+            call!(args, |a| notifications::notifications_list(app.state(), a))
+        }
+        "notifications_mark_read" => {
+            call!(args, |a| notifications::notifications_mark_read(
+                app.state(),
+                a
+            ))
+        }
+        "notifications_clear" => call!(notifications::notifications_clear(app.state())),
+        "hooks_deliveries" => call!(args, |a| hooks::hooks_deliveries(app.clone(), a)),
+        "hooks_test" => call!(args, |a| hooks::hooks_test(app.clone(), a)),
+        "integration_list" => call!(args, |a| integration::integration_list(app.clone(), a)),
+        "integration_get" => call!(args, |a| integration::integration_get(app.clone(), a)),
+        "integration_resolve" => call!(args, |a| integration::integration_resolve(
+            app.clone(),
+            caller.clone(),
+            a
+        )),
+        "integration_continue" => call!(args, |a| integration::integration_continue(
+            app.clone(),
+            caller.clone(),
+            app.state(),
+            a
+        )),
+        "integration_abort" => call!(args, |a| integration::integration_abort(
+            app.clone(),
+            caller.clone(),
+            a
+        )),
+        "merge_queue_get" => call!(args, |a| merge_queue::merge_queue_get(
+            app.clone(),
+            app.state(),
+            a
+        )),
+        "merge_queue_enqueue" => call!(args, |a| merge_queue::merge_queue_enqueue(
+            app.clone(),
+            caller.clone(),
+            app.state(),
+            a
+        )),
+        "merge_queue_remove" => call!(args, |a| merge_queue::merge_queue_remove(
+            app.clone(),
+            caller.clone(),
+            app.state(),
+            a
+        )),
+        "merge_queue_pause" => call!(args, |a| merge_queue::merge_queue_pause(
+            app.clone(),
+            caller.clone(),
+            app.state(),
+            a
+        )),
+        "merge_queue_resume" => call!(args, |a| merge_queue::merge_queue_resume(
+            app.clone(),
+            caller.clone(),
+            app.state(),
+            a
+        )),
+        "gateway_status" => call!(gateway::gateway_status(app.state())),
+        "gateway_usage" => call!(args, |a| gateway::gateway_usage(app.state(), a)),
+        "gateway_set_rate_cap" => call!(args, |a| gateway::gateway_set_rate_cap(app.state(), a)),
+        "secrets_list" => call!(secrets::secrets_list(app.clone())),
+        "secrets_set" => call!(args, |a| secrets::secrets_set(app.clone(), a)),
+        "secrets_delete" => call!(args, |a| secrets::secrets_delete(app.clone(), a)),
+        "session_list" => call!(session::session_list(app.state())),
+        "session_cd" => call!(args, |a| session::session_cd(app.state(), a)),
+        "session_create" => call!(args, |a| session::session_create(
+            app.clone(),
+            app.state(),
+            a
+        )),
+        "session_destroy" => call!(args, |a| session::session_destroy(
+            app.clone(),
+            app.state(),
+            a
+        )),
+        "session_write" => call!(args, |a| session::session_write(app.state(), a)),
+        "session_broadcast" => {
+            call!(args, |a| session::session_broadcast(
+                app.clone(),
+                caller.clone(),
+                app.state(),
+                a
+            ))
+        }
+        "session_set_labels" => call!(args, |a| session::session_set_labels(app.state(), a)),
+        "session_restart" => call!(args, |a| session::session_restart(
+            app.clone(),
+            app.state(),
+            a
+        )),
+        "session_fork" => call!(args, |a| session::session_fork(app.clone(), app.state(), a)),
+        "session_handoff" => call!(args, |a| session::session_handoff(
+            app.clone(),
+            app.state(),
+            a
+        )),
+        "session_resize" => call!(args, |a| session::session_resize(app.state(), a)),
+        "session_scrollback" => call!(args, |a| session::session_scrollback(app.state(), a)),
+        "command_complete" => {
+            call!(args, |a| command_bar::command_complete(
+                app.clone(),
+                app.state(),
+                app.state(),
+                a
+            ))
+        }
+        "command_execute" => {
+            call!(args, |a| command_bar::command_execute(
+                app.clone(),
+                caller.clone(),
+                app.state(),
+                a
+            ))
+        }
+        "checkpoint_list" => call!(args, |a| checkpoints::checkpoint_list(app.state(), a)),
+        "checkpoint_create" => call!(args, |a| checkpoints::checkpoint_create(app.state(), a)),
+        "checkpoint_diff" => call!(args, |a| checkpoints::checkpoint_diff(app.state(), a)),
+        "checkpoint_restore" => call!(args, |a| checkpoints::checkpoint_restore(app.state(), a)),
+        "attempts_start" => {
+            call!(args, |a| attempts::attempts_start(
+                app.clone(),
+                app.state(),
+                a
+            ))
+        }
+        "attempts_list" => call!(args, |a| attempts::attempts_list(app.clone(), a)),
+        "attempts_compare" => call!(args, |a| attempts::attempts_compare(app.clone(), a)),
+        "attempts_promote" => call!(args, |a| attempts::attempts_promote(app.clone(), a)),
+        "git_create_worktree" => {
+            call!(args, |a| git::git_create_worktree(
+                app.clone(),
+                app.state(),
+                a
+            ))
+        }
+        "git_remove_worktree" => {
+            call!(args, |a| git::git_remove_worktree(
+                app.clone(),
+                caller.clone(),
+                app.state(),
+                a
+            ))
+        }
+        "git_delete_worktree" => {
+            call!(args, |a| git::git_delete_worktree(
+                app.clone(),
+                caller.clone(),
+                app.state(),
+                a
+            ))
+        }
+        "git_detect_orphans" => {
+            call!(args, |a| git::git_detect_orphans(
+                app.clone(),
+                app.state(),
+                a
+            ))
+        }
+        "git_cleanup_orphans" => {
+            call!(args, |a| git::git_cleanup_orphans(
+                app.clone(),
+                caller.clone(),
+                app.state(),
+                a
+            ))
+        }
+        "git_branches" => call!(args, |a| git::git_branches(app.clone(), a)),
+        "git_commit_log" => call!(args, |a| git::git_commit_log(app.clone(), a)),
+        "git_commit_files" => call!(args, |a| git::git_commit_files(app.clone(), a)),
+        "git_refs_for_commit" => call!(args, |a| git::git_refs_for_commit(app.clone(), a)),
+        "git_ensure_worktree" => call!(args, |a| git::git_ensure_worktree(app.clone(), a)),
+        "git_list_worktrees" => call!(args, |a| git::git_list_worktrees(app.clone(), a)),
+        "git_diff" => call!(args, |a| review::git_diff(app.clone(), a)),
+        "git_merge" => call!(args, |a| review::git_merge(
+            app.clone(),
+            caller.clone(),
+            app.state(),
+            a
+        )),
+        "git_merge_preview" => call!(args, |a| review::git_merge_preview(app.clone(), a)),
+        "review_create" => call!(args, |a| review::review_create(app.clone(), app.state(), a)),
+        "review_list" => call!(args, |a| review::review_list(app.clone(), a)),
+        "review_get" => call!(args, |a| review::review_get(app.clone(), a)),
+        "review_set_status" => call!(args, |a| review::review_set_status(app.clone(), a)),
+        "review_set_decision" => call!(args, |a| review::review_set_decision(app.clone(), a)),
+        "review_set_merge_strategy" => {
+            call!(args, |a| review::review_set_merge_strategy(app.clone(), a))
+        }
+        "review_add_comment" => call!(args, |a| review::review_add_comment(app.clone(), a)),
+        "review_resolve_comment" => {
+            call!(args, |a| review::review_resolve_comment(app.clone(), a))
+        }
+        "session_snapshot_list" => {
+            call!(args, |a| persistence::session_snapshot_list(app.clone(), a))
+        }
+        "session_snapshot_save_named" => call!(args, |a| {
+            persistence::session_snapshot_save_named(app.clone(), app.state(), a)
+        }),
+        "localhost_session_list" => {
+            call!(args, |a| localhost::localhost_session_list(
+                app.clone(),
+                app.state(),
+                a
+            ))
+        }
+        "localhost_session_upsert" => {
+            call!(args, |a| localhost::localhost_session_upsert(
+                app.clone(),
+                app.state(),
+                a
+            ))
+        }
+        "localhost_session_delete" => {
+            call!(args, |a| localhost::localhost_session_delete(
+                app.clone(),
+                app.state(),
+                a
+            ))
+        }
+        "localhost_session_restart" => {
+            call!(args, |a| localhost::localhost_session_restart(
+                app.clone(),
+                app.state(),
+                a
+            ))
+        }
+        "localhost_session_start" => {
+            call!(args, |a| localhost::localhost_session_start(
+                app.clone(),
+                app.state(),
+                a
+            ))
+        }
+        "localhost_session_stop" => {
+            call!(args, |a| localhost::localhost_session_stop(
+                app.clone(),
+                app.state(),
+                a
+            ))
+        }
+        "localhost_session_logs" => {
+            call!(args, |a| localhost::localhost_session_logs(
+                app.clone(),
+                app.state(),
+                a
+            ))
+        }
+        other => Err(format!("unknown command {other:?}")),
+    }
+}
+
+/// [`dispatch`] bound to `app`, for the control socket and the remote API.
+pub fn handler(app: &AppContext) -> CommandHandler {
+    let app = app.clone();
+    Arc::new(move |actor, command, args| dispatch(&app, actor, command, args))
+}
+
+/// Starts, restarts or stops the remote API to match `settings`.
+pub(crate) fn remote_api_apply(app: &AppContext, settings: &RemoteApiView) {
+    let api = app.state::<SharedRemoteApi>();
+    let mut api = api.lock().expect("remote API mutex poisoned");
+    if !settings.enabled {
+        api.shutdown();
+        return;
+    }
+    if api.is_running_as(settings.port, settings.bind_lan) {
+        return;
+    }
+    api.shutdown();
+    let started = remote_api::load_or_create_token(app).and_then(|token| {
+        api.start(
+            Some(app.clone()),
+            handler(app),
+            token,
+            settings.port,
+            settings.bind_lan,
+        )
+    });
+    if let Err(err) = started {
+        eprintln!("remote api: {err:#}");
+    }
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub fn remote_api_status(
+    api: Managed<SharedRemoteApi>,
+) -> std::result::Result<RemoteApiStatus, String> {
+    let guard = api.lock().map_err(|_| "mutex poisoned".to_string())?;
+    Ok(guard.status())
+}
+
+/// Issues a new token and disconnects every client using the old one.
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub fn remote_api_rotate_token(
+    app: AppContext,
+    api: Managed<SharedRemoteApi>,
+) -> std::result::Result<RemoteApiStatus, String> {
+    let token = remote_api::rotate_token(&app).map_err(|e| format!("{e:#}"))?;
+    let guard = api.lock().map_err(|_| "mutex poisoned".to_string())?;
+    guard.set_token(token);
+    Ok(guard.status())
+}
@@ -1,13 +1,17 @@
 use std::path::PathBuf;
 
-use tauri::Emitter;
-use tauri::State;
-
-use crate::core::git_manager::{FileDiff, GitManager, MergeResult, MergeStrategy};
+use serde_json::json;
+
+use crate::core::app_context::{AppContext, Managed};
+use crate::core::audit_log::{self, AuditActor, Caller};
+use crate::core::event_bus::publish;
+use crate::core::git_events::publish_merge_event;
+use crate::core::git_manager::{FileDiff, GitManager, MergePreview, MergeResult, MergeStrategy};
+use crate::core::integration;
 use crate::core::review_store::{ReviewComment, ReviewDecision, ReviewItem, ReviewStatus};
 use crate::core::session_manager::SharedSessionManager;
 use crate::core::settings as core_settings;
-use crate::events::{now_rfc3339, GitEvent, GitEventType, GIT_EVENT_NAME};
+use crate::events::{now_rfc3339, AppEvent, ReviewEvent, ReviewEventKind};
 
 #[derive(Debug, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -17,6 +21,14 @@ pub struct GitDiffArgs {
     pub base_branch: String,
 }
 
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitMergePreviewArgs {
+    pub project_path: String,
+    pub branch: String,
+    pub base_branch: String,
+}
+
 #[derive(Debug, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GitMergeArgs {
@@ -24,6 +36,9 @@ pub struct GitMergeArgs {
     pub branch: String,
     pub base_branch: String,
     pub strategy: MergeStrategy,
+    /// Overrides `git.interactive_conflicts` for this merge.
+    #[serde(default)]
+    pub interactive: Option<bool>,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -94,24 +109,20 @@ pub struct ReviewResolveCommentArgs {
 }
 
 fn make_manager(
-    app: &tauri::AppHandle,
+    app: &AppContext,
     project_path: PathBuf,
 ) -> std::result::Result<GitManager, String> {
-    let settings = core_settings::settings_get(app).map_err(|e| format!("{e:#}"))?;
-    GitManager::new(
-        project_path,
-        &settings.git.worktree_base_path,
-        &settings.git.branch_prefix,
-    )
-    .map_err(|e| format!("{e:#}"))
+    GitManager::for_project(app, &project_path).map_err(|e| format!("{e:#}"))
 }
 
 fn maybe_delegate_conflicts(
+    app: &AppContext,
     sessions: &mut crate::core::session_manager::SessionManager,
     project_path: &str,
     branch: &str,
     base_branch: &str,
     conflict_files: &[String],
+    integration_worktree: Option<&str>,
 ) {
     let mut target = None;
     for s in sessions.list_sessions() {
@@ -135,30 +146,88 @@ fn maybe_delegate_conflicts(
     for f in conflict_files {
         prompt.push_str(&format!("- {f}\r\n"));
     }
-    prompt.push_str("\r\nPlease resolve all conflicts and commit the resolution.\r\n");
+    match integration_worktree {
+        Some(dir) => prompt.push_str(&format!(
+            "\r\nThe merge is still in progress in the integration worktree {dir} ({base_branch} checked out, your branch being merged in). Resolve the conflicts there, `git add` the files and commit the merge. Don't rebase or merge in your own worktree.\r\n"
+        )),
+        None => {
+            prompt.push_str("\r\nPlease resolve all conflicts and commit the resolution.\r\n")
+        }
+    }
 
-    let _ = sessions.write(session_id, &prompt);
+    let outcome = sessions
+        .write(session_id, &prompt)
+        .map_err(|e| format!("{e:#}"));
+    audit_log::record(
+        app,
+        Some(project_path),
+        &AuditActor::Rule {
+            name: "git.auto_delegate_conflicts".to_string(),
+        },
+        "conflict_auto_delegate",
+        json!({
+            "sessionId": session_id,
+            "branch": branch,
+            "baseBranch": base_branch,
+            "conflictFiles": conflict_files,
+            "integrationWorktree": integration_worktree,
+        }),
+        &outcome,
+    );
 }
 
-#[tauri::command]
-pub fn git_diff(
-    app: tauri::AppHandle,
-    args: GitDiffArgs,
-) -> std::result::Result<Vec<FileDiff>, String> {
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn git_diff(app: AppContext, args: GitDiffArgs) -> std::result::Result<Vec<FileDiff>, String> {
     let gm = make_manager(&app, PathBuf::from(args.project_path))?;
     gm.generate_diff(&args.branch, &args.base_branch)
         .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+/// Whether `branch` would merge cleanly into `base_branch`, without touching any worktree.
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn git_merge_preview(
+    app: AppContext,
+    args: GitMergePreviewArgs,
+) -> std::result::Result<MergePreview, String> {
+    let gm = make_manager(&app, PathBuf::from(args.project_path))?;
+    gm.merge_preview(&args.branch, &args.base_branch)
+        .map_err(|e| format!("{e:#}"))
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn git_merge(
-    app: tauri::AppHandle,
-    sessions: State<'_, SharedSessionManager>,
+    app: AppContext,
+    caller: Caller,
+    sessions: Managed<SharedSessionManager>,
+    args: GitMergeArgs,
+) -> std::result::Result<MergeResult, String> {
+    let project_path = args.project_path.clone();
+    let audit_args = json!({
+        "projectPath": args.project_path,
+        "branch": args.branch,
+        "baseBranch": args.base_branch,
+        "strategy": args.strategy,
+    });
+    let res = merge(&app, &sessions, args);
+    audit_log::record(
+        &app,
+        Some(&project_path),
+        &caller,
+        "git_merge",
+        audit_args,
+        &res,
+    );
+    res
+}
+
+fn merge(
+    app: &AppContext,
+    sessions: &SharedSessionManager,
     args: GitMergeArgs,
 ) -> std::result::Result<MergeResult, String> {
-    let settings = core_settings::settings_get(&app).map_err(|e| format!("{e:#}"))?;
+    let settings = core_settings::settings_get(app).map_err(|e| format!("{e:#}"))?;
 
-    let gm = make_manager(&app, PathBuf::from(&args.project_path))?;
+    let gm = make_manager(app, PathBuf::from(&args.project_path))?;
     let branch = gm
         .normalize_branch(&args.branch)
         .map_err(|e| format!("{e:#}"))?;
@@ -166,57 +235,53 @@ pub fn git_merge(
         .normalize_base_branch(&args.base_branch)
         .map_err(|e| format!("{e:#}"))?;
 
-    let res = gm
+    let mut res = gm
         .merge_branch(&branch, &base_branch, args.strategy)
         .map_err(|e| format!("{e:#}"))?;
 
-    // Emit a UI event for the git activity feed.
-    let _ = app.emit(
-        GIT_EVENT_NAME,
-        GitEvent {
-            id: format!(
-                "{}-{}",
-                if res.success { "merge" } else { "conflict" },
-                now_rfc3339()
-            ),
-            event_type: if res.success {
-                GitEventType::MergeCompleted
-            } else {
-                GitEventType::ConflictDetected
-            },
-            timestamp: now_rfc3339(),
-            project_path: args.project_path.clone(),
-            session_id: None,
-            branch: Some(branch.clone()),
-            hash: None,
-            message: None,
-            author: None,
-            base_branch: Some(base_branch.clone()),
-            strategy: Some(
-                match args.strategy {
-                    MergeStrategy::Merge => "merge",
-                    MergeStrategy::Squash => "squash",
-                    MergeStrategy::Rebase => "rebase",
-                }
-                .to_string(),
-            ),
-            conflict_files: res.conflict_files.clone(),
-        },
+    let interactive = args
+        .interactive
+        .unwrap_or(settings.git.interactive_conflicts);
+    if !res.success && interactive && args.strategy != MergeStrategy::Rebase {
+        // Falls back to the plain conflict report if the merge can't be redone there.
+        match integration::start(&gm, &branch, &base_branch, args.strategy) {
+            Ok(it) => res.integration = Some(it),
+            Err(err) => eprintln!("integration: {err:#}"),
+        }
+    }
+
+    publish_merge_event(
+        app,
+        sessions,
+        &args.project_path,
+        &branch,
+        &base_branch,
+        args.strategy,
+        &res,
     );
 
     if !res.success && settings.git.auto_delegate_conflicts {
         if let Some(files) = res.conflict_files.as_deref() {
             let mut guard = sessions.lock().expect("session manager mutex poisoned");
-            maybe_delegate_conflicts(&mut guard, &args.project_path, &branch, &base_branch, files);
+            maybe_delegate_conflicts(
+                app,
+                &mut guard,
+                &args.project_path,
+                &branch,
+                &base_branch,
+                files,
+                res.integration.as_ref().map(|it| it.worktree_path.as_str()),
+            );
         }
     }
 
     Ok(res)
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn review_create(
-    app: tauri::AppHandle,
+    app: AppContext,
+    sessions: Managed<SharedSessionManager>,
     args: ReviewCreateArgs,
 ) -> std::result::Result<ReviewItem, String> {
     let project_path = PathBuf::from(&args.project_path);
@@ -228,56 +293,53 @@ pub fn review_create(
         .normalize_base_branch(&args.base_branch)
         .map_err(|e| format!("{e:#}"))?;
 
+    let session_labels = sessions
+        .lock()
+        .expect("session manager mutex poisoned")
+        .get_session_info(args.session_id)
+        .map(|s| s.labels);
+
     crate::core::review_store::review_create(
         &app,
         &gm,
         &project_path,
         args.session_id,
+        session_labels,
         &branch,
         &base_branch,
     )
     .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn review_list(
-    app: tauri::AppHandle,
+    app: AppContext,
     args: ReviewListArgs,
 ) -> std::result::Result<Vec<ReviewItem>, String> {
     let project_path = PathBuf::from(&args.project_path);
     crate::core::review_store::review_list(&app, &project_path).map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
-pub fn review_get(
-    app: tauri::AppHandle,
-    args: ReviewGetArgs,
-) -> std::result::Result<ReviewItem, String> {
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn review_get(app: AppContext, args: ReviewGetArgs) -> std::result::Result<ReviewItem, String> {
     let project_path = PathBuf::from(&args.project_path);
     crate::core::review_store::review_get(&app, &project_path, &args.id)
         .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn review_set_status(
-    app: tauri::AppHandle,
+    app: AppContext,
     args: ReviewSetStatusArgs,
 ) -> std::result::Result<ReviewItem, String> {
     let project_path = PathBuf::from(&args.project_path);
-    let mut item = crate::core::review_store::review_get(&app, &project_path, &args.id)
-        .map_err(|e| format!("{e:#}"))?;
-    item.status = args.status;
-    item.updated_at = time::OffsetDateTime::now_utc()
-        .format(&time::format_description::well_known::Rfc3339)
-        .map_err(|e| format!("{e:#}"))?;
-    crate::core::review_store::review_save(&app, &project_path, &item)
-        .map_err(|e| format!("{e:#}"))?;
-    Ok(item)
+    crate::core::review_store::review_set_status(&app, &project_path, &args.id, args.status)
+        .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn review_set_decision(
-    app: tauri::AppHandle,
+    app: AppContext,
     args: ReviewSetDecisionArgs,
 ) -> std::result::Result<ReviewItem, String> {
     let project_path = PathBuf::from(&args.project_path);
@@ -294,12 +356,20 @@ pub fn review_set_decision(
 
     crate::core::review_store::review_save(&app, &project_path, &item)
         .map_err(|e| format!("{e:#}"))?;
+    publish(
+        &app,
+        AppEvent::Review(ReviewEvent::new(
+            ReviewEventKind::StatusChanged,
+            &project_path,
+            &item,
+        )),
+    );
     Ok(item)
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn review_set_merge_strategy(
-    app: tauri::AppHandle,
+    app: AppContext,
     args: ReviewSetMergeStrategyArgs,
 ) -> std::result::Result<ReviewItem, String> {
     let project_path = PathBuf::from(&args.project_path);
@@ -314,9 +384,9 @@ pub fn review_set_merge_strategy(
     Ok(item)
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn review_add_comment(
-    app: tauri::AppHandle,
+    app: AppContext,
     args: ReviewAddCommentArgs,
 ) -> std::result::Result<ReviewItem, String> {
     let project_path = PathBuf::from(&args.project_path);
@@ -341,9 +411,9 @@ pub fn review_add_comment(
     Ok(item)
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn review_resolve_comment(
-    app: tauri::AppHandle,
+    app: AppContext,
     args: ReviewResolveCommentArgs,
 ) -> std::result::Result<ReviewItem, String> {
     let project_path = PathBuf::from(&args.project_path);
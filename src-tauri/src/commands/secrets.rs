@@ -0,0 +1,83 @@
+use crate::core::app_context::AppContext;
+use crate::core::secrets::{mask, SecretStore, SECRET_REF_PREFIX};
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretSetArgs {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretDeleteArgs {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretEntry {
+    pub name: String,
+    pub masked: String,
+    /// Value to put in MCP server env to use this secret.
+    pub reference: String,
+}
+
+fn valid_name(name: &str) -> std::result::Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    {
+        return Err(format!("invalid secret name: {name:?}"));
+    }
+    // Provider keys are managed through settings.
+    if name.starts_with("provider.") || name.starts_with("profile.") {
+        return Err(format!("secret name is reserved: {name}"));
+    }
+    Ok(name)
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn secrets_list(app: AppContext) -> std::result::Result<Vec<SecretEntry>, String> {
+    let store = SecretStore::open(&app).map_err(|e| format!("{e:#}"))?;
+    let mut out = Vec::new();
+    for name in store.names() {
+        let value = store
+            .get(&name)
+            .map_err(|e| format!("{e:#}"))?
+            .unwrap_or_default();
+        out.push(SecretEntry {
+            masked: mask(&value),
+            reference: format!("{SECRET_REF_PREFIX}{name}"),
+            name,
+        });
+    }
+    Ok(out)
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn secrets_set(
+    app: AppContext,
+    args: SecretSetArgs,
+) -> std::result::Result<SecretEntry, String> {
+    let name = valid_name(&args.name)?;
+    let mut store = SecretStore::open(&app).map_err(|e| format!("{e:#}"))?;
+    store.set(name, &args.value).map_err(|e| format!("{e:#}"))?;
+    Ok(SecretEntry {
+        name: name.to_string(),
+        masked: mask(&args.value),
+        reference: format!("{SECRET_REF_PREFIX}{name}"),
+    })
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn secrets_delete(
+    app: AppContext,
+    args: SecretDeleteArgs,
+) -> std::result::Result<bool, String> {
+    let name = valid_name(&args.name)?;
+    let mut store = SecretStore::open(&app).map_err(|e| format!("{e:#}"))?;
+    store.remove(name).map_err(|e| format!("{e:#}"))
+}
@@ -1,8 +1,22 @@
-use tauri::State;
+use std::path::PathBuf;
 
+use serde_json::json;
+
+use crate::core::app_context::{AppContext, Managed};
+use crate::core::audit_log::{self, Caller};
+use crate::core::event_bus::publish;
+use crate::core::persistence::{project_session_config_get, project_session_config_set};
+use crate::core::session_broadcast::{
+    write_each, BroadcastArgs, BroadcastDelivery, BroadcastResult,
+};
+use crate::core::session_env::shell_single_quote_escape;
+use crate::core::session_fork::{ForkSessionArgs, ForkSessionResponse};
+use crate::core::session_handoff::{HandoffArgs, HandoffResponse};
+use crate::core::session_labels::SessionLabels;
 use crate::core::session_manager::{
-    CodexProvider, CreateSessionArgs, CreateSessionResponse, SessionInfo, SharedSessionManager,
+    CreateSessionArgs, CreateSessionResponse, RestartSessionArgs, SessionInfo, SharedSessionManager,
 };
+use crate::events::AppEvent;
 
 #[derive(Debug, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -13,38 +27,37 @@ pub struct SessionIdArgs {
 #[derive(Debug, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionWriteArgs {
-    pub session_id: usize,
+    #[serde(default)]
+    pub session_id: Option<usize>,
+    /// Alternative to `session_id`: `@name`, `@tag:<tag>` or `@group:<group>`.
+    #[serde(default)]
+    pub target: Option<String>,
     pub data: String,
 }
 
 #[derive(Debug, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct SessionResizeArgs {
+pub struct SessionSetLabelsArgs {
     pub session_id: usize,
-    pub cols: u16,
-    pub rows: u16,
+    #[serde(flatten)]
+    pub labels: SessionLabels,
 }
 
 #[derive(Debug, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct SessionCdArgs {
+pub struct SessionResizeArgs {
     pub session_id: usize,
-    pub dir: String,
-    #[serde(default)]
-    pub branch: Option<String>,
+    pub cols: u16,
+    pub rows: u16,
 }
 
 #[derive(Debug, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct SessionRestartArgs {
+pub struct SessionCdArgs {
     pub session_id: usize,
     pub dir: String,
     #[serde(default)]
     pub branch: Option<String>,
-    #[serde(default)]
-    pub model: Option<String>,
-    #[serde(default)]
-    pub codex_provider: Option<CodexProvider>,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -59,10 +72,10 @@ pub struct SessionScrollbackResponse {
     pub data_b64: String,
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn session_create(
-    app: tauri::AppHandle,
-    manager: State<'_, SharedSessionManager>,
+    app: AppContext,
+    manager: Managed<SharedSessionManager>,
     args: CreateSessionArgs,
 ) -> std::result::Result<CreateSessionResponse, String> {
     let mut guard = manager.lock().expect("session manager mutex poisoned");
@@ -71,10 +84,10 @@ pub fn session_create(
         .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn session_destroy(
-    app: tauri::AppHandle,
-    manager: State<'_, SharedSessionManager>,
+    app: AppContext,
+    manager: Managed<SharedSessionManager>,
     args: SessionIdArgs,
 ) -> std::result::Result<DestroySessionResponse, String> {
     let mut guard = manager.lock().expect("session manager mutex poisoned");
@@ -84,25 +97,59 @@ pub fn session_destroy(
         .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+/// Writes to one session, or to every session matching `target`. With a target each session
+/// is attempted and gets its own entry in the result, so one failure doesn't leave the rest
+/// unwritten.
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn session_write(
-    manager: State<'_, SharedSessionManager>,
+    manager: Managed<SharedSessionManager>,
     args: SessionWriteArgs,
-) -> std::result::Result<(), String> {
+) -> std::result::Result<Vec<BroadcastDelivery>, String> {
     let mut guard = manager.lock().expect("session manager mutex poisoned");
-    guard
-        .write(args.session_id, &args.data)
-        .map_err(|e| format!("{e:#}"))
+    match (args.session_id, args.target.as_deref()) {
+        (Some(id), None) => {
+            guard.write(id, &args.data).map_err(|e| format!("{e:#}"))?;
+            Ok(vec![BroadcastDelivery {
+                session_id: id,
+                ok: true,
+                error: None,
+            }])
+        }
+        (None, Some(target)) => {
+            let ids = guard
+                .resolve_targets(target, None)
+                .map_err(|e| format!("{e:#}"))?;
+            Ok(write_each(&mut guard, ids, &args.data))
+        }
+        _ => Err("exactly one of sessionId or target is required".to_string()),
+    }
 }
 
-fn shell_single_quote_escape(s: &str) -> String {
-    // Bash-safe single-quote escaping: ' -> '\''.
-    s.replace('\'', "'\\''")
+/// Updates a session's name/color/tags/group and saves them to its pane config.
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn session_set_labels(
+    manager: Managed<SharedSessionManager>,
+    args: SessionSetLabelsArgs,
+) -> std::result::Result<SessionInfo, String> {
+    let mut guard = manager.lock().expect("session manager mutex poisoned");
+    let info = guard
+        .set_labels(args.session_id, args.labels)
+        .map_err(|e| format!("{e:#}"))?;
+    drop(guard);
+
+    let project_path = PathBuf::from(&info.project_path);
+    let mut cfg = project_session_config_get(&project_path, info.pane_index)
+        .map_err(|e| format!("{e:#}"))?
+        .unwrap_or_default();
+    cfg.set_labels(info.labels.clone());
+    project_session_config_set(&project_path, info.pane_index, cfg.into())
+        .map_err(|e| format!("{e:#}"))?;
+    Ok(info)
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn session_cd(
-    manager: State<'_, SharedSessionManager>,
+    manager: Managed<SharedSessionManager>,
     args: SessionCdArgs,
 ) -> std::result::Result<(), String> {
     let dir = args.dir.trim();
@@ -122,28 +169,66 @@ pub fn session_cd(
     Ok(())
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn session_restart(
-    app: tauri::AppHandle,
-    manager: State<'_, SharedSessionManager>,
-    args: SessionRestartArgs,
+    app: AppContext,
+    manager: Managed<SharedSessionManager>,
+    args: RestartSessionArgs,
 ) -> std::result::Result<SessionInfo, String> {
     let mut guard = manager.lock().expect("session manager mutex poisoned");
     guard
-        .restart_session(
-            app,
-            args.session_id,
-            args.dir,
-            args.branch,
-            args.model,
-            args.codex_provider,
-        )
+        .restart_session(app, args)
         .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn session_fork(
+    app: AppContext,
+    manager: Managed<SharedSessionManager>,
+    args: ForkSessionArgs,
+) -> std::result::Result<ForkSessionResponse, String> {
+    crate::core::session_fork::fork_session(&manager, &app, args).map_err(|e| format!("{e:#}"))
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn session_handoff(
+    app: AppContext,
+    manager: Managed<SharedSessionManager>,
+    args: HandoffArgs,
+) -> std::result::Result<HandoffResponse, String> {
+    crate::core::session_handoff::handoff_session(&manager, &app, args)
+        .map_err(|e| format!("{e:#}"))
+}
+
+/// Writes the same input to every session matching a selector such as `@idle` or `@tag:ui`.
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn session_broadcast(
+    app: AppContext,
+    caller: Caller,
+    manager: Managed<SharedSessionManager>,
+    args: BroadcastArgs,
+) -> std::result::Result<BroadcastResult, String> {
+    let mut guard = manager.lock().expect("session manager mutex poisoned");
+    let result =
+        crate::core::session_broadcast::broadcast(&mut guard, &args).map_err(|e| format!("{e:#}"));
+    drop(guard);
+    // The data itself may hold anything typed into a prompt; only its size is recorded.
+    audit_log::record(
+        &app,
+        args.project_path.as_deref(),
+        &caller,
+        "session_broadcast",
+        json!({ "target": args.target, "bytes": args.data.len() }),
+        &result,
+    );
+    let result = result?;
+    publish(&app, AppEvent::SessionBroadcast(result.clone()));
+    Ok(result)
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn session_resize(
-    manager: State<'_, SharedSessionManager>,
+    manager: Managed<SharedSessionManager>,
     args: SessionResizeArgs,
 ) -> std::result::Result<(), String> {
     let mut guard = manager.lock().expect("session manager mutex poisoned");
@@ -152,17 +237,17 @@ pub fn session_resize(
         .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn session_list(
-    manager: State<'_, SharedSessionManager>,
+    manager: Managed<SharedSessionManager>,
 ) -> std::result::Result<Vec<SessionInfo>, String> {
     let guard = manager.lock().expect("session manager mutex poisoned");
     Ok(guard.list_sessions())
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn session_scrollback(
-    manager: State<'_, SharedSessionManager>,
+    manager: Managed<SharedSessionManager>,
     args: SessionIdArgs,
 ) -> std::result::Result<SessionScrollbackResponse, String> {
     let guard = manager.lock().expect("session manager mutex poisoned");
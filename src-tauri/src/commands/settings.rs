@@ -1,8 +1,12 @@
-use tauri::State;
+use serde_json::json;
 
+use crate::core::app_context::{AppContext, Managed};
+use crate::core::audit_log::{self, Caller};
+use crate::core::llm_gateway::SharedLlmGateway;
 use crate::core::process_pool::{PoolConfig, ProcessPool, SharedProcessPool};
 use crate::core::settings::{
-    OllamaPullResult, ProviderKeyValidationResult, ProviderModelsResult, SettingsView,
+    OllamaPullResult, ProviderKeyValidationResult, ProviderModelsResult,
+    ProviderProfileValidationResult, ProviderProfileView, SettingsView,
 };
 
 #[derive(Debug, serde::Deserialize)]
@@ -16,6 +20,14 @@ pub struct SettingsSetArgs {
 pub struct ProviderValidateArgs {
     pub provider: String,
     pub api_key: String,
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderProfileValidateArgs {
+    pub profile: ProviderProfileView,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -33,49 +45,93 @@ pub struct OllamaPullArgs {
     pub base_url: Option<String>,
 }
 
-#[tauri::command]
-pub fn settings_get(app: tauri::AppHandle) -> std::result::Result<SettingsView, String> {
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn settings_get(app: AppContext) -> std::result::Result<SettingsView, String> {
     crate::core::settings::settings_get(&app).map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn settings_set(
-    app: tauri::AppHandle,
-    pool: State<'_, SharedProcessPool>,
+    app: AppContext,
+    caller: Caller,
+    pool: Managed<SharedProcessPool>,
+    gateway: Managed<SharedLlmGateway>,
     args: SettingsSetArgs,
 ) -> std::result::Result<SettingsView, String> {
-    let view =
-        crate::core::settings::settings_set(&app, args.settings).map_err(|e| format!("{e:#}"))?;
+    let before = crate::core::settings::settings_get(&app)
+        .ok()
+        .and_then(|v| serde_json::to_value(v).ok());
+    let res =
+        crate::core::settings::settings_set(&app, args.settings).map_err(|e| format!("{e:#}"));
+    // Paths only: the view carries provider keys.
+    let changed = match (&before, &res) {
+        (Some(before), Ok(view)) => serde_json::to_value(view)
+            .map(|after| audit_log::changed_paths(before, &after))
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    audit_log::record(
+        &app,
+        None,
+        &caller,
+        "settings_set",
+        json!({ "changed": changed }),
+        &res.as_ref().map(|_| ()).map_err(|e| e.to_string()),
+    );
+    let view = res?;
 
     // Apply performance settings immediately (best-effort). This updates limits for new sessions
     // and changes how the pool refills. Existing sessions are not affected.
     let cfg: PoolConfig = crate::core::settings::pool_config_from_settings(&view);
     ProcessPool::reconfigure(pool.inner().clone(), cfg);
 
+    // Gateway rate caps apply to in-flight sessions too; enable/port take effect for new ones.
+    if let Ok(gw) = gateway.inner().lock() {
+        gw.set_default_rate_cap(view.gateway.max_requests_per_minute);
+    }
+
+    super::remote::remote_api_apply(&app, &view.remote_api);
+
     Ok(view)
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn settings_validate_provider_key(
+    app: AppContext,
     args: ProviderValidateArgs,
 ) -> std::result::Result<ProviderKeyValidationResult, String> {
-    crate::core::settings::validate_provider_key(&args.provider, &args.api_key)
+    let api_key = crate::core::settings::unmask_provider_key(&app, &args.provider, &args.api_key)
+        .map_err(|e| format!("{e:#}"))?;
+    crate::core::settings::validate_provider_key(&args.provider, &api_key, args.base_url.as_deref())
         .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
+pub fn settings_validate_provider_profile(
+    app: AppContext,
+    args: ProviderProfileValidateArgs,
+) -> std::result::Result<ProviderProfileValidationResult, String> {
+    let profile = crate::core::settings::unmask_profile_key(&app, args.profile)
+        .map_err(|e| format!("{e:#}"))?;
+    crate::core::settings::validate_provider_profile(&profile).map_err(|e| format!("{e:#}"))
+}
+
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn settings_list_provider_models(
+    app: AppContext,
     args: ProviderModelsArgs,
 ) -> std::result::Result<ProviderModelsResult, String> {
-    crate::core::settings::list_provider_models(
+    let api_key = crate::core::settings::unmask_provider_key(
+        &app,
         &args.provider,
         args.api_key.as_deref().unwrap_or(""),
-        args.base_url.as_deref(),
     )
-    .map_err(|e| format!("{e:#}"))
+    .map_err(|e| format!("{e:#}"))?;
+    crate::core::settings::list_provider_models(&args.provider, &api_key, args.base_url.as_deref())
+        .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn settings_ollama_pull_model(
     args: OllamaPullArgs,
 ) -> std::result::Result<OllamaPullResult, String> {
@@ -22,7 +22,7 @@ pub struct SkillsSetEnabledArgs {
     pub agent_type: Option<AgentType>,
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn skills_discover(
     args: SkillsDiscoverArgs,
 ) -> std::result::Result<SkillsDiscoveryResult, String> {
@@ -36,7 +36,7 @@ pub fn skills_discover(
         .map_err(|e| format!("{e:#}"))
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "desktop", tauri::command)]
 pub fn skills_set_enabled(args: SkillsSetEnabledArgs) -> std::result::Result<(), String> {
     let agent_type = args.agent_type.unwrap_or(AgentType::ClaudeCode);
     skills_discovery::set_skill_enabled_for_agent(
@@ -1,7 +1,10 @@
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 pub type SharedAgentRegistry = Arc<Mutex<AgentRegistry>>;
@@ -13,6 +16,8 @@ pub enum AgentType {
     GeminiCli,
     Codex,
     Openrouter,
+    /// Scripted agent built into the Synk binary (`--simulated-agent`), for demos and tests.
+    Simulated,
     Terminal,
 }
 
@@ -23,7 +28,26 @@ impl AgentType {
             AgentType::GeminiCli => Some("gemini"),
             AgentType::Codex => Some("codex"),
             AgentType::Openrouter => Some("codex"),
-            AgentType::Terminal => None,
+            // Launched via the Synk executable; see `AgentRegistry::detect_with`.
+            AgentType::Simulated | AgentType::Terminal => None,
+        }
+    }
+
+    /// Oldest CLI release that understands the flags Synk passes at launch (see
+    /// `agent_command_with_model` in the session manager). These are floors we've launched
+    /// against, not the first release of each flag; raise one when Synk starts relying on
+    /// something newer.
+    pub fn min_version(self) -> Option<&'static str> {
+        match self {
+            // `--model`; 1.0.0 is the first stable release.
+            AgentType::ClaudeCode => Some("1.0.0"),
+            // `--model`, which every published release accepts, so this only rejects a binary
+            // that reports a version but isn't the real CLI.
+            AgentType::GeminiCli => Some("0.1.0"),
+            // `--sandbox workspace-write --ask-for-approval on-failure` and `-c key=value`; the
+            // earliest releases predate the Rust CLI and don't take all of them.
+            AgentType::Codex | AgentType::Openrouter => Some("0.20.0"),
+            AgentType::Simulated | AgentType::Terminal => None,
         }
     }
 
@@ -33,6 +57,7 @@ impl AgentType {
             AgentType::GeminiCli => "Gemini CLI",
             AgentType::Codex => "OpenAI Codex",
             AgentType::Openrouter => "OpenRouter",
+            AgentType::Simulated => "Simulated Agent",
             AgentType::Terminal => "Terminal",
         }
     }
@@ -47,6 +72,191 @@ pub struct DetectedAgent {
     pub path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
+    /// `true` when `path` comes from a settings override rather than `PATH`.
+    #[serde(default)]
+    pub custom_path: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_version: Option<String>,
+    /// `None` when the version could not be parsed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_ok: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<AgentAuthStatus>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthState {
+    Authenticated,
+    Missing,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentAuthStatus {
+    pub state: AuthState,
+    /// "oauth" | "auth_file" | "api_key_env" | "synk_settings" | "cloud_provider"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    pub detail: String,
+}
+
+impl AgentAuthStatus {
+    fn ok(method: &str, detail: impl Into<String>) -> Self {
+        Self {
+            state: AuthState::Authenticated,
+            method: Some(method.to_string()),
+            detail: detail.into(),
+        }
+    }
+
+    fn missing(detail: impl Into<String>) -> Self {
+        Self {
+            state: AuthState::Missing,
+            method: None,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Inputs for auth detection. Only local files and env are read; never the network.
+#[derive(Debug, Clone, Default)]
+pub struct AuthProbe {
+    pub home: Option<PathBuf>,
+    pub env: HashMap<String, String>,
+    /// Providers ("openai", "openrouter") whose keys Synk exports into Codex sessions.
+    pub stored_keys: HashSet<String>,
+    /// Plain Codex sessions are routed to OpenRouter (settings default provider).
+    pub codex_uses_openrouter: bool,
+}
+
+impl AuthProbe {
+    pub fn from_process() -> Self {
+        let home = std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from);
+        Self {
+            home,
+            env: std::env::vars().collect(),
+            ..Default::default()
+        }
+    }
+
+    fn env_set<'k>(&self, key: &'k str) -> Option<&'k str> {
+        self.env
+            .get(key)
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .map(|_| key)
+    }
+
+    fn env_truthy(&self, key: &str) -> bool {
+        self.env
+            .get(key)
+            .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false)
+    }
+
+    fn home_file(&self, rel: &str) -> Option<PathBuf> {
+        let path = self.home.as_ref()?.join(rel);
+        let meta = std::fs::metadata(&path).ok()?;
+        (meta.is_file() && meta.len() > 0).then_some(path)
+    }
+
+    fn home_json(&self, rel: &str) -> Option<serde_json::Value> {
+        let text = std::fs::read_to_string(self.home_file(rel)?).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+}
+
+/// Whether an agent CLI can start without hitting a login prompt, judged from local state.
+/// Mirrors what session bootstrap exports (e.g. Codex only sees keys stored in Synk).
+pub fn detect_auth(agent_type: AgentType, probe: &AuthProbe) -> Option<AgentAuthStatus> {
+    match agent_type {
+        AgentType::ClaudeCode => Some(claude_auth(probe)),
+        AgentType::GeminiCli => Some(gemini_auth(probe)),
+        AgentType::Codex if !probe.codex_uses_openrouter => Some(codex_auth(probe)),
+        AgentType::Codex | AgentType::Openrouter => Some(openrouter_auth(probe)),
+        AgentType::Simulated | AgentType::Terminal => None,
+    }
+}
+
+fn claude_auth(probe: &AuthProbe) -> AgentAuthStatus {
+    if let Some(key) = probe
+        .env_set("ANTHROPIC_API_KEY")
+        .or_else(|| probe.env_set("ANTHROPIC_AUTH_TOKEN"))
+    {
+        return AgentAuthStatus::ok("api_key_env", format!("{key} is set"));
+    }
+    if probe.env_truthy("CLAUDE_CODE_USE_BEDROCK") || probe.env_truthy("CLAUDE_CODE_USE_VERTEX") {
+        return AgentAuthStatus::ok("cloud_provider", "Bedrock/Vertex credentials");
+    }
+    if probe.home_file(".claude/.credentials.json").is_some() {
+        return AgentAuthStatus::ok("oauth", "~/.claude/.credentials.json");
+    }
+    // On macOS the token lives in the keychain; the account record is mirrored here.
+    if probe
+        .home_json(".claude.json")
+        .is_some_and(|v| v.get("oauthAccount").is_some_and(|a| a.is_object()))
+    {
+        return AgentAuthStatus::ok("oauth", "signed in (~/.claude.json)");
+    }
+    AgentAuthStatus::missing("no Claude login or ANTHROPIC_API_KEY")
+}
+
+fn codex_auth(probe: &AuthProbe) -> AgentAuthStatus {
+    if probe.stored_keys.contains("openai") {
+        return AgentAuthStatus::ok("synk_settings", "OpenAI key stored in Synk");
+    }
+    if let Some(auth) = probe.home_json(".codex/auth.json") {
+        if auth.get("tokens").is_some_and(|t| t.is_object()) {
+            return AgentAuthStatus::ok("oauth", "ChatGPT login (~/.codex/auth.json)");
+        }
+        if auth
+            .get("OPENAI_API_KEY")
+            .and_then(|k| k.as_str())
+            .is_some_and(|k| !k.trim().is_empty())
+        {
+            return AgentAuthStatus::ok("auth_file", "API key in ~/.codex/auth.json");
+        }
+    }
+    AgentAuthStatus::missing("no Codex login and no OpenAI key in Synk")
+}
+
+fn openrouter_auth(probe: &AuthProbe) -> AgentAuthStatus {
+    if probe.stored_keys.contains("openrouter") {
+        return AgentAuthStatus::ok("synk_settings", "OpenRouter key stored in Synk");
+    }
+    AgentAuthStatus::missing("no OpenRouter key in Synk settings")
+}
+
+fn gemini_auth(probe: &AuthProbe) -> AgentAuthStatus {
+    if let Some(key) = probe
+        .env_set("GEMINI_API_KEY")
+        .or_else(|| probe.env_set("GOOGLE_API_KEY"))
+    {
+        return AgentAuthStatus::ok("api_key_env", format!("{key} is set"));
+    }
+    if probe.env_truthy("GOOGLE_GENAI_USE_VERTEXAI") {
+        return AgentAuthStatus::ok("cloud_provider", "Vertex AI credentials");
+    }
+    if probe.home_file(".gemini/oauth_creds.json").is_some() {
+        return AgentAuthStatus::ok("oauth", "~/.gemini/oauth_creds.json");
+    }
+    AgentAuthStatus::missing("no Gemini login or GEMINI_API_KEY")
+}
+
+impl DetectedAgent {
+    /// Command to type into the shell: the override path (quoted) or the bare CLI name.
+    pub fn launch_command(&self) -> String {
+        match (&self.path, self.custom_path) {
+            (Some(path), true) => format!("'{}'", path.replace('\'', "'\\''")),
+            _ => self.command.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -56,6 +266,11 @@ pub struct AgentRegistry {
 
 impl AgentRegistry {
     pub fn detect() -> Self {
+        Self::detect_with(&HashMap::new())
+    }
+
+    /// Detects agents, preferring binary paths from settings over `PATH` lookups.
+    pub fn detect_with(overrides: &HashMap<AgentType, String>) -> Self {
         let mut detected = HashMap::new();
 
         // Terminal is always available (we spawn a shell in the PTY pool).
@@ -68,9 +283,36 @@ impl AgentRegistry {
                 found: true,
                 path: None,
                 version: None,
+                custom_path: false,
+                min_version: None,
+                version_ok: None,
+                warning: None,
+                auth: None,
             },
         );
 
+        // The simulated agent is this executable run with `--simulated-agent`.
+        let exe = std::env::current_exe()
+            .ok()
+            .map(|p| p.to_string_lossy().to_string());
+        detected.insert(
+            AgentType::Simulated,
+            DetectedAgent {
+                agent_type: AgentType::Simulated,
+                command: "synk --simulated-agent".to_string(),
+                found: exe.is_some(),
+                path: exe,
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+                custom_path: true,
+                min_version: None,
+                version_ok: None,
+                warning: None,
+                auth: None,
+            },
+        );
+
+        // Codex and OpenRouter share a binary; probe each distinct binary once.
+        let mut probed: HashMap<String, (Option<String>, Option<String>)> = HashMap::new();
         for agent_type in [
             AgentType::ClaudeCode,
             AgentType::GeminiCli,
@@ -80,12 +322,46 @@ impl AgentRegistry {
             let cmd = agent_type
                 .cli_command()
                 .expect("non-terminal agent has command");
-            let path = which_like(cmd);
-            let version = if path.is_some() {
-                version_like(cmd)
-            } else {
-                None
+            let custom = overrides
+                .get(&agent_type)
+                .map(|p| p.trim())
+                .filter(|p| !p.is_empty())
+                .map(expand_home);
+
+            let mut warning = None;
+            let (path, version) = match &custom {
+                Some(p) if is_executable(Path::new(p)) => probed
+                    .entry(p.clone())
+                    .or_insert_with(|| (Some(p.clone()), version_like(p)))
+                    .clone(),
+                Some(p) => {
+                    warning = Some(format!("configured path {p} is not an executable file"));
+                    (None, None)
+                }
+                None => probed
+                    .entry(cmd.to_string())
+                    .or_insert_with(|| {
+                        let path = which_like(cmd);
+                        let version = path.as_ref().and_then(|_| version_like(cmd));
+                        (path, version)
+                    })
+                    .clone(),
             };
+
+            let min_version = agent_type.min_version();
+            let version_ok = match (version.as_deref(), min_version) {
+                (Some(v), Some(min)) => version_at_least(v, min),
+                _ => None,
+            };
+            if version_ok == Some(false) {
+                warning = Some(format!(
+                    "{} {} is older than the minimum supported {}; update it or launch flags may fail",
+                    agent_type.display_name(),
+                    version.as_deref().unwrap_or_default(),
+                    min_version.unwrap_or_default()
+                ));
+            }
+
             detected.insert(
                 agent_type,
                 DetectedAgent {
@@ -94,6 +370,11 @@ impl AgentRegistry {
                     found: path.is_some(),
                     path,
                     version,
+                    custom_path: custom.is_some(),
+                    min_version: min_version.map(|s| s.to_string()),
+                    version_ok,
+                    warning,
+                    auth: None,
                 },
             );
         }
@@ -109,6 +390,7 @@ impl AgentRegistry {
             AgentType::GeminiCli,
             AgentType::Codex,
             AgentType::Openrouter,
+            AgentType::Simulated,
             AgentType::Terminal,
         ] {
             if let Some(v) = self.detected.get(&t) {
@@ -118,6 +400,18 @@ impl AgentRegistry {
         out
     }
 
+    /// Re-reads auth state for every agent. Cheap (local files/env), so callers refresh it
+    /// whenever they list agents.
+    pub fn apply_auth(&mut self, probe: &AuthProbe) {
+        for (agent_type, agent) in self.detected.iter_mut() {
+            agent.auth = detect_auth(*agent_type, probe);
+        }
+    }
+
+    pub fn get(&self, agent_type: AgentType) -> Option<&DetectedAgent> {
+        self.detected.get(&agent_type)
+    }
+
     pub fn is_installed(&self, agent_type: AgentType) -> bool {
         self.detected
             .get(&agent_type)
@@ -148,6 +442,51 @@ fn which_like(cmd: &str) -> Option<String> {
     }
 }
 
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}/{rest}", home.trim_end_matches('/')),
+        _ => path.to_string(),
+    }
+}
+
+fn is_executable(path: &Path) -> bool {
+    let Ok(meta) = std::fs::metadata(path) else {
+        return false;
+    };
+    if !meta.is_file() {
+        return false;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        meta.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        true
+    }
+}
+
+/// First `major.minor[.patch]` in a `--version` line, e.g. `codex-cli 0.46.0` -> `[0, 46, 0]`.
+fn parse_version(text: &str) -> Option<[u64; 3]> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| Regex::new(r"(\d+)\.(\d+)(?:\.(\d+))?").expect("invalid regex"));
+    let caps = re.captures(text)?;
+    let part = |i: usize| {
+        caps.get(i)
+            .and_then(|m| m.as_str().parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    Some([part(1), part(2), part(3)])
+}
+
+/// `None` if either side has no parseable version.
+fn version_at_least(version: &str, min: &str) -> Option<bool> {
+    let have = parse_version(version)?;
+    let want = parse_version(min)?;
+    Some(have.cmp(&want) != Ordering::Less)
+}
+
 fn version_like(cmd: &str) -> Option<String> {
     let output = Command::new(cmd).arg("--version").output().ok()?;
     if !output.status.success() {
@@ -165,3 +504,95 @@ fn version_like(cmd: &str) -> Option<String> {
         Some(first.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        detect_auth, parse_version, version_at_least, AgentRegistry, AgentType, AuthProbe,
+        AuthState,
+    };
+    use crate::core::test_fixtures::TempDir;
+    use std::collections::HashMap;
+
+    #[test]
+    fn parses_versions_from_cli_output() {
+        assert_eq!(parse_version("codex-cli 0.46.0"), Some([0, 46, 0]));
+        assert_eq!(parse_version("2.0.14 (Claude Code)"), Some([2, 0, 14]));
+        assert_eq!(parse_version("v1.2"), Some([1, 2, 0]));
+        assert_eq!(parse_version("unknown"), None);
+    }
+
+    #[test]
+    fn compares_against_minimum() {
+        assert_eq!(version_at_least("codex-cli 0.46.0", "0.20.0"), Some(true));
+        assert_eq!(version_at_least("codex-cli 0.9.3", "0.20.0"), Some(false));
+        assert_eq!(version_at_least("1.0.0", "1.0.0"), Some(true));
+        assert_eq!(version_at_least("dev build", "1.0.0"), None);
+    }
+
+    #[test]
+    fn invalid_override_is_reported_not_found() {
+        let mut overrides = HashMap::new();
+        overrides.insert(
+            AgentType::GeminiCli,
+            "/nonexistent/synk-test/gemini".to_string(),
+        );
+        let reg = AgentRegistry::detect_with(&overrides);
+        let gemini = reg.get(AgentType::GeminiCli).unwrap();
+        assert!(!gemini.found);
+        assert!(gemini.custom_path);
+        assert!(gemini.warning.is_some());
+    }
+
+    #[test]
+    fn auth_from_env_and_files() {
+        let home = TempDir::new("auth-files");
+        let mut probe = AuthProbe {
+            home: Some(home.to_path_buf()),
+            ..Default::default()
+        };
+        for agent in [
+            AgentType::ClaudeCode,
+            AgentType::GeminiCli,
+            AgentType::Codex,
+        ] {
+            assert_eq!(
+                detect_auth(agent, &probe).unwrap().state,
+                AuthState::Missing
+            );
+        }
+
+        std::fs::create_dir_all(home.join(".codex")).unwrap();
+        std::fs::write(
+            home.join(".codex/auth.json"),
+            r#"{"OPENAI_API_KEY":null,"tokens":{"id_token":"x"}}"#,
+        )
+        .unwrap();
+        std::fs::create_dir_all(home.join(".gemini")).unwrap();
+        std::fs::write(home.join(".gemini/oauth_creds.json"), "{}").unwrap();
+        probe
+            .env
+            .insert("ANTHROPIC_API_KEY".to_string(), "sk-ant".to_string());
+
+        let claude = detect_auth(AgentType::ClaudeCode, &probe).unwrap();
+        assert_eq!(claude.state, AuthState::Authenticated);
+        assert_eq!(claude.method.as_deref(), Some("api_key_env"));
+        let codex = detect_auth(AgentType::Codex, &probe).unwrap();
+        assert_eq!(codex.method.as_deref(), Some("oauth"));
+        let gemini = detect_auth(AgentType::GeminiCli, &probe).unwrap();
+        assert_eq!(gemini.method.as_deref(), Some("oauth"));
+
+        // OpenRouter ignores the Codex login; only a stored key counts.
+        probe.codex_uses_openrouter = true;
+        assert_eq!(
+            detect_auth(AgentType::Codex, &probe).unwrap().state,
+            AuthState::Missing
+        );
+        probe.stored_keys.insert("openrouter".to_string());
+        assert_eq!(
+            detect_auth(AgentType::Openrouter, &probe).unwrap().state,
+            AuthState::Authenticated
+        );
+        assert!(detect_auth(AgentType::Terminal, &probe).is_none());
+    }
+}
@@ -0,0 +1,286 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "desktop")]
+use anyhow::Context;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+#[cfg(feature = "desktop")]
+use tauri::ipc::{CommandArg, CommandItem, InvokeError};
+#[cfg(feature = "desktop")]
+use tauri::path::BaseDirectory;
+#[cfg(feature = "desktop")]
+use tauri::{Emitter, Listener, Manager};
+
+#[cfg(feature = "desktop")]
+use crate::core::audit_log::{AuditActor, Caller};
+
+pub type EventId = u32;
+
+type EventHandler = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// What core code and commands need from the host: the config dir, app events and managed
+/// state. Backed by the Tauri app on the desktop and by [`Headless`] under `synk serve`.
+#[derive(Clone)]
+pub enum AppContext {
+    #[cfg(feature = "desktop")]
+    Tauri(tauri::AppHandle),
+    Headless(Arc<Headless>),
+}
+
+#[cfg(feature = "desktop")]
+impl From<tauri::AppHandle> for AppContext {
+    fn from(app: tauri::AppHandle) -> Self {
+        Self::Tauri(app)
+    }
+}
+
+#[cfg(feature = "desktop")]
+impl From<&tauri::AppHandle> for AppContext {
+    fn from(app: &tauri::AppHandle) -> Self {
+        Self::Tauri(app.clone())
+    }
+}
+
+impl AppContext {
+    pub fn headless(config_dir: PathBuf) -> Self {
+        Self::Headless(Arc::new(Headless::new(config_dir)))
+    }
+
+    /// Whether this is the desktop app rather than `synk serve`.
+    pub fn is_desktop(&self) -> bool {
+        match self {
+            #[cfg(feature = "desktop")]
+            Self::Tauri(_) => true,
+            Self::Headless(_) => false,
+        }
+    }
+
+    /// Synk's own config directory (`<platform config dir>/synk`).
+    pub fn config_dir(&self) -> Result<PathBuf> {
+        match self {
+            #[cfg(feature = "desktop")]
+            Self::Tauri(app) => app
+                .path()
+                .resolve("synk", BaseDirectory::Config)
+                .context("resolve app config dir"),
+            Self::Headless(h) => Ok(h.config_dir.clone()),
+        }
+    }
+
+    /// `rel` inside [`AppContext::config_dir`].
+    pub fn config_path(&self, rel: impl AsRef<Path>) -> Result<PathBuf> {
+        Ok(self.config_dir()?.join(rel))
+    }
+
+    /// Only `core::event_bus` emits; everything else publishes an `AppEvent` through it.
+    pub(super) fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) -> Result<()> {
+        match self {
+            #[cfg(feature = "desktop")]
+            Self::Tauri(app) => app.emit(event, payload).map_err(Into::into),
+            Self::Headless(h) => {
+                h.emit(event, &serde_json::to_string(&payload)?);
+                Ok(())
+            }
+        }
+    }
+
+    /// Calls `handler` with the JSON payload of every `event`, whoever emitted it.
+    pub fn listen_any(
+        &self,
+        event: impl Into<String>,
+        handler: impl Fn(&str) + Send + Sync + 'static,
+    ) -> EventId {
+        match self {
+            #[cfg(feature = "desktop")]
+            Self::Tauri(app) => app.listen_any(event, move |e| handler(e.payload())),
+            Self::Headless(h) => h.listen(event.into(), Arc::new(handler)),
+        }
+    }
+
+    pub fn unlisten(&self, id: EventId) {
+        match self {
+            #[cfg(feature = "desktop")]
+            Self::Tauri(app) => app.unlisten(id),
+            Self::Headless(h) => {
+                h.listeners
+                    .lock()
+                    .expect("listeners mutex poisoned")
+                    .remove(&id);
+            }
+        }
+    }
+
+    /// Registers `value` for [`AppContext::state`]. Like Tauri, the first value of a type wins.
+    pub fn manage<T: Send + Sync + 'static>(&self, value: T) -> bool {
+        match self {
+            #[cfg(feature = "desktop")]
+            Self::Tauri(app) => app.manage(value),
+            Self::Headless(h) => {
+                let mut state = h.state.lock().expect("state mutex poisoned");
+                if state.contains_key(&TypeId::of::<T>()) {
+                    return false;
+                }
+                state.insert(TypeId::of::<T>(), Box::new(value));
+                true
+            }
+        }
+    }
+
+    pub fn try_state<T: Clone + Send + Sync + 'static>(&self) -> Option<Managed<T>> {
+        match self {
+            #[cfg(feature = "desktop")]
+            Self::Tauri(app) => app.try_state::<T>().map(|s| Managed(s.inner().clone())),
+            Self::Headless(h) => h
+                .state
+                .lock()
+                .expect("state mutex poisoned")
+                .get(&TypeId::of::<T>())
+                .and_then(|v| v.downcast_ref::<T>())
+                .map(|v| Managed(v.clone())),
+        }
+    }
+
+    /// Panics if `T` was never managed, as `tauri::Manager::state` does.
+    pub fn state<T: Clone + Send + Sync + 'static>(&self) -> Managed<T> {
+        self.try_state()
+            .unwrap_or_else(|| panic!("state not managed: {}", std::any::type_name::<T>()))
+    }
+}
+
+#[cfg(feature = "desktop")]
+impl<'de> CommandArg<'de, tauri::Wry> for AppContext {
+    fn from_command(command: CommandItem<'de, tauri::Wry>) -> Result<Self, InvokeError> {
+        tauri::AppHandle::from_command(command).map(Self::Tauri)
+    }
+}
+
+/// Managed state taken by a command. Works like `tauri::State` but can also be built from a
+/// headless [`AppContext`], so the same commands serve the webview and `synk serve`.
+#[derive(Clone)]
+pub struct Managed<T>(T);
+
+impl<T> Managed<T> {
+    pub fn inner(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Deref for Managed<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+#[cfg(feature = "desktop")]
+impl<'de, T: Clone + Send + Sync + 'static> CommandArg<'de, tauri::Wry> for Managed<T> {
+    fn from_command(command: CommandItem<'de, tauri::Wry>) -> Result<Self, InvokeError> {
+        tauri::State::<T>::from_command(command).map(|s| Managed(s.inner().clone()))
+    }
+}
+
+/// IPC calls come from the webview, i.e. the person at the keyboard.
+#[cfg(feature = "desktop")]
+impl<'de> CommandArg<'de, tauri::Wry> for Caller {
+    fn from_command(_command: CommandItem<'de, tauri::Wry>) -> Result<Self, InvokeError> {
+        Ok(Caller(AuditActor::User))
+    }
+}
+
+/// Host for running without a webview: a plain config dir, an in-process event bus and a
+/// type-keyed state map.
+pub struct Headless {
+    config_dir: PathBuf,
+    next_listener: AtomicU32,
+    listeners: Mutex<HashMap<EventId, (String, EventHandler)>>,
+    state: Mutex<HashMap<TypeId, Box<dyn Any + Send + Sync>>>,
+}
+
+impl Headless {
+    fn new(config_dir: PathBuf) -> Self {
+        Self {
+            config_dir,
+            next_listener: AtomicU32::new(1),
+            listeners: Mutex::new(HashMap::new()),
+            state: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn listen(&self, event: String, handler: EventHandler) -> EventId {
+        let id = self.next_listener.fetch_add(1, Ordering::Relaxed);
+        self.listeners
+            .lock()
+            .expect("listeners mutex poisoned")
+            .insert(id, (event, handler));
+        id
+    }
+
+    fn emit(&self, event: &str, payload: &str) {
+        // Handlers run outside the lock so they can unlisten (or emit) themselves.
+        let handlers: Vec<EventHandler> = self
+            .listeners
+            .lock()
+            .expect("listeners mutex poisoned")
+            .values()
+            .filter(|(name, _)| name == event)
+            .map(|(_, h)| h.clone())
+            .collect();
+        for handler in handlers {
+            handler(payload);
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/synk`, else `~/.config/synk`: where the Linux desktop build keeps its
+/// config, so a headless instance on the same machine shares it.
+pub fn default_headless_config_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()) {
+        return Ok(PathBuf::from(dir).join("synk"));
+    }
+    let home = std::env::var_os("HOME")
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| anyhow!("HOME is not set; pass --config-dir"))?;
+    Ok(PathBuf::from(home).join(".config").join("synk"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    use super::AppContext;
+
+    #[test]
+    fn headless_events_reach_listeners_until_unlistened() {
+        let ctx = AppContext::headless(PathBuf::from("/tmp/synk-test"));
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let id = ctx.listen_any("git:event", move |p| {
+            sink.lock().unwrap().push(p.to_string())
+        });
+        ctx.emit("git:event", serde_json::json!({"n": 1})).unwrap();
+        ctx.emit("session:exit", 2).unwrap();
+        ctx.unlisten(id);
+        ctx.emit("git:event", 3).unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec![r#"{"n":1}"#.to_string()]);
+    }
+
+    #[test]
+    fn headless_state_and_paths() {
+        let ctx = AppContext::headless(PathBuf::from("/tmp/synk-test"));
+        assert!(ctx.try_state::<Arc<Mutex<u8>>>().is_none());
+        assert!(ctx.manage(Arc::new(Mutex::new(7u8))));
+        assert!(!ctx.manage(Arc::new(Mutex::new(9u8))));
+        assert_eq!(*ctx.state::<Arc<Mutex<u8>>>().lock().unwrap(), 7);
+        assert_eq!(
+            ctx.config_path("settings.json").unwrap(),
+            PathBuf::from("/tmp/synk-test/settings.json")
+        );
+    }
+}
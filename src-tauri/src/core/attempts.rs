@@ -0,0 +1,553 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::core::agent_detection::AgentType;
+use crate::core::app_context::AppContext;
+use crate::core::cost_tracker::{SessionCostSnapshot, SharedCostTrackers};
+use crate::core::git_manager::{parse_unified_diff, FileDiff, GitManager};
+use crate::core::review_store::{self, ReviewItem};
+use crate::core::session_labels::SessionLabels;
+use crate::core::session_manager::{CodexProvider, CreateSessionArgs, SharedSessionManager};
+
+/// One contender in a best-of-N run.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttemptSpec {
+    pub agent_type: AgentType,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub codex_provider: Option<CodexProvider>,
+    #[serde(default)]
+    pub provider_profile: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttemptsStartArgs {
+    pub project_path: String,
+    pub prompt: String,
+    /// Defaults to the repo's default branch.
+    #[serde(default)]
+    pub base_branch: Option<String>,
+    pub attempts: Vec<AttemptSpec>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attempt {
+    pub index: usize,
+    pub session_id: usize,
+    pub pane_index: usize,
+    pub agent_type: AgentType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub branch: String,
+    pub worktree_path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttemptGroup {
+    pub id: String,
+    pub project_path: String,
+    pub prompt: String,
+    pub base_branch: String,
+    /// Every attempt starts here, so their diffs are directly comparable.
+    pub base_commit: String,
+    pub created_at: String,
+    pub attempts: Vec<Attempt>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub winner: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttemptResult {
+    pub index: usize,
+    pub session_id: usize,
+    pub agent_type: AgentType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub branch: String,
+    pub files_changed: u32,
+    pub additions: u32,
+    pub deletions: u32,
+    pub files: Vec<String>,
+    /// The worktree has changes not yet committed to `branch`. They are counted above, but a
+    /// review of the branch won't include them.
+    pub uncommitted: bool,
+    /// Only known for sessions routed through the LLM gateway.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<SessionCostSnapshot>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttemptComparison {
+    pub group_id: String,
+    pub base_commit: String,
+    pub attempts: Vec<AttemptResult>,
+    /// Files touched by at least one attempt.
+    pub union_files: Vec<String>,
+    /// Files touched by every attempt that produced a diff.
+    pub common_files: Vec<String>,
+}
+
+/// Groups are kept one file per group under `attempts/<project>/` in the config dir, next
+/// to the project's reviews.
+fn attempts_dir(app: &AppContext, project_path: &Path) -> Result<PathBuf> {
+    let project = review_store::project_slug(project_path);
+    app.config_path(format!("attempts/{project}"))
+        .context("resolve attempts dir")
+}
+
+fn group_path(app: &AppContext, project_path: &Path, id: &str) -> Result<PathBuf> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(anyhow!("unknown attempt group {id}"));
+    }
+    Ok(attempts_dir(app, project_path)?.join(format!("{id}.json")))
+}
+
+fn save_group(app: &AppContext, group: &AttemptGroup) -> Result<()> {
+    let path = group_path(app, Path::new(&group.project_path), &group.id)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    let text = serde_json::to_string_pretty(group).context("serialize AttemptGroup")?;
+    fs::write(&path, format!("{text}\n")).with_context(|| format!("write {}", path.display()))
+}
+
+pub fn attempt_group_get(app: &AppContext, project_path: &Path, id: &str) -> Result<AttemptGroup> {
+    let path = group_path(app, project_path, id)?;
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(anyhow!("unknown attempt group {id}"));
+        }
+        Err(err) => return Err(err).with_context(|| format!("read {}", path.display())),
+    };
+    serde_json::from_str(&text).with_context(|| format!("parse {}", path.display()))
+}
+
+/// Newest first.
+pub fn attempt_group_list(app: &AppContext, project_path: &Path) -> Result<Vec<AttemptGroup>> {
+    let dir = attempts_dir(app, project_path)?;
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("read_dir {}", dir.display())),
+    };
+    let mut out: Vec<AttemptGroup> = Vec::new();
+    for ent in entries.flatten() {
+        let path = ent.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        let Ok(text) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Ok(group) = serde_json::from_str::<AttemptGroup>(&text) {
+            out.push(group);
+        }
+    }
+    out.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(out)
+}
+
+fn new_id() -> String {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    let n = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    format!("{n}")
+}
+
+/// Creates one worktree per attempt from the same base commit and launches an agent session in
+/// each with `args.prompt` as its launch prompt. If any attempt fails to start, the ones already started are
+/// torn down again. The worktrees are created before the session manager is locked, so other
+/// sessions aren't held up by the checkouts.
+pub fn start_attempts(
+    sessions: &SharedSessionManager,
+    app: &AppContext,
+    args: AttemptsStartArgs,
+) -> Result<AttemptGroup> {
+    if args.attempts.is_empty() {
+        return Err(anyhow!("at least one attempt is required"));
+    }
+    let prompt = args.prompt.trim();
+    if prompt.is_empty() {
+        return Err(anyhow!("prompt is empty"));
+    }
+
+    let gm = GitManager::for_project(app, &PathBuf::from(&args.project_path))?;
+    let base_branch = match args.base_branch.as_deref().map(str::trim) {
+        Some(b) if !b.is_empty() => gm.normalize_base_branch(b)?,
+        _ => gm.default_base_branch()?,
+    };
+    let base_commit = gm.resolve_commit(&base_branch)?;
+
+    let id = new_id();
+    let tag = &id[id.len().saturating_sub(6)..];
+    let mut worktrees: Vec<(PathBuf, String)> = Vec::new();
+    for index in 0..args.attempts.len() {
+        let created = gm
+            .unused_branch_name(&format!("attempt-{tag}-{}", index + 1))
+            .and_then(|branch| gm.create_worktree(&branch, &base_commit));
+        match created {
+            Ok(worktree) => worktrees.push(worktree),
+            Err(err) => {
+                for (_, branch) in &worktrees {
+                    let _ = gm.remove_worktree(branch);
+                }
+                return Err(err).with_context(|| format!("start attempt {}", index + 1));
+            }
+        }
+    }
+
+    let mut manager = sessions.lock().expect("session manager mutex poisoned");
+    let mut attempts: Vec<Attempt> = Vec::new();
+    let mut started = || -> Result<()> {
+        for (index, (spec, (worktree, branch))) in args.attempts.iter().zip(&worktrees).enumerate()
+        {
+            let worktree_path = worktree.to_string_lossy().to_string();
+            let created = manager
+                .create_session(
+                    app.clone(),
+                    CreateSessionArgs {
+                        agent_type: spec.agent_type,
+                        project_path: args.project_path.clone(),
+                        branch: Some(branch.clone()),
+                        working_dir: Some(worktree_path.clone()),
+                        model: spec.model.clone(),
+                        codex_provider: spec.codex_provider,
+                        provider_profile: spec.provider_profile.clone(),
+                        env: None,
+                        labels: SessionLabels {
+                            name: Some(format!("attempt {}", index + 1)),
+                            tags: vec!["attempt".to_string()],
+                            group: Some(format!("attempt-{tag}")),
+                            ..Default::default()
+                        },
+                        initial_prompt: Some(prompt.to_string()),
+                    },
+                )
+                .with_context(|| format!("start attempt {}", index + 1))?;
+            let model = manager
+                .get_session_info(created.session_id)
+                .and_then(|s| s.model);
+            attempts.push(Attempt {
+                index,
+                session_id: created.session_id,
+                pane_index: created.pane_index,
+                agent_type: created.agent_type,
+                model,
+                branch: branch.clone(),
+                worktree_path,
+                warning: created.warning,
+            });
+        }
+        Ok(())
+    };
+    let result = started();
+    if let Err(err) = result {
+        for a in &attempts {
+            let _ = manager.destroy_session(app.clone(), a.session_id);
+        }
+        drop(manager);
+        for (_, branch) in &worktrees {
+            let _ = gm.remove_worktree(branch);
+        }
+        return Err(err);
+    }
+    drop(manager);
+
+    let group = AttemptGroup {
+        id,
+        project_path: args.project_path,
+        prompt: prompt.to_string(),
+        base_branch,
+        base_commit,
+        created_at: OffsetDateTime::now_utc().format(&Rfc3339)?,
+        attempts,
+        winner: None,
+        review_id: None,
+    };
+    save_group(app, &group)?;
+    Ok(group)
+}
+
+/// Diffs every attempt's worktree against the group's base commit, so work the agent hasn't
+/// committed yet counts too. Attempts whose worktree is gone fall back to their branch.
+pub fn compare_attempts(group: &AttemptGroup, app: &AppContext) -> Result<AttemptComparison> {
+    let gm = GitManager::for_project(app, &PathBuf::from(&group.project_path))?;
+    let trackers = app.try_state::<SharedCostTrackers>();
+
+    let mut results = Vec::with_capacity(group.attempts.len());
+    for a in &group.attempts {
+        let cost = trackers.as_ref().and_then(|t| {
+            t.lock()
+                .expect("cost tracker mutex poisoned")
+                .snapshot(a.session_id)
+        });
+
+        let mut result = AttemptResult {
+            index: a.index,
+            session_id: a.session_id,
+            agent_type: a.agent_type,
+            model: a.model.clone(),
+            branch: a.branch.clone(),
+            files_changed: 0,
+            additions: 0,
+            deletions: 0,
+            files: Vec::new(),
+            uncommitted: false,
+            cost,
+            error: None,
+        };
+        let worktree = Path::new(&a.worktree_path);
+        let diffed = if worktree.is_dir() {
+            add_worktree_changes(&mut result, worktree, &group.base_commit)
+        } else {
+            gm.generate_diff(&a.branch, &group.base_commit)
+                .map(|files| add_file_diffs(&mut result, files))
+        };
+        if let Err(err) = diffed {
+            result.error = Some(format!("{err:#}"));
+        }
+        results.push(result);
+    }
+
+    let (union_files, common_files) = touched_file_sets(
+        results
+            .iter()
+            .filter(|r| r.error.is_none())
+            .map(|r| r.files.as_slice()),
+    );
+    Ok(AttemptComparison {
+        group_id: group.id.clone(),
+        base_commit: group.base_commit.clone(),
+        attempts: results,
+        union_files,
+        common_files,
+    })
+}
+
+fn add_file_diffs(result: &mut AttemptResult, files: Vec<FileDiff>) {
+    let (files_changed, additions, deletions) = review_store::compute_stats(&files);
+    result.files_changed += files_changed;
+    result.additions += additions;
+    result.deletions += deletions;
+    result.files.extend(files.into_iter().map(|f| f.path));
+}
+
+/// Tracked changes in `dir` against `base_commit`, plus untracked files counted as added
+/// lines.
+fn add_worktree_changes(result: &mut AttemptResult, dir: &Path, base_commit: &str) -> Result<()> {
+    let diff = GitManager::working_tree_diff(dir, base_commit)?;
+    add_file_diffs(result, parse_unified_diff(&diff));
+    let untracked = GitManager::untracked_files(dir)?;
+    for path in &untracked {
+        // Binary or unreadable files still count as changed, with no lines.
+        let lines = fs::read_to_string(dir.join(path)).map_or(0, |t| t.lines().count());
+        result.files_changed += 1;
+        result.additions += lines as u32;
+        result.files.push(path.clone());
+    }
+    result.files.sort();
+    result.uncommitted =
+        !untracked.is_empty() || !GitManager::uncommitted_diff(dir)?.trim().is_empty();
+    Ok(())
+}
+
+/// Opens a review for the winning attempt against the group's base branch.
+pub fn promote_attempt(
+    app: &AppContext,
+    project_path: &Path,
+    group_id: &str,
+    index: usize,
+) -> Result<ReviewItem> {
+    let mut group = attempt_group_get(app, project_path, group_id)?;
+    let attempt = group
+        .attempts
+        .iter()
+        .find(|a| a.index == index)
+        .ok_or_else(|| anyhow!("attempt {index} not found in group {group_id}"))?;
+
+    let gm = GitManager::for_project(app, project_path)?;
+    let session_labels = app.try_state::<SharedSessionManager>().and_then(|m| {
+        m.inner()
+            .lock()
+            .ok()
+            .and_then(|m| m.get_session_info(attempt.session_id))
+            .map(|s| s.labels)
+    });
+    let review = review_store::review_create(
+        app,
+        &gm,
+        project_path,
+        attempt.session_id,
+        session_labels,
+        &attempt.branch,
+        &group.base_branch,
+    )?;
+    group.winner = Some(index);
+    group.review_id = Some(review.id.clone());
+    save_group(app, &group)?;
+    Ok(review)
+}
+
+/// Sorted union and intersection of the files each attempt touched.
+fn touched_file_sets<'a>(
+    per_attempt: impl Iterator<Item = &'a [String]>,
+) -> (Vec<String>, Vec<String>) {
+    let mut union: BTreeSet<String> = BTreeSet::new();
+    let mut common: Option<BTreeSet<String>> = None;
+    for files in per_attempt {
+        let set: BTreeSet<String> = files.iter().cloned().collect();
+        union.extend(set.iter().cloned());
+        common = Some(match common {
+            Some(c) => c.intersection(&set).cloned().collect(),
+            None => set,
+        });
+    }
+    (
+        union.into_iter().collect(),
+        common.unwrap_or_default().into_iter().collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{
+        attempt_group_get, attempt_group_list, compare_attempts, save_group, touched_file_sets,
+        Attempt, AttemptGroup,
+    };
+    use crate::core::agent_detection::AgentType;
+    use crate::core::app_context::AppContext;
+    use crate::core::test_fixtures::{git, init_repo, TempDir};
+
+    fn v(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn union_and_intersection_of_touched_files() {
+        let a = v(&["src/a.rs", "src/b.rs"]);
+        let b = v(&["src/b.rs", "README.md"]);
+        let c = v(&["src/b.rs", "src/a.rs"]);
+        let (union, common) =
+            touched_file_sets([a.as_slice(), b.as_slice(), c.as_slice()].into_iter());
+        assert_eq!(union, v(&["README.md", "src/a.rs", "src/b.rs"]));
+        assert_eq!(common, v(&["src/b.rs"]));
+
+        let (union, common) = touched_file_sets(std::iter::empty());
+        assert!(union.is_empty() && common.is_empty());
+    }
+
+    #[test]
+    fn groups_survive_a_fresh_context() {
+        let tmp = TempDir::new("attempts");
+        let project = Path::new("/work/demo");
+        let group = |id: &str, created_at: &str| AttemptGroup {
+            id: id.to_string(),
+            project_path: project.to_string_lossy().to_string(),
+            prompt: "fix it".to_string(),
+            base_branch: "main".to_string(),
+            base_commit: "abc123".to_string(),
+            created_at: created_at.to_string(),
+            attempts: Vec::new(),
+            winner: None,
+            review_id: None,
+        };
+        let app = AppContext::headless(tmp.to_path_buf());
+        save_group(&app, &group("1", "2026-01-01T00:00:00Z")).unwrap();
+        save_group(&app, &group("2", "2026-01-02T00:00:00Z")).unwrap();
+
+        let app = AppContext::headless(tmp.to_path_buf());
+        let ids: Vec<String> = attempt_group_list(&app, project)
+            .unwrap()
+            .into_iter()
+            .map(|g| g.id)
+            .collect();
+        assert_eq!(ids, vec!["2", "1"]);
+        assert_eq!(
+            attempt_group_get(&app, project, "1").unwrap().prompt,
+            "fix it"
+        );
+        assert!(attempt_group_get(&app, project, "3").is_err());
+        assert!(attempt_group_get(&app, project, "../1").is_err());
+        assert!(attempt_group_list(&app, Path::new("/work/other"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn comparison_counts_uncommitted_work() {
+        let tmp = TempDir::new("attempts-compare");
+        let repo = tmp.join("repo");
+        init_repo(&repo).unwrap();
+        let base_commit = git(&repo, &["rev-parse", "HEAD"]).unwrap();
+        git(&repo, &["switch", "-c", "feat/attempt-1"]).unwrap();
+        std::fs::write(repo.join("committed.txt"), "one\n").unwrap();
+        git(&repo, &["add", "."]).unwrap();
+        git(&repo, &["commit", "-m", "work"]).unwrap();
+        std::fs::write(repo.join("README.md"), "hello\nworld\n").unwrap();
+        std::fs::write(repo.join("new.txt"), "a\nb\n").unwrap();
+
+        let attempt = |index: usize, worktree_path: String| Attempt {
+            index,
+            session_id: index,
+            pane_index: index,
+            agent_type: AgentType::Terminal,
+            model: None,
+            branch: "feat/attempt-1".to_string(),
+            worktree_path,
+            warning: None,
+        };
+        let group = AttemptGroup {
+            id: "1".to_string(),
+            project_path: repo.to_string_lossy().to_string(),
+            prompt: "fix it".to_string(),
+            base_branch: "main".to_string(),
+            base_commit,
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            attempts: vec![
+                attempt(0, repo.to_string_lossy().to_string()),
+                // A removed worktree falls back to the branch's commits.
+                attempt(1, tmp.join("gone").to_string_lossy().to_string()),
+            ],
+            winner: None,
+            review_id: None,
+        };
+        let app = AppContext::headless(tmp.join("config"));
+        let cmp = compare_attempts(&group, &app).unwrap();
+
+        let live = &cmp.attempts[0];
+        assert_eq!(live.error, None);
+        assert_eq!(live.files, v(&["README.md", "committed.txt", "new.txt"]));
+        assert_eq!((live.additions, live.deletions), (4, 0));
+        assert!(live.uncommitted);
+
+        let gone = &cmp.attempts[1];
+        assert_eq!(gone.error, None);
+        assert_eq!(gone.files, v(&["committed.txt"]));
+        assert!(!gone.uncommitted);
+        assert_eq!(cmp.common_files, v(&["committed.txt"]));
+    }
+}
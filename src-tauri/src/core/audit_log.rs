@@ -0,0 +1,382 @@
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::core::app_context::AppContext;
+use crate::core::review_store::project_slug;
+use crate::events::now_rfc3339;
+
+/// `<config dir>/audit.jsonl` for app-wide actions; project actions go to
+/// `<config dir>/audit/<project>.jsonl` so the log stays out of the repository.
+pub const AUDIT_FILE: &str = "audit.jsonl";
+const AUDIT_DIR: &str = "audit";
+
+const DEFAULT_QUERY_LIMIT: usize = 200;
+
+/// Serializes appends from concurrent commands so lines never interleave.
+static APPEND_LOCK: Mutex<()> = Mutex::new(());
+
+/// Who triggered an audited action. Set by the transport a command arrived on, never by its
+/// arguments: the webview is `user`, the control socket reports the session whose shell the
+/// calling process descends from, and the remote API reports the peer address.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum AuditActor {
+    #[default]
+    User,
+    #[serde(rename_all = "camelCase")]
+    Session {
+        session_id: usize,
+    },
+    /// An automatic reaction configured in settings, e.g. conflict auto-delegation.
+    Rule {
+        name: String,
+    },
+    Scheduler {
+        name: String,
+    },
+    Remote {
+        addr: String,
+    },
+}
+
+impl AuditActor {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Session { .. } => "session",
+            Self::Rule { .. } => "rule",
+            Self::Scheduler { .. } => "scheduler",
+            Self::Remote { .. } => "remote",
+        }
+    }
+}
+
+/// The actor behind the command being handled, injected by the transport rather than
+/// deserialized from the command's arguments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Caller(pub AuditActor);
+
+impl Deref for Caller {
+    type Target = AuditActor;
+
+    fn deref(&self) -> &AuditActor {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub timestamp: String,
+    /// Usually the command name, e.g. `git_cleanup_orphans`.
+    pub action: String,
+    pub actor: AuditActor,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_path: Option<String>,
+    pub args: Value,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub result: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditQueryArgs {
+    /// The project's log; the app-wide log (settings, broadcasts) when omitted.
+    #[serde(default)]
+    pub project_path: Option<String>,
+    #[serde(default)]
+    pub action: Option<String>,
+    /// `user`, `session`, `rule`, `scheduler` or `remote`.
+    #[serde(default)]
+    pub actor: Option<String>,
+    /// Only entries recorded by this session.
+    #[serde(default)]
+    pub session_id: Option<usize>,
+    /// RFC 3339; entries at or after this time.
+    #[serde(default)]
+    pub since: Option<String>,
+    /// Case-sensitive substring of the entry's args, result or error (e.g. a branch name).
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Newest first, at most this many (default 200).
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+fn log_path(app: &AppContext, project_path: Option<&str>) -> Result<PathBuf> {
+    match project_path {
+        Some(p) => {
+            let file = format!("{}.jsonl", project_slug(Path::new(p)));
+            app.config_path(Path::new(AUDIT_DIR).join(file))
+        }
+        None => app.config_path(AUDIT_FILE),
+    }
+}
+
+/// Appends one JSON line to `path`, creating the file and its directory on first use.
+pub fn append<T: Serialize>(path: &Path, entry: &T) -> Result<()> {
+    let mut line = serde_json::to_string(entry).context("serialize log entry")?;
+    line.push('\n');
+    let _guard = APPEND_LOCK.lock().expect("audit lock poisoned");
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("open {}", path.display()))?;
+    file.write_all(line.as_bytes())
+        .with_context(|| format!("append to {}", path.display()))
+}
+
+/// Records the outcome of an audited command. Best-effort: a log that can't be written is
+/// reported on stderr and never fails the command itself.
+pub fn record<T: Serialize>(
+    app: &AppContext,
+    project_path: Option<&str>,
+    actor: &AuditActor,
+    action: &str,
+    args: Value,
+    outcome: &std::result::Result<T, String>,
+) {
+    let (ok, result, error) = match outcome {
+        Ok(v) => (true, serde_json::to_value(v).unwrap_or(Value::Null), None),
+        Err(err) => (false, Value::Null, Some(err.clone())),
+    };
+    let entry = AuditEntry {
+        timestamp: now_rfc3339(),
+        action: action.to_string(),
+        actor: actor.clone(),
+        project_path: project_path.map(str::to_string),
+        args,
+        ok,
+        result,
+        error,
+    };
+    if let Err(err) = log_path(app, project_path).and_then(|path| append(&path, &entry)) {
+        eprintln!("audit log: {err:#}");
+    }
+}
+
+/// Dotted paths of the leaves that differ between two JSON documents, e.g. `git.branchPrefix`.
+/// Lets settings changes be audited without copying values (API keys among them) into the log.
+pub fn changed_paths(before: &Value, after: &Value) -> Vec<String> {
+    fn walk(prefix: &str, before: &Value, after: &Value, out: &mut Vec<String>) {
+        match (before, after) {
+            (Value::Object(a), Value::Object(b)) => {
+                let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+                keys.sort();
+                keys.dedup();
+                for key in keys {
+                    let path = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{prefix}.{key}")
+                    };
+                    let null = Value::Null;
+                    walk(
+                        &path,
+                        a.get(key).unwrap_or(&null),
+                        b.get(key).unwrap_or(&null),
+                        out,
+                    );
+                }
+            }
+            (a, b) if a != b => out.push(prefix.to_string()),
+            _ => {}
+        }
+    }
+    let mut out = Vec::new();
+    walk("", before, after, &mut out);
+    out
+}
+
+fn parse_time(s: &str) -> Option<OffsetDateTime> {
+    OffsetDateTime::parse(s, &Rfc3339).ok()
+}
+
+fn matches(entry: &AuditEntry, args: &AuditQueryArgs, since: Option<OffsetDateTime>) -> bool {
+    // Project logs are keyed by directory name, so two checkouts named alike share a file.
+    if args.project_path.is_some() && entry.project_path != args.project_path {
+        return false;
+    }
+    if args.action.as_deref().is_some_and(|a| a != entry.action) {
+        return false;
+    }
+    if args
+        .actor
+        .as_deref()
+        .is_some_and(|k| k != entry.actor.kind())
+    {
+        return false;
+    }
+    if let Some(id) = args.session_id {
+        if entry.actor != (AuditActor::Session { session_id: id }) {
+            return false;
+        }
+    }
+    if let Some(since) = since {
+        if parse_time(&entry.timestamp).is_none_or(|t| t < since) {
+            return false;
+        }
+    }
+    if let Some(text) = args.text.as_deref() {
+        let haystack = format!(
+            "{} {} {}",
+            entry.args,
+            entry.result,
+            entry.error.as_deref().unwrap_or_default()
+        );
+        if !haystack.contains(text) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Reads matching entries from `path`, newest first. Unparseable lines are skipped.
+pub fn read(path: &Path, args: &AuditQueryArgs) -> Result<Vec<AuditEntry>> {
+    let since = match args.since.as_deref() {
+        Some(s) => Some(parse_time(s).with_context(|| format!("invalid since {s:?}"))?),
+        None => None,
+    };
+    let file = match fs::File::open(path) {
+        Ok(f) => f,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("open {}", path.display())),
+    };
+    let mut out: Vec<AuditEntry> = BufReader::new(file)
+        .lines()
+        .map_while(|l| l.ok())
+        .filter_map(|l| serde_json::from_str(&l).ok())
+        .filter(|e| matches(e, args, since))
+        .collect();
+    out.reverse();
+    out.truncate(args.limit.unwrap_or(DEFAULT_QUERY_LIMIT));
+    Ok(out)
+}
+
+pub fn query(app: &AppContext, args: &AuditQueryArgs) -> Result<Vec<AuditEntry>> {
+    let path = log_path(app, args.project_path.as_deref())?;
+    read(&path, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{append, changed_paths, read, AuditActor, AuditEntry, AuditQueryArgs};
+    use crate::core::test_fixtures::TempDir;
+
+    fn entry(action: &str, actor: AuditActor, args: serde_json::Value, ts: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: ts.to_string(),
+            action: action.to_string(),
+            actor,
+            project_path: Some("/repo".to_string()),
+            args,
+            ok: true,
+            result: serde_json::Value::Null,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn appends_and_filters_newest_first() {
+        let dir = TempDir::new("audit");
+        let path = dir.join("audit").join("repo.jsonl");
+
+        append(
+            &path,
+            &entry(
+                "git_cleanup_orphans",
+                AuditActor::User,
+                json!({"projectPath": "/repo"}),
+                "2026-01-01T10:00:00Z",
+            ),
+        )
+        .unwrap();
+        append(
+            &path,
+            &entry(
+                "git_remove_worktree",
+                AuditActor::Session { session_id: 3 },
+                json!({"branch": "synk/feature-x"}),
+                "2026-01-02T10:00:00Z",
+            ),
+        )
+        .unwrap();
+
+        let all = read(&path, &AuditQueryArgs::default()).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].action, "git_remove_worktree");
+
+        let by_branch = read(
+            &path,
+            &AuditQueryArgs {
+                text: Some("feature-x".to_string()),
+                actor: Some("session".to_string()),
+                session_id: Some(3),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(by_branch.len(), 1);
+
+        let recent = read(
+            &path,
+            &AuditQueryArgs {
+                since: Some("2026-01-02T00:00:00Z".to_string()),
+                action: Some("git_cleanup_orphans".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(recent.is_empty());
+
+        let same_name = AuditQueryArgs {
+            project_path: Some("/elsewhere/repo".to_string()),
+            ..Default::default()
+        };
+        assert!(read(&path, &same_name).unwrap().is_empty());
+        assert!(read(&dir.join("missing.jsonl"), &AuditQueryArgs::default())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn changed_paths_lists_leaves_only() {
+        let before = json!({"git": {"branchPrefix": "synk/", "autoDelegate": false}, "ui": 1});
+        let after =
+            json!({"git": {"branchPrefix": "wip/", "autoDelegate": false}, "new": {"a": 1}});
+        assert_eq!(
+            changed_paths(&before, &after),
+            vec!["git.branchPrefix", "new", "ui"]
+        );
+    }
+
+    #[test]
+    fn actor_wire_format() {
+        assert_eq!(
+            serde_json::to_value(AuditActor::Session { session_id: 2 }).unwrap(),
+            json!({"kind": "session", "sessionId": 2})
+        );
+        assert_eq!(
+            serde_json::from_value::<AuditActor>(json!({"kind": "user"})).unwrap(),
+            AuditActor::User
+        );
+    }
+}
@@ -0,0 +1,475 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Serialize;
+
+use crate::core::git_manager::{parse_unified_diff, FileDiff};
+
+/// Checkpoints for a session live under `refs/synk/checkpoints/<session key>/`.
+pub const CHECKPOINT_REF_ROOT: &str = "refs/synk/checkpoints";
+
+/// Older checkpoints beyond this many per session are dropped as new ones are taken.
+pub const MAX_CHECKPOINTS_PER_SESSION: usize = 100;
+
+/// Commit trailers recording where a checkpoint was taken, so its refs can be dropped once
+/// that worktree or branch is gone.
+const WORKTREE_TRAILER: &str = "Synk-Worktree";
+const BRANCH_TRAILER: &str = "Synk-Branch";
+
+/// Output quiet for this long after a burst counts as the end of an agent turn.
+pub const TURN_IDLE_AFTER: Duration = Duration::from_secs(3);
+
+/// Output this soon after input is submitted is taken as the terminal echoing it.
+pub const ECHO_WINDOW: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    pub index: usize,
+    pub ref_name: String,
+    pub commit: String,
+    pub tree: String,
+    /// Branch HEAD the snapshot was taken on top of.
+    pub head: String,
+    pub created_at: String,
+}
+
+/// Busy/idle tracking over a session's output stream. A turn only starts with output that
+/// follows submitted input by more than [`ECHO_WINDOW`], so startup banners and the echo of
+/// the user's own typing don't count.
+#[derive(Debug, Default)]
+pub struct TurnTracker {
+    submitted: Option<Instant>,
+    last_output: Option<Instant>,
+}
+
+impl TurnTracker {
+    /// A line of input was sent to the session.
+    pub fn submitted(&mut self, now: Instant) {
+        self.submitted = Some(now);
+    }
+
+    /// Records output; true while it belongs to a turn.
+    pub fn output(&mut self, now: Instant) -> bool {
+        let replying = self
+            .submitted
+            .is_some_and(|t| now.duration_since(t) >= ECHO_WINDOW);
+        if self.last_output.is_some() || replying {
+            self.last_output = Some(now);
+        }
+        self.last_output.is_some()
+    }
+
+    /// True exactly once per turn, when its output has been quiet for `TURN_IDLE_AFTER`.
+    pub fn turn_ended(&mut self, now: Instant) -> bool {
+        match self.last_output {
+            Some(t) if now.duration_since(t) >= TURN_IDLE_AFTER => {
+                self.last_output = None;
+                self.submitted = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn session_ref_prefix(session_key: &str) -> String {
+    format!("{CHECKPOINT_REF_ROOT}/{session_key}/")
+}
+
+fn git(dir: &Path, args: &[&str], index: Option<&Path>) -> Result<String> {
+    let mut cmd = Command::new("git");
+    cmd.current_dir(dir).args(args);
+    if let Some(index) = index {
+        cmd.env("GIT_INDEX_FILE", index);
+    }
+    let out = cmd
+        .output()
+        .with_context(|| format!("run git {}", args.join(" ")))?;
+    if !out.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&out.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).trim_end().to_string())
+}
+
+pub fn is_work_tree(dir: &Path) -> bool {
+    git(dir, &["rev-parse", "--is-inside-work-tree"], None).is_ok_and(|v| v == "true")
+}
+
+fn toplevel(dir: &Path) -> Result<PathBuf> {
+    Ok(PathBuf::from(git(
+        dir,
+        &["rev-parse", "--show-toplevel"],
+        None,
+    )?))
+}
+
+/// Scratch index file, so snapshots and restores never touch the worktree's real index.
+struct TempIndex {
+    path: PathBuf,
+}
+
+impl TempIndex {
+    /// Starts from a copy of the real index (keeps git's stat cache, so `add -A` stays fast).
+    fn from_worktree(top: &Path) -> Result<Self> {
+        let tmp = Self::empty()?;
+        let real = top.join(git(top, &["rev-parse", "--git-path", "index"], None)?);
+        if fs::copy(&real, &tmp.path).is_err() {
+            git(top, &["read-tree", "HEAD"], Some(&tmp.path))?;
+        }
+        Ok(tmp)
+    }
+
+    fn empty() -> Result<Self> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let path = std::env::temp_dir().join(format!(
+            "synk-checkpoint-index-{}-{nanos}",
+            std::process::id()
+        ));
+        Ok(Self { path })
+    }
+}
+
+impl Drop for TempIndex {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Tree object for the worktree as it is on disk, tracked and untracked (minus ignored) files.
+fn snapshot_tree(top: &Path) -> Result<String> {
+    let index = TempIndex::from_worktree(top)?;
+    git(top, &["add", "-A"], Some(&index.path))?;
+    git(top, &["write-tree"], Some(&index.path))
+}
+
+pub fn list_checkpoints(dir: &Path, session_key: &str) -> Result<Vec<Checkpoint>> {
+    let prefix = session_ref_prefix(session_key);
+    let text = git(
+        dir,
+        &[
+            "for-each-ref",
+            "--format=%(refname)%09%(objectname)%09%(tree)%09%(parent)%09%(creatordate:iso-strict)",
+            &prefix,
+        ],
+        None,
+    )?;
+    let mut out: Vec<Checkpoint> = text
+        .lines()
+        .filter_map(|line| {
+            let mut it = line.split('\t');
+            let ref_name = it.next()?.to_string();
+            let index = ref_name.strip_prefix(&prefix)?.parse().ok()?;
+            Some(Checkpoint {
+                index,
+                ref_name,
+                commit: it.next()?.to_string(),
+                tree: it.next()?.to_string(),
+                head: it.next()?.to_string(),
+                created_at: it.next()?.to_string(),
+            })
+        })
+        .collect();
+    out.sort_by_key(|c| c.index);
+    Ok(out)
+}
+
+fn find_checkpoint(dir: &Path, session_key: &str, index: usize) -> Result<Checkpoint> {
+    list_checkpoints(dir, session_key)?
+        .into_iter()
+        .find(|c| c.index == index)
+        .ok_or_else(|| anyhow!("session {session_key} has no checkpoint {index}"))
+}
+
+/// Snapshots `dir` into the next checkpoint ref, leaving the branch and index alone.
+/// Returns `None` when nothing changed since the latest checkpoint.
+pub fn create_checkpoint(dir: &Path, session_key: &str) -> Result<Option<Checkpoint>> {
+    let top = toplevel(dir)?;
+    let head = git(&top, &["rev-parse", "--verify", "HEAD"], None)
+        .context("checkpoints need a commit to build on")?;
+    let tree = snapshot_tree(&top)?;
+
+    let existing = list_checkpoints(&top, session_key)?;
+    if existing.last().is_some_and(|c| c.tree == tree) {
+        return Ok(None);
+    }
+    let index = existing.last().map(|c| c.index + 1).unwrap_or(1);
+    let message = format!("synk checkpoint {index} (session {session_key})");
+    let mut trailers = format!("{WORKTREE_TRAILER}: {}", top.display());
+    if let Ok(branch) = git(&top, &["symbolic-ref", "--quiet", "--short", "HEAD"], None) {
+        trailers.push_str(&format!("\n{BRANCH_TRAILER}: {branch}"));
+    }
+    let commit = git(
+        &top,
+        &[
+            "commit-tree",
+            &tree,
+            "-p",
+            &head,
+            "-m",
+            &message,
+            "-m",
+            &trailers,
+        ],
+        None,
+    )?;
+    let ref_name = format!("{}{index}", session_ref_prefix(session_key));
+    git(&top, &["update-ref", &ref_name, &commit], None)?;
+
+    let excess = (existing.len() + 1).saturating_sub(MAX_CHECKPOINTS_PER_SESSION);
+    for old in existing.iter().take(excess) {
+        git(&top, &["update-ref", "-d", &old.ref_name], None)?;
+    }
+
+    Ok(Some(find_checkpoint(&top, session_key, index)?))
+}
+
+/// Deletes the checkpoint refs, of any session, whose worktree or branch no longer exists.
+/// Checkpoints that don't record where they were taken are kept. Returns how many went.
+pub fn drop_stale_checkpoints(repo: &Path) -> Result<usize> {
+    let text = git(
+        repo,
+        &[
+            "for-each-ref",
+            "--format=%(refname)%00%(trailers:only,unfold,separator=%x1F)",
+            CHECKPOINT_REF_ROOT,
+        ],
+        None,
+    )?;
+    let branches = git(
+        repo,
+        &["for-each-ref", "--format=%(refname:short)", "refs/heads"],
+        None,
+    )?;
+    let branches: Vec<&str> = branches.lines().collect();
+
+    let mut dropped = 0;
+    for line in text.lines() {
+        let Some((ref_name, trailers)) = line.split_once('\0') else {
+            continue;
+        };
+        let trailer = |key: &str| {
+            trailers
+                .split('\x1f')
+                .find_map(|t| t.strip_prefix(key)?.strip_prefix(": "))
+        };
+        let worktree_gone = trailer(WORKTREE_TRAILER).is_some_and(|w| !Path::new(w).is_dir());
+        let branch_gone = trailer(BRANCH_TRAILER).is_some_and(|b| !branches.contains(&b));
+        if worktree_gone || branch_gone {
+            git(repo, &["update-ref", "-d", ref_name], None)?;
+            dropped += 1;
+        }
+    }
+    Ok(dropped)
+}
+
+/// Changes from checkpoint `index` to checkpoint `against`, or to the current worktree.
+pub fn diff_checkpoint(
+    dir: &Path,
+    session_key: &str,
+    index: usize,
+    against: Option<usize>,
+) -> Result<Vec<FileDiff>> {
+    let top = toplevel(dir)?;
+    let from = find_checkpoint(&top, session_key, index)?.tree;
+    let to = match against {
+        Some(other) => find_checkpoint(&top, session_key, other)?.tree,
+        None => snapshot_tree(&top)?,
+    };
+    let raw = git(
+        &top,
+        &[
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            "--unified=3",
+            &from,
+            &to,
+        ],
+        None,
+    )?;
+    Ok(parse_unified_diff(&raw))
+}
+
+/// Puts the worktree's files back to checkpoint `index`. The current state is checkpointed
+/// first (returned, if it differed) so the restore itself can be undone.
+pub fn restore_checkpoint(
+    dir: &Path,
+    session_key: &str,
+    index: usize,
+) -> Result<Option<Checkpoint>> {
+    let top = toplevel(dir)?;
+    let target = find_checkpoint(&top, session_key, index)?;
+    let safety = create_checkpoint(&top, session_key)?;
+
+    // Files that exist now but not in the checkpoint have to go.
+    let current = snapshot_tree(&top)?;
+    let added = git(
+        &top,
+        &[
+            "diff",
+            "--name-only",
+            "--no-renames",
+            "--diff-filter=A",
+            "-z",
+            &target.tree,
+            &current,
+        ],
+        None,
+    )?;
+    for rel in added.split('\0').filter(|s| !s.is_empty()) {
+        let path = top.join(rel);
+        fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+    }
+
+    let index_file = TempIndex::empty()?;
+    git(&top, &["read-tree", &target.tree], Some(&index_file.path))?;
+    git(
+        &top,
+        &["checkout-index", "-a", "-f"],
+        Some(&index_file.path),
+    )?;
+    Ok(safety)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    use super::{
+        create_checkpoint, diff_checkpoint, drop_stale_checkpoints, git, list_checkpoints,
+        restore_checkpoint, TurnTracker, ECHO_WINDOW, TURN_IDLE_AFTER,
+    };
+    use crate::core::test_fixtures::{init_repo, TempDir};
+
+    fn temp_repo() -> TempDir {
+        let dir = TempDir::new("checkpoints");
+        init_repo(&dir).unwrap();
+        fs::write(dir.join("a.txt"), "one\n").unwrap();
+        git(&dir, &["add", "a.txt"], None).unwrap();
+        git(&dir, &["commit", "-q", "-m", "add a.txt"], None).unwrap();
+        dir
+    }
+
+    fn read(dir: &Path, name: &str) -> String {
+        fs::read_to_string(dir.join(name)).unwrap()
+    }
+
+    #[test]
+    fn checkpoint_and_restore_leave_branch_and_index_alone() {
+        let dir = temp_repo();
+        let head = git(&dir, &["rev-parse", "HEAD"], None).unwrap();
+
+        fs::write(dir.join("a.txt"), "two\n").unwrap();
+        fs::write(dir.join("b.txt"), "new\n").unwrap();
+        let first = create_checkpoint(&dir, "100-1")
+            .unwrap()
+            .expect("first checkpoint");
+        assert_eq!(first.index, 1);
+        assert_eq!(first.head, head);
+        assert!(
+            create_checkpoint(&dir, "100-1").unwrap().is_none(),
+            "unchanged tree"
+        );
+
+        fs::write(dir.join("a.txt"), "wrecked\n").unwrap();
+        fs::remove_file(dir.join("b.txt")).unwrap();
+        fs::write(dir.join("c.txt"), "junk\n").unwrap();
+        let diff = diff_checkpoint(&dir, "100-1", 1, None).unwrap();
+        let mut paths: Vec<&str> = diff.iter().map(|f| f.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(paths, ["a.txt", "b.txt", "c.txt"]);
+
+        let safety = restore_checkpoint(&dir, "100-1", 1)
+            .unwrap()
+            .expect("safety checkpoint");
+        assert_eq!(safety.index, 2);
+        assert_eq!(read(&dir, "a.txt"), "two\n");
+        assert_eq!(read(&dir, "b.txt"), "new\n");
+        assert!(!dir.join("c.txt").exists());
+
+        assert_eq!(git(&dir, &["rev-parse", "HEAD"], None).unwrap(), head);
+        assert_eq!(
+            git(&dir, &["diff", "--cached", "--name-only"], None).unwrap(),
+            ""
+        );
+        assert_eq!(list_checkpoints(&dir, "100-1").unwrap().len(), 2);
+        // The same run-local id in a later run gets a different key.
+        assert!(list_checkpoints(&dir, "200-1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn removed_worktrees_and_branches_take_their_checkpoints() {
+        let dir = temp_repo();
+        let worktree = dir.join("wt");
+        let wt = worktree.to_string_lossy().to_string();
+        git(
+            &dir,
+            &["worktree", "add", "-q", "-b", "wt-branch", &wt],
+            None,
+        )
+        .unwrap();
+        fs::write(worktree.join("a.txt"), "wt\n").unwrap();
+        create_checkpoint(&worktree, "1-1").unwrap().unwrap();
+
+        git(&dir, &["switch", "-q", "-c", "gone"], None).unwrap();
+        fs::write(dir.join("a.txt"), "gone\n").unwrap();
+        create_checkpoint(&dir, "2-1").unwrap().unwrap();
+        fs::write(dir.join("a.txt"), "one\n").unwrap();
+        git(&dir, &["switch", "-q", "main"], None).unwrap();
+
+        fs::write(dir.join("a.txt"), "main\n").unwrap();
+        create_checkpoint(&dir, "3-1").unwrap().unwrap();
+        assert_eq!(drop_stale_checkpoints(&dir).unwrap(), 0);
+
+        git(&dir, &["branch", "-q", "-D", "gone"], None).unwrap();
+        git(&dir, &["worktree", "remove", "--force", &wt], None).unwrap();
+        assert_eq!(drop_stale_checkpoints(&dir).unwrap(), 2);
+        assert!(list_checkpoints(&dir, "1-1").unwrap().is_empty());
+        assert!(list_checkpoints(&dir, "2-1").unwrap().is_empty());
+        assert_eq!(list_checkpoints(&dir, "3-1").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn turn_ends_once_after_quiet_period() {
+        let start = Instant::now();
+        let mut t = TurnTracker::default();
+        assert!(!t.turn_ended(start + TURN_IDLE_AFTER));
+
+        t.submitted(start);
+        let reply = start + ECHO_WINDOW;
+        assert!(t.output(reply));
+        assert!(!t.turn_ended(reply + Duration::from_millis(500)));
+        assert!(t.turn_ended(reply + TURN_IDLE_AFTER));
+        assert!(!t.turn_ended(reply + TURN_IDLE_AFTER * 2));
+    }
+
+    #[test]
+    fn banners_and_echo_are_not_turns() {
+        let start = Instant::now();
+        let mut t = TurnTracker::default();
+        assert!(!t.output(start), "output with no input");
+        assert!(!t.turn_ended(start + TURN_IDLE_AFTER));
+
+        t.submitted(start);
+        assert!(!t.output(start + Duration::from_millis(5)), "echo");
+        assert!(!t.turn_ended(start + TURN_IDLE_AFTER));
+
+        // After a turn ends, more output needs new input.
+        assert!(t.output(start + ECHO_WINDOW));
+        assert!(t.turn_ended(start + ECHO_WINDOW + TURN_IDLE_AFTER));
+        assert!(!t.output(start + TURN_IDLE_AFTER * 3));
+    }
+}
@@ -0,0 +1,586 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine;
+use serde_json::{json, Value};
+
+use crate::core::control_socket::{socket_path, EventLine, Response, SUBSCRIBE_COMMAND};
+use crate::core::session_labels::parse_agent;
+
+/// First arguments that make `synk` act as a client of the running app.
+pub const SUBCOMMANDS: &[&str] = &[
+    "ls", "new", "send", "tail", "merge", "review", "exec", "call", "help",
+];
+
+const USAGE: &str = "\
+usage: synk <command> [options]
+
+  ls                               list running sessions
+  new --agent <agent> [--branch <branch>] [--base <branch>] [--model <model>]
+      [--name <name>] [--tag <tag>]... [--group <group>]
+                                   start a session (in a worktree when --branch is given)
+  send <target> <text...>          type text + Enter into @all, @idle, @tag:x, 3, ...
+  tail <session> [--once]          print a session's output and follow it
+  merge <branch> [--strategy merge|squash|rebase] [--base <branch>]
+  review list                      list review items
+  exec \"<command bar line>\"        e.g. synk exec \"/snapshot save before-refactor\"
+  call <command> [json-args]       invoke any remote command directly
+  serve [--port <port>] [--lan]    run Synk headless (no window); see synk serve --help
+
+options:
+  --project <path>   project (default: $SYNK_PROJECT_PATH, else the current git repo)
+  --json             print raw JSON results
+";
+
+const VALUE_FLAGS: &[&str] = &[
+    "--agent",
+    "--branch",
+    "--base",
+    "--model",
+    "--name",
+    "--tag",
+    "--group",
+    "--project",
+    "--strategy",
+];
+
+pub fn is_subcommand(arg: &str) -> bool {
+    SUBCOMMANDS.contains(&arg)
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ParsedArgs {
+    positional: Vec<String>,
+    values: HashMap<String, Vec<String>>,
+    switches: Vec<String>,
+}
+
+impl ParsedArgs {
+    fn value(&self, flag: &str) -> Option<&str> {
+        self.values
+            .get(flag)
+            .and_then(|v| v.last())
+            .map(String::as_str)
+    }
+
+    fn all(&self, flag: &str) -> Vec<String> {
+        self.values.get(flag).cloned().unwrap_or_default()
+    }
+
+    fn switch(&self, flag: &str) -> bool {
+        self.switches.iter().any(|s| s == flag)
+    }
+}
+
+/// Flags may appear anywhere; `--` ends flag parsing so text can start with `--`.
+fn parse_args(args: &[String]) -> Result<ParsedArgs> {
+    let mut parsed = ParsedArgs::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            parsed.positional.extend(iter.by_ref().cloned());
+            break;
+        }
+        if let Some((flag, value)) = arg.split_once('=').filter(|(f, _)| f.starts_with("--")) {
+            if !VALUE_FLAGS.contains(&flag) {
+                bail!("unknown option {flag}");
+            }
+            parsed
+                .values
+                .entry(flag.to_string())
+                .or_default()
+                .push(value.to_string());
+        } else if VALUE_FLAGS.contains(&arg.as_str()) {
+            let value = iter.next().ok_or_else(|| anyhow!("{arg} needs a value"))?;
+            parsed
+                .values
+                .entry(arg.clone())
+                .or_default()
+                .push(value.clone());
+        } else if arg.starts_with("--") && arg.len() > 2 {
+            parsed.switches.push(arg.clone());
+        } else {
+            parsed.positional.push(arg.clone());
+        }
+    }
+    Ok(parsed)
+}
+
+/// `--project`, else `$SYNK_PROJECT_PATH` (set inside Synk sessions), else the main checkout
+/// of the git repo around the cwd (so it also works from a worktree), else the cwd.
+fn project_path(parsed: &ParsedArgs) -> String {
+    if let Some(p) = parsed.value("--project") {
+        return absolute(Path::new(p));
+    }
+    if let Some(p) = std::env::var("SYNK_PROJECT_PATH")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+    {
+        return p;
+    }
+    let cwd = std::env::current_dir().unwrap_or_else(|_| ".".into());
+    let common_dir = std::process::Command::new("git")
+        .args(["rev-parse", "--path-format=absolute", "--git-common-dir"])
+        .current_dir(&cwd)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+    match common_dir {
+        Some(dir) => Path::new(&dir)
+            .parent()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or(dir),
+        None => cwd.to_string_lossy().to_string(),
+    }
+}
+
+fn absolute(p: &Path) -> String {
+    std::fs::canonicalize(p)
+        .unwrap_or_else(|_| p.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(unix)]
+struct Client {
+    reader: std::io::BufReader<std::os::unix::net::UnixStream>,
+    writer: std::os::unix::net::UnixStream,
+    next_id: u64,
+}
+
+#[cfg(unix)]
+impl Client {
+    fn connect() -> Result<Self> {
+        let path = socket_path();
+        let stream = std::os::unix::net::UnixStream::connect(&path)
+            .with_context(|| format!("connect to {} (is Synk running?)", path.display()))?;
+        Ok(Self {
+            writer: stream.try_clone().context("clone socket")?,
+            reader: std::io::BufReader::new(stream),
+            next_id: 1,
+        })
+    }
+
+    fn send(&mut self, command: &str, args: Value) -> Result<Response> {
+        use std::io::BufRead;
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let line = json!({ "id": id, "command": command, "args": args });
+        writeln!(self.writer, "{line}").context("send request")?;
+        let mut reply = String::new();
+        if self.reader.read_line(&mut reply).context("read response")? == 0 {
+            bail!("Synk closed the connection");
+        }
+        serde_json::from_str(&reply).context("parse response")
+    }
+
+    fn call(&mut self, command: &str, args: Value) -> Result<Value> {
+        let response = self.send(command, args)?;
+        if !response.ok {
+            bail!(
+                "{}",
+                response.error.unwrap_or_else(|| "request failed".into())
+            );
+        }
+        Ok(response.result.unwrap_or(Value::Null))
+    }
+
+    /// Turns this connection into an event stream; read it with [`Client::next_event`].
+    fn subscribe(&mut self, events: &[&str], session_id: Option<usize>) -> Result<()> {
+        self.call(
+            SUBSCRIBE_COMMAND,
+            json!({ "events": events, "sessionId": session_id }),
+        )
+        .map(|_| ())
+    }
+
+    fn next_event(&mut self) -> Result<Option<EventLine>> {
+        use std::io::BufRead;
+
+        let mut line = String::new();
+        if self.reader.read_line(&mut line).context("read event")? == 0 {
+            return Ok(None);
+        }
+        serde_json::from_str(&line).map(Some).context("parse event")
+    }
+}
+
+/// Entry point for `synk <subcommand> ...`; `args` starts at the subcommand.
+pub fn main_from_args(args: &[String]) -> i32 {
+    match run(args) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("synk: {err:#}");
+            1
+        }
+    }
+}
+
+fn print_json(v: &Value) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(v)?);
+    Ok(())
+}
+
+fn str_field<'a>(v: &'a Value, key: &str) -> &'a str {
+    v.get(key).and_then(Value::as_str).unwrap_or("")
+}
+
+#[cfg(not(unix))]
+fn run(_args: &[String]) -> Result<i32> {
+    bail!("the synk CLI needs a unix platform")
+}
+
+#[cfg(unix)]
+fn run(args: &[String]) -> Result<i32> {
+    let Some((sub, rest)) = args.split_first() else {
+        eprint!("{USAGE}");
+        return Ok(2);
+    };
+    let parsed = parse_args(rest)?;
+    let json_out = parsed.switch("--json");
+    let pos = &parsed.positional;
+
+    match sub.as_str() {
+        "help" => {
+            print!("{USAGE}");
+            Ok(0)
+        }
+        "ls" => {
+            let sessions = Client::connect()?.call("session_list", Value::Null)?;
+            if json_out {
+                print_json(&sessions)?;
+                return Ok(0);
+            }
+            print!("{}", format_sessions(&sessions));
+            Ok(0)
+        }
+        "new" => {
+            let agent_arg = parsed
+                .value("--agent")
+                .ok_or_else(|| anyhow!("usage: synk new --agent <agent> [--branch <branch>]"))?;
+            let agent =
+                parse_agent(agent_arg).ok_or_else(|| anyhow!("unknown agent {agent_arg:?}"))?;
+            let project = project_path(&parsed);
+            let mut client = Client::connect()?;
+            let (working_dir, branch) = match parsed.value("--branch") {
+                Some(branch) => {
+                    let wt = client.call(
+                        "git_ensure_worktree",
+                        json!({
+                            "projectPath": project,
+                            "branch": branch,
+                            "baseBranch": parsed.value("--base"),
+                        }),
+                    )?;
+                    (
+                        Some(str_field(&wt, "worktreePath").to_string()),
+                        Some(str_field(&wt, "branch").to_string()),
+                    )
+                }
+                None => (None, None),
+            };
+            let created = client.call(
+                "session_create",
+                json!({
+                    "agentType": agent,
+                    "projectPath": project,
+                    "branch": branch,
+                    "workingDir": working_dir,
+                    "model": parsed.value("--model"),
+                    "name": parsed.value("--name"),
+                    "tags": parsed.all("--tag"),
+                    "group": parsed.value("--group"),
+                }),
+            )?;
+            if json_out {
+                print_json(&created)?;
+            } else {
+                println!(
+                    "session {} (pane {})",
+                    created.get("sessionId").unwrap_or(&Value::Null),
+                    created.get("paneIndex").unwrap_or(&Value::Null)
+                );
+            }
+            Ok(0)
+        }
+        "send" => {
+            let (Some(target), true) = (pos.first(), pos.len() > 1) else {
+                bail!("usage: synk send <target> <text...>");
+            };
+            let result = Client::connect()?.call(
+                "session_broadcast",
+                json!({ "target": target, "data": format!("{}\r", pos[1..].join(" ")) }),
+            )?;
+            if json_out {
+                print_json(&result)?;
+            }
+            let mut failed = false;
+            for d in result
+                .get("deliveries")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                if d.get("ok").and_then(Value::as_bool) != Some(true) {
+                    failed = true;
+                    eprintln!(
+                        "synk: session {}: {}",
+                        d.get("sessionId").unwrap_or(&Value::Null),
+                        str_field(d, "error")
+                    );
+                }
+            }
+            Ok(i32::from(failed))
+        }
+        "tail" => {
+            let id: usize = pos
+                .first()
+                .ok_or_else(|| anyhow!("usage: synk tail <session> [--once]"))?
+                .trim_start_matches('#')
+                .parse()
+                .context("session must be a numeric id")?;
+            tail(id, parsed.switch("--once"))
+        }
+        "merge" => {
+            let branch = pos
+                .first()
+                .ok_or_else(|| anyhow!("usage: synk merge <branch> [--strategy s] [--base b]"))?;
+            let mut line = format!("/merge {branch}");
+            if let Some(s) = parsed.value("--strategy") {
+                line.push_str(&format!(" {s}"));
+            }
+            if let Some(b) = parsed.value("--base") {
+                line.push_str(&format!(" into {b}"));
+            }
+            let outcome = Client::connect()?.call(
+                "command_execute",
+                json!({ "projectPath": project_path(&parsed), "input": line }),
+            )?;
+            if json_out {
+                print_json(&outcome)?;
+            }
+            let result = outcome.get("result").cloned().unwrap_or(Value::Null);
+            if result.get("success").and_then(Value::as_bool) == Some(true) {
+                if !json_out {
+                    println!("merged {branch}");
+                }
+                return Ok(0);
+            }
+            let files: Vec<&str> = result
+                .get("conflictFiles")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            eprintln!(
+                "synk: merge of {branch} has conflicts: {}",
+                files.join(", ")
+            );
+            Ok(1)
+        }
+        "review" => {
+            if pos.first().map(String::as_str) != Some("list") {
+                bail!("usage: synk review list");
+            }
+            let items = Client::connect()?.call(
+                "review_list",
+                json!({ "projectPath": project_path(&parsed) }),
+            )?;
+            if json_out {
+                print_json(&items)?;
+                return Ok(0);
+            }
+            print!("{}", format_reviews(&items));
+            Ok(0)
+        }
+        "exec" => {
+            if pos.is_empty() {
+                bail!("usage: synk exec \"<command bar line>\"");
+            }
+            let outcome = Client::connect()?.call(
+                "command_execute",
+                json!({ "projectPath": project_path(&parsed), "input": pos.join(" ") }),
+            )?;
+            print_json(&outcome)?;
+            Ok(0)
+        }
+        "call" => {
+            let command = pos
+                .first()
+                .ok_or_else(|| anyhow!("usage: synk call <command> [json-args]"))?;
+            let args = match pos.get(1) {
+                Some(raw) => serde_json::from_str(raw).context("args must be JSON")?,
+                None => Value::Null,
+            };
+            print_json(&Client::connect()?.call(command, args)?)?;
+            Ok(0)
+        }
+        other => bail!("unknown command {other:?}\n\n{USAGE}"),
+    }
+}
+
+/// Prints the scrollback, then streams live output until the session exits.
+#[cfg(unix)]
+fn tail(session_id: usize, once: bool) -> Result<i32> {
+    let b64 = base64::engine::general_purpose::STANDARD;
+    let mut out = std::io::stdout();
+
+    // Subscribe before reading the scrollback so nothing falls in between.
+    let mut events = if once {
+        None
+    } else {
+        let mut c = Client::connect()?;
+        c.subscribe(&["session:output", "session:exit"], Some(session_id))?;
+        Some(c)
+    };
+
+    let scrollback =
+        Client::connect()?.call("session_scrollback", json!({ "sessionId": session_id }))?;
+    out.write_all(&b64.decode(str_field(&scrollback, "dataB64"))?)?;
+    out.flush()?;
+
+    let Some(events) = events.as_mut() else {
+        return Ok(0);
+    };
+    while let Some(line) = events.next_event()? {
+        match line.event.as_str() {
+            "session:output" => {
+                out.write_all(&b64.decode(str_field(&line.payload, "dataB64"))?)?;
+                out.flush()?;
+            }
+            "session:exit" => return Ok(0),
+            _ => {}
+        }
+    }
+    Ok(0)
+}
+
+fn format_sessions(sessions: &Value) -> String {
+    let rows: Vec<[String; 6]> = sessions
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|s| {
+            let tags: Vec<&str> = s
+                .get("tags")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            [
+                s.get("sessionId").map(Value::to_string).unwrap_or_default(),
+                s.get("paneIndex").map(Value::to_string).unwrap_or_default(),
+                str_field(s, "agentType").to_string(),
+                str_field(s, "branch").to_string(),
+                str_field(s, "name").to_string(),
+                tags.join(","),
+            ]
+        })
+        .collect();
+    if rows.is_empty() {
+        return "no sessions\n".to_string();
+    }
+    table(["ID", "PANE", "AGENT", "BRANCH", "NAME", "TAGS"], rows)
+}
+
+fn format_reviews(items: &Value) -> String {
+    let rows: Vec<[String; 5]> = items
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|r| {
+            [
+                str_field(r, "id").to_string(),
+                str_field(r, "status").to_string(),
+                str_field(r, "branch").to_string(),
+                str_field(r, "baseBranch").to_string(),
+                format!(
+                    "+{} -{}",
+                    r.get("additions").and_then(Value::as_u64).unwrap_or(0),
+                    r.get("deletions").and_then(Value::as_u64).unwrap_or(0)
+                ),
+            ]
+        })
+        .collect();
+    if rows.is_empty() {
+        return "no reviews\n".to_string();
+    }
+    table(["ID", "STATUS", "BRANCH", "BASE", "CHANGES"], rows)
+}
+
+fn table<const N: usize>(header: [&str; N], rows: Vec<[String; N]>) -> String {
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+    let mut out = String::new();
+    let mut push_row = |cells: Vec<&str>| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(c, w)| format!("{c:<w$}"))
+            .collect();
+        out.push_str(line.join("  ").trim_end());
+        out.push('\n');
+    };
+    push_row(header.to_vec());
+    for row in &rows {
+        push_row(row.iter().map(String::as_str).collect());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{format_sessions, parse_args};
+
+    fn argv(s: &[&str]) -> Vec<String> {
+        s.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_flags_anywhere() {
+        let p = parse_args(&argv(&[
+            "--agent",
+            "codex",
+            "x",
+            "--tag=a",
+            "--tag",
+            "b",
+            "--json",
+            "--",
+            "--literal",
+        ]))
+        .unwrap();
+        assert_eq!(p.value("--agent"), Some("codex"));
+        assert_eq!(p.all("--tag"), vec!["a", "b"]);
+        assert!(p.switch("--json"));
+        assert_eq!(p.positional, vec!["x", "--literal"]);
+        assert!(parse_args(&argv(&["--branch"])).is_err());
+        assert!(parse_args(&argv(&["--bogus=1"])).is_err());
+    }
+
+    #[test]
+    fn formats_session_table() {
+        let out = format_sessions(&json!([
+            {"sessionId": 3, "paneIndex": 0, "agentType": "codex", "branch": "feat/x",
+             "tags": ["api"]},
+            {"sessionId": 12, "paneIndex": 1, "agentType": "claude_code", "name": "reviewer"}
+        ]));
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "ID  PANE  AGENT        BRANCH  NAME      TAGS");
+        assert_eq!(lines[1], "3   0     codex        feat/x            api");
+        assert_eq!(lines[2], "12  1     claude_code          reviewer");
+        assert_eq!(format_sessions(&json!([])), "no sessions\n");
+    }
+}
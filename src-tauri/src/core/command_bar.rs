@@ -0,0 +1,554 @@
+use anyhow::{anyhow, bail, Result};
+use serde::Serialize;
+
+use crate::core::agent_detection::AgentType;
+use crate::core::git_manager::MergeStrategy;
+use crate::core::session_labels::{parse_agent, SessionSelector};
+use crate::core::session_manager::SessionInfo;
+
+/// A parsed command-bar line.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BarCommand {
+    /// `/new claude [@feat/auth]`
+    New {
+        agent: AgentType,
+        branch: Option<String>,
+    },
+    /// `/send @idle "run tests"`
+    Send { target: String, text: String },
+    /// `/close @tag:scratch`
+    Close { target: String },
+    /// `/merge feat/x [squash] [into main]`
+    Merge {
+        branch: String,
+        strategy: Option<MergeStrategy>,
+        base: Option<String>,
+    },
+    /// `/snapshot save nightly`
+    SnapshotSave { name: String },
+    /// `/snapshot list`
+    SnapshotList,
+    /// `/localhost start web` (spec id or label)
+    LocalhostStart { spec: String },
+    /// `/localhost stop web`
+    LocalhostStop { spec: String },
+}
+
+const COMMANDS: &[(&str, &str)] = &[
+    ("/new", "<agent> [@branch]  start a session"),
+    ("/send", "<target> <text>  type text into sessions"),
+    ("/close", "<target>  close sessions"),
+    ("/merge", "<branch> [merge|squash|rebase] [into <base>]"),
+    ("/snapshot", "save <name> | list"),
+    ("/localhost", "start|stop <spec>"),
+];
+
+/// Agent keywords accepted by `/new` (and `@agent:`), in suggestion order.
+const AGENTS: &[(&str, AgentType)] = &[
+    ("claude", AgentType::ClaudeCode),
+    ("gemini", AgentType::GeminiCli),
+    ("codex", AgentType::Codex),
+    ("openrouter", AgentType::Openrouter),
+    ("terminal", AgentType::Terminal),
+];
+
+const STRATEGIES: &[&str] = &["merge", "squash", "rebase"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Token {
+    text: String,
+    /// Byte offset of the token's first character (its opening quote, if quoted).
+    start: usize,
+}
+
+fn push_char(current: &mut Option<Token>, start: usize, c: Option<char>) {
+    let token = current.get_or_insert_with(|| Token {
+        text: String::new(),
+        start,
+    });
+    token.text.extend(c);
+}
+
+/// Shell-like split: whitespace separates, `"..."` / `'...'` group, `\` escapes inside double
+/// quotes. Also reports whether the input ends inside an open quote.
+fn tokenize(input: &str) -> (Vec<Token>, bool) {
+    let mut tokens = Vec::new();
+    let mut current: Option<Token> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = input.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some('"') if c == '\\' => push_char(&mut current, i, chars.next().map(|(_, n)| n)),
+            Some(_) => push_char(&mut current, i, Some(c)),
+            None if c.is_whitespace() => tokens.extend(current.take()),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                push_char(&mut current, i, None);
+            }
+            None => push_char(&mut current, i, Some(c)),
+        }
+    }
+    tokens.extend(current);
+    (tokens, quote.is_some())
+}
+
+fn parse_strategy(s: &str) -> Option<MergeStrategy> {
+    match s.to_lowercase().as_str() {
+        "merge" => Some(MergeStrategy::Merge),
+        "squash" => Some(MergeStrategy::Squash),
+        "rebase" => Some(MergeStrategy::Rebase),
+        _ => None,
+    }
+}
+
+fn check_target(target: Option<&String>) -> Result<String> {
+    let target = target.ok_or_else(|| anyhow!("missing target (e.g. @idle or 3)"))?;
+    SessionSelector::parse_list(target)?;
+    Ok(target.clone())
+}
+
+/// The text of `/send`: the rest of the line exactly as typed, or the contents of a single
+/// quoted argument.
+fn send_text(input: &str, rest: &[Token]) -> String {
+    match rest {
+        [] => String::new(),
+        [only] if input[only.start..].starts_with(['"', '\'']) => only.text.clone(),
+        [first, ..] => input[first.start..]
+            .trim_end_matches(['\r', '\n'])
+            .to_string(),
+    }
+}
+
+pub fn parse(input: &str) -> Result<BarCommand> {
+    let (tokens, open_quote) = tokenize(input);
+    if open_quote {
+        bail!("unterminated quote");
+    }
+    let mut words = tokens.iter().map(|t| t.text.clone());
+    let head = words.next().ok_or_else(|| anyhow!("empty command"))?;
+    let args: Vec<String> = words.collect();
+    let extra = |n: usize| -> Result<()> {
+        match args.get(n) {
+            Some(w) => Err(anyhow!("unexpected argument {w:?} for {head}")),
+            None => Ok(()),
+        }
+    };
+
+    match head.as_str() {
+        "/new" => {
+            let word = args
+                .first()
+                .ok_or_else(|| anyhow!("usage: /new <agent> [@branch]"))?;
+            let agent = parse_agent(word).ok_or_else(|| anyhow!("unknown agent {word:?}"))?;
+            let branch = match args.get(1) {
+                Some(b) => Some(
+                    b.strip_prefix('@')
+                        .filter(|b| !b.is_empty())
+                        .ok_or_else(|| anyhow!("branch must be written as @<branch>"))?
+                        .to_string(),
+                ),
+                None => None,
+            };
+            extra(2)?;
+            Ok(BarCommand::New { agent, branch })
+        }
+        "/send" => {
+            let target = check_target(args.first())?;
+            let text = send_text(input, &tokens[2..]);
+            if text.trim().is_empty() {
+                bail!("usage: /send <target> <text>");
+            }
+            Ok(BarCommand::Send { target, text })
+        }
+        "/close" => {
+            let target = check_target(args.first())?;
+            extra(1)?;
+            Ok(BarCommand::Close { target })
+        }
+        "/merge" => {
+            let branch = args
+                .first()
+                .ok_or_else(|| anyhow!("usage: /merge <branch> [strategy] [into <base>]"))?
+                .clone();
+            let mut rest = &args[1..];
+            let strategy = rest.first().and_then(|s| parse_strategy(s));
+            if strategy.is_some() {
+                rest = &rest[1..];
+            }
+            let base = match rest {
+                [] => None,
+                [into, base] if into == "into" => Some(base.clone()),
+                [w, ..] => bail!("unexpected argument {w:?} for /merge"),
+            };
+            Ok(BarCommand::Merge {
+                branch,
+                strategy,
+                base,
+            })
+        }
+        "/snapshot" => match args.first().map(String::as_str) {
+            Some("save") => {
+                let name = args[1..].join(" ");
+                if name.trim().is_empty() {
+                    bail!("usage: /snapshot save <name>");
+                }
+                Ok(BarCommand::SnapshotSave { name })
+            }
+            Some("list") => {
+                extra(1)?;
+                Ok(BarCommand::SnapshotList)
+            }
+            _ => bail!("usage: /snapshot save <name> | /snapshot list"),
+        },
+        "/localhost" => {
+            let (Some(action), Some(spec)) = (args.first(), args.get(1)) else {
+                bail!("usage: /localhost start|stop <spec>");
+            };
+            extra(2)?;
+            let spec = spec.clone();
+            match action.as_str() {
+                "start" => Ok(BarCommand::LocalhostStart { spec }),
+                "stop" => Ok(BarCommand::LocalhostStop { spec }),
+                other => bail!("unknown localhost action {other:?}"),
+            }
+        }
+        other => bail!("unknown command {other:?}"),
+    }
+}
+
+/// A session `/close` could not close.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloseFailure {
+    pub session_id: usize,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloseResult {
+    pub session_ids: Vec<usize>,
+    pub failed: Vec<CloseFailure>,
+}
+
+/// Runs `destroy` for every id so one failure does not leave the rest open.
+pub fn close_each(ids: Vec<usize>, mut destroy: impl FnMut(usize) -> Result<()>) -> CloseResult {
+    let mut result = CloseResult::default();
+    for session_id in ids {
+        match destroy(session_id) {
+            Ok(()) => result.session_ids.push(session_id),
+            Err(err) => result.failed.push(CloseFailure {
+                session_id,
+                error: format!("{err:#}"),
+            }),
+        }
+    }
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompletionKind {
+    Command,
+    Agent,
+    Branch,
+    Target,
+    Strategy,
+    Keyword,
+    Snapshot,
+    Localhost,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Completion {
+    /// Text that replaces the word being completed.
+    pub value: String,
+    pub kind: CompletionKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionResult {
+    /// Byte offset in the input where the completed word starts.
+    pub replace_from: usize,
+    pub items: Vec<Completion>,
+}
+
+/// Everything completion can suggest, gathered by the caller from the live managers.
+#[derive(Debug, Default)]
+pub struct CompletionSources {
+    pub branches: Vec<String>,
+    /// `(target, detail)`, see [`session_targets`].
+    pub targets: Vec<(String, String)>,
+    pub snapshots: Vec<String>,
+    /// `(spec id, label)`
+    pub localhost: Vec<(String, String)>,
+}
+
+/// Selector suggestions for the given sessions: ids, names, tags, groups and the built-ins.
+pub fn session_targets(sessions: &[SessionInfo]) -> Vec<(String, String)> {
+    let mut out: Vec<(String, String)> = vec![
+        ("@all".into(), "every session".into()),
+        ("@idle".into(), "sessions waiting for input".into()),
+        ("@active".into(), "sessions mid-turn".into()),
+    ];
+    let mut push = |value: String, detail: String| {
+        if !out.iter().any(|(v, _)| *v == value) {
+            out.push((value, detail));
+        }
+    };
+    for s in sessions {
+        let what = format!(
+            "{} on {}",
+            s.agent_type.display_name(),
+            s.branch.as_deref().unwrap_or("-")
+        );
+        push(s.session_id.to_string(), what.clone());
+        if let Some(name) = &s.labels.name {
+            if !name.contains([' ', ',']) {
+                push(format!("@{name}"), what.clone());
+            }
+        }
+        for tag in &s.labels.tags {
+            push(format!("@tag:{tag}"), "tag".into());
+        }
+        if let Some(group) = &s.labels.group {
+            push(format!("@group:{group}"), "group".into());
+        }
+        let agent = AGENTS
+            .iter()
+            .find(|(_, a)| *a == s.agent_type)
+            .map(|(k, _)| *k);
+        if let Some(agent) = agent {
+            push(
+                format!("@agent:{agent}"),
+                s.agent_type.display_name().into(),
+            );
+        }
+    }
+    out
+}
+
+/// Suggestions for the word under the cursor, which is taken to be the end of `input`.
+pub fn complete(input: &str, sources: &CompletionSources) -> CompletionResult {
+    let (tokens, open_quote) = tokenize(input);
+    let at_new_word = !open_quote && input.ends_with(char::is_whitespace);
+    let (index, prefix, replace_from) = match tokens.last() {
+        Some(last) if !at_new_word => (tokens.len() - 1, last.text.as_str(), last.start),
+        _ => (tokens.len(), "", input.len()),
+    };
+    let word = |i: usize| tokens.get(i).map(|t| t.text.as_str());
+
+    let mut items: Vec<Completion> = Vec::new();
+    let mut add = |kind: CompletionKind, value: &str, detail: Option<&str>| {
+        if value.to_lowercase().starts_with(&prefix.to_lowercase()) {
+            items.push(Completion {
+                value: value.to_string(),
+                kind,
+                detail: detail.map(str::to_string),
+            });
+        }
+    };
+    let branches = |add: &mut dyn FnMut(CompletionKind, &str, Option<&str>), at: bool| {
+        for b in &sources.branches {
+            let v = if at { format!("@{b}") } else { b.clone() };
+            add(CompletionKind::Branch, &v, None);
+        }
+    };
+
+    match (word(0), index) {
+        (_, 0) => {
+            for (c, detail) in COMMANDS {
+                add(CompletionKind::Command, c, Some(detail));
+            }
+        }
+        (Some("/new"), 1) => {
+            for (k, agent) in AGENTS {
+                add(CompletionKind::Agent, k, Some(agent.display_name()));
+            }
+        }
+        (Some("/new"), 2) => branches(&mut add, true),
+        (Some("/send" | "/close"), 1) => {
+            for (t, detail) in &sources.targets {
+                add(CompletionKind::Target, t, Some(detail));
+            }
+        }
+        (Some("/merge"), 1) => branches(&mut add, false),
+        (Some("/merge"), 2) => {
+            for s in STRATEGIES {
+                add(CompletionKind::Strategy, s, None);
+            }
+            add(CompletionKind::Keyword, "into", None);
+        }
+        (Some("/merge"), 3) if word(2).is_some_and(|w| parse_strategy(w).is_some()) => {
+            add(CompletionKind::Keyword, "into", None)
+        }
+        (Some("/merge"), 3 | 4) if word(index - 1) == Some("into") => branches(&mut add, false),
+        (Some("/snapshot"), 1) => {
+            add(CompletionKind::Keyword, "save", None);
+            add(CompletionKind::Keyword, "list", None);
+        }
+        (Some("/snapshot"), 2) if word(1) == Some("save") => {
+            for s in &sources.snapshots {
+                add(CompletionKind::Snapshot, s, Some("overwrite"));
+            }
+        }
+        (Some("/localhost"), 1) => {
+            add(CompletionKind::Keyword, "start", None);
+            add(CompletionKind::Keyword, "stop", None);
+        }
+        (Some("/localhost"), 2) => {
+            for (id, label) in &sources.localhost {
+                add(CompletionKind::Localhost, id, Some(label));
+            }
+        }
+        _ => {}
+    }
+    CompletionResult {
+        replace_from,
+        items,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        close_each, complete, parse, tokenize, BarCommand, CloseFailure, CompletionKind,
+        CompletionSources,
+    };
+    use crate::core::agent_detection::AgentType;
+    use crate::core::git_manager::MergeStrategy;
+
+    #[test]
+    fn tokenizes_quotes_and_escapes() {
+        let (tokens, open) = tokenize(r#"/send @idle "run \"all\" tests" 'x y'"#);
+        assert!(!open);
+        let words: Vec<&str> = tokens.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(words, ["/send", "@idle", r#"run "all" tests"#, "x y"]);
+        assert_eq!(tokens[2].start, 12);
+        assert!(tokenize("/send 1 \"open").1);
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(
+            parse("/new claude @feat/auth").unwrap(),
+            BarCommand::New {
+                agent: AgentType::ClaudeCode,
+                branch: Some("feat/auth".into())
+            }
+        );
+        assert_eq!(
+            parse(r#"/send @idle "run tests""#).unwrap(),
+            BarCommand::Send {
+                target: "@idle".into(),
+                text: "run tests".into()
+            }
+        );
+        assert!(matches!(
+            parse("/merge feat/x squash").unwrap(),
+            BarCommand::Merge {
+                strategy: Some(MergeStrategy::Squash),
+                base: None,
+                ..
+            }
+        ));
+        assert!(matches!(
+            parse("/merge feat/x into develop").unwrap(),
+            BarCommand::Merge { strategy: None, base: Some(b), .. } if b == "develop"
+        ));
+        assert_eq!(
+            parse("/snapshot save nightly").unwrap(),
+            BarCommand::SnapshotSave {
+                name: "nightly".into()
+            }
+        );
+        assert_eq!(
+            parse("/localhost stop web").unwrap(),
+            BarCommand::LocalhostStop { spec: "web".into() }
+        );
+
+        for bad in [
+            "",
+            "/new vim",
+            "/new claude feat/auth",
+            "/send @idle",
+            "/send idle hi",
+            "/close 1 2",
+            "/merge feat/x sideways",
+            "/snapshot",
+            "/send 1 \"open",
+            "/frobnicate",
+        ] {
+            assert!(parse(bad).is_err(), "{bad:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn send_keeps_the_text_as_typed() {
+        let text = |input: &str| match parse(input).unwrap() {
+            BarCommand::Send { text, .. } => text,
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(text("/send 1 git  log  -n 3"), "git  log  -n 3");
+        assert_eq!(text(r#"/send 1 echo "a  b" 'c'"#), r#"echo "a  b" 'c'"#);
+        assert_eq!(text("/send 1 'say \"hi\"'"), r#"say "hi""#);
+        assert_eq!(text("/send @idle go \n"), "go ");
+    }
+
+    #[test]
+    fn close_attempts_every_session() {
+        let mut attempted = Vec::new();
+        let result = close_each(vec![1, 2, 3], |id| {
+            attempted.push(id);
+            anyhow::ensure!(id != 2, "pty gone");
+            Ok(())
+        });
+        assert_eq!(attempted, [1, 2, 3]);
+        assert_eq!(result.session_ids, [1, 3]);
+        assert_eq!(
+            result.failed,
+            [CloseFailure {
+                session_id: 2,
+                error: "pty gone".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn completes_by_position() {
+        let sources = CompletionSources {
+            branches: vec!["feat/auth".into(), "main".into()],
+            targets: vec![
+                ("@idle".into(), String::new()),
+                ("@tag:ui".into(), String::new()),
+            ],
+            ..Default::default()
+        };
+        let values = |input: &str| -> Vec<String> {
+            complete(input, &sources)
+                .items
+                .into_iter()
+                .map(|c| c.value)
+                .collect()
+        };
+
+        assert_eq!(values("/s"), ["/send", "/snapshot"]);
+        assert_eq!(values("/new cl"), ["claude"]);
+        assert_eq!(values("/new claude @f"), ["@feat/auth"]);
+        assert_eq!(values("/send @t"), ["@tag:ui"]);
+        assert_eq!(
+            values("/merge feat/auth "),
+            ["merge", "squash", "rebase", "into"]
+        );
+        assert_eq!(values("/merge feat/auth squash into m"), ["main"]);
+
+        let r = complete("/merge ma", &sources);
+        assert_eq!(r.replace_from, 7);
+        assert_eq!(r.items[0].kind, CompletionKind::Branch);
+    }
+}
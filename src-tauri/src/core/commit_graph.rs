@@ -0,0 +1,434 @@
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde::{Deserialize, Serialize};
+
+/// `git log --format` for [`parse_log`]: fields split by 0x1f, records ended by 0x1e.
+pub const LOG_FORMAT: &str = "--format=%H%x1f%P%x1f%an%x1f%ae%x1f%aI%x1f%cI%x1f%s%x1f%D%x1e";
+
+pub const DEFAULT_PAGE_SIZE: usize = 200;
+pub const MAX_PAGE_SIZE: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GitRefKind {
+    /// `HEAD`, detached or not.
+    Head,
+    Branch,
+    Remote,
+    Tag,
+    /// Anything else, e.g. checkpoint refs.
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitRef {
+    /// Short name: `main`, `origin/main`, `v1.2`.
+    pub name: String,
+    /// `refs/heads/main`, or `HEAD`.
+    pub full_name: String,
+    pub kind: GitRefKind,
+}
+
+impl GitRef {
+    pub fn from_full_name(full: &str) -> Self {
+        let (kind, name) = if full == "HEAD" {
+            (GitRefKind::Head, full)
+        } else if let Some(n) = full.strip_prefix("refs/heads/") {
+            (GitRefKind::Branch, n)
+        } else if let Some(n) = full.strip_prefix("refs/remotes/") {
+            (GitRefKind::Remote, n)
+        } else if let Some(n) = full.strip_prefix("refs/tags/") {
+            (GitRefKind::Tag, n)
+        } else {
+            (GitRefKind::Other, full)
+        };
+        Self {
+            name: name.to_string(),
+            full_name: full.to_string(),
+            kind,
+        }
+    }
+}
+
+/// How to draw one row of the graph. Columns are 0-based; lines run from a row to the next.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphRow {
+    /// Column of the commit's dot.
+    pub lane: usize,
+    /// Columns whose line from the row above ends at the dot (one per child drawn so far).
+    pub incoming: Vec<usize>,
+    /// Column each parent continues in below this row, in parent order.
+    pub parent_lanes: Vec<usize>,
+    /// Columns that pass straight through this row.
+    pub passthrough: Vec<usize>,
+    /// Columns needed to draw the row.
+    pub width: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitNode {
+    pub hash: String,
+    pub parents: Vec<String>,
+    pub author_name: String,
+    pub author_email: String,
+    /// RFC 3339.
+    pub author_date: String,
+    pub commit_date: String,
+    pub subject: String,
+    pub refs: Vec<GitRef>,
+    pub graph: GraphRow,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitLogPage {
+    /// Newest first (children before parents).
+    pub commits: Vec<CommitNode>,
+    /// Pass back for the next page; `None` at the end of history.
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitFileStatus {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+    Copied,
+    TypeChanged,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitFileChange {
+    pub path: String,
+    pub status: CommitFileStatus,
+    /// Source of a rename or copy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    /// Rename/copy similarity, 0-100.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<u8>,
+    /// `None` for binary files.
+    pub additions: Option<u32>,
+    pub deletions: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitRefs {
+    /// Refs pointing at the commit.
+    pub at: Vec<GitRef>,
+    /// Branches and tags whose history includes it.
+    pub containing: Vec<GitRef>,
+}
+
+/// Columns and the commit each one is waiting for, carried from one page to the next.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LaneState {
+    lanes: Vec<Option<String>>,
+}
+
+impl LaneState {
+    fn free_slot(&mut self) -> usize {
+        match self.lanes.iter().position(Option::is_none) {
+            Some(i) => i,
+            None => {
+                self.lanes.push(None);
+                self.lanes.len() - 1
+            }
+        }
+    }
+
+    /// Commits the open lanes are waiting for: the parents of commits already placed.
+    pub fn pending(&self) -> impl Iterator<Item = &str> {
+        self.lanes.iter().flatten().map(String::as_str)
+    }
+
+    fn position(&self, hash: &str) -> Option<usize> {
+        self.lanes.iter().position(|l| l.as_deref() == Some(hash))
+    }
+
+    /// Places the next commit (children come before parents) and points its lanes at the parents.
+    pub fn place(&mut self, hash: &str, parents: &[String]) -> GraphRow {
+        let width_before = self.lanes.len();
+        let mut incoming = Vec::new();
+        let mut passthrough = Vec::new();
+        for (i, l) in self.lanes.iter().enumerate() {
+            match l.as_deref() {
+                Some(h) if h == hash => incoming.push(i),
+                Some(_) => passthrough.push(i),
+                None => {}
+            }
+        }
+        for &i in &incoming {
+            self.lanes[i] = None;
+        }
+        let lane = match incoming.first() {
+            Some(&i) => i,
+            None => self.free_slot(),
+        };
+
+        let mut parent_lanes = Vec::with_capacity(parents.len());
+        for (n, parent) in parents.iter().enumerate() {
+            // A parent some other lane already waits for joins that lane.
+            let col = match self.position(parent) {
+                Some(i) => i,
+                None if n == 0 && self.lanes[lane].is_none() => lane,
+                None => self.free_slot(),
+            };
+            self.lanes[col] = Some(parent.clone());
+            parent_lanes.push(col);
+        }
+
+        let width = width_before.max(self.lanes.len()).max(lane + 1);
+        while self.lanes.last().is_some_and(Option::is_none) {
+            self.lanes.pop();
+        }
+        GraphRow {
+            lane,
+            incoming,
+            parent_lanes,
+            passthrough,
+            width,
+        }
+    }
+}
+
+/// Where the next page starts: the lanes after the last commit returned, and the starting tips
+/// not returned yet. History left to walk is exactly what those reach.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LogCursor {
+    pub tips: Vec<String>,
+    pub lanes: LaneState,
+}
+
+impl LogCursor {
+    /// Revisions for `git log` to resume from, without repeats.
+    pub fn starts(&self) -> Vec<&str> {
+        let tips = self.tips.iter().map(String::as_str);
+        let mut out: Vec<&str> = Vec::new();
+        for hash in self.lanes.pending().chain(tips) {
+            if !out.contains(&hash) {
+                out.push(hash);
+            }
+        }
+        out
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    /// Cursors come back from clients, so everything in one must be a plain object id before it
+    /// goes anywhere near `git log`'s arguments.
+    pub fn decode(s: &str) -> Result<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(s.trim()).context("invalid cursor")?;
+        let cursor: Self = serde_json::from_slice(&bytes).context("invalid cursor")?;
+        if let Some(bad) = cursor.starts().into_iter().find(|h| !is_object_id(h)) {
+            bail!("invalid cursor: {bad:?} is not a commit id");
+        }
+        Ok(cursor)
+    }
+}
+
+/// A full SHA-1 or SHA-256 object id in lowercase hex.
+fn is_object_id(s: &str) -> bool {
+    matches!(s.len(), 40 | 64) && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// `refs/remotes/<remote>/HEAD`, which only mirrors the remote's default branch.
+pub fn is_remote_head(full: &str) -> bool {
+    full.starts_with("refs/remotes/") && full.ends_with("/HEAD")
+}
+
+/// Parses `%D` with `--decorate=full`, e.g. `HEAD -> refs/heads/main, tag: refs/tags/v1`.
+/// Remote `HEAD` symrefs are dropped.
+pub fn parse_decorations(s: &str) -> Vec<GitRef> {
+    let mut out = Vec::new();
+    for part in s.split(", ").map(str::trim).filter(|p| !p.is_empty()) {
+        let part = part.strip_prefix("tag: ").unwrap_or(part);
+        if let Some((head, target)) = part.split_once(" -> ") {
+            out.push(GitRef::from_full_name(head));
+            out.push(GitRef::from_full_name(target));
+        } else if !is_remote_head(part) {
+            out.push(GitRef::from_full_name(part));
+        }
+    }
+    out
+}
+
+/// Parses [`LOG_FORMAT`] output into commits without graph rows.
+pub fn parse_log(text: &str) -> Vec<CommitNode> {
+    text.split('\x1e')
+        .filter_map(|record| {
+            let record = record.trim_start_matches(['\n', '\r']);
+            let f: Vec<&str> = record.split('\x1f').collect();
+            if f.len() < 8 || f[0].is_empty() {
+                return None;
+            }
+            Some(CommitNode {
+                hash: f[0].to_string(),
+                parents: f[1].split_whitespace().map(str::to_string).collect(),
+                author_name: f[2].to_string(),
+                author_email: f[3].to_string(),
+                author_date: f[4].to_string(),
+                commit_date: f[5].to_string(),
+                subject: f[6].to_string(),
+                refs: parse_decorations(f[7].trim()),
+                graph: GraphRow::default(),
+            })
+        })
+        .collect()
+}
+
+fn file_status(code: &str) -> Result<(CommitFileStatus, Option<u8>)> {
+    let similarity = code.get(1..).and_then(|s| s.parse().ok());
+    let status = match code.chars().next() {
+        Some('A') => CommitFileStatus::Added,
+        Some('M') => CommitFileStatus::Modified,
+        Some('D') => CommitFileStatus::Deleted,
+        Some('R') => CommitFileStatus::Renamed,
+        Some('C') => CommitFileStatus::Copied,
+        Some('T') => CommitFileStatus::TypeChanged,
+        _ => bail!("unexpected diff status {code:?}"),
+    };
+    Ok((status, similarity))
+}
+
+/// Parses `git diff-tree --name-status -z`.
+pub fn parse_name_status(text: &str) -> Result<Vec<CommitFileChange>> {
+    let mut out = Vec::new();
+    let mut fields = text.split('\0').filter(|f| !f.is_empty());
+    while let Some(code) = fields.next() {
+        let (status, similarity) = file_status(code)?;
+        let first = fields.next().context("truncated name-status output")?;
+        let (old_path, path) = match status {
+            CommitFileStatus::Renamed | CommitFileStatus::Copied => {
+                let to = fields.next().context("truncated name-status output")?;
+                (Some(first.to_string()), to.to_string())
+            }
+            _ => (None, first.to_string()),
+        };
+        out.push(CommitFileChange {
+            path,
+            status,
+            old_path,
+            similarity,
+            additions: None,
+            deletions: None,
+        });
+    }
+    Ok(out)
+}
+
+/// Parses `git diff-tree --numstat -z` into (additions, deletions, path); counts are `None`
+/// for binary files.
+pub fn parse_numstat(text: &str) -> Vec<(Option<u32>, Option<u32>, String)> {
+    let mut out = Vec::new();
+    let mut fields = text.split('\0');
+    while let Some(head) = fields.next() {
+        let mut cols = head.splitn(3, '\t');
+        let (Some(a), Some(d), Some(path)) = (cols.next(), cols.next(), cols.next()) else {
+            continue;
+        };
+        // Renames and copies leave the path empty and follow with the old and new paths.
+        let path = if path.is_empty() {
+            let _old = fields.next();
+            fields.next().unwrap_or_default()
+        } else {
+            path
+        };
+        out.push((a.parse().ok(), d.parse().ok(), path.to_string()));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_decorations, parse_name_status, parse_numstat, GitRefKind, LaneState};
+    use super::{CommitFileStatus, LogCursor};
+
+    fn place(state: &mut LaneState, hash: &str, parents: &[&str]) -> (usize, Vec<usize>) {
+        let parents: Vec<String> = parents.iter().map(|p| p.to_string()).collect();
+        let row = state.place(hash, &parents);
+        (row.lane, row.parent_lanes)
+    }
+
+    #[test]
+    fn lanes_fork_and_merge() {
+        // m merges b into a; both branch from r.
+        let mut s = LaneState::default();
+        assert_eq!(place(&mut s, "m", &["a", "b"]), (0, vec![0, 1]));
+        assert_eq!(place(&mut s, "a", &["r"]), (0, vec![0]));
+        let row = s.place("b", &["r".to_string()]);
+        assert_eq!((row.lane, row.parent_lanes.clone()), (1, vec![0]));
+        assert_eq!(row.passthrough, vec![0]);
+        let row = s.place("r", &[]);
+        assert_eq!((row.lane, row.incoming), (0, vec![0]));
+        assert_eq!(s, LaneState::default());
+
+        // A second tip not reachable from the first gets its own column.
+        let mut s = LaneState::default();
+        assert_eq!(place(&mut s, "x", &["r"]), (0, vec![0]));
+        let row = s.place("y", &["q".to_string()]);
+        assert_eq!((row.lane, row.width), (1, 2));
+
+        let cursor = LogCursor {
+            tips: vec!["z".into(), "q".into()],
+            lanes: s,
+        };
+        assert_eq!(cursor.starts(), vec!["r", "q", "z"]);
+        // Not object ids, so not accepted back.
+        assert!(LogCursor::decode(&cursor.encode()).is_err());
+        assert!(LogCursor::decode("not a cursor").is_err());
+
+        let hash = |c: char| c.to_string().repeat(40);
+        let mut s = LaneState::default();
+        s.place(&hash('a'), &[hash('b')]);
+        let cursor = LogCursor {
+            tips: vec![hash('c')],
+            lanes: s,
+        };
+        assert_eq!(LogCursor::decode(&cursor.encode()).unwrap(), cursor);
+        let injected = LogCursor {
+            tips: vec!["--output=/tmp/x".to_string()],
+            ..LogCursor::default()
+        };
+        assert!(LogCursor::decode(&injected.encode()).is_err());
+    }
+
+    #[test]
+    fn parses_decorations_and_file_changes() {
+        let refs = parse_decorations(
+            "HEAD -> refs/heads/main, tag: refs/tags/v1, refs/remotes/origin/main, refs/remotes/origin/HEAD",
+        );
+        let kinds: Vec<(GitRefKind, &str)> =
+            refs.iter().map(|r| (r.kind, r.name.as_str())).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (GitRefKind::Head, "HEAD"),
+                (GitRefKind::Branch, "main"),
+                (GitRefKind::Tag, "v1"),
+                (GitRefKind::Remote, "origin/main"),
+            ]
+        );
+
+        let files =
+            parse_name_status("M\0a.txt\0R087\0old.rs\0new.rs\0C100\0a.txt\0b.txt\0").unwrap();
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[1].status, CommitFileStatus::Renamed);
+        assert_eq!(files[1].old_path.as_deref(), Some("old.rs"));
+        assert_eq!(files[1].similarity, Some(87));
+        assert_eq!(files[2].path, "b.txt");
+
+        let stats = parse_numstat("1\t2\ta.txt\0-\t-\t\0old.png\0new.png\0");
+        assert_eq!(stats[0], (Some(1), Some(2), "a.txt".to_string()));
+        assert_eq!(stats[1], (None, None, "new.png".to_string()));
+    }
+}
@@ -0,0 +1,537 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::core::app_context::{AppContext, EventId};
+use crate::core::audit_log::AuditActor;
+use crate::core::session_manager::SharedSessionManager;
+
+/// Overrides where the app listens and where `synk` connects.
+pub const SOCKET_ENV: &str = "SYNK_SOCKET";
+
+/// Pseudo-command that turns a connection into an event stream.
+pub const SUBSCRIBE_COMMAND: &str = "subscribe";
+
+/// Runs a named command with JSON args on behalf of an actor the transport identified; see
+/// `commands::remote::dispatch`.
+pub type CommandHandler =
+    Arc<dyn Fn(&AuditActor, &str, Value) -> Result<Value, String> + Send + Sync>;
+
+/// One request per line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Request {
+    #[serde(default)]
+    pub id: Value,
+    pub command: String,
+    #[serde(default)]
+    pub args: Value,
+}
+
+/// One response per request line, in order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Response {
+    #[serde(default)]
+    pub id: Value,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Pushed to subscribed connections for every matching app event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventLine {
+    pub event: String,
+    pub payload: Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscribeArgs {
+    /// Event names such as `session:output` or `git:event`.
+    pub events: Vec<String>,
+    /// Only forward payloads whose `sessionId` is this one.
+    #[serde(default)]
+    pub session_id: Option<usize>,
+}
+
+/// `$SYNK_SOCKET`, else `$XDG_RUNTIME_DIR/synk/synk.sock`, else a per-user temp dir.
+pub fn socket_path() -> PathBuf {
+    if let Some(p) = std::env::var_os(SOCKET_ENV).filter(|p| !p.is_empty()) {
+        return PathBuf::from(p);
+    }
+    default_socket_dir().join("synk.sock")
+}
+
+fn default_socket_dir() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR").filter(|p| !p.is_empty()) {
+        Some(runtime) => PathBuf::from(runtime).join("synk"),
+        None => std::env::temp_dir().join(format!("synk-{}", user_id())),
+    }
+}
+
+/// Refuses a socket dir someone else could have planted in a shared temp dir: a symlink, a
+/// directory owned by another user, or one other users can enter.
+#[cfg(unix)]
+fn ensure_private_dir(dir: &std::path::Path) -> Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    use anyhow::{bail, Context};
+
+    let meta = std::fs::symlink_metadata(dir).with_context(|| format!("stat {}", dir.display()))?;
+    if !meta.file_type().is_dir() {
+        bail!("{} is not a directory", dir.display());
+    }
+    if meta.uid() != user_id() {
+        bail!("{} is owned by uid {}, not us", dir.display(), meta.uid());
+    }
+    if meta.mode() & 0o777 != 0o700 {
+        bail!(
+            "{} has mode {:o}, expected 700",
+            dir.display(),
+            meta.mode() & 0o777
+        );
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn user_id() -> u32 {
+    unsafe { libc::getuid() }
+}
+
+#[cfg(not(unix))]
+fn user_id() -> u32 {
+    0
+}
+
+/// The session whose shell `pid` runs under, found by walking up its ancestors.
+fn owning_session(
+    pid: u32,
+    shells: &HashMap<u32, usize>,
+    parent_of: impl Fn(u32) -> Option<u32>,
+) -> Option<usize> {
+    let mut pid = pid;
+    // Bounded in case the process table changes under us and forms a cycle.
+    for _ in 0..64 {
+        if let Some(id) = shells.get(&pid) {
+            return Some(*id);
+        }
+        match parent_of(pid) {
+            Some(parent) if parent > 1 && parent != pid => pid = parent,
+            _ => return None,
+        }
+    }
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // `pid (comm) state ppid ...`; comm may itself contain spaces and parentheses.
+    let rest = &stat[stat.rfind(')')? + 1..];
+    rest.split_whitespace().nth(1)?.parse().ok()
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn parent_pid(pid: u32) -> Option<u32> {
+    let out = std::process::Command::new("ps")
+        .args(["-o", "ppid=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&out.stdout).trim().parse().ok()
+}
+
+#[cfg(target_os = "linux")]
+fn peer_pid(stream: &std::os::unix::net::UnixStream) -> Option<u32> {
+    use std::os::fd::AsRawFd;
+
+    let mut cred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    let rc = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            (&mut cred as *mut libc::ucred).cast(),
+            &mut len,
+        )
+    };
+    (rc == 0 && cred.pid > 0).then_some(cred.pid as u32)
+}
+
+#[cfg(target_os = "macos")]
+fn peer_pid(stream: &std::os::unix::net::UnixStream) -> Option<u32> {
+    use std::os::fd::AsRawFd;
+
+    let mut pid: libc::pid_t = 0;
+    let mut len = std::mem::size_of::<libc::pid_t>() as libc::socklen_t;
+    let rc = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_LOCAL,
+            libc::LOCAL_PEERPID,
+            (&mut pid as *mut libc::pid_t).cast(),
+            &mut len,
+        )
+    };
+    (rc == 0 && pid > 0).then_some(pid as u32)
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+fn peer_pid(_stream: &std::os::unix::net::UnixStream) -> Option<u32> {
+    None
+}
+
+/// Who is on the other end of a connection: the session whose shell the client was started
+/// from, else the user. Resolved once, when the client connects and is certainly alive.
+#[cfg(unix)]
+fn connection_actor(app: &AppContext, stream: &std::os::unix::net::UnixStream) -> AuditActor {
+    let session = peer_pid(stream).and_then(|pid| {
+        let sessions = app.try_state::<SharedSessionManager>()?;
+        let shells = sessions
+            .lock()
+            .expect("session manager mutex poisoned")
+            .shell_pids();
+        owning_session(pid, &shells, parent_pid)
+    });
+    match session {
+        Some(session_id) => AuditActor::Session { session_id },
+        None => AuditActor::User,
+    }
+}
+
+fn payload_matches(payload: &Value, session_id: Option<usize>) -> bool {
+    match session_id {
+        None => true,
+        Some(id) => payload
+            .get("sessionId")
+            .and_then(Value::as_u64)
+            .is_some_and(|v| v == id as u64),
+    }
+}
+
+/// Unix socket the `synk` CLI talks to. Newline-delimited JSON: each [`Request`] line gets a
+/// [`Response`] line; after a `subscribe` request the connection only carries [`EventLine`]s.
+#[derive(Default)]
+pub struct ControlSocket {
+    path: Option<PathBuf>,
+    stop: Arc<AtomicBool>,
+    accept_thread: Option<JoinHandle<()>>,
+}
+
+pub type SharedControlSocket = Arc<Mutex<ControlSocket>>;
+
+impl ControlSocket {
+    #[cfg(not(unix))]
+    pub fn start(&mut self, app: AppContext, handler: CommandHandler) -> Result<PathBuf> {
+        let _ = (app, handler);
+        Err(anyhow::anyhow!("the control socket needs a unix platform"))
+    }
+
+    #[cfg(unix)]
+    pub fn start(&mut self, app: AppContext, handler: CommandHandler) -> Result<PathBuf> {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        use std::os::unix::net::{UnixListener, UnixStream};
+        use std::time::Duration;
+
+        use anyhow::{anyhow, Context};
+
+        if let Some(path) = &self.path {
+            return Ok(path.clone());
+        }
+        let path = socket_path();
+        if let Some(dir) = path.parent() {
+            std::fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(dir)
+                .with_context(|| format!("create {}", dir.display()))?;
+            // `create` succeeds on a directory that was already there; an explicit
+            // `$SYNK_SOCKET` is the user's own choice of location.
+            if dir == default_socket_dir() {
+                ensure_private_dir(dir)?;
+            }
+        }
+        if path.exists() {
+            if UnixStream::connect(&path).is_ok() {
+                return Err(anyhow!(
+                    "another Synk instance is listening on {}",
+                    path.display()
+                ));
+            }
+            // Left behind by a crashed instance.
+            std::fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+        }
+        let listener =
+            UnixListener::bind(&path).with_context(|| format!("bind {}", path.display()))?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+            .with_context(|| format!("chmod {}", path.display()))?;
+        listener
+            .set_nonblocking(true)
+            .context("set control socket nonblocking")?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_accept = stop.clone();
+        let accept_thread = std::thread::spawn(move || {
+            while !stop_accept.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let app = app.clone();
+                        let handler = handler.clone();
+                        let stop = stop_accept.clone();
+                        std::thread::spawn(move || {
+                            if let Err(err) = serve_connection(stream, &app, &handler, &stop) {
+                                eprintln!("control socket: {err:#}");
+                            }
+                        });
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        std::thread::sleep(Duration::from_millis(25));
+                    }
+                    Err(e) => {
+                        eprintln!("control socket: accept failed: {e}");
+                        std::thread::sleep(Duration::from_millis(250));
+                    }
+                }
+            }
+        });
+
+        self.stop = stop;
+        self.accept_thread = Some(accept_thread);
+        self.path = Some(path.clone());
+        Ok(path)
+    }
+
+    pub fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(t) = self.accept_thread.take() {
+            let _ = t.join();
+        }
+        if let Some(path) = self.path.take() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn serve_connection(
+    stream: std::os::unix::net::UnixStream,
+    app: &AppContext,
+    handler: &CommandHandler,
+    stop: &AtomicBool,
+) -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+
+    use anyhow::Context;
+
+    stream
+        .set_nonblocking(false)
+        .context("set control connection blocking")?;
+    let actor = connection_actor(app, &stream);
+    let mut writer = stream.try_clone().context("clone control connection")?;
+    let reader = BufReader::new(stream);
+    for line in reader.lines() {
+        let line = line.context("read request")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request: Request = match serde_json::from_str(&line) {
+            Ok(r) => r,
+            Err(err) => {
+                let response = Response {
+                    id: Value::Null,
+                    ok: false,
+                    result: None,
+                    error: Some(format!("invalid request: {err}")),
+                };
+                writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+                continue;
+            }
+        };
+
+        if request.command == SUBSCRIBE_COMMAND {
+            let args: SubscribeArgs = match serde_json::from_value(request.args) {
+                Ok(a) => a,
+                Err(err) => {
+                    let response = Response {
+                        id: request.id,
+                        ok: false,
+                        result: None,
+                        error: Some(format!("invalid args: {err}")),
+                    };
+                    writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+                    continue;
+                }
+            };
+            return stream_events(writer, app, request.id, args, stop);
+        }
+
+        let response = match handler(&actor, &request.command, request.args) {
+            Ok(result) => Response {
+                id: request.id,
+                ok: true,
+                result: Some(result),
+                error: None,
+            },
+            Err(error) => Response {
+                id: request.id,
+                ok: false,
+                result: None,
+                error: Some(error),
+            },
+        };
+        writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+    }
+    Ok(())
+}
+
+/// Listens for `args.events` and hands every matching one to `sink`. Unlisten the returned
+/// ids when the subscriber goes away.
+pub(crate) fn forward_events(
+    app: &AppContext,
+    args: &SubscribeArgs,
+    sink: Arc<dyn Fn(EventLine) + Send + Sync>,
+) -> Vec<EventId> {
+    args.events
+        .iter()
+        .map(|name| {
+            let event = name.clone();
+            let session_id = args.session_id;
+            let sink = sink.clone();
+            app.listen_any(name.clone(), move |payload| {
+                let payload: Value = serde_json::from_str(payload).unwrap_or(Value::Null);
+                if payload_matches(&payload, session_id) {
+                    sink(EventLine {
+                        event: event.clone(),
+                        payload,
+                    });
+                }
+            })
+        })
+        .collect()
+}
+
+/// Acknowledges the subscription once listening, then forwards app events to the connection
+/// until the client goes away or the app shuts down.
+#[cfg(unix)]
+fn stream_events(
+    mut writer: std::os::unix::net::UnixStream,
+    app: &AppContext,
+    id: Value,
+    args: SubscribeArgs,
+    stop: &AtomicBool,
+) -> Result<()> {
+    use std::io::Write;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let (tx, rx) = mpsc::channel::<EventLine>();
+    let ids = forward_events(
+        app,
+        &args,
+        Arc::new(move |line: EventLine| {
+            let _ = tx.send(line);
+        }),
+    );
+
+    let result = (|| -> Result<()> {
+        let ack = Response {
+            id,
+            ok: true,
+            result: None,
+            error: None,
+        };
+        writeln!(writer, "{}", serde_json::to_string(&ack)?)?;
+        while !stop.load(Ordering::Relaxed) {
+            match rx.recv_timeout(Duration::from_millis(250)) {
+                Ok(line) => writeln!(writer, "{}", serde_json::to_string(&line)?)?,
+                Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        Ok(())
+    })();
+    for id in ids {
+        app.unlisten(id);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use std::collections::HashMap;
+
+    use super::{owning_session, payload_matches, Request};
+
+    #[test]
+    fn requests_default_missing_fields() {
+        let r: Request = serde_json::from_str(r#"{"command":"session_list"}"#).unwrap();
+        assert_eq!(r.command, "session_list");
+        assert!(r.id.is_null() && r.args.is_null());
+    }
+
+    #[test]
+    fn attributes_processes_to_the_session_they_run_under() {
+        // 40 is session 2's shell; 41 an agent it started; 42 the `synk` the agent ran.
+        let parents = HashMap::from([(42, 41), (41, 40), (40, 10), (10, 1), (50, 10)]);
+        let shells = HashMap::from([(40, 2), (60, 3)]);
+        let parent_of = |pid| parents.get(&pid).copied();
+        assert_eq!(owning_session(42, &shells, parent_of), Some(2));
+        assert_eq!(owning_session(40, &shells, parent_of), Some(2));
+        assert_eq!(owning_session(50, &shells, parent_of), None);
+        assert_eq!(owning_session(99, &shells, parent_of), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reads_parent_pids() {
+        let expected = unsafe { libc::getppid() } as u32;
+        assert_eq!(super::parent_pid(std::process::id()), Some(expected));
+    }
+
+    #[test]
+    fn filters_payloads_by_session() {
+        let p = json!({"sessionId": 3, "dataB64": ""});
+        assert!(payload_matches(&p, None));
+        assert!(payload_matches(&p, Some(3)));
+        assert!(!payload_matches(&p, Some(4)));
+        assert!(!payload_matches(&json!({"projectPath": "/x"}), Some(3)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn socket_dir_must_be_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        use super::ensure_private_dir;
+        use crate::core::test_fixtures::TempDir;
+
+        let tmp = TempDir::new("sockdir");
+        let dir = tmp.join("dir");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(ensure_private_dir(&dir).is_err());
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700)).unwrap();
+        ensure_private_dir(&dir).unwrap();
+
+        let link = tmp.join("link");
+        std::os::unix::fs::symlink(&dir, &link).unwrap();
+        assert!(ensure_private_dir(&link).is_err());
+        assert!(ensure_private_dir(&tmp.join("missing")).is_err());
+    }
+}
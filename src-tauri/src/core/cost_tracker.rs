@@ -1,16 +1,18 @@
 use std::collections::HashMap;
 use std::fs;
+use std::sync::{Arc, Mutex};
 
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use tauri::path::BaseDirectory;
-use tauri::{AppHandle, Manager};
 
 use crate::core::agent_detection::AgentType;
+use crate::core::app_context::AppContext;
+use crate::core::llm_gateway::GatewaySessionUsage;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum CostSource {
+    Gateway,
     Mcp,
     OutputParsed,
     Heuristic,
@@ -48,6 +50,8 @@ pub struct CostTracker {
     parsed_input_tokens: Option<u64>,
     parsed_output_tokens: Option<u64>,
     parsed_total_cost: Option<f64>,
+    metered_input_tokens: Option<u64>,
+    metered_output_tokens: Option<u64>,
     heuristic_input_chars: u64,
     heuristic_output_chars: u64,
     line_buf: String,
@@ -66,17 +70,27 @@ pub struct CostTracker {
 }
 
 impl CostTracker {
-    pub fn new(agent_type: AgentType, initial_model: Option<String>, app: &AppHandle) -> Self {
+    pub fn new(agent_type: AgentType, initial_model: Option<String>, app: &AppContext) -> Self {
+        Self::with_pricing(agent_type, initial_model, read_pricing_table(app))
+    }
+
+    fn with_pricing(
+        agent_type: AgentType,
+        initial_model: Option<String>,
+        pricing_by_provider: HashMap<String, HashMap<String, ModelPricing>>,
+    ) -> Self {
         Self {
             agent_type,
             model: initial_model,
             parsed_input_tokens: None,
             parsed_output_tokens: None,
             parsed_total_cost: None,
+            metered_input_tokens: None,
+            metered_output_tokens: None,
             heuristic_input_chars: 0,
             heuristic_output_chars: 0,
             line_buf: String::new(),
-            pricing_by_provider: read_pricing_table(app),
+            pricing_by_provider,
             last_snapshot: None,
             re_model: Regex::new(r"(?i)(?:using model|model):\s*([A-Za-z0-9._:-]+)")
                 .expect("invalid regex"),
@@ -104,13 +118,12 @@ impl CostTracker {
         }
     }
 
-    pub fn ingest_input(&mut self, input: &str) {
-        if self.agent_type == AgentType::Terminal || input.is_empty() {
+    /// Counts typed input towards the heuristic estimate.
+    pub fn ingest_input_chars(&mut self, chars: u64) {
+        if self.agent_type == AgentType::Terminal {
             return;
         }
-        self.heuristic_input_chars = self
-            .heuristic_input_chars
-            .saturating_add(input.chars().count() as u64);
+        self.heuristic_input_chars = self.heuristic_input_chars.saturating_add(chars);
     }
 
     pub fn ingest_output_bytes(&mut self, bytes: &[u8]) -> bool {
@@ -146,11 +159,47 @@ impl CostTracker {
         false
     }
 
+    /// Session totals metered by the LLM gateway. These are exact, so they win over anything
+    /// parsed from terminal output.
+    pub fn ingest_gateway_usage(
+        &mut self,
+        input_tokens: u64,
+        output_tokens: u64,
+        model: Option<&str>,
+    ) -> bool {
+        self.metered_input_tokens = Some(input_tokens);
+        self.metered_output_tokens = Some(output_tokens);
+        if let Some(m) = model.map(str::trim).filter(|m| !m.is_empty()) {
+            self.model = Some(m.to_string());
+        }
+
+        let next = self.snapshot();
+        if snapshot_changed(&self.last_snapshot, &next) {
+            self.last_snapshot = next;
+            return self.last_snapshot.is_some();
+        }
+        false
+    }
+
     pub fn snapshot(&self) -> Option<SessionCostSnapshot> {
         if self.agent_type == AgentType::Terminal {
             return None;
         }
 
+        if let (Some(input_tokens), Some(output_tokens)) =
+            (self.metered_input_tokens, self.metered_output_tokens)
+        {
+            let model = self.model.clone();
+            let rates = self.pricing_for(model.as_deref());
+            return Some(SessionCostSnapshot {
+                input_tokens,
+                output_tokens,
+                total_cost: estimate_cost(input_tokens, output_tokens, rates),
+                model,
+                source: CostSource::Gateway,
+            });
+        }
+
         let heuristic_input_tokens = chars_to_tokens(self.heuristic_input_chars);
         let heuristic_output_tokens = chars_to_tokens(self.heuristic_output_chars);
         let input_tokens = self.parsed_input_tokens.unwrap_or(heuristic_input_tokens);
@@ -322,13 +371,64 @@ impl CostTracker {
     }
 }
 
+/// Live per-session trackers. The output pump feeds them terminal output and the LLM gateway
+/// feeds them each metered response, so anything reading a session's cost sees the same numbers
+/// the `cost:updated` event carried.
+#[derive(Default)]
+pub struct CostTrackers {
+    sessions: HashMap<usize, CostTracker>,
+}
+
+pub type SharedCostTrackers = Arc<Mutex<CostTrackers>>;
+
+impl CostTrackers {
+    /// Applies a session's running gateway totals, creating its tracker with `new` on first
+    /// use. Returns the snapshot when it changed.
+    pub fn ingest_gateway_usage(
+        &mut self,
+        totals: &GatewaySessionUsage,
+        new: impl FnOnce() -> CostTracker,
+    ) -> Option<SessionCostSnapshot> {
+        let tracker = self.sessions.entry(totals.session_id).or_insert_with(new);
+        tracker
+            .ingest_gateway_usage(
+                totals.input_tokens,
+                totals.output_tokens,
+                totals.last_model.as_deref(),
+            )
+            .then(|| tracker.snapshot())
+            .flatten()
+    }
+
+    /// Applies a chunk of a session's terminal output and the characters typed since the last
+    /// one, creating its tracker with `new` on first use. Returns the snapshot when it changed.
+    pub fn ingest_output(
+        &mut self,
+        session_id: usize,
+        typed_chars: u64,
+        output: &[u8],
+        new: impl FnOnce() -> CostTracker,
+    ) -> Option<SessionCostSnapshot> {
+        let tracker = self.sessions.entry(session_id).or_insert_with(new);
+        tracker.ingest_input_chars(typed_chars);
+        tracker
+            .ingest_output_bytes(output)
+            .then(|| tracker.snapshot())
+            .flatten()
+    }
+
+    pub fn snapshot(&self, session_id: usize) -> Option<SessionCostSnapshot> {
+        self.sessions.get(&session_id)?.snapshot()
+    }
+}
+
 fn provider_for_agent(agent_type: AgentType) -> Option<&'static str> {
     match agent_type {
         AgentType::ClaudeCode => Some("anthropic"),
         AgentType::GeminiCli => Some("google"),
         AgentType::Codex => Some("openai"),
         AgentType::Openrouter => Some("openai"),
-        AgentType::Terminal => None,
+        AgentType::Simulated | AgentType::Terminal => None,
     }
 }
 
@@ -381,13 +481,10 @@ fn snapshot_changed(prev: &Option<SessionCostSnapshot>, next: &Option<SessionCos
     }
 }
 
-fn read_pricing_table(app: &AppHandle) -> HashMap<String, HashMap<String, ModelPricing>> {
+fn read_pricing_table(app: &AppContext) -> HashMap<String, HashMap<String, ModelPricing>> {
     let mut out: HashMap<String, HashMap<String, ModelPricing>> = HashMap::new();
 
-    let Ok(path) = app
-        .path()
-        .resolve("synk/pricing.json", BaseDirectory::Config)
-    else {
+    let Ok(path) = app.config_path("pricing.json") else {
         return out;
     };
 
@@ -421,3 +518,109 @@ fn read_pricing_table(app: &AppHandle) -> HashMap<String, HashMap<String, ModelP
 
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{CostSource, CostTracker, CostTrackers};
+    use crate::core::agent_detection::AgentType;
+    use crate::core::llm_gateway::GatewaySessionUsage;
+    use crate::core::simulated_agent::{load_scenario, run_scenario, RunOptions};
+    use crate::core::test_fixtures::TempDir;
+
+    /// Feeds a builtin simulated-agent scenario's output to the tracker in chunks, as the output
+    /// pump does.
+    fn track(agent: AgentType, scenario: &str) -> CostTracker {
+        let dir = TempDir::new("cost");
+        let mut out = Vec::new();
+        let mut input = std::io::Cursor::new(b"y\n".to_vec());
+        run_scenario(
+            &load_scenario(scenario).unwrap(),
+            &mut out,
+            &mut input,
+            &dir,
+            RunOptions { instant: true },
+        )
+        .unwrap();
+
+        let mut tracker = CostTracker::with_pricing(agent, None, HashMap::new());
+        // Split mid-line to exercise line buffering.
+        let (a, b) = out.split_at(out.len() / 2);
+        tracker.ingest_output_bytes(a);
+        tracker.ingest_output_bytes(b);
+        tracker
+    }
+
+    #[test]
+    fn parses_claude_code_totals() {
+        let snap = track(AgentType::ClaudeCode, "demo").snapshot().unwrap();
+        assert_eq!(snap.source, CostSource::OutputParsed);
+        assert_eq!(snap.input_tokens, 1840);
+        assert_eq!(snap.output_tokens, 312);
+        assert!((snap.total_cost - 0.0102).abs() < 1e-9);
+        assert_eq!(snap.model.as_deref(), Some("claude-sim-1"));
+    }
+
+    #[test]
+    fn sums_codex_per_turn_usage() {
+        let snap = track(AgentType::Codex, "codex").snapshot().unwrap();
+        assert_eq!(snap.source, CostSource::OutputParsed);
+        assert_eq!(snap.input_tokens, 2100);
+        assert_eq!(snap.output_tokens, 230);
+    }
+
+    #[test]
+    fn sums_gemini_response_usage() {
+        let snap = track(AgentType::GeminiCli, "gemini").snapshot().unwrap();
+        assert_eq!(snap.input_tokens, 640);
+        assert_eq!(snap.output_tokens, 44);
+    }
+
+    #[test]
+    fn gateway_usage_overrides_parsed_output() {
+        let mut tracker = track(AgentType::Codex, "codex");
+        assert!(tracker.ingest_gateway_usage(5000, 700, Some("gpt-exact")));
+        let snap = tracker.snapshot().unwrap();
+        assert_eq!(snap.source, CostSource::Gateway);
+        assert_eq!(snap.input_tokens, 5000);
+        assert_eq!(snap.model.as_deref(), Some("gpt-exact"));
+    }
+
+    #[test]
+    fn session_trackers_follow_gateway_totals() {
+        let mut trackers = CostTrackers::default();
+        let new = || CostTracker::with_pricing(AgentType::ClaudeCode, None, HashMap::new());
+        let mut totals = GatewaySessionUsage {
+            session_id: 3,
+            input_tokens: 1_000_000,
+            output_tokens: 0,
+            ..Default::default()
+        };
+
+        let first = trackers.ingest_gateway_usage(&totals, new).unwrap();
+        assert_eq!(first.source, CostSource::Gateway);
+        assert!((first.total_cost - 3.0).abs() < 1e-9);
+        assert!(trackers.ingest_gateway_usage(&totals, new).is_none());
+
+        totals.output_tokens = 1_000_000;
+        let later = trackers.ingest_gateway_usage(&totals, new).unwrap();
+        assert!((later.total_cost - 18.0).abs() < 1e-9);
+        assert_eq!(trackers.snapshot(3).unwrap().output_tokens, 1_000_000);
+        assert!(trackers.snapshot(4).is_none());
+    }
+
+    #[test]
+    fn session_trackers_estimate_then_parse_output() {
+        let mut trackers = CostTrackers::default();
+        let new = || CostTracker::with_pricing(AgentType::Codex, None, HashMap::new());
+        let guess = trackers.ingest_output(3, 8, b"thinking\n", new).unwrap();
+        assert_eq!(guess.source, CostSource::Heuristic);
+        assert_eq!((guess.input_tokens, guess.output_tokens), (2, 3));
+
+        let usage = b"Usage: 10 prompt + 5 completion = 15 total tokens\n";
+        let parsed = trackers.ingest_output(3, 0, usage, new).unwrap();
+        assert_eq!(parsed.source, CostSource::OutputParsed);
+        assert_eq!((parsed.input_tokens, parsed.output_tokens), (10, 5));
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::core::app_context::AppContext;
+use crate::events::{now_rfc3339, AppEvent, SESSION_OUTPUT_EVENT_NAME};
+
+/// Events kept per topic for late subscribers.
+const REPLAY_CAPACITY: usize = 500;
+/// Terminal output arrives in small chunks; keep enough to repaint a pane.
+const OUTPUT_REPLAY_CAPACITY: usize = 2000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedEvent {
+    pub seq: u64,
+    pub topic: String,
+    pub timestamp: String,
+    pub payload: Value,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventsSinceArgs {
+    /// Return events with a higher sequence number; 0 for everything still buffered.
+    #[serde(default)]
+    pub seq: u64,
+    /// Only these topics (e.g. `git:event`); all of them when empty.
+    #[serde(default)]
+    pub topics: Vec<String>,
+    /// Oldest first, at most this many.
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventsSinceResult {
+    pub events: Vec<RecordedEvent>,
+    /// Sequence number of the newest event published so far.
+    pub latest_seq: u64,
+    /// Some matching events after `seq` were already evicted from the replay buffer.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Default)]
+struct TopicBuffer {
+    events: VecDeque<RecordedEvent>,
+    /// Highest sequence number dropped from `events`.
+    evicted_through: u64,
+}
+
+/// In-process handler for published events. Runs on the publishing thread, after the bus lock
+/// is released, so it should hand work off rather than block or take other locks.
+pub type Subscriber = Arc<dyn Fn(&AppEvent) + Send + Sync>;
+
+/// Numbers every published event and keeps the last few per topic, so a reloaded frontend
+/// or a new panel can catch up with [`EventBus::since`].
+#[derive(Default)]
+pub struct EventBus {
+    latest_seq: u64,
+    topics: HashMap<String, TopicBuffer>,
+    subscribers: Vec<(Vec<&'static str>, Subscriber)>,
+}
+
+pub type SharedEventBus = Arc<Mutex<EventBus>>;
+
+fn capacity(topic: &str) -> usize {
+    if topic == SESSION_OUTPUT_EVENT_NAME {
+        OUTPUT_REPLAY_CAPACITY
+    } else {
+        REPLAY_CAPACITY
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assigns the next sequence number and keeps the event for replay.
+    pub fn record(&mut self, topic: &str, payload: Value) -> u64 {
+        self.latest_seq += 1;
+        let seq = self.latest_seq;
+        let buf = self.topics.entry(topic.to_string()).or_default();
+        buf.events.push_back(RecordedEvent {
+            seq,
+            topic: topic.to_string(),
+            timestamp: now_rfc3339(),
+            payload,
+        });
+        while buf.events.len() > capacity(topic) {
+            if let Some(old) = buf.events.pop_front() {
+                buf.evicted_through = old.seq;
+            }
+        }
+        seq
+    }
+
+    /// Calls `handler` for every event published under one of `topics`.
+    pub fn subscribe(
+        &mut self,
+        topics: &[&'static str],
+        handler: impl Fn(&AppEvent) + Send + Sync + 'static,
+    ) {
+        self.subscribers.push((topics.to_vec(), Arc::new(handler)));
+    }
+
+    fn subscribers_for(&self, topic: &str) -> Vec<Subscriber> {
+        self.subscribers
+            .iter()
+            .filter(|(topics, _)| topics.contains(&topic))
+            .map(|(_, s)| s.clone())
+            .collect()
+    }
+
+    pub fn since(&self, args: &EventsSinceArgs) -> EventsSinceResult {
+        let wanted = |topic: &str| args.topics.is_empty() || args.topics.iter().any(|t| t == topic);
+        let mut events = Vec::new();
+        let mut truncated = false;
+        for (topic, buf) in &self.topics {
+            if !wanted(topic) {
+                continue;
+            }
+            truncated |= buf.evicted_through > args.seq;
+            events.extend(buf.events.iter().filter(|e| e.seq > args.seq).cloned());
+        }
+        events.sort_by_key(|e| e.seq);
+        if let Some(limit) = args.limit {
+            events.truncate(limit);
+        }
+        EventsSinceResult {
+            events,
+            latest_seq: self.latest_seq,
+            truncated,
+        }
+    }
+}
+
+/// The one way subsystems publish: records `event` on the managed bus (when there is one),
+/// emits it under its topic with `seq` added to the payload, then runs in-process subscribers.
+pub fn publish(app: &AppContext, event: AppEvent) {
+    let topic = event.topic();
+    let mut payload = match event.payload() {
+        Ok(p) => p,
+        Err(err) => {
+            eprintln!("event bus: serialize {topic}: {err}");
+            return;
+        }
+    };
+    let mut subscribers = Vec::new();
+    if let Some(bus) = app.try_state::<SharedEventBus>() {
+        // Emit outside the lock: headless listeners run inline and may publish themselves.
+        let mut bus = bus.lock().expect("event bus mutex poisoned");
+        let seq = bus.record(topic, payload.clone());
+        subscribers = bus.subscribers_for(topic);
+        drop(bus);
+        if let Value::Object(map) = &mut payload {
+            map.insert("seq".to_string(), seq.into());
+        }
+    }
+    let _ = app.emit(topic, payload);
+    for subscriber in subscribers {
+        subscriber(&event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::{json, Value};
+
+    use super::{publish, EventBus, EventsSinceArgs, SharedEventBus, REPLAY_CAPACITY};
+    use crate::core::app_context::AppContext;
+    use crate::events::{AppEvent, SessionExitEvent};
+
+    #[test]
+    fn replays_in_order_and_filters_by_topic() {
+        let mut bus = EventBus::new();
+        bus.record("git:event", json!({"n": 1}));
+        bus.record("session:exit", json!({"n": 2}));
+        bus.record("git:event", json!({"n": 3}));
+
+        let all = bus.since(&EventsSinceArgs::default());
+        assert_eq!(
+            all.events.iter().map(|e| e.seq).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(all.latest_seq, 3);
+
+        let git = bus.since(&EventsSinceArgs {
+            seq: 1,
+            topics: vec!["git:event".to_string()],
+            limit: None,
+        });
+        assert_eq!(git.events.len(), 1);
+        assert_eq!(git.events[0].payload, json!({"n": 3}));
+        assert!(!git.truncated);
+    }
+
+    #[test]
+    fn reports_evicted_events() {
+        let mut bus = EventBus::new();
+        for n in 0..REPLAY_CAPACITY + 5 {
+            bus.record("git:event", json!({ "n": n }));
+        }
+        let res = bus.since(&EventsSinceArgs::default());
+        assert_eq!(res.events.len(), REPLAY_CAPACITY);
+        assert_eq!(res.events[0].seq, 6);
+        assert!(res.truncated);
+        assert!(
+            !bus.since(&EventsSinceArgs {
+                seq: 5,
+                ..Default::default()
+            })
+            .truncated
+        );
+    }
+
+    #[test]
+    fn publish_adds_seq_to_emitted_payload() {
+        let ctx = AppContext::headless(PathBuf::from("/tmp/synk-test"));
+        let bus: SharedEventBus = Arc::new(Mutex::new(EventBus::new()));
+        ctx.manage(bus.clone());
+        let seen = Arc::new(Mutex::new(Vec::<Value>::new()));
+        let sink = seen.clone();
+        ctx.listen_any("session:exit", move |p| {
+            sink.lock().unwrap().push(serde_json::from_str(p).unwrap())
+        });
+
+        publish(
+            &ctx,
+            AppEvent::SessionExit(SessionExitEvent {
+                session_id: 4,
+                exit_code: Some(0),
+            }),
+        );
+        assert_eq!(seen.lock().unwrap()[0]["seq"], json!(1));
+        assert_eq!(seen.lock().unwrap()[0]["sessionId"], json!(4));
+        let recorded = bus.lock().unwrap().since(&EventsSinceArgs::default());
+        assert_eq!(recorded.events[0].topic, "session:exit");
+    }
+
+    #[test]
+    fn subscribers_see_only_their_topics() {
+        let ctx = AppContext::headless(PathBuf::from("/tmp/synk-test"));
+        let bus: SharedEventBus = Arc::new(Mutex::new(EventBus::new()));
+        ctx.manage(bus.clone());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        bus.lock().unwrap().subscribe(&["session:exit"], move |e| {
+            sink.lock().unwrap().push(e.topic())
+        });
+
+        let exit = || {
+            AppEvent::SessionExit(SessionExitEvent {
+                session_id: 1,
+                exit_code: Some(0),
+            })
+        };
+        publish(&ctx, exit());
+        bus.lock().unwrap().record("git:event", json!({}));
+        publish(&ctx, exit());
+        assert_eq!(*seen.lock().unwrap(), vec!["session:exit", "session:exit"]);
+    }
+}
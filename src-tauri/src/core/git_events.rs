@@ -7,9 +7,12 @@ use std::sync::{
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use tauri::Emitter;
-
-use crate::events::{now_rfc3339, GitEvent, GitEventType, GIT_EVENT_NAME};
+use crate::core::app_context::AppContext;
+use crate::core::event_bus::publish;
+use crate::core::git_manager::{MergeResult, MergeStrategy};
+use crate::core::session_labels::SessionLabels;
+use crate::core::session_manager::SharedSessionManager;
+use crate::events::{now_rfc3339, AppEvent, GitEvent, GitEventType};
 
 pub type SharedGitEventWatcher = Arc<std::sync::Mutex<GitEventWatcher>>;
 
@@ -57,6 +60,56 @@ fn new_id(prefix: &str) -> String {
     format!("{prefix}-{n}")
 }
 
+/// Emits the merge outcome as a git activity event.
+pub fn publish_merge_event(
+    app: &AppContext,
+    sessions: &SharedSessionManager,
+    project_path: &str,
+    branch: &str,
+    base_branch: &str,
+    strategy: MergeStrategy,
+    res: &MergeResult,
+) {
+    let session = sessions
+        .lock()
+        .expect("session manager mutex poisoned")
+        .find_by_branch(project_path, branch);
+
+    publish(
+        app,
+        AppEvent::Git(Box::new(GitEvent {
+            id: format!(
+                "{}-{}",
+                if res.success { "merge" } else { "conflict" },
+                now_rfc3339()
+            ),
+            event_type: if res.success {
+                GitEventType::MergeCompleted
+            } else {
+                GitEventType::ConflictDetected
+            },
+            timestamp: now_rfc3339(),
+            project_path: project_path.to_string(),
+            session_id: session.as_ref().map(|s| s.session_id),
+            session_labels: session.map(|s| s.labels).filter(|l| !l.is_empty()),
+            branch: Some(branch.to_string()),
+            hash: None,
+            message: None,
+            author: None,
+            base_branch: Some(base_branch.to_string()),
+            strategy: Some(
+                match strategy {
+                    MergeStrategy::Merge => "merge",
+                    MergeStrategy::Squash => "squash",
+                    MergeStrategy::Rebase => "rebase",
+                }
+                .to_string(),
+            ),
+            conflict_files: res.conflict_files.clone(),
+        })),
+    );
+}
+
 impl GitEventWatcher {
     pub fn new() -> Self {
         Self {
@@ -68,7 +121,7 @@ impl GitEventWatcher {
 
     pub fn start(
         watcher: SharedGitEventWatcher,
-        app: tauri::AppHandle,
+        app: AppContext,
         sessions: crate::core::session_manager::SharedSessionManager,
     ) {
         let watcher_for_thread = watcher.clone();
@@ -90,12 +143,14 @@ impl GitEventWatcher {
                 };
 
                 // Group sessions by project so we only hit git once per repo for branch list.
-                let mut by_project: HashMap<String, Vec<(usize, Option<String>)>> = HashMap::new();
+                type SessionEntry = (usize, Option<String>, SessionLabels);
+                let mut by_project: HashMap<String, Vec<SessionEntry>> = HashMap::new();
                 for s in list {
-                    by_project
-                        .entry(s.project_path.clone())
-                        .or_default()
-                        .push((s.session_id, s.working_dir.clone()));
+                    by_project.entry(s.project_path.clone()).or_default().push((
+                        s.session_id,
+                        s.working_dir.clone(),
+                        s.labels.clone(),
+                    ));
                 }
 
                 for (project_path, sess) in by_project {
@@ -114,10 +169,10 @@ impl GitEventWatcher {
                             .collect();
 
                     // Gather latest commit info per session without holding watcher lock.
-                    // (session_id, branch, hash, author, message)
-                    let mut latest_commits: Vec<(usize, String, String, String, String)> =
-                        Vec::new();
-                    for (session_id, working_dir) in &sess {
+                    // (session_id, labels, branch, hash, author, message)
+                    type LatestCommit = (usize, SessionLabels, String, String, String, String);
+                    let mut latest_commits: Vec<LatestCommit> = Vec::new();
+                    for (session_id, working_dir, labels) in &sess {
                         let Some(wd) = working_dir.as_deref() else {
                             continue;
                         };
@@ -144,7 +199,14 @@ impl GitEventWatcher {
                         let author = parts.first().map(|s| s.trim()).unwrap_or("").to_string();
                         let message = parts.get(1).map(|s| s.trim()).unwrap_or("").to_string();
 
-                        latest_commits.push((*session_id, branch, hash, author, message));
+                        latest_commits.push((
+                            *session_id,
+                            labels.clone(),
+                            branch,
+                            hash,
+                            author,
+                            message,
+                        ));
                     }
 
                     let mut events_to_emit: Vec<GitEvent> = Vec::new();
@@ -168,6 +230,7 @@ impl GitEventWatcher {
                                 timestamp: now_rfc3339(),
                                 project_path: project_path.clone(),
                                 session_id: None,
+                                session_labels: None,
                                 branch: Some(b.clone()),
                                 hash: None,
                                 message: None,
@@ -185,6 +248,7 @@ impl GitEventWatcher {
                                 timestamp: now_rfc3339(),
                                 project_path: project_path.clone(),
                                 session_id: None,
+                                session_labels: None,
                                 branch: Some(b.clone()),
                                 hash: None,
                                 message: None,
@@ -199,7 +263,7 @@ impl GitEventWatcher {
                     }
 
                     // Commit events for sessions.
-                    for (session_id, branch, hash, author, message) in latest_commits {
+                    for (session_id, labels, branch, hash, author, message) in latest_commits {
                         let prev = st.last_head_by_session.get(&session_id).cloned();
                         if prev.is_none() {
                             // Baseline on first sighting of this session.
@@ -217,6 +281,7 @@ impl GitEventWatcher {
                             timestamp: now_rfc3339(),
                             project_path: project_path.clone(),
                             session_id: Some(session_id),
+                            session_labels: (!labels.is_empty()).then_some(labels),
                             branch: if branch.trim().is_empty() {
                                 None
                             } else {
@@ -241,7 +306,7 @@ impl GitEventWatcher {
 
                     drop(state_guard);
                     for ev in events_to_emit {
-                        let _ = app.emit(GIT_EVENT_NAME, ev);
+                        publish(&app, AppEvent::Git(Box::new(ev)));
                     }
                 }
 
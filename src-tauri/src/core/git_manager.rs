@@ -6,6 +6,14 @@ use std::time::{Duration, SystemTime};
 
 use anyhow::{bail, Context, Result};
 
+use crate::core::app_context::AppContext;
+use crate::core::checkpoints;
+use crate::core::commit_graph::{
+    self, CommitFileChange, CommitLogPage, CommitRefs, GitRef, LogCursor, DEFAULT_PAGE_SIZE,
+    MAX_PAGE_SIZE,
+};
+use crate::core::integration::Integration;
+
 #[derive(Debug, Clone)]
 pub struct GitManager {
     project_path: PathBuf,
@@ -82,7 +90,7 @@ pub struct FileDiff {
     pub hunks: Vec<DiffHunk>,
 }
 
-#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum MergeStrategy {
     Merge,
@@ -96,6 +104,23 @@ pub struct MergeResult {
     pub success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub conflict_files: Option<Vec<String>>,
+    /// Set when the conflicted merge was kept in progress for interactive resolution.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub integration: Option<Integration>,
+}
+
+/// Outcome of merging `branch_commit` into `base_commit`, computed without touching a worktree.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergePreview {
+    pub base_commit: String,
+    pub branch_commit: String,
+    pub mergeable: bool,
+    pub conflict_files: Vec<String>,
+    /// What the merge would change on the base branch.
+    pub files_changed: u32,
+    pub additions: u32,
+    pub deletions: u32,
 }
 
 fn home_dir() -> Result<PathBuf> {
@@ -135,7 +160,11 @@ fn project_name_from_path(project_path: &Path) -> String {
         .to_string()
 }
 
-fn slugify_branch(name: &str) -> String {
+fn looks_like_commit_id(s: &str) -> bool {
+    (7..=40).contains(&s.len()) && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+pub(crate) fn slugify_branch(name: &str) -> String {
     let mut out = String::new();
     let mut prev_dash = false;
 
@@ -212,7 +241,7 @@ fn parse_hunk_header(line: &str) -> Option<(u32, u32, u32, u32)> {
     Some((old_start, old_count, new_start, new_count))
 }
 
-fn parse_unified_diff(text: &str) -> Vec<FileDiff> {
+pub fn parse_unified_diff(text: &str) -> Vec<FileDiff> {
     let mut out: Vec<FileDiff> = Vec::new();
 
     let mut cur_old_path: Option<String> = None;
@@ -369,10 +398,29 @@ impl GitManager {
         })
     }
 
+    /// A manager for `project_path` using the worktree location and branch prefix from settings.
+    pub fn for_project(app: &AppContext, project_path: &Path) -> Result<Self> {
+        let settings = crate::core::settings::settings_get(app)?;
+        Self::new(
+            project_path.to_path_buf(),
+            &settings.git.worktree_base_path,
+            &settings.git.branch_prefix,
+        )
+    }
+
     pub fn worktree_project_root(&self) -> &Path {
         &self.worktree_project_root
     }
 
+    pub fn project_path(&self) -> &Path {
+        &self.project_path
+    }
+
+    /// Integration worktrees live beside the managed worktrees so orphan cleanup skips them.
+    pub fn integration_root(&self) -> PathBuf {
+        self.worktree_project_root.with_extension("integration")
+    }
+
     pub fn normalize_branch(&self, branch: &str) -> Result<String> {
         let b = branch.trim();
         if b.is_empty() {
@@ -493,6 +541,10 @@ impl GitManager {
         if self.branch_exists(base)? {
             return Ok(base.to_string());
         }
+        // Forks start from a session's HEAD commit rather than a branch.
+        if looks_like_commit_id(base) && self.rev_exists(base)? {
+            return Ok(base.to_string());
+        }
 
         // Common migration path: repo default is still "master" but callers assume "main" (or vice-versa).
         if base == "main" && self.branch_exists("master")? {
@@ -593,6 +645,7 @@ impl GitManager {
                 .with_context(|| format!("force delete branch {branch}"))?;
         }
 
+        self.drop_stale_checkpoints();
         Ok(())
     }
 
@@ -724,9 +777,17 @@ impl GitManager {
             let _ = self.run_git(&["branch", "-D", branch]);
         }
 
+        self.drop_stale_checkpoints();
         Ok(())
     }
 
+    /// Best-effort: checkpoints of a removed worktree or branch can't be restored anywhere.
+    fn drop_stale_checkpoints(&self) {
+        if let Err(err) = checkpoints::drop_stale_checkpoints(&self.project_path) {
+            eprintln!("git_manager: drop stale checkpoints: {err:#}");
+        }
+    }
+
     // -------------------------------------------------------------------------
     // Diff / merge (Task 3A.2)
     // -------------------------------------------------------------------------
@@ -806,6 +867,102 @@ impl GitManager {
         Ok(!self.get_conflict_files()?.is_empty())
     }
 
+    /// Commit checked out in `dir` (a project root or one of its worktrees).
+    pub fn head_commit(dir: &Path) -> Result<String> {
+        let out = Command::new("git")
+            .current_dir(dir)
+            .args(["rev-parse", "--verify", "HEAD"])
+            .output()
+            .with_context(|| format!("run git rev-parse HEAD in {}", dir.display()))?;
+        if !out.status.success() {
+            bail!(
+                "no HEAD commit in {}: {}",
+                dir.display(),
+                decode_utf8_lossy(&out.stderr)
+            );
+        }
+        Ok(decode_utf8_lossy(&out.stdout))
+    }
+
+    /// Uncommitted changes (staged and unstaged) in `dir` relative to its HEAD.
+    pub fn uncommitted_diff(dir: &Path) -> Result<String> {
+        let out = Command::new("git")
+            .current_dir(dir)
+            .args(["diff", "--no-color", "--no-ext-diff", "HEAD"])
+            .output()
+            .with_context(|| format!("run git diff HEAD in {}", dir.display()))?;
+        if !out.status.success() {
+            bail!(
+                "git diff HEAD failed in {}: {}",
+                dir.display(),
+                decode_utf8_lossy(&out.stderr)
+            );
+        }
+        Ok(decode_utf8_lossy(&out.stdout))
+    }
+
+    /// Tracked files in `dir`'s working tree, committed or not, against `base`. Untracked
+    /// files aren't part of it; see `untracked_files`.
+    pub fn working_tree_diff(dir: &Path, base: &str) -> Result<String> {
+        let out = Command::new("git")
+            .current_dir(dir)
+            .args(["diff", "--no-color", "--no-ext-diff", "--unified=3"])
+            .args(["--end-of-options", base, "--"])
+            .output()
+            .with_context(|| format!("run git diff {base} in {}", dir.display()))?;
+        if !out.status.success() {
+            bail!(
+                "git diff {base} failed in {}: {}",
+                dir.display(),
+                decode_utf8_lossy(&out.stderr)
+            );
+        }
+        Ok(decode_utf8_lossy(&out.stdout))
+    }
+
+    /// Untracked, non-ignored files in `dir`, relative to it.
+    pub fn untracked_files(dir: &Path) -> Result<Vec<String>> {
+        let out = Command::new("git")
+            .current_dir(dir)
+            .args(["ls-files", "--others", "--exclude-standard", "-z"])
+            .output()
+            .with_context(|| format!("run git ls-files in {}", dir.display()))?;
+        if !out.status.success() {
+            bail!(
+                "git ls-files failed in {}: {}",
+                dir.display(),
+                decode_utf8_lossy(&out.stderr)
+            );
+        }
+        Ok(String::from_utf8_lossy(&out.stdout)
+            .split('\0')
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Full commit id `rev` points at.
+    pub fn resolve_commit(&self, rev: &str) -> Result<String> {
+        if rev.trim().starts_with('-') {
+            bail!("invalid revision {rev:?}");
+        }
+        let spec = format!("{}^{{commit}}", rev.trim());
+        self.run_git(&["rev-parse", "--verify", &spec])
+            .with_context(|| format!("resolve {rev}"))
+    }
+
+    /// First unused branch name among `<stem>`, `<stem>-2`, `<stem>-3`, ... (after prefixing).
+    pub fn unused_branch_name(&self, stem: &str) -> Result<String> {
+        let stem = self.normalize_branch(stem)?;
+        let mut candidate = stem.clone();
+        let mut n = 2;
+        while self.branch_exists(&candidate)? || self.worktree_path_for_branch(&candidate).exists() {
+            candidate = format!("{stem}-{n}");
+            n += 1;
+        }
+        Ok(candidate)
+    }
+
     fn current_branch(&self) -> Result<Option<String>> {
         let out = Command::new("git")
             .current_dir(&self.project_path)
@@ -886,6 +1043,7 @@ impl GitManager {
                     MergeResult {
                         success: true,
                         conflict_files: None,
+                        integration: None,
                     }
                 } else {
                     let files = self.get_conflict_files_in(&self.project_path)?;
@@ -894,6 +1052,7 @@ impl GitManager {
                     MergeResult {
                         success: false,
                         conflict_files: Some(files),
+                        integration: None,
                     }
                 }
             }
@@ -908,6 +1067,7 @@ impl GitManager {
                     MergeResult {
                         success: false,
                         conflict_files: Some(files),
+                        integration: None,
                     }
                 } else {
                     // Use a deterministic message; UI can customize later.
@@ -916,6 +1076,7 @@ impl GitManager {
                     MergeResult {
                         success: true,
                         conflict_files: None,
+                        integration: None,
                     }
                 }
             }
@@ -942,6 +1103,7 @@ impl GitManager {
                         return Ok(MergeResult {
                             success: false,
                             conflict_files: Some(files),
+                            integration: None,
                         });
                     }
                 } else {
@@ -962,6 +1124,7 @@ impl GitManager {
                         return Ok(MergeResult {
                             success: false,
                             conflict_files: Some(files),
+                            integration: None,
                         });
                     }
                 }
@@ -979,12 +1142,14 @@ impl GitManager {
                     MergeResult {
                         success: true,
                         conflict_files: None,
+                        integration: None,
                     }
                 } else {
                     let files = self.get_conflict_files_in(&self.project_path)?;
                     MergeResult {
                         success: false,
                         conflict_files: Some(files),
+                        integration: None,
                     }
                 }
             }
@@ -1001,6 +1166,204 @@ impl GitManager {
         Ok(result)
     }
 
+    /// Predicts `merge_branch` with `git merge-tree --write-tree` (git 2.38+). The diff stat is
+    /// taken against the merged tree, so for conflicts it counts the marker lines too.
+    pub fn merge_preview(&self, branch: &str, base_branch: &str) -> Result<MergePreview> {
+        let branch = self.normalize_branch(branch)?;
+        let base_branch = self.normalize_base_branch(base_branch)?;
+        let branch_commit = self.resolve_commit(&branch)?;
+        let base_commit = self.resolve_commit(&base_branch)?;
+
+        let args = [
+            "merge-tree",
+            "--write-tree",
+            "--name-only",
+            "--no-messages",
+            "-z",
+            &base_commit,
+            &branch_commit,
+        ];
+        let out = Command::new("git")
+            .current_dir(&self.project_path)
+            .args(args)
+            .output()
+            .with_context(|| format!("run git {}", shell_join(&args)))?;
+        // 0: clean, 1: conflicts; anything else is a failure (or a git without --write-tree).
+        if !matches!(out.status.code(), Some(0 | 1)) {
+            bail!(
+                "git merge-tree failed (code={:?}): {}",
+                out.status.code(),
+                decode_utf8_lossy(&out.stderr).trim()
+            );
+        }
+        let text = decode_utf8_lossy(&out.stdout);
+        let mut fields = text.split('\0');
+        let tree = fields.next().unwrap_or_default().trim().to_string();
+        let mut conflict_files: Vec<String> = fields
+            .filter(|f| !f.is_empty())
+            .map(str::to_string)
+            .collect();
+        conflict_files.dedup();
+
+        let stats = commit_graph::parse_numstat(&self.run_git(&[
+            "diff",
+            "--numstat",
+            "-z",
+            &base_commit,
+            &tree,
+        ])?);
+        Ok(MergePreview {
+            mergeable: out.status.success(),
+            conflict_files,
+            files_changed: stats.len() as u32,
+            additions: stats.iter().filter_map(|(a, _, _)| *a).sum(),
+            deletions: stats.iter().filter_map(|(_, d, _)| *d).sum(),
+            base_commit,
+            branch_commit,
+        })
+    }
+
+    // -------------------------------------------------------------------------
+    // History (unified git panel)
+    // -------------------------------------------------------------------------
+
+    /// One page of history, newest first, reachable from `branch` or from every branch, remote
+    /// and tag when `None`. Later pages resume from `cursor`, which remembers where the walk
+    /// stopped rather than how far it got, so each page only reads its own commits.
+    pub fn commit_log(
+        &self,
+        branch: Option<&str>,
+        cursor: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<CommitLogPage> {
+        let mut cursor = match cursor {
+            Some(c) => LogCursor::decode(c)?,
+            None => LogCursor {
+                tips: self.log_tips(branch)?,
+                ..LogCursor::default()
+            },
+        };
+        let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+        let starts: Vec<String> = cursor.starts().into_iter().map(str::to_string).collect();
+        if starts.is_empty() {
+            // A repo without commits, or a cursor past the root.
+            return Ok(CommitLogPage {
+                commits: Vec::new(),
+                next_cursor: None,
+            });
+        }
+        // One extra record tells whether another page exists.
+        let max_count = format!("--max-count={}", limit + 1);
+        let mut args = vec![
+            "log",
+            "--no-color",
+            "--date-order",
+            "--decorate=full",
+            commit_graph::LOG_FORMAT,
+            &max_count,
+            "--end-of-options",
+        ];
+        args.extend(starts.iter().map(String::as_str));
+        let text = self.run_git(&args)?;
+
+        let mut commits = commit_graph::parse_log(&text);
+        let has_more = commits.len() > limit;
+        commits.truncate(limit);
+        for c in &mut commits {
+            c.graph = cursor.lanes.place(&c.hash, &c.parents);
+        }
+        cursor
+            .tips
+            .retain(|tip| !commits.iter().any(|c| &c.hash == tip));
+        Ok(CommitLogPage {
+            commits,
+            next_cursor: has_more.then(|| cursor.encode()),
+        })
+    }
+
+    /// Commits the history walk starts from: `branch`, else every branch, remote, tag and `HEAD`.
+    fn log_tips(&self, branch: Option<&str>) -> Result<Vec<String>> {
+        if let Some(b) = branch {
+            return Ok(vec![self.resolve_commit(b)?]);
+        }
+        let mut args = vec!["rev-list", "--no-walk", "--branches", "--remotes", "--tags"];
+        // An unborn `HEAD` has nothing to show yet.
+        if self.rev_exists("HEAD")? {
+            args.push("HEAD");
+        }
+        Ok(self
+            .run_git(&args)?
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Files changed by `rev` against its first parent (everything, for a root commit), with
+    /// renames and copies detected.
+    pub fn commit_files(&self, rev: &str) -> Result<Vec<CommitFileChange>> {
+        let hash = self.resolve_commit(rev)?;
+        let parent = self.resolve_commit(&format!("{hash}^")).ok();
+        let diff = |format: &str| {
+            let mut args = vec![
+                "diff-tree",
+                "-r",
+                "--no-commit-id",
+                "-M",
+                "-C",
+                "-z",
+                format,
+            ];
+            match &parent {
+                Some(p) => args.push(p),
+                None => args.push("--root"),
+            }
+            args.push(&hash);
+            self.run_git(&args)
+        };
+        let mut files = commit_graph::parse_name_status(&diff("--name-status")?)?;
+        let stats = commit_graph::parse_numstat(&diff("--numstat")?);
+        // Both listings come from the same diff, in the same order.
+        for (file, (additions, deletions, path)) in files.iter_mut().zip(stats) {
+            if file.path == path {
+                file.additions = additions;
+                file.deletions = deletions;
+            }
+        }
+        Ok(files)
+    }
+
+    /// Refs pointing at `rev`, and the branches and tags that contain it.
+    pub fn refs_for_commit(&self, rev: &str) -> Result<CommitRefs> {
+        let hash = self.resolve_commit(rev)?;
+        let refs = |filter: &str| -> Result<Vec<GitRef>> {
+            let text = self.run_git(&[
+                "for-each-ref",
+                "--format=%(refname)",
+                filter,
+                &hash,
+                "refs/heads",
+                "refs/remotes",
+                "refs/tags",
+            ])?;
+            Ok(text
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty() && !commit_graph::is_remote_head(l))
+                .map(GitRef::from_full_name)
+                .collect())
+        };
+        let mut at = refs("--points-at")?;
+        if self.resolve_commit("HEAD").ok().as_deref() == Some(hash.as_str()) {
+            at.insert(0, GitRef::from_full_name("HEAD"));
+        }
+        Ok(CommitRefs {
+            at,
+            containing: refs("--contains")?,
+        })
+    }
+
     pub fn list_branches(&self) -> Result<Vec<String>> {
         let text = self
             .run_git(&["branch", "--format=%(refname:short)"])
@@ -1020,66 +1383,13 @@ impl GitManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use anyhow::anyhow;
-    use std::time::{Duration, UNIX_EPOCH};
-
-    fn unique_tmp_dir(prefix: &str) -> PathBuf {
-        let n = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or(Duration::from_secs(0))
-            .as_nanos();
-        std::env::temp_dir().join(format!("{prefix}-{n}"))
-    }
-
-    fn git(dir: &Path, args: &[&str]) -> Result<()> {
-        let out = Command::new("git")
-            .current_dir(dir)
-            .args(args)
-            .output()
-            .with_context(|| format!("run git {}", shell_join(args)))?;
-        if !out.status.success() {
-            return Err(anyhow!(
-                "git {} failed\nstdout: {}\nstderr: {}",
-                shell_join(args),
-                decode_utf8_lossy(&out.stdout),
-                decode_utf8_lossy(&out.stderr)
-            ));
-        }
-        Ok(())
-    }
-
-    fn git_out(dir: &Path, args: &[&str]) -> Result<String> {
-        let out = Command::new("git")
-            .current_dir(dir)
-            .args(args)
-            .output()
-            .with_context(|| format!("run git {}", shell_join(args)))?;
-        if !out.status.success() {
-            return Err(anyhow!(
-                "git {} failed\nstdout: {}\nstderr: {}",
-                shell_join(args),
-                decode_utf8_lossy(&out.stdout),
-                decode_utf8_lossy(&out.stderr)
-            ));
-        }
-        Ok(decode_utf8_lossy(&out.stdout))
-    }
-
-    fn init_repo(dir: &Path) -> Result<()> {
-        fs::create_dir_all(dir).context("create tmp repo dir")?;
-        git(dir, &["init", "-b", "main"])?;
-        git(dir, &["config", "user.name", "synk"])?;
-        git(dir, &["config", "user.email", "synk@example.com"])?;
-        fs::write(dir.join("README.md"), "hello\n").context("write README")?;
-        git(dir, &["add", "."])?;
-        git(dir, &["commit", "-m", "init"])?;
-        Ok(())
-    }
+    use crate::core::test_fixtures::{git, init_repo, TempDir};
 
     #[test]
     fn create_and_remove_worktree_deletes_branch() -> Result<()> {
-        let repo = unique_tmp_dir("synk-git-repo");
-        let wts = unique_tmp_dir("synk-worktrees");
+        let tmp = TempDir::new("git");
+        let repo = tmp.join("repo");
+        let wts = tmp.join("worktrees");
         init_repo(&repo)?;
 
         let mgr = GitManager::new(repo.clone(), wts.to_string_lossy().as_ref(), "feat/")?;
@@ -1093,10 +1403,34 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn worktree_can_start_from_commit() -> Result<()> {
+        let tmp = TempDir::new("git-fork");
+        let repo = tmp.join("repo");
+        let wts = tmp.join("worktrees");
+        init_repo(&repo)?;
+
+        let mgr = GitManager::new(repo.clone(), wts.to_string_lossy().as_ref(), "feat/")?;
+        let (wt, _) = mgr.create_worktree("feat/source", "main")?;
+        fs::write(wt.join("work.txt"), "wip\n").context("write work.txt")?;
+        git(&wt, &["add", "work.txt"])?;
+        git(&wt, &["commit", "-m", "wip"])?;
+
+        let head = GitManager::head_commit(&wt)?;
+        let branch = mgr.unused_branch_name("source-fork")?;
+        assert_eq!(branch, "feat/source-fork");
+        let (fork, branch) = mgr.create_worktree(&branch, &head)?;
+        assert!(fork.join("work.txt").exists(), "fork should include source commits");
+        assert_eq!(GitManager::head_commit(&fork)?, head);
+        assert_eq!(mgr.unused_branch_name("source-fork")?, format!("{branch}-2"));
+        Ok(())
+    }
+
     #[test]
     fn detect_orphans_ignores_active() -> Result<()> {
-        let repo = unique_tmp_dir("synk-git-repo2");
-        let wts = unique_tmp_dir("synk-worktrees2");
+        let tmp = TempDir::new("git");
+        let repo = tmp.join("repo");
+        let wts = tmp.join("worktrees");
         init_repo(&repo)?;
 
         let mgr = GitManager::new(repo.clone(), wts.to_string_lossy().as_ref(), "feat/")?;
@@ -1119,8 +1453,9 @@ mod tests {
 
     #[test]
     fn generate_diff_returns_structured_file_diffs() -> Result<()> {
-        let repo = unique_tmp_dir("synk-git-repo3");
-        let wts = unique_tmp_dir("synk-worktrees3");
+        let tmp = TempDir::new("git");
+        let repo = tmp.join("repo");
+        let wts = tmp.join("worktrees");
         init_repo(&repo)?;
 
         let mgr = GitManager::new(repo.clone(), wts.to_string_lossy().as_ref(), "feat/")?;
@@ -1153,8 +1488,9 @@ mod tests {
 
     #[test]
     fn squash_merge_creates_single_commit_on_main() -> Result<()> {
-        let repo = unique_tmp_dir("synk-git-repo4");
-        let wts = unique_tmp_dir("synk-worktrees4");
+        let tmp = TempDir::new("git");
+        let repo = tmp.join("repo");
+        let wts = tmp.join("worktrees");
         init_repo(&repo)?;
 
         let mgr = GitManager::new(repo.clone(), wts.to_string_lossy().as_ref(), "feat/")?;
@@ -1168,13 +1504,13 @@ mod tests {
         git(&wt, &["add", "b.txt"])?;
         git(&wt, &["commit", "-m", "add b.txt"])?;
 
-        let before: u32 = git_out(&repo, &["rev-list", "--count", "main"])?
+        let before: u32 = git(&repo, &["rev-list", "--count", "main"])?
             .parse()
             .unwrap_or(0);
         let res = mgr.merge_branch("feat/squash", "main", MergeStrategy::Squash)?;
         assert!(res.success, "expected squash merge to succeed");
 
-        let after: u32 = git_out(&repo, &["rev-list", "--count", "main"])?
+        let after: u32 = git(&repo, &["rev-list", "--count", "main"])?
             .parse()
             .unwrap_or(0);
         assert_eq!(after, before + 1, "squash should add exactly 1 commit");
@@ -1183,8 +1519,9 @@ mod tests {
 
     #[test]
     fn merge_conflict_returns_conflict_files() -> Result<()> {
-        let repo = unique_tmp_dir("synk-git-repo5");
-        let wts = unique_tmp_dir("synk-worktrees5");
+        let tmp = TempDir::new("git");
+        let repo = tmp.join("repo");
+        let wts = tmp.join("worktrees");
         init_repo(&repo)?;
 
         let mgr = GitManager::new(repo.clone(), wts.to_string_lossy().as_ref(), "feat/")?;
@@ -1211,4 +1548,126 @@ mod tests {
         let _ = git(&repo, &["merge", "--abort"]);
         Ok(())
     }
+
+    #[test]
+    fn merge_preview_predicts_conflicts_without_touching_the_tree() -> Result<()> {
+        let tmp = TempDir::new("git-preview");
+        let repo = tmp.join("repo");
+        let wts = tmp.join("worktrees");
+        init_repo(&repo)?;
+
+        let mgr = GitManager::new(repo.clone(), wts.to_string_lossy().as_ref(), "feat/")?;
+        let (wt, _) = mgr.create_worktree("feat/preview", "main")?;
+
+        fs::write(wt.join("README.md"), "hello from branch\n").context("write README in branch")?;
+        git(&wt, &["add", "README.md"])?;
+        git(&wt, &["commit", "-m", "branch edit"])?;
+
+        let preview = mgr.merge_preview("feat/preview", "main")?;
+        assert!(preview.mergeable && preview.conflict_files.is_empty());
+        assert_eq!(
+            (preview.files_changed, preview.additions, preview.deletions),
+            (1, 1, 1)
+        );
+
+        fs::write(repo.join("README.md"), "hello from main\n").context("write README in main")?;
+        git(&repo, &["add", "README.md"])?;
+        git(&repo, &["commit", "-m", "main edit"])?;
+
+        let head = git(&repo, &["rev-parse", "HEAD"])?;
+        let preview = mgr.merge_preview("feat/preview", "main")?;
+        assert!(!preview.mergeable);
+        assert_eq!(preview.conflict_files, vec!["README.md"]);
+        assert_eq!(preview.base_commit, head.trim());
+        assert_eq!(git(&repo, &["status", "--porcelain"])?, "");
+        Ok(())
+    }
+
+    #[test]
+    fn commit_log_pages_keep_the_graph_and_files_track_copies() -> Result<()> {
+        use crate::core::commit_graph::{CommitFileStatus, GitRefKind};
+
+        let tmp = TempDir::new("git-history");
+        let repo = tmp.join("repo");
+        let wts = tmp.join("worktrees");
+        init_repo(&repo)?;
+        let body = |tag: &str| {
+            (1..=20)
+                .map(|i| format!("{tag} line {i}\n"))
+                .collect::<String>()
+        };
+        fs::write(repo.join("lib.rs"), body("lib")).context("write lib.rs")?;
+        fs::write(repo.join("util.rs"), body("util")).context("write util.rs")?;
+        git(&repo, &["add", "."])?;
+        git(&repo, &["commit", "-m", "add sources"])?;
+
+        git(&repo, &["switch", "-c", "feat/x"])?;
+        fs::write(repo.join("copy.rs"), body("lib")).context("write copy.rs")?;
+        fs::write(repo.join("lib.rs"), format!("{}extra\n", body("lib"))).context("edit lib.rs")?;
+        git(&repo, &["mv", "util.rs", "helpers.rs"])?;
+        git(&repo, &["add", "."])?;
+        git(&repo, &["commit", "-m", "copy and rename"])?;
+
+        // Never merged, so paging has to carry its tip until the walk gets to it.
+        git(&repo, &["switch", "-c", "side", "main"])?;
+        fs::write(repo.join("side.txt"), "side\n").context("write side.txt")?;
+        git(&repo, &["add", "side.txt"])?;
+        git(&repo, &["commit", "-m", "side work"])?;
+
+        git(&repo, &["switch", "main"])?;
+        fs::write(repo.join("main.txt"), "main\n").context("write main.txt")?;
+        git(&repo, &["add", "main.txt"])?;
+        git(&repo, &["commit", "-m", "main work"])?;
+        git(&repo, &["merge", "--no-ff", "feat/x", "-m", "merge feat/x"])?;
+        git(&repo, &["tag", "v1"])?;
+
+        let mgr = GitManager::new(repo.clone(), wts.to_string_lossy().as_ref(), "feat/")?;
+        let full = mgr.commit_log(None, None, None)?;
+        assert_eq!(full.commits.len(), 6);
+        assert!(full.next_cursor.is_none());
+        let merge = &full.commits[0];
+        assert_eq!(merge.subject, "merge feat/x");
+        assert_eq!(merge.parents.len(), 2);
+        assert_eq!(merge.graph.parent_lanes, vec![0, 1]);
+        let kinds: Vec<GitRefKind> = merge.refs.iter().map(|r| r.kind).collect();
+        assert!(kinds.contains(&GitRefKind::Head) && kinds.contains(&GitRefKind::Tag));
+
+        let mut paged = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = mgr.commit_log(None, cursor.as_deref(), Some(2))?;
+            paged.extend(page.commits);
+            match page.next_cursor {
+                Some(c) => cursor = Some(c),
+                None => break,
+            }
+        }
+        assert_eq!(paged, full.commits, "pages should continue the same lanes");
+        assert_eq!(mgr.commit_log(Some("feat/x"), None, None)?.commits.len(), 3);
+        assert!(mgr.commit_log(Some("--all"), None, None).is_err());
+
+        let files = mgr.commit_files("feat/x")?;
+        let find = |p: &str| files.iter().find(|f| f.path == p).cloned();
+        let copied = find("copy.rs").context("copy.rs listed")?;
+        assert_eq!(copied.status, CommitFileStatus::Copied);
+        assert_eq!(copied.old_path.as_deref(), Some("lib.rs"));
+        let renamed = find("helpers.rs").context("helpers.rs listed")?;
+        assert_eq!(renamed.status, CommitFileStatus::Renamed);
+        assert_eq!(renamed.old_path.as_deref(), Some("util.rs"));
+        let edited = find("lib.rs").context("lib.rs listed")?;
+        assert_eq!((edited.additions, edited.deletions), (Some(1), Some(0)));
+
+        let root = full.commits.last().context("root commit")?;
+        let root_files = mgr.commit_files(&root.hash)?;
+        assert_eq!(root_files.len(), 1);
+        assert_eq!(root_files[0].status, CommitFileStatus::Added);
+
+        let refs = mgr.refs_for_commit("feat/x")?;
+        let names = |v: &[crate::core::commit_graph::GitRef]| {
+            v.iter().map(|r| r.name.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(names(&refs.at), vec!["feat/x"]);
+        assert_eq!(names(&refs.containing), vec!["feat/x", "main", "v1"]);
+        Ok(())
+    }
 }
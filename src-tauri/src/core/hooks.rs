@@ -0,0 +1,647 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::core::app_context::AppContext;
+use crate::core::audit_log;
+use crate::core::event_bus::SharedEventBus;
+use crate::core::notifications::NotificationKind;
+use crate::core::session_manager::SharedSessionManager;
+use crate::core::settings::{self as core_settings, HookView, MAX_HOOK_ATTEMPTS};
+use crate::events::{
+    now_rfc3339, AppEvent, GitEventType, ReviewEventKind, GIT_EVENT_NAME, NOTIFICATION_EVENT_NAME,
+    REVIEW_EVENT_NAME, SESSION_EXIT_EVENT_NAME,
+};
+
+/// One line per delivery (after retries), in the config dir.
+pub const DELIVERY_LOG_FILE: &str = "hooks.jsonl";
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+/// Wait before the second attempt; doubles after every further failure, up to
+/// `MAX_RETRY_BACKOFF`.
+const RETRY_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+const DEFAULT_DELIVERIES_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    ReviewReady,
+    MergeCompleted,
+    ConflictDetected,
+    SessionCrashed,
+    /// A session crossed `notifications.cost_threshold`.
+    BudgetExceeded,
+}
+
+impl HookEvent {
+    pub const ALL: [HookEvent; 5] = [
+        Self::ReviewReady,
+        Self::MergeCompleted,
+        Self::ConflictDetected,
+        Self::SessionCrashed,
+        Self::BudgetExceeded,
+    ];
+
+    /// The name used in `HookView::events` and the `X-Synk-Event` header.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ReviewReady => "review_ready",
+            Self::MergeCompleted => "merge_completed",
+            Self::ConflictDetected => "conflict_detected",
+            Self::SessionCrashed => "session_crashed",
+            Self::BudgetExceeded => "budget_exceeded",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.as_str() == s)
+    }
+}
+
+/// The JSON body POSTed to URLs and written to commands' stdin.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookPayload {
+    pub event: HookEvent,
+    pub timestamp: String,
+    /// One-line summary; also what Slack-style incoming webhooks display.
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<usize>,
+    /// The source event as published on the bus.
+    pub data: Value,
+    /// Set for `hooks_test` deliveries.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub test: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookDelivery {
+    pub timestamp: String,
+    pub hook_id: String,
+    pub hook_name: String,
+    pub event: HookEvent,
+    /// The URL or command.
+    pub target: String,
+    pub attempts: u32,
+    pub ok: bool,
+    /// HTTP status of the last attempt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookDeliveriesArgs {
+    #[serde(default)]
+    pub hook_id: Option<String>,
+    /// Newest first, at most this many (default 100).
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Target<'a> {
+    Url(&'a str),
+    Command(&'a str),
+}
+
+fn non_empty(v: &Option<String>) -> Option<&str> {
+    v.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn target(hook: &HookView) -> Result<Target<'_>> {
+    match (non_empty(&hook.url), non_empty(&hook.command)) {
+        (Some(url), None) => Ok(Target::Url(url)),
+        (None, Some(command)) => Ok(Target::Command(command)),
+        (Some(_), Some(_)) => bail!("hook {:?} sets both a url and a command", hook.name),
+        (None, None) => bail!("hook {:?} has neither a url nor a command", hook.name),
+    }
+}
+
+/// Maps a bus event to the hook event it triggers, if any.
+pub fn payload_for(
+    event: &AppEvent,
+    project_of: &dyn Fn(usize) -> Option<String>,
+) -> Option<HookPayload> {
+    let (kind, text, project_path, session_id) = match event {
+        AppEvent::Review(e) if e.kind == ReviewEventKind::Created => (
+            HookEvent::ReviewReady,
+            format!("Review ready: {} into {}", e.branch, e.base_branch),
+            Some(e.project_path.clone()),
+            Some(e.session_id),
+        ),
+        AppEvent::Git(e) => {
+            let (kind, verb) = match e.event_type {
+                GitEventType::MergeCompleted => (HookEvent::MergeCompleted, "Merged"),
+                GitEventType::ConflictDetected => (HookEvent::ConflictDetected, "Conflict merging"),
+                _ => return None,
+            };
+            let text = format!(
+                "{verb} {} into {}",
+                e.branch.as_deref().unwrap_or("branch"),
+                e.base_branch.as_deref().unwrap_or("base")
+            );
+            (kind, text, Some(e.project_path.clone()), e.session_id)
+        }
+        // Closing a session or a clean `exit` reports 0; unknown statuses are not crashes.
+        AppEvent::SessionExit(e) if e.exit_code.is_some_and(|c| c != 0) => (
+            HookEvent::SessionCrashed,
+            format!(
+                "Session #{} exited with code {}",
+                e.session_id,
+                e.exit_code.unwrap_or_default()
+            ),
+            project_of(e.session_id),
+            Some(e.session_id),
+        ),
+        AppEvent::Notification(n) if n.kind == NotificationKind::CostThreshold => (
+            HookEvent::BudgetExceeded,
+            format!("{}. {}", n.title, n.body),
+            n.project_path.clone(),
+            n.session_id,
+        ),
+        _ => return None,
+    };
+    Some(HookPayload {
+        event: kind,
+        timestamp: now_rfc3339(),
+        text,
+        project_path,
+        session_id,
+        data: event.payload().unwrap_or(Value::Null),
+        test: false,
+    })
+}
+
+/// Sends `payload` to the hook, retrying up to `max_attempts` times with a doubling `backoff`
+/// capped at `MAX_RETRY_BACKOFF`.
+pub fn deliver(hook: &HookView, payload: &HookPayload, backoff: Duration) -> HookDelivery {
+    let started = Instant::now();
+    let mut delivery = HookDelivery {
+        timestamp: now_rfc3339(),
+        hook_id: hook.id.clone(),
+        hook_name: hook.name.clone(),
+        event: payload.event,
+        target: String::new(),
+        attempts: 0,
+        ok: false,
+        status: None,
+        error: None,
+        duration_ms: 0,
+    };
+    let target = match target(hook) {
+        Ok(t) => t,
+        Err(err) => {
+            delivery.error = Some(format!("{err:#}"));
+            return delivery;
+        }
+    };
+    delivery.target = match target {
+        Target::Url(s) | Target::Command(s) => s.to_string(),
+    };
+
+    let body = serde_json::to_vec(payload).unwrap_or_default();
+    let mut delay = backoff;
+    for attempt in 1..=hook.max_attempts.clamp(1, MAX_HOOK_ATTEMPTS) {
+        if attempt > 1 {
+            std::thread::sleep(delay);
+            delay = (delay * 2).min(MAX_RETRY_BACKOFF);
+        }
+        delivery.attempts = attempt;
+        let (status, result) = match target {
+            Target::Url(url) => post(url, payload.event, &body),
+            Target::Command(command) => (None, run_command(command, payload.event, &body)),
+        };
+        delivery.status = status;
+        match result {
+            Ok(()) => {
+                delivery.ok = true;
+                delivery.error = None;
+                break;
+            }
+            Err(err) => delivery.error = Some(format!("{err:#}")),
+        }
+    }
+    delivery.duration_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+    delivery
+}
+
+fn post(url: &str, event: HookEvent, body: &[u8]) -> (Option<u16>, Result<()>) {
+    let client = match reqwest::blocking::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+    {
+        Ok(c) => c,
+        Err(err) => return (None, Err(err).context("build http client")),
+    };
+    let sent = client
+        .post(url)
+        .header("content-type", "application/json")
+        .header("x-synk-event", event.as_str())
+        .body(body.to_vec())
+        .send();
+    match sent {
+        Ok(res) if res.status().is_success() => (Some(res.status().as_u16()), Ok(())),
+        Ok(res) => (
+            Some(res.status().as_u16()),
+            Err(anyhow!("POST {url}: HTTP {}", res.status())),
+        ),
+        Err(err) => (None, Err(err).with_context(|| format!("POST {url}"))),
+    }
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.args(["/C", command]);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command]);
+    cmd
+}
+
+/// Runs `command` with the payload on stdin and `SYNK_HOOK_EVENT` set; non-zero exit fails.
+fn run_command(command: &str, event: HookEvent, body: &[u8]) -> Result<()> {
+    let mut child = shell(command)
+        .env("SYNK_HOOK_EVENT", event.as_str())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("spawn {command:?}"))?;
+    let stderr = child.stderr.take();
+    let reader = std::thread::spawn(move || {
+        let mut text = String::new();
+        if let Some(mut stderr) = stderr {
+            let _ = stderr.read_to_string(&mut text);
+        }
+        text
+    });
+    // Written off this thread so a command that never reads its input can't hold us past the
+    // timeout; one that exits first just breaks the pipe.
+    if let Some(mut stdin) = child.stdin.take() {
+        let body = body.to_vec();
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(&body);
+        });
+    }
+
+    let deadline = Instant::now() + COMMAND_TIMEOUT;
+    let status = loop {
+        if let Some(status) = child.try_wait().context("wait for hook command")? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            bail!("{command:?} timed out after {}s", COMMAND_TIMEOUT.as_secs());
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    if status.success() {
+        return Ok(());
+    }
+    let stderr = reader.join().unwrap_or_default();
+    match stderr.trim().lines().last() {
+        Some(line) => bail!("{command:?} failed ({status}): {line}"),
+        None => bail!("{command:?} failed ({status})"),
+    }
+}
+
+fn log_path(app: &AppContext) -> Result<PathBuf> {
+    app.config_path(DELIVERY_LOG_FILE)
+}
+
+fn log_delivery(app: &AppContext, delivery: &HookDelivery) {
+    if let Err(err) = log_path(app).and_then(|path| audit_log::append(&path, delivery)) {
+        eprintln!("hooks: {err:#}");
+    }
+}
+
+/// Reads logged deliveries from `path`, newest first. Unparseable lines are skipped.
+pub fn read_deliveries(path: &Path, args: &HookDeliveriesArgs) -> Result<Vec<HookDelivery>> {
+    let file = match fs::File::open(path) {
+        Ok(f) => f,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("open {}", path.display())),
+    };
+    let mut out: Vec<HookDelivery> = BufReader::new(file)
+        .lines()
+        .map_while(|l| l.ok())
+        .filter_map(|l| serde_json::from_str(&l).ok())
+        .filter(|d: &HookDelivery| args.hook_id.as_deref().is_none_or(|id| id == d.hook_id))
+        .collect();
+    out.reverse();
+    out.truncate(args.limit.unwrap_or(DEFAULT_DELIVERIES_LIMIT));
+    Ok(out)
+}
+
+pub fn deliveries(app: &AppContext, args: &HookDeliveriesArgs) -> Result<Vec<HookDelivery>> {
+    read_deliveries(&log_path(app)?, args)
+}
+
+/// Sends a sample payload to the hook once, whatever its event filter, and logs the result.
+pub fn send_test(app: &AppContext, hook_id: &str) -> Result<HookDelivery> {
+    let settings = core_settings::settings_get(app)?;
+    let hook = settings
+        .hooks
+        .into_iter()
+        .find(|h| h.id == hook_id)
+        .with_context(|| format!("no hook with id {hook_id:?}"))?;
+    let event = hook
+        .events
+        .iter()
+        .find_map(|e| HookEvent::parse(e))
+        .unwrap_or(HookEvent::ReviewReady);
+    let payload = HookPayload {
+        event,
+        timestamp: now_rfc3339(),
+        text: format!("Test delivery for {} from Synk", event.as_str()),
+        project_path: None,
+        session_id: None,
+        data: Value::Null,
+        test: true,
+    };
+    let hook = HookView {
+        max_attempts: 1,
+        ..hook
+    };
+    let delivery = deliver(&hook, &payload, RETRY_BACKOFF);
+    log_delivery(app, &delivery);
+    Ok(delivery)
+}
+
+/// Delivers `payload` to every enabled hook subscribed to its event, each on its own thread so
+/// a slow or retrying hook doesn't hold up the others.
+fn dispatch(app: &AppContext, hooks: Vec<HookView>, payload: HookPayload) {
+    let payload = Arc::new(payload);
+    for hook in hooks {
+        if !hook.enabled || !hook.events.iter().any(|e| e == payload.event.as_str()) {
+            continue;
+        }
+        let app = app.clone();
+        let payload = payload.clone();
+        std::thread::spawn(move || {
+            let delivery = deliver(&hook, &payload, RETRY_BACKOFF);
+            if let Some(err) = delivery.error.as_deref().filter(|_| !delivery.ok) {
+                eprintln!(
+                    "hooks: {} failed after {} attempt(s): {err}",
+                    hook.name, delivery.attempts
+                );
+            }
+            log_delivery(&app, &delivery);
+        });
+    }
+}
+
+fn project_of(app: &AppContext, session_id: usize) -> Option<String> {
+    let sessions = app.try_state::<SharedSessionManager>()?;
+    let info = sessions
+        .lock()
+        .expect("session manager mutex poisoned")
+        .get_session_info(session_id)?;
+    Some(info.project_path)
+}
+
+/// Starts delivering published events to the hooks configured in settings.
+pub fn start(app: AppContext) {
+    let Some(bus) = app.try_state::<SharedEventBus>() else {
+        return;
+    };
+    // Subscribers run on the publishing thread; session lookups and settings reads happen on
+    // the worker instead.
+    let (tx, rx) = mpsc::channel::<AppEvent>();
+    bus.lock().expect("event bus mutex poisoned").subscribe(
+        &[
+            REVIEW_EVENT_NAME,
+            GIT_EVENT_NAME,
+            SESSION_EXIT_EVENT_NAME,
+            NOTIFICATION_EVENT_NAME,
+        ],
+        move |event| {
+            let _ = tx.send(event.clone());
+        },
+    );
+
+    std::thread::spawn(move || {
+        for event in rx {
+            let Some(payload) = payload_for(&event, &|id| project_of(&app, id)) else {
+                continue;
+            };
+            match core_settings::settings_get(&app) {
+                Ok(settings) => dispatch(&app, settings.hooks, payload),
+                Err(err) => eprintln!("hooks: {err:#}"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    use serde_json::{json, Value};
+
+    use super::{
+        deliver, payload_for, read_deliveries, run_command, HookDeliveriesArgs, HookEvent,
+        HookPayload, COMMAND_TIMEOUT,
+    };
+    use crate::core::audit_log::append;
+    use crate::core::settings::{HookView, MAX_HOOK_ATTEMPTS};
+    use crate::core::test_fixtures::TempDir;
+    use crate::events::{AppEvent, SessionExitEvent};
+
+    fn payload(event: HookEvent) -> HookPayload {
+        HookPayload {
+            event,
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            text: "Merged feat/x into main".to_string(),
+            project_path: Some("/repo".to_string()),
+            session_id: Some(2),
+            data: Value::Null,
+            test: false,
+        }
+    }
+
+    /// Answers each request with the next status in `statuses` and forwards the bodies.
+    fn receiver(statuses: Vec<u16>) -> (String, mpsc::Receiver<(String, Value)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for status in statuses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let (mut len, mut event) = (0usize, String::new());
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end().to_ascii_lowercase();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(v) = line.strip_prefix("content-length:") {
+                        len = v.trim().parse().unwrap();
+                    }
+                    if let Some(v) = line.strip_prefix("x-synk-event:") {
+                        event = v.trim().to_string();
+                    }
+                }
+                let mut body = vec![0; len];
+                reader.read_exact(&mut body).unwrap();
+                let mut stream = reader.into_inner();
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} X\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                )
+                .unwrap();
+                tx.send((event, serde_json::from_slice(&body).unwrap()))
+                    .unwrap();
+            }
+        });
+        (url, rx)
+    }
+
+    #[test]
+    fn posts_and_retries_until_success() {
+        let (url, rx) = receiver(vec![503, 200]);
+        let hook = HookView {
+            id: "chat".to_string(),
+            name: "Team chat".to_string(),
+            events: vec!["merge_completed".to_string()],
+            url: Some(url),
+            ..Default::default()
+        };
+        let d = deliver(
+            &hook,
+            &payload(HookEvent::MergeCompleted),
+            Duration::from_millis(1),
+        );
+        assert!(d.ok, "{:?}", d.error);
+        assert_eq!((d.attempts, d.status), (2, Some(200)));
+
+        let (event, body) = rx.recv().unwrap();
+        assert_eq!(event, "merge_completed");
+        assert_eq!(body["event"], json!("merge_completed"));
+        assert_eq!(body["text"], json!("Merged feat/x into main"));
+        assert_eq!(body["sessionId"], json!(2));
+        assert!(body.get("test").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn commands_get_the_payload_and_failures_are_retried() {
+        let dir = TempDir::new("hooks");
+        let out = dir.join("event.json");
+
+        let hook = HookView {
+            id: "script".to_string(),
+            command: Some(format!("cat > '{}'", out.display())),
+            ..Default::default()
+        };
+        let d = deliver(&hook, &payload(HookEvent::ReviewReady), Duration::ZERO);
+        assert!(d.ok, "{:?}", d.error);
+        let written: Value = serde_json::from_slice(&std::fs::read(&out).unwrap()).unwrap();
+        assert_eq!(written["event"], json!("review_ready"));
+
+        let failing = HookView {
+            id: "broken".to_string(),
+            command: Some("echo nope >&2; exit 3".to_string()),
+            max_attempts: 2,
+            ..Default::default()
+        };
+        let d = deliver(&failing, &payload(HookEvent::ReviewReady), Duration::ZERO);
+        assert!(!d.ok);
+        assert_eq!(d.attempts, 2);
+        assert!(
+            d.error.as_deref().unwrap().ends_with("nope"),
+            "{:?}",
+            d.error
+        );
+
+        let unbounded = HookView {
+            max_attempts: 1000,
+            ..failing.clone()
+        };
+        let clamped = deliver(&unbounded, &payload(HookEvent::ReviewReady), Duration::ZERO);
+        assert_eq!(clamped.attempts, MAX_HOOK_ATTEMPTS);
+
+        let log = dir.join("hooks.jsonl");
+        append(&log, &d).unwrap();
+        let found = read_deliveries(
+            &log,
+            &HookDeliveriesArgs {
+                hook_id: Some("broken".to_string()),
+                limit: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(found, vec![d]);
+
+        let misconfigured = deliver(
+            &HookView::default(),
+            &payload(HookEvent::ReviewReady),
+            Duration::ZERO,
+        );
+        assert_eq!(misconfigured.attempts, 0);
+        assert!(!misconfigured.ok);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn commands_that_ignore_stdin_and_fill_stderr_finish() {
+        let body = vec![b'x'; 1 << 20];
+        let started = Instant::now();
+        let res = run_command(
+            "head -c 300000 /dev/zero >&2; exit 4",
+            HookEvent::ReviewReady,
+            &body,
+        );
+        assert!(res.unwrap_err().to_string().contains("failed"));
+        assert!(started.elapsed() < COMMAND_TIMEOUT);
+    }
+
+    #[test]
+    fn only_crashes_trigger_session_hooks() {
+        let exit = |code| {
+            AppEvent::SessionExit(SessionExitEvent {
+                session_id: 4,
+                exit_code: code,
+            })
+        };
+        let project = |_| Some("/repo".to_string());
+        assert!(payload_for(&exit(Some(0)), &project).is_none());
+        assert!(payload_for(&exit(None), &project).is_none());
+        let p = payload_for(&exit(Some(137)), &project).unwrap();
+        assert_eq!(p.event, HookEvent::SessionCrashed);
+        assert_eq!(p.project_path.as_deref(), Some("/repo"));
+        assert_eq!(p.data["exitCode"], json!(137));
+        assert_eq!(
+            HookEvent::parse("budget_exceeded"),
+            Some(HookEvent::BudgetExceeded)
+        );
+    }
+}
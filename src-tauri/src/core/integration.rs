@@ -0,0 +1,647 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::core::git_manager::{slugify_branch, GitManager, MergeStrategy};
+use crate::events::now_rfc3339;
+
+/// A merge left in progress in its own detached worktree so its conflicts can be resolved
+/// before the base branch moves. In that worktree "ours" is the base branch and "theirs" the
+/// branch being merged.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Integration {
+    /// Slug of `branch`; one integration per branch at a time.
+    pub id: String,
+    pub project_path: String,
+    pub branch: String,
+    pub base_branch: String,
+    pub strategy: MergeStrategy,
+    /// Base tip the merge started from; continuing refuses if the base has moved since.
+    pub base_commit: String,
+    pub worktree_path: String,
+    /// Files that conflicted when the merge started.
+    pub conflict_files: Vec<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictHunk {
+    pub index: usize,
+    /// 1-based line of the `<<<<<<<` marker in the working file.
+    pub start_line: usize,
+    pub ours: String,
+    /// Merge base text; integrations always use diff3 markers.
+    pub base: Option<String>,
+    pub theirs: String,
+    #[serde(skip)]
+    raw: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictFile {
+    pub path: String,
+    /// Index stages 1-3; `None` when that side has no such file or it isn't text.
+    pub base: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+    pub binary: bool,
+    /// Conflict regions in the working file; empty for binary and modify/delete conflicts.
+    pub hunks: Vec<ConflictHunk>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrationDetail {
+    pub integration: Integration,
+    /// Files still unmerged.
+    pub conflicts: Vec<ConflictFile>,
+    /// Files that conflicted at the start and have been resolved since.
+    pub resolved: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Resolution {
+    Ours,
+    Theirs,
+    /// Ours followed by theirs.
+    Both,
+    Edited {
+        content: String,
+    },
+}
+
+enum Segment {
+    Text(String),
+    Conflict(ConflictHunk),
+}
+
+fn is_marker(line: &str, marker: &str) -> bool {
+    line.strip_prefix(marker)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\n', '\r']))
+}
+
+fn parse_segments(text: &str) -> Vec<Segment> {
+    #[derive(PartialEq)]
+    enum Part {
+        Ours,
+        Base,
+        Theirs,
+    }
+    let mut out = Vec::new();
+    let mut plain = String::new();
+    let mut open: Option<(ConflictHunk, Part)> = None;
+    let mut count = 0;
+    for (n, line) in text.split_inclusive('\n').enumerate() {
+        let Some((hunk, part)) = open.as_mut() else {
+            if is_marker(line, "<<<<<<<") {
+                if !plain.is_empty() {
+                    out.push(Segment::Text(std::mem::take(&mut plain)));
+                }
+                let hunk = ConflictHunk {
+                    index: count,
+                    start_line: n + 1,
+                    ours: String::new(),
+                    base: None,
+                    theirs: String::new(),
+                    raw: line.to_string(),
+                };
+                count += 1;
+                open = Some((hunk, Part::Ours));
+            } else {
+                plain.push_str(line);
+            }
+            continue;
+        };
+        hunk.raw.push_str(line);
+        let mut closed = false;
+        match part {
+            Part::Ours if is_marker(line, "|||||||") => {
+                hunk.base = Some(String::new());
+                *part = Part::Base;
+            }
+            Part::Ours | Part::Base if is_marker(line, "=======") => *part = Part::Theirs,
+            Part::Ours => hunk.ours.push_str(line),
+            Part::Base => hunk.base.get_or_insert_with(String::new).push_str(line),
+            Part::Theirs if is_marker(line, ">>>>>>>") => closed = true,
+            Part::Theirs => hunk.theirs.push_str(line),
+        }
+        if closed {
+            if let Some((hunk, _)) = open.take() {
+                out.push(Segment::Conflict(hunk));
+            }
+        }
+    }
+    // An unterminated region is left as it was.
+    if let Some((hunk, _)) = open {
+        plain.push_str(&hunk.raw);
+    }
+    if !plain.is_empty() {
+        out.push(Segment::Text(plain));
+    }
+    out
+}
+
+fn render(segments: &[Segment], replace: impl Fn(&ConflictHunk) -> Option<String>) -> String {
+    let mut out = String::new();
+    for s in segments {
+        match s {
+            Segment::Text(t) => out.push_str(t),
+            Segment::Conflict(h) => out.push_str(&replace(h).unwrap_or_else(|| h.raw.clone())),
+        }
+    }
+    out
+}
+
+/// Conflict regions marked in `text`.
+pub fn conflict_hunks(text: &str) -> Vec<ConflictHunk> {
+    parse_segments(text)
+        .into_iter()
+        .filter_map(|s| match s {
+            Segment::Conflict(h) => Some(h),
+            Segment::Text(_) => None,
+        })
+        .collect()
+}
+
+pub(crate) fn run(dir: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let out = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .with_context(|| format!("run git {}", args.join(" ")))?;
+    if !out.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&out.stderr).trim()
+        );
+    }
+    Ok(out.stdout)
+}
+
+pub(crate) fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let out = run(dir, args)?;
+    Ok(String::from_utf8_lossy(&out).trim_end().to_string())
+}
+
+pub(crate) fn unmerged(dir: &Path) -> Result<Vec<String>> {
+    let text = git(dir, &["diff", "--name-only", "--diff-filter=U", "-z"])?;
+    let mut files: Vec<String> = text
+        .split('\0')
+        .filter(|f| !f.is_empty())
+        .map(str::to_string)
+        .collect();
+    files.dedup();
+    Ok(files)
+}
+
+/// Blob ids of index stages 1 (base), 2 (ours) and 3 (theirs) for an unmerged path.
+fn stage_blobs(dir: &Path, path: &str) -> Result<[Option<String>; 3]> {
+    let text = git(dir, &["ls-files", "-u", "-z", "--", path])?;
+    let mut stages: [Option<String>; 3] = Default::default();
+    for entry in text.split('\0').filter(|e| !e.is_empty()) {
+        // "<mode> <object> <stage>\t<path>"
+        let meta = entry.split('\t').next().unwrap_or_default();
+        let mut fields = meta.split(' ');
+        let (_mode, Some(blob), Some(stage)) = (fields.next(), fields.next(), fields.next()) else {
+            continue;
+        };
+        if let Some(slot) = stage
+            .parse::<usize>()
+            .ok()
+            .and_then(|s| stages.get_mut(s.wrapping_sub(1)))
+        {
+            *slot = Some(blob.to_string());
+        }
+    }
+    Ok(stages)
+}
+
+fn as_text(bytes: Vec<u8>) -> Option<String> {
+    if bytes.contains(&0) {
+        return None;
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn conflict_file(dir: &Path, path: &str) -> Result<ConflictFile> {
+    let mut binary = false;
+    let mut sides: [Option<String>; 3] = Default::default();
+    for (slot, blob) in sides.iter_mut().zip(stage_blobs(dir, path)?) {
+        let Some(blob) = blob else { continue };
+        *slot = as_text(run(dir, &["cat-file", "blob", &blob])?);
+        binary |= slot.is_none();
+    }
+    let hunks = match fs::read(dir.join(path)) {
+        Ok(bytes) if !binary => as_text(bytes)
+            .map(|t| conflict_hunks(&t))
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    let [base, ours, theirs] = sides;
+    Ok(ConflictFile {
+        path: path.to_string(),
+        base,
+        ours,
+        theirs,
+        binary,
+        hunks,
+    })
+}
+
+fn meta_path(gm: &GitManager, id: &str) -> PathBuf {
+    gm.integration_root().join(format!("{id}.json"))
+}
+
+fn save(gm: &GitManager, it: &Integration) -> Result<()> {
+    let path = meta_path(gm, &it.id);
+    let text = serde_json::to_string_pretty(it).context("serialize integration")?;
+    fs::write(&path, format!("{text}\n")).with_context(|| format!("write {}", path.display()))
+}
+
+pub fn load(gm: &GitManager, id: &str) -> Result<Integration> {
+    if id.is_empty() || slugify_branch(id) != id {
+        bail!("invalid integration id {id:?}");
+    }
+    let path = meta_path(gm, id);
+    let text = fs::read_to_string(&path)
+        .with_context(|| format!("no merge in progress with id {id:?}"))?;
+    serde_json::from_str(&text).with_context(|| format!("parse {}", path.display()))
+}
+
+/// Integrations waiting for conflict resolution in this project.
+pub fn list(gm: &GitManager) -> Result<Vec<Integration>> {
+    let entries = match fs::read_dir(gm.integration_root()) {
+        Ok(e) => e,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).context("read integration dir"),
+    };
+    let mut out: Vec<Integration> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|x| x == "json"))
+        .filter_map(|e| fs::read_to_string(e.path()).ok())
+        .filter_map(|t| serde_json::from_str(&t).ok())
+        .collect();
+    out.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    Ok(out)
+}
+
+fn discard(gm: &GitManager, it: &Integration) {
+    let _ = git(
+        gm.project_path(),
+        &["worktree", "remove", "--force", &it.worktree_path],
+    );
+    let _ = git(gm.project_path(), &["worktree", "prune"]);
+    let _ = fs::remove_file(meta_path(gm, &it.id));
+}
+
+/// Merges `branch` into `base_branch` again in a detached integration worktree and leaves the
+/// conflicted merge in progress there. Only merge and squash merges can be resumed.
+pub fn start(
+    gm: &GitManager,
+    branch: &str,
+    base_branch: &str,
+    strategy: MergeStrategy,
+) -> Result<Integration> {
+    if strategy == MergeStrategy::Rebase {
+        bail!("rebase conflicts can't be resolved in an integration worktree");
+    }
+    let id = slugify_branch(branch);
+    if meta_path(gm, &id).exists() {
+        bail!("a merge of {branch} is already waiting for conflict resolution");
+    }
+    let base_commit = gm.resolve_commit(base_branch)?;
+    let root = gm.integration_root();
+    fs::create_dir_all(&root).with_context(|| format!("create {}", root.display()))?;
+    let dir = root.join(&id);
+    let dir_s = dir.to_string_lossy().to_string();
+    // Left over from a crash, it would block `worktree add`.
+    let _ = git(
+        gm.project_path(),
+        &["worktree", "remove", "--force", &dir_s],
+    );
+    git(
+        gm.project_path(),
+        &["worktree", "add", "--detach", &dir_s, &base_commit],
+    )?;
+
+    let message = format!("Merge branch '{branch}' into {base_branch}");
+    let mut args = vec!["-c", "merge.conflictStyle=diff3", "merge"];
+    match strategy {
+        MergeStrategy::Squash => args.push("--squash"),
+        _ => args.extend(["--no-ff", "-m", &message]),
+    }
+    args.push(branch);
+    let merged = run(&dir, &args);
+
+    let it = Integration {
+        id,
+        project_path: gm.project_path().to_string_lossy().to_string(),
+        branch: branch.to_string(),
+        base_branch: base_branch.to_string(),
+        strategy,
+        base_commit,
+        worktree_path: dir_s,
+        conflict_files: unmerged(&dir).unwrap_or_default(),
+        created_at: now_rfc3339(),
+    };
+    if it.conflict_files.is_empty() {
+        discard(gm, &it);
+        return match merged {
+            Ok(_) => bail!("{branch} now merges into {base_branch} cleanly; merge it again"),
+            Err(err) => Err(err),
+        };
+    }
+    if let Err(err) = save(gm, &it) {
+        discard(gm, &it);
+        return Err(err);
+    }
+    Ok(it)
+}
+
+pub fn detail(gm: &GitManager, id: &str) -> Result<IntegrationDetail> {
+    let integration = load(gm, id)?;
+    let dir = PathBuf::from(&integration.worktree_path);
+    let open = unmerged(&dir)?;
+    let conflicts = open
+        .iter()
+        .map(|p| conflict_file(&dir, p))
+        .collect::<Result<Vec<_>>>()?;
+    let resolved = integration
+        .conflict_files
+        .iter()
+        .filter(|p| !open.contains(p))
+        .cloned()
+        .collect();
+    Ok(IntegrationDetail {
+        integration,
+        conflicts,
+        resolved,
+    })
+}
+
+/// Resolves one conflicted file, or hunk `hunk` of it. The file is staged once no conflict
+/// markers remain; an edited whole file that still has markers is refused.
+pub fn resolve(
+    gm: &GitManager,
+    id: &str,
+    path: &str,
+    hunk: Option<usize>,
+    resolution: &Resolution,
+) -> Result<IntegrationDetail> {
+    let it = load(gm, id)?;
+    let dir = PathBuf::from(&it.worktree_path);
+    if !unmerged(&dir)?.iter().any(|p| p == path) {
+        bail!("{path} has no unresolved conflicts");
+    }
+    let file = dir.join(path);
+    let read_text =
+        || fs::read_to_string(&file).with_context(|| format!("{path} is not a readable text file"));
+    let write = |content: &[u8]| -> Result<()> {
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+        }
+        fs::write(&file, content).with_context(|| format!("write {path}"))
+    };
+
+    match (hunk, resolution) {
+        (None, Resolution::Ours | Resolution::Theirs) => {
+            let [_, ours, theirs] = stage_blobs(&dir, path)?;
+            let side = if *resolution == Resolution::Ours {
+                ours
+            } else {
+                theirs
+            };
+            match side {
+                Some(blob) => write(&run(&dir, &["cat-file", "blob", &blob])?)?,
+                // That side deleted the file.
+                None => {
+                    git(&dir, &["rm", "--quiet", "--force", "--", path])?;
+                    return detail(gm, id);
+                }
+            }
+        }
+        (None, Resolution::Both) => {
+            let segments = parse_segments(&read_text()?);
+            if !segments.iter().any(|s| matches!(s, Segment::Conflict(_))) {
+                bail!("{path} has no conflict markers to combine");
+            }
+            write(render(&segments, |h| Some(format!("{}{}", h.ours, h.theirs))).as_bytes())?;
+        }
+        (None, Resolution::Edited { content }) => {
+            let left = conflict_hunks(content).len();
+            if left > 0 {
+                bail!("{path} still has {left} conflict hunk(s) in the edited content");
+            }
+            write(content.as_bytes())?
+        }
+        (Some(index), resolution) => {
+            let segments = parse_segments(&read_text()?);
+            let Some(h) = segments.iter().find_map(|s| match s {
+                Segment::Conflict(h) if h.index == index => Some(h),
+                _ => None,
+            }) else {
+                bail!("{path} has no conflict hunk {index}");
+            };
+            let replacement = match resolution {
+                Resolution::Ours => h.ours.clone(),
+                Resolution::Theirs => h.theirs.clone(),
+                Resolution::Both => format!("{}{}", h.ours, h.theirs),
+                Resolution::Edited { content } => content.clone(),
+            };
+            let text = render(&segments, |h| {
+                (h.index == index).then(|| replacement.clone())
+            });
+            write(text.as_bytes())?;
+            if !conflict_hunks(&text).is_empty() {
+                return detail(gm, id);
+            }
+        }
+    }
+    git(&dir, &["add", "--", path])?;
+    detail(gm, id)
+}
+
+/// Commits the resolved merge (unless someone already did in the worktree), moves the base
+/// branch to it and removes the integration worktree. Returns the new base commit.
+pub fn finish(gm: &GitManager, id: &str) -> Result<String> {
+    let it = load(gm, id)?;
+    let dir = PathBuf::from(&it.worktree_path);
+    let open = unmerged(&dir)?;
+    if !open.is_empty() {
+        bail!(
+            "{} file(s) still have conflicts: {}",
+            open.len(),
+            open.join(", ")
+        );
+    }
+    if gm.resolve_commit(&it.base_branch)? != it.base_commit {
+        bail!(
+            "{} moved since the merge started; abort and merge again",
+            it.base_branch
+        );
+    }
+    if git(&dir, &["rev-parse", "HEAD"])? == it.base_commit {
+        match it.strategy {
+            MergeStrategy::Squash => {
+                git(&dir, &["commit", "-m", &format!("squash: {}", it.branch)])?;
+            }
+            _ => {
+                git(&dir, &["commit", "--no-edit"])?;
+            }
+        }
+    }
+    let merged = git(&dir, &["rev-parse", "HEAD"])?;
+    advance_base(gm, &it.base_branch, &it.base_commit, &merged)?;
+    discard(gm, &it);
+    Ok(merged)
+}
+
+/// Moves `base_branch` from `old` to its descendant `new`. A checked-out base is fast-forwarded
+/// in place so its working tree follows.
+pub(crate) fn advance_base(gm: &GitManager, base_branch: &str, old: &str, new: &str) -> Result<()> {
+    let checkout = gm
+        .list_worktrees()?
+        .into_iter()
+        .find(|w| w.branch.as_deref() == Some(base_branch));
+    match checkout {
+        Some(wt) => {
+            git(Path::new(&wt.path), &["merge", "--ff-only", new])
+                .with_context(|| format!("fast-forward {base_branch} in {}", wt.path))?;
+        }
+        None => {
+            let base_ref = format!("refs/heads/{base_branch}");
+            git(gm.project_path(), &["update-ref", &base_ref, new, old])?;
+        }
+    }
+    Ok(())
+}
+
+/// Abandons the merge and removes its worktree; the base branch was never touched.
+pub fn abort(gm: &GitManager, id: &str) -> Result<()> {
+    let it = load(gm, id)?;
+    let _ = git(Path::new(&it.worktree_path), &["merge", "--abort"]);
+    discard(gm, &it);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_fixtures::{init_repo, TempDir};
+
+    /// `main` and `feat/x` both change line 2 of `a.txt` and all of `b.txt`.
+    fn repo_with_conflict() -> Result<(TempDir, PathBuf, GitManager)> {
+        let tmp = TempDir::new("integration");
+        let repo = tmp.join("repo");
+        init_repo(&repo)?;
+        fs::write(repo.join("a.txt"), "1\n2\n3\n")?;
+        fs::write(repo.join("b.txt"), "x\n")?;
+        git(&repo, &["add", "."])?;
+        git(&repo, &["commit", "-m", "add a and b"])?;
+        git(&repo, &["checkout", "-b", "feat/x"])?;
+        fs::write(repo.join("a.txt"), "1\nfeat\n3\n")?;
+        fs::write(repo.join("b.txt"), "feat-b\n")?;
+        git(&repo, &["commit", "-am", "feat"])?;
+        git(&repo, &["checkout", "main"])?;
+        fs::write(repo.join("a.txt"), "1\nmain\n3\n")?;
+        fs::write(repo.join("b.txt"), "main-b\n")?;
+        git(&repo, &["commit", "-am", "main"])?;
+        let wt = tmp.join("worktrees").to_string_lossy().to_string();
+        let gm = GitManager::new(repo.clone(), &wt, "feat/")?;
+        Ok((tmp, repo, gm))
+    }
+
+    #[test]
+    fn resolves_and_continues_a_conflicted_merge() -> Result<()> {
+        let (_tmp, repo, gm) = repo_with_conflict()?;
+        let main_before = gm.resolve_commit("main")?;
+        let res = gm.merge_branch("feat/x", "main", MergeStrategy::Merge)?;
+        assert!(!res.success);
+
+        let it = start(&gm, "feat/x", "main", MergeStrategy::Merge)?;
+        assert_eq!(it.conflict_files, vec!["a.txt", "b.txt"]);
+        assert!(start(&gm, "feat/x", "main", MergeStrategy::Merge).is_err());
+        assert_eq!(gm.resolve_commit("main")?, main_before);
+
+        let d = detail(&gm, &it.id)?;
+        let a = &d.conflicts[0];
+        assert_eq!(a.base.as_deref(), Some("1\n2\n3\n"));
+        assert_eq!(a.hunks.len(), 1);
+        assert_eq!(
+            (a.hunks[0].ours.as_str(), a.hunks[0].base.as_deref()),
+            ("main\n", Some("2\n"))
+        );
+
+        let d = resolve(&gm, &it.id, "a.txt", Some(0), &Resolution::Both)?;
+        assert_eq!(d.resolved, vec!["a.txt"]);
+        assert!(finish(&gm, &it.id).is_err());
+        let marked = "<<<<<<< ours\nmain-b\n=======\nfeat-b\n>>>>>>> theirs\n";
+        let edited = Resolution::Edited {
+            content: marked.to_string(),
+        };
+        assert!(resolve(&gm, &it.id, "b.txt", None, &edited).is_err());
+        assert_eq!(detail(&gm, &it.id)?.resolved, vec!["a.txt"]);
+        resolve(&gm, &it.id, "b.txt", None, &Resolution::Theirs)?;
+        assert!(resolve(&gm, &it.id, "b.txt", None, &Resolution::Ours).is_err());
+
+        let merged = finish(&gm, &it.id)?;
+        assert_eq!(gm.resolve_commit("main")?, merged);
+        assert_eq!(
+            fs::read_to_string(repo.join("a.txt"))?,
+            "1\nmain\nfeat\n3\n"
+        );
+        assert_eq!(fs::read_to_string(repo.join("b.txt"))?, "feat-b\n");
+        assert!(list(&gm)?.is_empty());
+        assert!(!Path::new(&it.worktree_path).exists());
+        Ok(())
+    }
+
+    #[test]
+    fn abort_leaves_base_untouched() -> Result<()> {
+        let (_tmp, _, gm) = repo_with_conflict()?;
+        let main_before = gm.resolve_commit("main")?;
+        let it = start(&gm, "feat/x", "main", MergeStrategy::Squash)?;
+        assert_eq!(list(&gm)?, vec![it.clone()]);
+        abort(&gm, &it.id)?;
+        assert!(list(&gm)?.is_empty());
+        assert_eq!(gm.resolve_commit("main")?, main_before);
+        Ok(())
+    }
+
+    #[test]
+    fn parses_diff3_hunks_and_replaces_one() {
+        let text = "a\n<<<<<<< HEAD\nours\n||||||| base\norig\n=======\ntheirs\n>>>>>>> feat/x\nb\n<<<<<<< HEAD\n1\n=======\n2\n>>>>>>> feat/x\n";
+        let hunks = conflict_hunks(text);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].start_line, 2);
+        assert_eq!(
+            (
+                hunks[0].ours.as_str(),
+                hunks[0].base.as_deref(),
+                hunks[0].theirs.as_str()
+            ),
+            ("ours\n", Some("orig\n"), "theirs\n")
+        );
+        assert_eq!(hunks[1].base, None);
+
+        let segments = parse_segments(text);
+        let out = render(&segments, |h| {
+            (h.index == 0).then(|| "merged\n".to_string())
+        });
+        assert!(out.starts_with("a\nmerged\nb\n<<<<<<< HEAD\n"));
+        assert_eq!(conflict_hunks(&out).len(), 1);
+
+        // Unterminated markers are not a conflict.
+        let broken = "x\n<<<<<<< HEAD\ny\n";
+        assert!(conflict_hunks(broken).is_empty());
+        assert_eq!(render(&parse_segments(broken), |_| None), broken);
+    }
+}
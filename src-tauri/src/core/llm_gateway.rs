@@ -0,0 +1,1058 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::core::app_context::AppContext;
+use crate::core::cost_tracker::{CostTracker, SharedCostTrackers};
+use crate::core::event_bus::publish;
+use crate::core::secrets::{random_token, token_matches};
+use crate::core::session_manager::SharedSessionManager;
+use crate::events::{now_rfc3339, AppEvent, GatewayRequestEvent, SessionCostUpdatedEvent};
+
+/// Route for OpenAI-compatible APIs (Codex, OpenRouter, custom OpenAI endpoints).
+pub const OPENAI_ROUTE: &str = "openai";
+/// Route for the Anthropic Messages API (Claude Code).
+pub const ANTHROPIC_ROUTE: &str = "anthropic";
+
+const MAX_HEADER_BYTES: usize = 64 * 1024;
+const MAX_REQUEST_BODY: usize = 32 * 1024 * 1024;
+// Non-streaming bodies larger than this are forwarded but not metered.
+const MAX_METERED_BODY: usize = 8 * 1024 * 1024;
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewaySessionUsage {
+    pub session_id: usize,
+    pub requests: u64,
+    pub errors: u64,
+    pub rate_limited: u64,
+    pub input_tokens: u64,
+    pub cached_input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_requests_per_minute: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayStatus {
+    pub running: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// 0 = unlimited.
+    pub default_max_requests_per_minute: u32,
+    pub sessions: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct TokenUsage {
+    input: Option<u64>,
+    cached_input: Option<u64>,
+    output: Option<u64>,
+    model: Option<String>,
+}
+
+#[derive(Debug)]
+struct SessionRoutes {
+    /// Part of the session's base URL, so other local processes can't spend as this session.
+    token: String,
+    upstreams: HashMap<String, String>,
+    cap: Option<u32>,
+    recent: VecDeque<Instant>,
+    usage: GatewaySessionUsage,
+}
+
+#[derive(Debug, Default)]
+struct GatewayState {
+    sessions: HashMap<usize, SessionRoutes>,
+    default_cap: u32,
+}
+
+enum Admission {
+    Forward(String),
+    UnknownRoute,
+    BadToken,
+    RateLimited(u32),
+}
+
+impl GatewayState {
+    fn admit(&mut self, session_id: usize, token: &str, route: &str, now: Instant) -> Admission {
+        let default_cap = self.default_cap;
+        let Some(rec) = self.sessions.get_mut(&session_id) else {
+            return Admission::UnknownRoute;
+        };
+        if !token_matches(token, &rec.token) {
+            return Admission::BadToken;
+        }
+        let Some(upstream) = rec.upstreams.get(route).cloned() else {
+            return Admission::UnknownRoute;
+        };
+
+        let cap = rec.cap.unwrap_or(default_cap);
+        if cap > 0 {
+            while rec
+                .recent
+                .front()
+                .is_some_and(|t| now.duration_since(*t) >= RATE_WINDOW)
+            {
+                rec.recent.pop_front();
+            }
+            if rec.recent.len() >= cap as usize {
+                rec.usage.rate_limited += 1;
+                return Admission::RateLimited(cap);
+            }
+        }
+        rec.recent.push_back(now);
+        Admission::Forward(upstream)
+    }
+
+    /// Adds one response to the session's totals and returns them.
+    fn record(
+        &mut self,
+        session_id: usize,
+        latency: Duration,
+        usage: &TokenUsage,
+        error: Option<&str>,
+    ) -> Option<GatewaySessionUsage> {
+        let rec = self.sessions.get_mut(&session_id)?;
+        let u = &mut rec.usage;
+        let latency_ms = latency.as_millis() as u64;
+        u.requests += 1;
+        u.input_tokens += usage.input.unwrap_or(0);
+        u.cached_input_tokens += usage.cached_input.unwrap_or(0);
+        u.output_tokens += usage.output.unwrap_or(0);
+        u.total_latency_ms += latency_ms;
+        u.last_latency_ms = Some(latency_ms);
+        if usage.model.is_some() {
+            u.last_model = usage.model.clone();
+        }
+        if let Some(err) = error {
+            u.errors += 1;
+            u.last_error = Some(err.to_string());
+        }
+        Some(u.clone())
+    }
+}
+
+/// Local OpenAI/Anthropic-compatible proxy. Sessions reach it via
+/// `http://127.0.0.1:<port>/s/<session_id>/<token>/<route>`; requests are forwarded to the session's
+/// registered upstream while usage, latency and errors are recorded per session.
+pub struct LlmGateway {
+    addr: Option<SocketAddr>,
+    stop: Arc<AtomicBool>,
+    accept_thread: Option<JoinHandle<()>>,
+    state: Arc<Mutex<GatewayState>>,
+}
+
+pub type SharedLlmGateway = Arc<Mutex<LlmGateway>>;
+
+impl Default for LlmGateway {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LlmGateway {
+    pub fn new() -> Self {
+        Self {
+            addr: None,
+            stop: Arc::new(AtomicBool::new(false)),
+            accept_thread: None,
+            state: Arc::new(Mutex::new(GatewayState::default())),
+        }
+    }
+
+    /// Starts the listener if needed and returns the bound port. `port = 0` picks a free port.
+    pub fn ensure_started(&mut self, app: Option<AppContext>, port: u16) -> Result<u16> {
+        if let Some(addr) = self.addr {
+            return Ok(addr.port());
+        }
+
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .with_context(|| format!("bind llm gateway on 127.0.0.1:{port}"))?;
+        listener
+            .set_nonblocking(true)
+            .context("set llm gateway listener nonblocking")?;
+        let addr = listener.local_addr().context("llm gateway local addr")?;
+
+        let client = reqwest::blocking::Client::builder()
+            .connect_timeout(Duration::from_secs(15))
+            .timeout(None)
+            .build()
+            .context("build llm gateway http client")?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_accept = stop.clone();
+        let state = self.state.clone();
+        let accept_thread = std::thread::spawn(move || {
+            while !stop_accept.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let state = state.clone();
+                        let client = client.clone();
+                        let app = app.clone();
+                        std::thread::spawn(move || {
+                            if let Err(err) = handle_connection(stream, &state, &client, app) {
+                                eprintln!("llm gateway: {err:#}");
+                            }
+                        });
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        std::thread::sleep(Duration::from_millis(25));
+                    }
+                    Err(e) => {
+                        eprintln!("llm gateway: accept failed: {e}");
+                        std::thread::sleep(Duration::from_millis(250));
+                    }
+                }
+            }
+        });
+
+        self.addr = Some(addr);
+        self.stop = stop;
+        self.accept_thread = Some(accept_thread);
+        Ok(addr.port())
+    }
+
+    /// Base URL a session should use for `route` (e.g. as `OPENAI_BASE_URL`). Carries the
+    /// session's token, so only hand it to that session.
+    pub fn base_url(&self, session_id: usize, route: &str) -> Option<String> {
+        let addr = self.addr?;
+        let state = self.state.lock().expect("gateway mutex poisoned");
+        let token = &state.sessions.get(&session_id)?.token;
+        Some(format!("http://{addr}/s/{session_id}/{token}/{route}"))
+    }
+
+    /// The per-session secret embedded in `base_url`, for redaction.
+    pub fn session_token(&self, session_id: usize) -> Option<String> {
+        let state = self.state.lock().expect("gateway mutex poisoned");
+        Some(state.sessions.get(&session_id)?.token.clone())
+    }
+
+    /// Maps `route -> upstream base URL` for a session. Usage from earlier runs of the same
+    /// session (restart) is kept.
+    pub fn register_session(&self, session_id: usize, upstreams: Vec<(String, String)>) {
+        let mut state = self.state.lock().expect("gateway mutex poisoned");
+        let rec = state
+            .sessions
+            .entry(session_id)
+            .or_insert_with(|| SessionRoutes {
+                token: random_token(),
+                upstreams: HashMap::new(),
+                cap: None,
+                recent: VecDeque::new(),
+                usage: GatewaySessionUsage {
+                    session_id,
+                    ..Default::default()
+                },
+            });
+        rec.upstreams = upstreams
+            .into_iter()
+            .map(|(route, base)| (route, base.trim_end_matches('/').to_string()))
+            .collect();
+    }
+
+    /// Stops routing for a session. Its usage stays queryable.
+    pub fn unregister_session(&self, session_id: usize) {
+        let mut state = self.state.lock().expect("gateway mutex poisoned");
+        if let Some(rec) = state.sessions.get_mut(&session_id) {
+            rec.upstreams.clear();
+            rec.recent.clear();
+        }
+    }
+
+    pub fn set_default_rate_cap(&self, max_requests_per_minute: u32) {
+        let mut state = self.state.lock().expect("gateway mutex poisoned");
+        state.default_cap = max_requests_per_minute;
+    }
+
+    /// Per-session override of the default cap (`None` = use default, `Some(0)` = unlimited).
+    pub fn set_session_rate_cap(
+        &self,
+        session_id: usize,
+        max_requests_per_minute: Option<u32>,
+    ) -> Result<GatewaySessionUsage> {
+        let mut state = self.state.lock().expect("gateway mutex poisoned");
+        let rec = state
+            .sessions
+            .get_mut(&session_id)
+            .ok_or_else(|| anyhow!("session {session_id} is not routed through the gateway"))?;
+        rec.cap = max_requests_per_minute;
+        rec.usage.max_requests_per_minute = max_requests_per_minute;
+        Ok(rec.usage.clone())
+    }
+
+    pub fn usage(&self, session_id: usize) -> Option<GatewaySessionUsage> {
+        let state = self.state.lock().expect("gateway mutex poisoned");
+        state.sessions.get(&session_id).map(|r| r.usage.clone())
+    }
+
+    pub fn usage_all(&self) -> Vec<GatewaySessionUsage> {
+        let state = self.state.lock().expect("gateway mutex poisoned");
+        let mut out: Vec<GatewaySessionUsage> =
+            state.sessions.values().map(|r| r.usage.clone()).collect();
+        out.sort_by_key(|u| u.session_id);
+        out
+    }
+
+    pub fn status(&self) -> GatewayStatus {
+        let state = self.state.lock().expect("gateway mutex poisoned");
+        GatewayStatus {
+            running: self.addr.is_some(),
+            port: self.addr.map(|a| a.port()),
+            default_max_requests_per_minute: state.default_cap,
+            sessions: state
+                .sessions
+                .values()
+                .filter(|r| !r.upstreams.is_empty())
+                .count(),
+        }
+    }
+
+    pub fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(t) = self.accept_thread.take() {
+            let _ = t.join();
+        }
+        self.addr = None;
+    }
+}
+
+// -----------------------------------------------------------------------------
+// HTTP handling (one request per connection; responses are chunked + Connection: close)
+// -----------------------------------------------------------------------------
+
+#[derive(Debug)]
+pub(crate) struct ProxyRequest {
+    pub(crate) method: String,
+    pub(crate) target: String,
+    pub(crate) headers: Vec<(String, String)>,
+    pub(crate) body: Vec<u8>,
+}
+
+impl ProxyRequest {
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+fn is_hop_header(name: &str) -> bool {
+    matches!(
+        name.to_ascii_lowercase().as_str(),
+        "host"
+            | "connection"
+            | "keep-alive"
+            | "proxy-connection"
+            | "proxy-authorization"
+            | "te"
+            | "trailer"
+            | "transfer-encoding"
+            | "upgrade"
+            | "content-length"
+            // Dropped so upstream replies uncompressed and the body can be metered.
+            | "accept-encoding"
+    )
+}
+
+pub(crate) fn read_request(reader: &mut impl BufRead) -> Result<ProxyRequest> {
+    let mut line = String::new();
+    let mut header_bytes = 0;
+    read_head_line(reader, &mut line, &mut header_bytes).context("read request line")?;
+    let mut parts = line.split_whitespace();
+    let method = parts
+        .next()
+        .ok_or_else(|| anyhow!("empty request"))?
+        .to_string();
+    let target = parts
+        .next()
+        .ok_or_else(|| anyhow!("missing request target"))?
+        .to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        let n = read_head_line(reader, &mut line, &mut header_bytes).context("read header")?;
+        let trimmed = line.trim_end_matches(['\r', '\n']);
+        if n == 0 || trimmed.is_empty() {
+            break;
+        }
+        if let Some((k, v)) = trimmed.split_once(':') {
+            headers.push((k.trim().to_string(), v.trim().to_string()));
+        }
+    }
+
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    };
+    let chunked = header("transfer-encoding")
+        .map(|v| v.to_ascii_lowercase().contains("chunked"))
+        .unwrap_or(false);
+    let body = if chunked {
+        read_chunked_body(reader)?
+    } else {
+        let len: usize = header("content-length")
+            .map(|v| v.parse().context("invalid content-length"))
+            .transpose()?
+            .unwrap_or(0);
+        if len > MAX_REQUEST_BODY {
+            return Err(anyhow!("request body too large"));
+        }
+        let mut body = vec![0u8; len];
+        reader.read_exact(&mut body).context("read request body")?;
+        body
+    };
+
+    Ok(ProxyRequest {
+        method,
+        target,
+        headers,
+        body,
+    })
+}
+
+/// Reads one line of the request head, giving up once the head passes `MAX_HEADER_BYTES`
+/// rather than buffering a line that never ends.
+fn read_head_line(
+    reader: &mut impl BufRead,
+    line: &mut String,
+    header_bytes: &mut usize,
+) -> Result<usize> {
+    let left = MAX_HEADER_BYTES.saturating_sub(*header_bytes) as u64;
+    let n = reader.by_ref().take(left + 1).read_line(line)?;
+    *header_bytes += n;
+    if *header_bytes > MAX_HEADER_BYTES {
+        return Err(anyhow!("request headers too large"));
+    }
+    Ok(n)
+}
+
+fn read_chunked_body(reader: &mut impl BufRead) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        reader.read_line(&mut line).context("read chunk size")?;
+        let size_str = line.trim().split(';').next().unwrap_or("");
+        let size = usize::from_str_radix(size_str, 16).context("invalid chunk size")?;
+        if size == 0 {
+            // Trailers (ignored) until the blank line.
+            loop {
+                line.clear();
+                let n = reader.read_line(&mut line).context("read trailer")?;
+                if n == 0 || line.trim().is_empty() {
+                    return Ok(body);
+                }
+            }
+        }
+        if body.len() + size > MAX_REQUEST_BODY {
+            return Err(anyhow!("request body too large"));
+        }
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader
+            .read_exact(&mut body[start..])
+            .context("read chunk data")?;
+        line.clear();
+        reader
+            .read_line(&mut line)
+            .context("read chunk terminator")?;
+    }
+}
+
+/// A parsed `/s/<session_id>/<token>/<route>/<rest>` target; `tail` is `/<rest>` with the
+/// query preserved.
+#[derive(Debug, PartialEq, Eq)]
+struct Route {
+    session_id: usize,
+    token: String,
+    route: String,
+    tail: String,
+}
+
+fn parse_route(target: &str) -> Option<Route> {
+    let rest = target.strip_prefix("/s/")?;
+    let (sid, rest) = rest.split_once('/')?;
+    let session_id = sid.parse().ok()?;
+    let (token, rest) = rest.split_once('/')?;
+    let (route, tail) = match rest.find(['/', '?']) {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, ""),
+    };
+    if route.is_empty() {
+        return None;
+    }
+    Some(Route {
+        session_id,
+        token: token.to_string(),
+        route: route.to_string(),
+        tail: tail.to_string(),
+    })
+}
+
+fn write_json_error(stream: &mut TcpStream, status: u16, reason: &str, message: &str) {
+    let body = serde_json::json!({
+        "error": { "type": "synk_gateway_error", "message": message }
+    })
+    .to_string();
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = stream.flush();
+}
+
+fn write_chunk(stream: &mut TcpStream, data: &[u8]) -> std::io::Result<()> {
+    write!(stream, "{:x}\r\n", data.len())?;
+    stream.write_all(data)?;
+    stream.write_all(b"\r\n")
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    state: &Mutex<GatewayState>,
+    client: &reqwest::blocking::Client,
+    app: Option<AppContext>,
+) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    let started = Instant::now();
+    let mut reader = BufReader::new(stream.try_clone().context("clone gateway stream")?);
+    let req = match read_request(&mut reader) {
+        Ok(r) => r,
+        Err(err) => {
+            write_json_error(&mut stream, 400, "Bad Request", &format!("{err:#}"));
+            return Ok(());
+        }
+    };
+
+    let Some(Route {
+        session_id,
+        token,
+        route,
+        tail,
+    }) = parse_route(&req.target)
+    else {
+        write_json_error(
+            &mut stream,
+            404,
+            "Not Found",
+            "expected /s/<session>/<token>/<route>/...",
+        );
+        return Ok(());
+    };
+
+    let admission = state
+        .lock()
+        .expect("gateway mutex poisoned")
+        .admit(session_id, &token, &route, started);
+    let upstream = match admission {
+        Admission::Forward(base) => base,
+        Admission::UnknownRoute => {
+            write_json_error(
+                &mut stream,
+                404,
+                "Not Found",
+                &format!("no {route} upstream registered for session {session_id}"),
+            );
+            return Ok(());
+        }
+        Admission::BadToken => {
+            write_json_error(
+                &mut stream,
+                403,
+                "Forbidden",
+                &format!("invalid token for session {session_id}"),
+            );
+            return Ok(());
+        }
+        Admission::RateLimited(cap) => {
+            write_json_error(
+                &mut stream,
+                429,
+                "Too Many Requests",
+                &format!("session {session_id} is capped at {cap} requests per minute"),
+            );
+            emit_request(
+                &app,
+                session_id,
+                &route,
+                429,
+                started.elapsed(),
+                &TokenUsage::default(),
+                Some("rate limited"),
+            );
+            return Ok(());
+        }
+    };
+
+    let url = format!("{upstream}{tail}");
+    let (status, usage, error) = match forward(&mut stream, client, &req, &url) {
+        Ok((status, usage)) => {
+            let error = (status >= 400).then(|| format!("upstream returned HTTP {status}"));
+            (status, usage, error)
+        }
+        Err(err) => {
+            let msg = format!("{err:#}");
+            write_json_error(&mut stream, 502, "Bad Gateway", &msg);
+            (502, TokenUsage::default(), Some(msg))
+        }
+    };
+
+    let latency = started.elapsed();
+    let totals = state.lock().expect("gateway mutex poisoned").record(
+        session_id,
+        latency,
+        &usage,
+        error.as_deref(),
+    );
+    if let (Some(app), Some(totals)) = (&app, totals) {
+        update_cost(app, &totals);
+    }
+    emit_request(
+        &app,
+        session_id,
+        &route,
+        status,
+        latency,
+        &usage,
+        error.as_deref(),
+    );
+    Ok(())
+}
+
+/// Feeds a session's running totals to its live cost tracker and announces the new cost.
+fn update_cost(app: &AppContext, totals: &GatewaySessionUsage) {
+    let session_id = totals.session_id;
+    let Some(info) = app.try_state::<SharedSessionManager>().and_then(|m| {
+        m.lock()
+            .expect("session manager mutex poisoned")
+            .get_session_info(session_id)
+    }) else {
+        return;
+    };
+    let Some(trackers) = app.try_state::<SharedCostTrackers>() else {
+        return;
+    };
+    let cost = trackers
+        .lock()
+        .expect("cost tracker mutex poisoned")
+        .ingest_gateway_usage(totals, || {
+            CostTracker::new(info.agent_type, info.model.clone(), app)
+        });
+    if let Some(cost) = cost {
+        publish(
+            app,
+            AppEvent::CostUpdated(SessionCostUpdatedEvent { session_id, cost }),
+        );
+    }
+}
+
+fn emit_request(
+    app: &Option<AppContext>,
+    session_id: usize,
+    route: &str,
+    status: u16,
+    latency: Duration,
+    usage: &TokenUsage,
+    error: Option<&str>,
+) {
+    let Some(app) = app else {
+        return;
+    };
+    publish(
+        app,
+        AppEvent::GatewayRequest(GatewayRequestEvent {
+            session_id,
+            route: route.to_string(),
+            status,
+            latency_ms: latency.as_millis() as u64,
+            input_tokens: usage.input.unwrap_or(0),
+            cached_input_tokens: usage.cached_input.unwrap_or(0),
+            output_tokens: usage.output.unwrap_or(0),
+            model: usage.model.clone(),
+            error: error.map(|s| s.to_string()),
+            timestamp: now_rfc3339(),
+        }),
+    );
+}
+
+/// Sends the request upstream and streams the response back. Errors before any response
+/// bytes are written are returned so the caller can reply 502.
+fn forward(
+    stream: &mut TcpStream,
+    client: &reqwest::blocking::Client,
+    req: &ProxyRequest,
+    url: &str,
+) -> Result<(u16, TokenUsage)> {
+    let method = reqwest::Method::from_bytes(req.method.as_bytes())
+        .map_err(|_| anyhow!("invalid method {}", req.method))?;
+    let mut builder = client.request(method, url);
+    for (k, v) in &req.headers {
+        if !is_hop_header(k) {
+            builder = builder.header(k.as_str(), v.as_str());
+        }
+    }
+    let mut resp = builder
+        .body(req.body.clone())
+        .send()
+        .with_context(|| format!("forward to {url}"))?;
+
+    let status = resp.status();
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\n",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("")
+    );
+    let mut event_stream = false;
+    for (k, v) in resp.headers() {
+        if is_hop_header(k.as_str()) {
+            continue;
+        }
+        let Ok(v) = v.to_str() else {
+            continue;
+        };
+        if k == reqwest::header::CONTENT_TYPE && v.contains("text/event-stream") {
+            event_stream = true;
+        }
+        head.push_str(&format!("{}: {}\r\n", k.as_str(), v));
+    }
+    head.push_str("Transfer-Encoding: chunked\r\nConnection: close\r\n\r\n");
+
+    let mut meter = UsageMeter::new(event_stream);
+    // Client disconnects are not upstream errors: keep draining so usage is still recorded.
+    let mut client_gone = stream.write_all(head.as_bytes()).is_err();
+    let mut buf = [0u8; 16 * 1024];
+    loop {
+        let n = match resp.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(err) => {
+                eprintln!("llm gateway: upstream read failed: {err}");
+                break;
+            }
+        };
+        meter.feed(&buf[..n]);
+        if !client_gone {
+            client_gone = write_chunk(stream, &buf[..n]).is_err();
+        }
+    }
+    if !client_gone {
+        let _ = stream.write_all(b"0\r\n\r\n");
+        let _ = stream.flush();
+    }
+
+    Ok((status.as_u16(), meter.finish()))
+}
+
+// -----------------------------------------------------------------------------
+// Usage extraction
+// -----------------------------------------------------------------------------
+
+/// Pulls token usage out of JSON responses or SSE streams (OpenAI Chat Completions,
+/// OpenAI Responses, Anthropic Messages).
+struct UsageMeter {
+    event_stream: bool,
+    buf: Vec<u8>,
+    overflow: bool,
+    usage: TokenUsage,
+}
+
+impl UsageMeter {
+    fn new(event_stream: bool) -> Self {
+        Self {
+            event_stream,
+            buf: Vec::new(),
+            overflow: false,
+            usage: TokenUsage::default(),
+        }
+    }
+
+    fn feed(&mut self, bytes: &[u8]) {
+        if self.overflow {
+            return;
+        }
+        self.buf.extend_from_slice(bytes);
+        if self.event_stream {
+            while let Some(pos) = self.buf.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = self.buf.drain(..=pos).collect();
+                self.ingest_sse_line(&line);
+            }
+        }
+        if self.buf.len() > MAX_METERED_BODY {
+            self.overflow = true;
+            self.buf = Vec::new();
+        }
+    }
+
+    fn ingest_sse_line(&mut self, line: &[u8]) {
+        let line = String::from_utf8_lossy(line);
+        let Some(data) = line.trim().strip_prefix("data:") else {
+            return;
+        };
+        if let Ok(v) = serde_json::from_str::<Value>(data.trim()) {
+            merge_usage(&v, &mut self.usage);
+        }
+    }
+
+    fn finish(mut self) -> TokenUsage {
+        if self.event_stream {
+            let rest = std::mem::take(&mut self.buf);
+            self.ingest_sse_line(&rest);
+        } else if !self.overflow {
+            if let Ok(v) = serde_json::from_slice::<Value>(&self.buf) {
+                merge_usage(&v, &mut self.usage);
+            }
+        }
+        self.usage
+    }
+}
+
+fn bump(slot: &mut Option<u64>, next: Option<u64>) {
+    if let Some(n) = next {
+        *slot = Some(slot.map_or(n, |cur| cur.max(n)));
+    }
+}
+
+/// Usage fields are cumulative within one response (Anthropic repeats them in
+/// `message_start`/`message_delta`), so we keep the max per field rather than summing.
+fn merge_usage(v: &Value, out: &mut TokenUsage) {
+    let nodes = [Some(v), v.get("message"), v.get("response")];
+    for node in nodes.into_iter().flatten() {
+        if let Some(model) = node.get("model").and_then(Value::as_str) {
+            if !model.trim().is_empty() {
+                out.model = Some(model.to_string());
+            }
+        }
+        let Some(u) = node.get("usage").filter(|u| u.is_object()) else {
+            continue;
+        };
+        let input = u
+            .get("prompt_tokens")
+            .or_else(|| u.get("input_tokens"))
+            .and_then(Value::as_u64);
+        let output = u
+            .get("completion_tokens")
+            .or_else(|| u.get("output_tokens"))
+            .and_then(Value::as_u64);
+        let cached = u
+            .get("cache_read_input_tokens")
+            .and_then(Value::as_u64)
+            .or_else(|| {
+                u.get("prompt_tokens_details")
+                    .or_else(|| u.get("input_tokens_details"))
+                    .and_then(|d| d.get("cached_tokens"))
+                    .and_then(Value::as_u64)
+            });
+        bump(&mut out.input, input);
+        bump(&mut out.output, output);
+        bump(&mut out.cached_input, cached);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal upstream: answers one request per connection with `body`, echoing the request
+    /// path in `x-upstream-path`.
+    fn mock_upstream(body: &'static str, content_type: &'static str) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let Ok(req) = read_request(&mut reader) else {
+                    continue;
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nx-upstream-path: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    req.target,
+                    body.len()
+                );
+            }
+        });
+        addr
+    }
+
+    fn post(url: &str) -> reqwest::blocking::Response {
+        reqwest::blocking::Client::new()
+            .post(url)
+            .header("content-type", "application/json")
+            .body(r#"{"model":"m"}"#)
+            .send()
+            .unwrap()
+    }
+
+    #[test]
+    fn route_parsing() {
+        assert_eq!(
+            parse_route("/s/3/t0k/openai/chat/completions?x=1"),
+            Some(Route {
+                session_id: 3,
+                token: "t0k".to_string(),
+                route: "openai".to_string(),
+                tail: "/chat/completions?x=1".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_route("/s/7/t0k/anthropic").map(|r| (r.route, r.tail)),
+            Some(("anthropic".to_string(), String::new()))
+        );
+        assert_eq!(parse_route("/v1/models"), None);
+        assert_eq!(parse_route("/s/x/t0k/openai/models"), None);
+        assert_eq!(parse_route("/s/3/openai"), None);
+    }
+
+    #[test]
+    fn request_head_is_capped() {
+        let mut ok = "GET /x HTTP/1.1\r\nHost: a\r\n\r\n".as_bytes();
+        assert_eq!(read_request(&mut ok).unwrap().target, "/x");
+
+        // An endless request line fails at the cap instead of reading all of it.
+        let mut endless = std::io::repeat(b'a').take(MAX_HEADER_BYTES as u64 * 4);
+        let mut reader = BufReader::new(&mut endless);
+        let err = read_request(&mut reader).unwrap_err();
+        assert!(format!("{err:#}").contains("too large"), "{err:#}");
+        assert!(endless.limit() >= MAX_HEADER_BYTES as u64 * 2);
+    }
+
+    #[test]
+    fn merges_anthropic_stream_usage() {
+        let mut meter = UsageMeter::new(true);
+        meter.feed(b"event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"model\":\"claude-x\",\"usage\":{\"input_tokens\":12,\"cache_read_input_tokens\":4,\"output_tokens\":1}}}\n\n");
+        meter.feed(b"data: {\"type\":\"message_delta\",\"usage\":{\"output_tok");
+        meter.feed(b"ens\":30}}\n\ndata: {\"type\":\"message_stop\"}\n");
+        let usage = meter.finish();
+        assert_eq!(usage.input, Some(12));
+        assert_eq!(usage.cached_input, Some(4));
+        assert_eq!(usage.output, Some(30));
+        assert_eq!(usage.model.as_deref(), Some("claude-x"));
+    }
+
+    #[test]
+    fn proxies_and_meters_openai_json() {
+        let upstream = mock_upstream(
+            r#"{"model":"gpt-test","usage":{"prompt_tokens":10,"completion_tokens":5,"prompt_tokens_details":{"cached_tokens":2}}}"#,
+            "application/json",
+        );
+        let mut gw = LlmGateway::new();
+        let port = gw.ensure_started(None, 0).unwrap();
+        gw.register_session(
+            1,
+            vec![(OPENAI_ROUTE.to_string(), format!("http://{upstream}/v1/"))],
+        );
+
+        let base = gw.base_url(1, OPENAI_ROUTE).unwrap();
+        let resp = post(&format!("{base}/chat/completions"));
+        assert_eq!(resp.status().as_u16(), 200);
+        assert_eq!(
+            resp.headers()
+                .get("x-upstream-path")
+                .and_then(|v| v.to_str().ok()),
+            Some("/v1/chat/completions")
+        );
+        assert!(resp.text().unwrap().contains("gpt-test"));
+
+        let usage = gw.usage(1).unwrap();
+        assert_eq!(usage.requests, 1);
+        assert_eq!(usage.errors, 0);
+        assert_eq!(usage.input_tokens, 10);
+        assert_eq!(usage.cached_input_tokens, 2);
+        assert_eq!(usage.output_tokens, 5);
+        assert_eq!(usage.last_model.as_deref(), Some("gpt-test"));
+
+        // Unknown sessions/routes and wrong tokens are rejected without touching upstream.
+        let token = base.rsplit('/').nth(1).unwrap();
+        let url = |path: &str| format!("http://127.0.0.1:{port}{path}");
+        assert_eq!(
+            post(&url(&format!("/s/2/{token}/openai/chat/completions")))
+                .status()
+                .as_u16(),
+            404
+        );
+        assert_eq!(
+            post(&url(&format!("/s/1/{token}/anthropic/v1/messages")))
+                .status()
+                .as_u16(),
+            404
+        );
+        assert_eq!(
+            post(&url("/s/1/guess/openai/chat/completions"))
+                .status()
+                .as_u16(),
+            403
+        );
+        assert_eq!(gw.usage(1).unwrap().requests, 1);
+        gw.shutdown();
+    }
+
+    #[test]
+    fn enforces_per_session_rate_cap() {
+        let upstream = mock_upstream(r#"{"ok":true}"#, "application/json");
+        let mut gw = LlmGateway::new();
+        gw.ensure_started(None, 0).unwrap();
+        gw.register_session(
+            4,
+            vec![(ANTHROPIC_ROUTE.to_string(), format!("http://{upstream}"))],
+        );
+        gw.set_session_rate_cap(4, Some(2)).unwrap();
+
+        let url = format!("{}/v1/messages", gw.base_url(4, ANTHROPIC_ROUTE).unwrap());
+        assert_eq!(post(&url).status().as_u16(), 200);
+        assert_eq!(post(&url).status().as_u16(), 200);
+        assert_eq!(post(&url).status().as_u16(), 429);
+
+        let usage = gw.usage(4).unwrap();
+        assert_eq!(usage.requests, 2);
+        assert_eq!(usage.rate_limited, 1);
+        assert_eq!(usage.max_requests_per_minute, Some(2));
+        gw.shutdown();
+    }
+
+    #[test]
+    fn upstream_failure_is_recorded_as_error() {
+        // Bind then drop to get a port with nothing listening.
+        let dead = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let mut gw = LlmGateway::new();
+        gw.ensure_started(None, 0).unwrap();
+        gw.register_session(
+            5,
+            vec![(OPENAI_ROUTE.to_string(), format!("http://{dead}"))],
+        );
+
+        let url = format!("{}/responses", gw.base_url(5, OPENAI_ROUTE).unwrap());
+        assert_eq!(post(&url).status().as_u16(), 502);
+        let usage = gw.usage(5).unwrap();
+        assert_eq!(usage.requests, 1);
+        assert_eq!(usage.errors, 1);
+        assert!(usage.last_error.is_some());
+        gw.shutdown();
+    }
+}
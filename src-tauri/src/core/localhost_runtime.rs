@@ -13,13 +13,10 @@ use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
-use tauri::path::BaseDirectory;
-use tauri::{Emitter, Manager};
 
-use crate::events::{
-    now_rfc3339, LocalhostSessionLogEvent, LocalhostSessionStatusEvent, LOCALHOST_LOG_EVENT_NAME,
-    LOCALHOST_STATUS_EVENT_NAME,
-};
+use crate::core::app_context::AppContext;
+use crate::core::event_bus::publish;
+use crate::events::{now_rfc3339, AppEvent, LocalhostSessionLogEvent, LocalhostSessionStatusEvent};
 
 pub type SharedLocalhostRuntime = Arc<Mutex<LocalhostRuntime>>;
 
@@ -114,13 +111,9 @@ fn project_slug(project_path: &Path) -> String {
         .to_string()
 }
 
-fn sessions_path(app: &tauri::AppHandle, project_path: &Path) -> Result<PathBuf> {
+fn sessions_path(app: &AppContext, project_path: &Path) -> Result<PathBuf> {
     let project = project_slug(project_path);
-    app.path()
-        .resolve(
-            format!("synk/localhost/{project}/sessions.json"),
-            BaseDirectory::Config,
-        )
+    app.config_path(format!("localhost/{project}/sessions.json"))
         .context("resolve localhost sessions path")
 }
 
@@ -131,7 +124,7 @@ fn ensure_parent(path: &Path) -> Result<()> {
     Ok(())
 }
 
-fn load_specs(app: &tauri::AppHandle, project_path: &Path) -> Result<Vec<LocalhostSessionSpec>> {
+fn load_specs(app: &AppContext, project_path: &Path) -> Result<Vec<LocalhostSessionSpec>> {
     let path = sessions_path(app, project_path)?;
     let text = match fs::read_to_string(&path) {
         Ok(s) => s,
@@ -142,11 +135,7 @@ fn load_specs(app: &tauri::AppHandle, project_path: &Path) -> Result<Vec<Localho
     Ok(parsed)
 }
 
-fn save_specs(
-    app: &tauri::AppHandle,
-    project_path: &Path,
-    specs: &[LocalhostSessionSpec],
-) -> Result<()> {
+fn save_specs(app: &AppContext, project_path: &Path, specs: &[LocalhostSessionSpec]) -> Result<()> {
     let path = sessions_path(app, project_path)?;
     ensure_parent(&path)?;
     let text = serde_json::to_string_pretty(specs).context("serialize localhost sessions")?;
@@ -217,7 +206,7 @@ fn wait_for_port(port: u16, timeout: Duration) -> bool {
 }
 
 #[cfg(unix)]
-fn terminate_process_group(pid: u32) {
+pub(crate) fn terminate_process_group(pid: u32) {
     unsafe {
         // Negative pid targets the process group.
         let _ = libc::kill(-(pid as i32), libc::SIGTERM);
@@ -233,7 +222,7 @@ fn terminate_process_group(pid: u32) {
 }
 
 #[cfg(not(unix))]
-fn terminate_process_group(pid: u32) {
+pub(crate) fn terminate_process_group(pid: u32) {
     let _ = Command::new("taskkill")
         .args(["/PID", &pid.to_string(), "/T", "/F"])
         .status();
@@ -242,7 +231,7 @@ fn terminate_process_group(pid: u32) {
 impl LocalhostRuntime {
     pub fn get_spec(
         &self,
-        app: &tauri::AppHandle,
+        app: &AppContext,
         project_path: &Path,
         id: &str,
     ) -> Result<Option<LocalhostSessionSpec>> {
@@ -250,11 +239,7 @@ impl LocalhostRuntime {
         Ok(specs.into_iter().find(|s| s.id == id))
     }
 
-    pub fn list(
-        &self,
-        app: &tauri::AppHandle,
-        project_path: &Path,
-    ) -> Result<Vec<LocalhostSessionView>> {
+    pub fn list(&self, app: &AppContext, project_path: &Path) -> Result<Vec<LocalhostSessionView>> {
         let specs = load_specs(app, project_path)?;
         let mut out = Vec::new();
         for spec in specs {
@@ -286,7 +271,7 @@ impl LocalhostRuntime {
 
     pub fn upsert_spec(
         &mut self,
-        app: &tauri::AppHandle,
+        app: &AppContext,
         mut spec: LocalhostSessionSpec,
     ) -> Result<Vec<LocalhostSessionSpec>> {
         let project_path_str = spec.project_path.clone();
@@ -318,7 +303,7 @@ impl LocalhostRuntime {
 
     pub fn delete_spec(
         &mut self,
-        app: &tauri::AppHandle,
+        app: &AppContext,
         project_path: &Path,
         id: &str,
     ) -> Result<Vec<LocalhostSessionSpec>> {
@@ -349,7 +334,7 @@ impl LocalhostRuntime {
             .unwrap_or(false)
     }
 
-    pub fn stop(&mut self, app: tauri::AppHandle, project_path: &str, id: &str) -> Result<()> {
+    pub fn stop(&mut self, app: AppContext, project_path: &str, id: &str) -> Result<()> {
         let key = rt_key(project_path, id);
         let Some(r) = self.running.remove(&key) else {
             return Ok(());
@@ -362,9 +347,9 @@ impl LocalhostRuntime {
         }
 
         // Emit a final "stopped" status.
-        let _ = app.emit(
-            LOCALHOST_STATUS_EVENT_NAME,
-            LocalhostSessionStatusEvent {
+        publish(
+            &app,
+            AppEvent::LocalhostStatus(LocalhostSessionStatusEvent {
                 project_path: project_path.to_string(),
                 id: id.to_string(),
                 status: LocalhostSessionStatus::Stopped,
@@ -372,14 +357,14 @@ impl LocalhostRuntime {
                 pid: None,
                 url: None,
                 last_exit_code: None,
-            },
+            }),
         );
         Ok(())
     }
 
     pub fn start(
         &mut self,
-        app: tauri::AppHandle,
+        app: AppContext,
         spec: LocalhostSessionSpec,
     ) -> Result<LocalhostSessionView> {
         if spec.project_path.trim().is_empty() {
@@ -418,9 +403,9 @@ impl LocalhostRuntime {
         );
 
         // Emit initial status immediately so the UI flips to "starting".
-        let _ = app.emit(
-            LOCALHOST_STATUS_EVENT_NAME,
-            LocalhostSessionStatusEvent {
+        publish(
+            &app,
+            AppEvent::LocalhostStatus(LocalhostSessionStatusEvent {
                 project_path: spec.project_path.clone(),
                 id: spec.id.clone(),
                 status: LocalhostSessionStatus::Starting,
@@ -428,7 +413,7 @@ impl LocalhostRuntime {
                 pid: None,
                 url: url.clone(),
                 last_exit_code: None,
-            },
+            }),
         );
 
         Ok(LocalhostSessionView {
@@ -444,7 +429,7 @@ impl LocalhostRuntime {
 
     pub fn start_with_runtime(
         runtime: SharedLocalhostRuntime,
-        app: tauri::AppHandle,
+        app: AppContext,
         spec: LocalhostSessionSpec,
     ) -> Result<LocalhostSessionView> {
         let mut guard = runtime.lock().expect("localhost runtime mutex poisoned");
@@ -476,16 +461,16 @@ impl LocalhostRuntime {
         Ok(view)
     }
 
-    pub fn shutdown_all(&mut self, app: tauri::AppHandle) {
+    pub fn shutdown_all(&mut self, app: AppContext) {
         let keys: Vec<String> = self.running.keys().cloned().collect();
         for key in keys {
             if let Some(r) = self.running.remove(&key) {
                 if let Some(pid) = r.pid {
                     terminate_process_group(pid);
                 }
-                let _ = app.emit(
-                    LOCALHOST_STATUS_EVENT_NAME,
-                    LocalhostSessionStatusEvent {
+                publish(
+                    &app,
+                    AppEvent::LocalhostStatus(LocalhostSessionStatusEvent {
                         project_path: r.spec.project_path.clone(),
                         id: r.spec.id.clone(),
                         status: LocalhostSessionStatus::Stopped,
@@ -493,7 +478,7 @@ impl LocalhostRuntime {
                         pid: None,
                         url: None,
                         last_exit_code: None,
-                    },
+                    }),
                 );
             }
         }
@@ -502,7 +487,7 @@ impl LocalhostRuntime {
 
 fn push_log(
     runtime: &SharedLocalhostRuntime,
-    app: &tauri::AppHandle,
+    app: &AppContext,
     spec: &LocalhostSessionSpec,
     stream: &str,
     line: &str,
@@ -517,21 +502,21 @@ fn push_log(
         }
     }
 
-    let _ = app.emit(
-        LOCALHOST_LOG_EVENT_NAME,
-        LocalhostSessionLogEvent {
+    publish(
+        app,
+        AppEvent::LocalhostLog(LocalhostSessionLogEvent {
             project_path: spec.project_path.clone(),
             id: spec.id.clone(),
             stream: stream.to_string(),
             line: line.to_string(),
             timestamp: now_rfc3339(),
-        },
+        }),
     );
 }
 
 fn set_status(
     runtime: &SharedLocalhostRuntime,
-    app: &tauri::AppHandle,
+    app: &AppContext,
     spec: &LocalhostSessionSpec,
     status: LocalhostSessionStatus,
     pid: Option<u32>,
@@ -554,9 +539,9 @@ fn set_status(
         }
     }
 
-    let _ = app.emit(
-        LOCALHOST_STATUS_EVENT_NAME,
-        LocalhostSessionStatusEvent {
+    publish(
+        app,
+        AppEvent::LocalhostStatus(LocalhostSessionStatusEvent {
             project_path: spec.project_path.clone(),
             id: spec.id.clone(),
             status,
@@ -564,13 +549,13 @@ fn set_status(
             pid,
             url,
             last_exit_code,
-        },
+        }),
     );
 }
 
 fn run_localhost_session(
     runtime: SharedLocalhostRuntime,
-    app: tauri::AppHandle,
+    app: AppContext,
     spec: LocalhostSessionSpec,
 ) -> Result<()> {
     let working_dir = PathBuf::from(&spec.working_dir);
@@ -817,7 +802,7 @@ fn run_localhost_session(
 }
 
 #[cfg(unix)]
-fn spawn_detached_process_group(mut cmd: Command) -> Result<Child> {
+pub(crate) fn spawn_detached_process_group(mut cmd: Command) -> Result<Child> {
     use std::os::unix::process::CommandExt;
     unsafe {
         cmd.pre_exec(|| {
@@ -833,6 +818,6 @@ fn spawn_detached_process_group(mut cmd: Command) -> Result<Child> {
 }
 
 #[cfg(not(unix))]
-fn spawn_detached_process_group(cmd: Command) -> Result<Child> {
+pub(crate) fn spawn_detached_process_group(cmd: Command) -> Result<Child> {
     cmd.spawn().context("spawn child")
 }
@@ -4,6 +4,8 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use crate::core::agent_detection::AgentType;
+use crate::core::app_context::AppContext;
+use crate::core::secrets::{SecretStore, SECRET_REF_PREFIX};
 use anyhow::{Context, Result};
 use serde::Deserialize;
 use serde::Serialize;
@@ -852,3 +854,124 @@ pub fn set_server_enabled(
     fs::write(&path, format!("{text}\n")).with_context(|| format!("write {}", path.display()))?;
     Ok(())
 }
+
+/// Moves plaintext `env` values out of the user's global Claude MCP config files into the
+/// secrets store, leaving `synk-secret:` references behind. Project `.mcp.json` files are
+/// usually committed and shared, so they're left alone. The store is only opened once there
+/// is something to move.
+pub fn migrate_global_env_secrets(app: &AppContext) -> Result<usize> {
+    let mut store: Option<SecretStore> = None;
+    let mut total = 0;
+    for path in global_mcp_read_paths()? {
+        let Some(text) = read_text_if_exists(&path)? else {
+            continue;
+        };
+        // Never rewrite a file we couldn't parse.
+        let Ok(mut root) = serde_json::from_str::<Value>(&text) else {
+            continue;
+        };
+        let label = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("mcp.json")
+            .trim_start_matches('.')
+            .to_string();
+        let moved = move_env_secrets(&mut root, &label, &mut |name, value| {
+            let store = match &mut store {
+                Some(s) => s,
+                None => store.insert(SecretStore::open(app)?),
+            };
+            store.set(name, value)
+        })?;
+        if moved > 0 {
+            let text = serde_json::to_string_pretty(&root).context("serialize mcp.json")?;
+            fs::write(&path, format!("{text}\n"))
+                .with_context(|| format!("write {}", path.display()))?;
+            total += moved;
+        }
+    }
+    Ok(total)
+}
+
+/// Replaces the plaintext `env` values of every server in an MCP config with references,
+/// handing each value to `store` as `mcp.<file>.<server>.<VAR>`. Values that point at the
+/// environment (`$VAR`, `${VAR}`) aren't secrets and stay as they are.
+fn move_env_secrets(
+    root: &mut Value,
+    file: &str,
+    store: &mut dyn FnMut(&str, &str) -> Result<()>,
+) -> Result<usize> {
+    let mut moved = 0;
+    for key in ["mcpServers", "servers"] {
+        let Some(servers) = root.get_mut(key).and_then(|v| v.as_object_mut()) else {
+            continue;
+        };
+        for (server, cfg) in servers.iter_mut() {
+            let Some(env) = cfg.get_mut("env").and_then(|v| v.as_object_mut()) else {
+                continue;
+            };
+            for (var, value) in env.iter_mut() {
+                let Some(text) = value.as_str() else {
+                    continue;
+                };
+                if text.trim().is_empty()
+                    || text.starts_with('$')
+                    || text.starts_with(SECRET_REF_PREFIX)
+                {
+                    continue;
+                }
+                let name = format!("mcp.{file}.{server}.{var}");
+                store(&name, text)?;
+                *value = Value::String(format!("{SECRET_REF_PREFIX}{name}"));
+                moved += 1;
+            }
+        }
+    }
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use super::move_env_secrets;
+    use crate::core::secrets::SECRET_REF_PREFIX;
+
+    #[test]
+    fn moves_plaintext_env_values_into_the_store() {
+        let mut root = json!({
+            "mcpServers": {
+                "github": {
+                    "command": "gh-mcp",
+                    "env": { "GITHUB_TOKEN": "ghp_secret", "HOME_DIR": "${HOME}" }
+                },
+                "done": { "env": { "KEY": format!("{SECRET_REF_PREFIX}mcp.claude.json.done.KEY") } }
+            },
+            "servers": { "plain": { "env": { "EMPTY": "", "N": 3 } } }
+        });
+        let mut stored = HashMap::new();
+        let moved = move_env_secrets(&mut root, "claude.json", &mut |name, value| {
+            stored.insert(name.to_string(), value.to_string());
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(moved, 1);
+        assert_eq!(
+            stored,
+            HashMap::from([(
+                "mcp.claude.json.github.GITHUB_TOKEN".to_string(),
+                "ghp_secret".to_string()
+            )])
+        );
+        let env = &root["mcpServers"]["github"]["env"];
+        assert_eq!(
+            env["GITHUB_TOKEN"],
+            format!("{SECRET_REF_PREFIX}mcp.claude.json.github.GITHUB_TOKEN")
+        );
+        assert_eq!(env["HOME_DIR"], "${HOME}");
+        assert_eq!(root["servers"]["plain"]["env"]["N"], 3);
+    }
+}
@@ -0,0 +1,867 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::core::app_context::AppContext;
+use crate::core::audit_log::{self, AuditActor};
+use crate::core::event_bus::publish;
+use crate::core::git_events::publish_merge_event;
+use crate::core::git_manager::{GitManager, MergeResult, MergeStrategy};
+use crate::core::integration::{advance_base, git, run, unmerged};
+use crate::core::localhost_runtime::{spawn_detached_process_group, terminate_process_group};
+use crate::core::review_store::{self, ReviewDecision, ReviewStatus};
+use crate::core::session_manager::SharedSessionManager;
+use crate::core::settings as core_settings;
+use crate::events::{now_rfc3339, AppEvent, MergeQueueEvent, MergeQueueEventKind};
+
+/// Times an entry is retried when its base branch moves while it is being merged.
+const BASE_MOVED_RETRIES: usize = 3;
+/// Lines of check output kept in a failure reason.
+const CHECK_TAIL_LINES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueEntryState {
+    Queued,
+    Running,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueEntry {
+    pub review_id: String,
+    pub branch: String,
+    pub base_branch: String,
+    pub strategy: MergeStrategy,
+    pub state: QueueEntryState,
+    pub enqueued_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueFailure {
+    pub review_id: String,
+    pub branch: String,
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflict_files: Vec<String>,
+    pub at: String,
+}
+
+/// A project's merge queue, stored next to its reviews.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MergeQueue {
+    pub entries: Vec<QueueEntry>,
+    /// Set when an entry fails; the failed entry leaves the queue and the rest waits.
+    pub paused: bool,
+    pub last_failure: Option<QueueFailure>,
+}
+
+/// What happened to one queue entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Landing {
+    /// The base branch now points at this commit.
+    Merged(String),
+    Conflict(Vec<String>),
+    CheckFailed(String),
+    /// The base branch moved while the entry was being merged; nothing was changed.
+    BaseMoved,
+}
+
+/// Projects with a worker running. Also serializes access to the queue files.
+#[derive(Default)]
+pub struct MergeQueues {
+    running: HashSet<String>,
+}
+
+pub type SharedMergeQueues = Arc<Mutex<MergeQueues>>;
+
+fn queue_path(app: &AppContext, project_path: &Path) -> Result<PathBuf> {
+    Ok(review_store::review_root_dir(app, project_path)?.join("merge_queue.json"))
+}
+
+fn load(app: &AppContext, project_path: &Path) -> Result<MergeQueue> {
+    let path = queue_path(app, project_path)?;
+    match fs::read_to_string(&path) {
+        Ok(text) => {
+            serde_json::from_str(&text).with_context(|| format!("parse {}", path.display()))
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(MergeQueue::default()),
+        Err(err) => Err(err).with_context(|| format!("read {}", path.display())),
+    }
+}
+
+fn save(app: &AppContext, project_path: &Path, queue: &MergeQueue) -> Result<()> {
+    let path = queue_path(app, project_path)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    let text = serde_json::to_string_pretty(queue).context("serialize merge queue")?;
+    fs::write(&path, format!("{text}\n")).with_context(|| format!("write {}", path.display()))
+}
+
+fn update<T>(
+    shared: &SharedMergeQueues,
+    app: &AppContext,
+    project_path: &Path,
+    f: impl FnOnce(&mut MergeQueue) -> Result<T>,
+) -> Result<T> {
+    let _guard = shared.lock().expect("merge queue mutex poisoned");
+    let mut queue = load(app, project_path)?;
+    let out = f(&mut queue)?;
+    save(app, project_path, &queue)?;
+    Ok(out)
+}
+
+fn announce(
+    app: &AppContext,
+    project_path: &Path,
+    kind: MergeQueueEventKind,
+    entry: Option<&QueueEntry>,
+    queued: usize,
+    commit: Option<String>,
+    reason: Option<String>,
+) {
+    publish(
+        app,
+        AppEvent::MergeQueue(MergeQueueEvent {
+            kind,
+            project_path: project_path.to_string_lossy().to_string(),
+            review_id: entry.map(|e| e.review_id.clone()),
+            branch: entry.map(|e| e.branch.clone()),
+            commit,
+            reason,
+            queued,
+            timestamp: now_rfc3339(),
+        }),
+    );
+}
+
+fn set_review_status(app: &AppContext, project_path: &Path, id: &str, status: ReviewStatus) {
+    if let Err(err) = review_store::review_set_status(app, project_path, id, status) {
+        eprintln!("merge queue: {err:#}");
+    }
+}
+
+/// `settings.git.default_merge_strategy`, falling back to squash.
+pub fn default_strategy(app: &AppContext) -> MergeStrategy {
+    let setting = core_settings::settings_get(app)
+        .map(|s| s.git.default_merge_strategy)
+        .unwrap_or_default();
+    match setting.as_str() {
+        "merge" => MergeStrategy::Merge,
+        "rebase" => MergeStrategy::Rebase,
+        _ => MergeStrategy::Squash,
+    }
+}
+
+pub fn get(
+    shared: &SharedMergeQueues,
+    app: &AppContext,
+    project_path: &Path,
+) -> Result<MergeQueue> {
+    let _guard = shared.lock().expect("merge queue mutex poisoned");
+    load(app, project_path)
+}
+
+/// Appends an approved review and starts the worker if needed. The review is checked and
+/// marked queued under the queue lock, so it cannot change hands with the worker in between.
+pub fn enqueue(
+    shared: &SharedMergeQueues,
+    app: &AppContext,
+    project_path: &Path,
+    review_id: &str,
+) -> Result<MergeQueue> {
+    let guard = shared.lock().expect("merge queue mutex poisoned");
+    let review = review_store::review_get(app, project_path, review_id)?;
+    if review.review_decision != Some(ReviewDecision::Approved) {
+        bail!("review {review_id} must be approved before it can be queued");
+    }
+    if matches!(
+        review.status,
+        ReviewStatus::Merged | ReviewStatus::Merging | ReviewStatus::Queued
+    ) {
+        bail!("review {review_id} is already merged or queued");
+    }
+    let entry = QueueEntry {
+        review_id: review.id.clone(),
+        branch: review.branch.clone(),
+        base_branch: review.base_branch.clone(),
+        strategy: review
+            .merge_strategy
+            .unwrap_or_else(|| default_strategy(app)),
+        state: QueueEntryState::Queued,
+        enqueued_at: now_rfc3339(),
+    };
+    let mut queue = load(app, project_path)?;
+    if queue.entries.iter().any(|e| e.review_id == review_id) {
+        bail!("review {review_id} is already queued");
+    }
+    queue.entries.push(entry.clone());
+    save(app, project_path, &queue)?;
+    set_review_status(app, project_path, review_id, ReviewStatus::Queued);
+    drop(guard);
+    announce(
+        app,
+        project_path,
+        MergeQueueEventKind::Enqueued,
+        Some(&entry),
+        queue.entries.len(),
+        None,
+        None,
+    );
+    kick(shared, app, project_path);
+    Ok(queue)
+}
+
+/// Takes a waiting review out of the queue; it goes back to approved.
+pub fn remove(
+    shared: &SharedMergeQueues,
+    app: &AppContext,
+    project_path: &Path,
+    review_id: &str,
+) -> Result<MergeQueue> {
+    let (queue, entry) = update(shared, app, project_path, |q| {
+        let Some(pos) = q.entries.iter().position(|e| e.review_id == review_id) else {
+            bail!("review {review_id} is not queued");
+        };
+        if q.entries[pos].state == QueueEntryState::Running {
+            bail!("review {review_id} is being merged");
+        }
+        let entry = q.entries.remove(pos);
+        Ok((q.clone(), entry))
+    })?;
+    set_review_status(app, project_path, review_id, ReviewStatus::Approved);
+    announce(
+        app,
+        project_path,
+        MergeQueueEventKind::Removed,
+        Some(&entry),
+        queue.entries.len(),
+        None,
+        None,
+    );
+    Ok(queue)
+}
+
+/// Pausing lets the entry being merged finish; resuming clears the last failure.
+pub fn set_paused(
+    shared: &SharedMergeQueues,
+    app: &AppContext,
+    project_path: &Path,
+    paused: bool,
+) -> Result<MergeQueue> {
+    let queue = update(shared, app, project_path, |q| {
+        q.paused = paused;
+        if !paused {
+            q.last_failure = None;
+        }
+        Ok(q.clone())
+    })?;
+    let kind = if paused {
+        MergeQueueEventKind::Paused
+    } else {
+        MergeQueueEventKind::Resumed
+    };
+    announce(
+        app,
+        project_path,
+        kind,
+        None,
+        queue.entries.len(),
+        None,
+        None,
+    );
+    if !paused {
+        kick(shared, app, project_path);
+    }
+    Ok(queue)
+}
+
+/// Restarts the workers of recent projects whose queues were left waiting when the app last
+/// exited.
+pub fn resume_all(shared: &SharedMergeQueues, app: &AppContext) {
+    let projects = match crate::core::persistence::list_recent_projects(app) {
+        Ok(projects) => projects,
+        Err(err) => {
+            eprintln!("merge queue: {err:#}");
+            return;
+        }
+    };
+    for project in projects {
+        let project_path = Path::new(&project.path);
+        match get(shared, app, project_path) {
+            Ok(q) if !q.paused && !q.entries.is_empty() => kick(shared, app, project_path),
+            Ok(_) => {}
+            Err(err) => eprintln!("merge queue: {err:#}"),
+        }
+    }
+}
+
+/// Starts a worker for the project unless one is running.
+pub fn kick(shared: &SharedMergeQueues, app: &AppContext, project_path: &Path) {
+    let key = project_path.to_string_lossy().to_string();
+    if !shared
+        .lock()
+        .expect("merge queue mutex poisoned")
+        .running
+        .insert(key)
+    {
+        return;
+    }
+    let shared = shared.clone();
+    let app = app.clone();
+    let project_path = project_path.to_path_buf();
+    std::thread::spawn(move || work(&shared, &app, &project_path));
+}
+
+/// The next entry to merge, marked running, with the queue length; `None` (and the worker
+/// deregistered, under the same lock) when the queue is empty or paused.
+fn next_entry(
+    shared: &SharedMergeQueues,
+    app: &AppContext,
+    project_path: &Path,
+) -> Option<(QueueEntry, usize)> {
+    let mut guard = shared.lock().expect("merge queue mutex poisoned");
+    let next = load(app, project_path).and_then(|mut q| {
+        if q.paused {
+            return Ok(None);
+        }
+        let Some(entry) = q.entries.first_mut() else {
+            return Ok(None);
+        };
+        // A `Running` head is left over from a crash; merge it again.
+        entry.state = QueueEntryState::Running;
+        let entry = entry.clone();
+        save(app, project_path, &q)?;
+        Ok(Some((entry, q.entries.len())))
+    });
+    let next = next.unwrap_or_else(|err| {
+        eprintln!("merge queue: {err:#}");
+        None
+    });
+    if next.is_none() {
+        guard
+            .running
+            .remove(&project_path.to_string_lossy().to_string());
+    }
+    next
+}
+
+fn work(shared: &SharedMergeQueues, app: &AppContext, project_path: &Path) {
+    let mut retries = 0;
+    while let Some((entry, queued)) = next_entry(shared, app, project_path) {
+        let approved = review_store::review_get(app, project_path, &entry.review_id)
+            .map(|r| r.review_decision == Some(ReviewDecision::Approved))
+            .unwrap_or(false);
+        if !approved {
+            // Rejected, sent back or deleted while waiting.
+            if let Err(err) = drop_entry(shared, app, project_path, &entry) {
+                eprintln!("merge queue: {err:#}");
+                shared
+                    .lock()
+                    .expect("merge queue mutex poisoned")
+                    .running
+                    .remove(&project_path.to_string_lossy().to_string());
+                return;
+            }
+            continue;
+        }
+        set_review_status(app, project_path, &entry.review_id, ReviewStatus::Merging);
+        announce(
+            app,
+            project_path,
+            MergeQueueEventKind::Started,
+            Some(&entry),
+            queued,
+            None,
+            None,
+        );
+
+        let settings = core_settings::settings_get(app).ok();
+        let check = settings
+            .as_ref()
+            .and_then(|s| s.git.queue_check_command.clone())
+            .filter(|c| !c.trim().is_empty());
+        let timeout = Duration::from_secs(
+            settings
+                .as_ref()
+                .map(|s| s.git.queue_check_timeout_secs)
+                .unwrap_or(1800)
+                .max(1),
+        );
+        let landing = GitManager::for_project(app, project_path)
+            .and_then(|gm| land(&gm, &entry, check.as_deref(), timeout));
+        if matches!(landing, Ok(Landing::BaseMoved)) && retries < BASE_MOVED_RETRIES {
+            retries += 1;
+            continue;
+        }
+        retries = 0;
+        finish_entry(shared, app, project_path, &entry, landing);
+    }
+}
+
+fn drop_entry(
+    shared: &SharedMergeQueues,
+    app: &AppContext,
+    project_path: &Path,
+    entry: &QueueEntry,
+) -> Result<()> {
+    let queued = update(shared, app, project_path, |q| {
+        q.entries.retain(|e| e.review_id != entry.review_id);
+        Ok(q.entries.len())
+    })?;
+    announce(
+        app,
+        project_path,
+        MergeQueueEventKind::Removed,
+        Some(entry),
+        queued,
+        None,
+        Some("review is no longer approved".to_string()),
+    );
+    Ok(())
+}
+
+fn finish_entry(
+    shared: &SharedMergeQueues,
+    app: &AppContext,
+    project_path: &Path,
+    entry: &QueueEntry,
+    landing: Result<Landing>,
+) {
+    let (status, outcome, conflict_files) = match landing {
+        Ok(Landing::Merged(commit)) => (ReviewStatus::Merged, Ok(commit), Vec::new()),
+        Ok(Landing::Conflict(files)) => (
+            ReviewStatus::MergeConflict,
+            Err(format!("conflicts in {}", files.join(", "))),
+            files,
+        ),
+        Ok(Landing::CheckFailed(reason)) => (ReviewStatus::CheckFailed, Err(reason), Vec::new()),
+        Ok(Landing::BaseMoved) => (
+            ReviewStatus::Approved,
+            Err(format!(
+                "{} kept moving during the merge",
+                entry.base_branch
+            )),
+            Vec::new(),
+        ),
+        Err(err) => (ReviewStatus::Approved, Err(format!("{err:#}")), Vec::new()),
+    };
+
+    let queued = update(shared, app, project_path, |q| {
+        q.entries.retain(|e| e.review_id != entry.review_id);
+        if let Err(reason) = &outcome {
+            q.paused = true;
+            q.last_failure = Some(QueueFailure {
+                review_id: entry.review_id.clone(),
+                branch: entry.branch.clone(),
+                reason: reason.clone(),
+                conflict_files: conflict_files.clone(),
+                at: now_rfc3339(),
+            });
+        }
+        Ok(q.entries.len())
+    })
+    .unwrap_or_else(|err| {
+        eprintln!("merge queue: {err:#}");
+        0
+    });
+
+    set_review_status(app, project_path, &entry.review_id, status);
+    audit_log::record(
+        app,
+        Some(&project_path.to_string_lossy()),
+        &AuditActor::Rule {
+            name: "merge_queue".to_string(),
+        },
+        "merge_queue_merge",
+        json!({
+            "reviewId": entry.review_id,
+            "branch": entry.branch,
+            "baseBranch": entry.base_branch,
+            "strategy": entry.strategy,
+        }),
+        &outcome,
+    );
+    if let Some(sessions) = app.try_state::<SharedSessionManager>() {
+        if outcome.is_ok() || !conflict_files.is_empty() {
+            publish_merge_event(
+                app,
+                &sessions,
+                &project_path.to_string_lossy(),
+                &entry.branch,
+                &entry.base_branch,
+                entry.strategy,
+                &MergeResult {
+                    success: outcome.is_ok(),
+                    conflict_files: (!conflict_files.is_empty()).then(|| conflict_files.clone()),
+                    integration: None,
+                },
+            );
+        }
+    }
+    match outcome {
+        Ok(commit) => announce(
+            app,
+            project_path,
+            MergeQueueEventKind::Merged,
+            Some(entry),
+            queued,
+            Some(commit),
+            None,
+        ),
+        Err(reason) => announce(
+            app,
+            project_path,
+            MergeQueueEventKind::Failed,
+            Some(entry),
+            queued,
+            None,
+            Some(reason),
+        ),
+    }
+}
+
+/// Merges `entry` onto the current base tip in a detached worktree (rebasing the branch there
+/// for the rebase strategy), runs `check` on the result and fast-forwards the base. Nothing
+/// outside that worktree changes unless every step passed.
+pub fn land(
+    gm: &GitManager,
+    entry: &QueueEntry,
+    check: Option<&str>,
+    timeout: Duration,
+) -> Result<Landing> {
+    let base_tip = gm.resolve_commit(&entry.base_branch)?;
+    let branch_tip = gm.resolve_commit(&entry.branch)?;
+    let root = gm.integration_root();
+    fs::create_dir_all(&root).with_context(|| format!("create {}", root.display()))?;
+    let dir = root.join("merge-queue");
+    let dir_s = dir.to_string_lossy().to_string();
+    let _ = git(
+        gm.project_path(),
+        &["worktree", "remove", "--force", &dir_s],
+    );
+    let start = match entry.strategy {
+        MergeStrategy::Rebase => &branch_tip,
+        _ => &base_tip,
+    };
+    git(
+        gm.project_path(),
+        &["worktree", "add", "--detach", &dir_s, start],
+    )?;
+
+    let landing = land_in(gm, &dir, entry, &base_tip, &branch_tip, check, timeout);
+    let _ = git(
+        gm.project_path(),
+        &["worktree", "remove", "--force", &dir_s],
+    );
+    landing
+}
+
+fn land_in(
+    gm: &GitManager,
+    dir: &Path,
+    entry: &QueueEntry,
+    base_tip: &str,
+    branch_tip: &str,
+    check: Option<&str>,
+    timeout: Duration,
+) -> Result<Landing> {
+    let merged = match entry.strategy {
+        MergeStrategy::Rebase => run(dir, &["rebase", base_tip]),
+        MergeStrategy::Merge => {
+            let message = format!("Merge branch '{}' into {}", entry.branch, entry.base_branch);
+            run(dir, &["merge", "--no-ff", "-m", &message, branch_tip])
+        }
+        MergeStrategy::Squash => run(dir, &["merge", "--squash", branch_tip]),
+    };
+    if let Err(err) = merged {
+        let files = unmerged(dir)?;
+        let abort = match entry.strategy {
+            MergeStrategy::Rebase => "rebase",
+            _ => "merge",
+        };
+        let _ = run(dir, &[abort, "--abort"]);
+        if files.is_empty() {
+            return Err(err);
+        }
+        return Ok(Landing::Conflict(files));
+    }
+    if entry.strategy == MergeStrategy::Squash
+        && run(dir, &["diff", "--cached", "--quiet"]).is_err()
+    {
+        git(dir, &["commit", "-m", &format!("squash: {}", entry.branch)])?;
+    }
+
+    if let Some(command) = check {
+        if let Some(reason) = run_check(dir, command, timeout)? {
+            return Ok(Landing::CheckFailed(reason));
+        }
+    }
+
+    let head = git(dir, &["rev-parse", "HEAD"])?;
+    if gm.resolve_commit(&entry.base_branch)? != base_tip {
+        return Ok(Landing::BaseMoved);
+    }
+    advance_base(gm, &entry.base_branch, base_tip, &head)?;
+
+    // Point the branch at its rebased commits, unless someone has it checked out.
+    if entry.strategy == MergeStrategy::Rebase
+        && !gm
+            .list_worktrees()?
+            .iter()
+            .any(|w| w.branch.as_deref() == Some(entry.branch.as_str()))
+    {
+        let branch_ref = format!("refs/heads/{}", entry.branch);
+        let _ = git(
+            gm.project_path(),
+            &["update-ref", &branch_ref, &head, branch_tip],
+        );
+    }
+    Ok(Landing::Merged(head))
+}
+
+/// Runs the check command through the shell, in its own process group, with output going to a
+/// log beside the worktree. Returns why it failed, or `None` if it passed; on timeout the whole
+/// group is killed so test runners and servers the check started do not outlive it.
+fn run_check(dir: &Path, command: &str, timeout: Duration) -> Result<Option<String>> {
+    let log_path = dir.with_extension("log");
+    let log =
+        fs::File::create(&log_path).with_context(|| format!("create {}", log_path.display()))?;
+    let mut cmd = if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.args(["/C", command]);
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.args(["-c", command]);
+        c
+    };
+    cmd.current_dir(dir)
+        .env("SYNK_MERGE_QUEUE", "1")
+        .stdin(Stdio::null())
+        .stdout(log.try_clone().context("clone check log handle")?)
+        .stderr(log);
+    let mut child = spawn_detached_process_group(cmd)
+        .with_context(|| format!("spawn check command {command:?}"))?;
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().context("wait for check command")? {
+            break Some(status);
+        }
+        if started.elapsed() >= timeout {
+            terminate_process_group(child.id());
+            let _ = child.wait();
+            break None;
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    };
+
+    let output = fs::read_to_string(&log_path).unwrap_or_default();
+    let lines: Vec<&str> = output.lines().collect();
+    let tail = lines[lines.len().saturating_sub(CHECK_TAIL_LINES)..].join("\n");
+    Ok(match status {
+        Some(s) if s.success() => None,
+        Some(s) => Some(format!("check `{command}` failed ({s})\n{tail}")),
+        None => Some(format!(
+            "check `{command}` timed out after {}s\n{tail}",
+            timeout.as_secs()
+        )),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_fixtures::{init_repo, TempDir};
+    use crate::events::REVIEW_EVENT_NAME;
+
+    fn repo() -> Result<(TempDir, PathBuf, GitManager)> {
+        let tmp = TempDir::new("merge-queue");
+        let repo = tmp.join("repo");
+        init_repo(&repo)?;
+        fs::write(repo.join("shared.txt"), "top\nmiddle\nbottom\n")?;
+        git(&repo, &["add", "."])?;
+        git(&repo, &["commit", "-m", "add shared.txt"])?;
+        let wt = tmp.join("worktrees").to_string_lossy().to_string();
+        let gm = GitManager::new(repo.clone(), &wt, "feat/")?;
+        Ok((tmp, repo, gm))
+    }
+
+    fn branch(repo: &Path, name: &str, file: &str, body: &str) -> Result<()> {
+        git(repo, &["branch", name, "main"])?;
+        git(repo, &["switch", name])?;
+        fs::write(repo.join(file), body)?;
+        git(repo, &["add", "."])?;
+        git(repo, &["commit", "-m", name])?;
+        git(repo, &["switch", "main"])?;
+        Ok(())
+    }
+
+    fn entry(branch: &str, strategy: MergeStrategy) -> QueueEntry {
+        QueueEntry {
+            review_id: branch.to_string(),
+            branch: branch.to_string(),
+            base_branch: "main".to_string(),
+            strategy,
+            state: QueueEntryState::Running,
+            enqueued_at: now_rfc3339(),
+        }
+    }
+
+    #[test]
+    fn lands_entries_in_order_and_stops_on_conflicts_and_checks() -> Result<()> {
+        let (_tmp, repo, gm) = repo()?;
+        let t = Duration::from_secs(30);
+        branch(&repo, "feat/a", "a.txt", "a\n")?;
+        branch(&repo, "feat/b", "b.txt", "b\n")?;
+        branch(&repo, "feat/c", "shared.txt", "top\nfrom c\nbottom\n")?;
+        branch(&repo, "feat/d", "shared.txt", "top\nfrom d\nbottom\n")?;
+
+        let first = land(&gm, &entry("feat/a", MergeStrategy::Squash), None, t)?;
+        assert_eq!(first, Landing::Merged(gm.resolve_commit("main")?));
+        assert!(repo.join("a.txt").exists(), "checked-out base follows");
+
+        // feat/b forked before feat/a landed; rebasing it keeps history linear.
+        let Landing::Merged(head) = land(&gm, &entry("feat/b", MergeStrategy::Rebase), None, t)?
+        else {
+            panic!("feat/b should land");
+        };
+        assert_eq!(gm.resolve_commit("feat/b")?, head);
+        assert_eq!(
+            git(&repo, &["rev-list", "--merges", "--count", "main"])?,
+            "0"
+        );
+
+        let failing = land(
+            &gm,
+            &entry("feat/c", MergeStrategy::Merge),
+            Some("echo checking; exit 3"),
+            t,
+        )?;
+        let Landing::CheckFailed(reason) = failing else {
+            panic!("check should fail: {failing:?}");
+        };
+        assert!(reason.contains("checking"));
+        assert_eq!(gm.resolve_commit("main")?, head, "failed check leaves main");
+
+        land(&gm, &entry("feat/c", MergeStrategy::Merge), Some("true"), t)?;
+        let conflict = land(&gm, &entry("feat/d", MergeStrategy::Merge), None, t)?;
+        assert_eq!(conflict, Landing::Conflict(vec!["shared.txt".to_string()]));
+        assert_eq!(git(&repo, &["status", "--porcelain"])?, "");
+        Ok(())
+    }
+
+    fn approved_review(app: &AppContext, gm: &GitManager, repo: &Path, branch: &str) -> String {
+        let mut review =
+            review_store::review_create(app, gm, repo, 1, None, branch, "main").unwrap();
+        review.review_decision = Some(ReviewDecision::Approved);
+        review.status = ReviewStatus::Approved;
+        review_store::review_save(app, repo, &review).unwrap();
+        review.id
+    }
+
+    #[test]
+    fn queue_merges_in_order_and_pauses_on_failure() -> Result<()> {
+        let (tmp, repo, gm) = repo()?;
+        let app = AppContext::headless(tmp.join("config"));
+        let mut settings = core_settings::settings_get(&app)?;
+        settings.git.worktree_base_path = tmp.join("worktrees").to_string_lossy().to_string();
+        settings.git.queue_check_command = Some("test ! -f c.txt".to_string());
+        core_settings::settings_set(&app, settings)?;
+
+        let mut ids = Vec::new();
+        for name in ["a", "b", "c", "d"] {
+            let branch_name = format!("feat/{name}");
+            branch(&repo, &branch_name, &format!("{name}.txt"), "x\n")?;
+            ids.push(approved_review(&app, &gm, &repo, &branch_name));
+        }
+
+        // Status changes from here on.
+        let statuses = Arc::new(Mutex::new(Vec::<(String, String)>::new()));
+        let seen = statuses.clone();
+        app.listen_any(REVIEW_EVENT_NAME, move |payload| {
+            let v: serde_json::Value = serde_json::from_str(payload).unwrap();
+            let field = |k: &str| v[k].as_str().unwrap_or_default().to_string();
+            seen.lock()
+                .unwrap()
+                .push((field("branch"), field("status")));
+        });
+        let status_of = |branch: &str| -> Vec<String> {
+            let all = statuses.lock().unwrap();
+            all.iter()
+                .filter(|(b, _)| b == branch)
+                .map(|(_, s)| s.clone())
+                .collect()
+        };
+
+        // Hold the worker back so the queue can be inspected between steps.
+        let shared = SharedMergeQueues::default();
+        let key = repo.to_string_lossy().to_string();
+        shared.lock().unwrap().running.insert(key.clone());
+        for id in &ids {
+            enqueue(&shared, &app, &repo, id)?;
+        }
+        assert!(enqueue(&shared, &app, &repo, &ids[0]).is_err());
+        assert_eq!(get(&shared, &app, &repo)?.entries.len(), 4);
+
+        // Sent back while waiting: the worker drops it.
+        let mut b = review_store::review_get(&app, &repo, &ids[1])?;
+        b.review_decision = Some(ReviewDecision::ChangesRequested);
+        b.status = ReviewStatus::ChangesRequested;
+        review_store::review_save(&app, &repo, &b)?;
+
+        work(&shared, &app, &repo);
+        assert!(!shared.lock().unwrap().running.contains(&key));
+
+        assert_eq!(status_of("feat/a"), ["queued", "merging", "merged"]);
+        assert_eq!(status_of("feat/b"), ["queued"]);
+        assert_eq!(status_of("feat/c"), ["queued", "merging", "check_failed"]);
+        assert_eq!(status_of("feat/d"), ["queued"]);
+        assert!(repo.join("a.txt").exists() && !repo.join("c.txt").exists());
+
+        let queue = get(&shared, &app, &repo)?;
+        assert!(queue.paused);
+        let failed = queue.last_failure.map(|f| f.review_id);
+        assert_eq!(failed.as_ref(), Some(&ids[2]));
+        let waiting: Vec<&String> = queue.entries.iter().map(|e| &e.review_id).collect();
+        assert_eq!(waiting, [&ids[3]]);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timed_out_checks_take_their_children_with_them() -> Result<()> {
+        let (tmp, _, _) = repo()?;
+        let dir = tmp.join("check");
+        fs::create_dir_all(&dir)?;
+        let reason = run_check(
+            &dir,
+            "sleep 30 & echo $! > pid; wait",
+            Duration::from_secs(1),
+        )?;
+        assert!(reason.is_some_and(|r| r.contains("timed out")));
+        let pid: i32 = fs::read_to_string(dir.join("pid"))?.trim().parse()?;
+        // The orphaned sleep is reaped by init; give it a moment.
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while unsafe { libc::kill(pid, 0) } == 0 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert_ne!(
+            unsafe { libc::kill(pid, 0) },
+            0,
+            "sleep {pid} outlived the check"
+        );
+        Ok(())
+    }
+}
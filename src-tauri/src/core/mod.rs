@@ -1,15 +1,39 @@
 pub mod agent_detection;
+pub mod app_context;
+pub mod audit_log;
+pub mod event_bus;
 pub mod process_pool;
+pub mod session_broadcast;
+pub mod session_env;
+pub mod session_fork;
+pub mod session_handoff;
+pub mod session_labels;
 pub mod session_manager;
 
 // Placeholder modules to match the documented file structure.
+pub mod attempts;
+pub mod checkpoints;
+pub mod cli;
+pub mod command_bar;
+pub mod commit_graph;
+pub mod control_socket;
 pub mod cost_tracker;
 pub mod git_events;
 pub mod git_manager;
+pub mod hooks;
+pub mod integration;
+pub mod llm_gateway;
 pub mod localhost_runtime;
 pub mod mcp_discovery;
 pub mod mcp_server;
+pub mod merge_queue;
+pub mod notifications;
 pub mod persistence;
+pub mod remote_api;
 pub mod review_store;
+pub mod secrets;
 pub mod settings;
+pub mod simulated_agent;
 pub mod skills_discovery;
+#[cfg(test)]
+pub(crate) mod test_fixtures;
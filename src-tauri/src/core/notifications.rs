@@ -0,0 +1,500 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::core::app_context::AppContext;
+use crate::core::event_bus::{publish, SharedEventBus};
+use crate::core::localhost_runtime::LocalhostSessionStatus;
+use crate::core::session_manager::SharedSessionManager;
+use crate::core::settings::{self as core_settings, NotificationsView};
+use crate::events::{
+    now_rfc3339, AppEvent, GitEventType, ReviewEventKind, COST_UPDATED_EVENT_NAME, GIT_EVENT_NAME,
+    LOCALHOST_STATUS_EVENT_NAME, REVIEW_EVENT_NAME, SESSION_EXIT_EVENT_NAME,
+    SESSION_IDLE_EVENT_NAME,
+};
+
+pub const NOTIFICATIONS_FILE: &str = "notifications.json";
+
+/// Oldest entries are dropped past this.
+const HISTORY_CAP: usize = 200;
+
+pub type SharedNotificationCenter = Arc<Mutex<NotificationCenter>>;
+
+/// One per `NotificationsDisk` toggle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    TaskCompleted,
+    AgentError,
+    MergeConflict,
+    ReviewReady,
+    CostThreshold,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    pub id: u64,
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+    pub timestamp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<usize>,
+    #[serde(default)]
+    pub read: bool,
+}
+
+/// A rule's output before it is numbered and stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Draft {
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+    pub project_path: Option<String>,
+    pub session_id: Option<usize>,
+}
+
+/// What the rules know about a live session.
+#[derive(Debug, Clone)]
+pub struct SessionRef {
+    pub label: String,
+    pub project_path: String,
+    /// [`SessionInfo::session_key`](crate::core::session_manager::SessionInfo::session_key).
+    pub session_key: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct HistoryDisk {
+    next_id: u64,
+    items: Vec<Notification>,
+    /// Session keys already reported over the cost threshold.
+    cost_alerted: HashSet<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationListArgs {
+    #[serde(default)]
+    pub unread_only: bool,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationList {
+    /// Newest first.
+    pub items: Vec<Notification>,
+    pub unread: usize,
+}
+
+/// Notification history, persisted to `notifications.json` so toasts and the unread count
+/// survive a reload or restart.
+#[derive(Default)]
+pub struct NotificationCenter {
+    path: Option<PathBuf>,
+    history: HistoryDisk,
+}
+
+impl NotificationCenter {
+    /// History stored at `path`; an unreadable file starts an empty one.
+    pub fn open(path: PathBuf) -> Self {
+        let history = match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|err| {
+                eprintln!("notifications: parse {}: {err}", path.display());
+                HistoryDisk::default()
+            }),
+            Err(_) => HistoryDisk::default(),
+        };
+        Self {
+            path: Some(path),
+            history,
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        }
+        let text = serde_json::to_string_pretty(&self.history).context("serialize history")?;
+        fs::write(path, format!("{text}\n")).with_context(|| format!("write {}", path.display()))
+    }
+
+    fn save_or_log(&self) {
+        if let Err(err) = self.save() {
+            eprintln!("notifications: {err:#}");
+        }
+    }
+
+    pub fn push(&mut self, draft: Draft) -> Notification {
+        self.history.next_id += 1;
+        let n = Notification {
+            id: self.history.next_id,
+            kind: draft.kind,
+            title: draft.title,
+            body: draft.body,
+            timestamp: now_rfc3339(),
+            project_path: draft.project_path,
+            session_id: draft.session_id,
+            read: false,
+        };
+        self.history.items.push(n.clone());
+        let excess = self.history.items.len().saturating_sub(HISTORY_CAP);
+        self.history.items.drain(..excess);
+        self.save_or_log();
+        n
+    }
+
+    pub fn list(&self, args: &NotificationListArgs) -> NotificationList {
+        let items = self
+            .history
+            .items
+            .iter()
+            .rev()
+            .filter(|n| !args.unread_only || !n.read)
+            .take(args.limit.unwrap_or(HISTORY_CAP))
+            .cloned()
+            .collect();
+        NotificationList {
+            items,
+            unread: self.history.items.iter().filter(|n| !n.read).count(),
+        }
+    }
+
+    /// Marks `ids` (every notification when `None`) read; returns how many changed.
+    pub fn mark_read(&mut self, ids: Option<&[u64]>) -> usize {
+        let mut changed = 0;
+        for n in &mut self.history.items {
+            if !n.read && ids.is_none_or(|ids| ids.contains(&n.id)) {
+                n.read = true;
+                changed += 1;
+            }
+        }
+        if changed > 0 {
+            self.save_or_log();
+        }
+        changed
+    }
+
+    pub fn clear(&mut self) {
+        self.history.items.clear();
+        self.save_or_log();
+    }
+
+    /// Once per session, across restarts: a draft when `total_cost` reaches the configured
+    /// threshold.
+    pub fn check_cost(
+        &mut self,
+        rules: &NotificationsView,
+        session_id: usize,
+        session: &SessionRef,
+        total_cost: f64,
+    ) -> Option<Draft> {
+        let threshold = rules.cost_threshold.filter(|t| *t > 0.0)?;
+        let alerted = &mut self.history.cost_alerted;
+        if total_cost < f64::from(threshold) || !alerted.insert(session.session_key.clone()) {
+            return None;
+        }
+        self.save_or_log();
+        Some(Draft {
+            kind: NotificationKind::CostThreshold,
+            title: format!("{} passed ${threshold:.2}", session.label),
+            body: format!("Estimated spend so far: ${total_cost:.2}"),
+            project_path: Some(session.project_path.clone()),
+            session_id: Some(session_id),
+        })
+    }
+
+    /// Loads history from the config dir and starts applying the notification rules to
+    /// published events on a worker thread.
+    pub fn start(center: SharedNotificationCenter, app: AppContext) {
+        match app.config_path(NOTIFICATIONS_FILE) {
+            Ok(path) => {
+                *center.lock().expect("notification mutex poisoned") =
+                    NotificationCenter::open(path)
+            }
+            Err(err) => eprintln!("notifications: {err:#}"),
+        }
+        let Some(bus) = app.try_state::<SharedEventBus>() else {
+            return;
+        };
+
+        // Subscribers run on the publishing thread, often under other locks; rules that need
+        // session state are evaluated here instead.
+        let (tx, rx) = mpsc::channel::<AppEvent>();
+        bus.lock().expect("event bus mutex poisoned").subscribe(
+            &[
+                SESSION_IDLE_EVENT_NAME,
+                SESSION_EXIT_EVENT_NAME,
+                GIT_EVENT_NAME,
+                REVIEW_EVENT_NAME,
+                LOCALHOST_STATUS_EVENT_NAME,
+                COST_UPDATED_EVENT_NAME,
+            ],
+            move |event| {
+                let _ = tx.send(event.clone());
+            },
+        );
+
+        std::thread::spawn(move || {
+            for event in rx {
+                let Ok(settings) = core_settings::settings_get(&app) else {
+                    continue;
+                };
+                let rules = settings.notifications;
+                let draft = match &event {
+                    AppEvent::CostUpdated(e) => {
+                        session_ref(&app, e.session_id).and_then(|session| {
+                            center
+                                .lock()
+                                .expect("notification mutex poisoned")
+                                .check_cost(&rules, e.session_id, &session, e.cost.total_cost)
+                        })
+                    }
+                    other => evaluate(&rules, other, &|id| session_ref(&app, id)),
+                };
+                let Some(draft) = draft else {
+                    continue;
+                };
+                let n = center
+                    .lock()
+                    .expect("notification mutex poisoned")
+                    .push(draft);
+                show_native(&app, &n);
+                publish(&app, AppEvent::Notification(n));
+            }
+        });
+    }
+}
+
+fn label(session_id: usize, session: Option<&SessionRef>) -> String {
+    match session {
+        Some(s) => s.label.clone(),
+        None => format!("Session #{session_id}"),
+    }
+}
+
+fn session_ref(app: &AppContext, session_id: usize) -> Option<SessionRef> {
+    let sessions = app.try_state::<SharedSessionManager>()?;
+    let info = sessions
+        .lock()
+        .expect("session manager mutex poisoned")
+        .get_session_info(session_id)?;
+    Some(SessionRef {
+        label: match info.labels.name {
+            Some(name) => format!("{name} (#{session_id})"),
+            None => format!("Session #{session_id}"),
+        },
+        project_path: info.project_path,
+        session_key: info.session_key,
+    })
+}
+
+/// Applies the notification toggles to one event. Cost is handled by
+/// [`NotificationCenter::check_cost`] since it needs per-session state.
+pub fn evaluate(
+    rules: &NotificationsView,
+    event: &AppEvent,
+    session: &dyn Fn(usize) -> Option<SessionRef>,
+) -> Option<Draft> {
+    match event {
+        AppEvent::SessionIdle(e) if rules.task_completed => {
+            let s = session(e.session_id);
+            Some(Draft {
+                kind: NotificationKind::TaskCompleted,
+                title: format!("{} finished", label(e.session_id, s.as_ref())),
+                body: "The agent is waiting for input.".to_string(),
+                project_path: s.map(|s| s.project_path),
+                session_id: Some(e.session_id),
+            })
+        }
+        // Closing a session or a clean `exit` reports 0; unknown statuses are not crashes.
+        AppEvent::SessionExit(e) if rules.agent_error && e.exit_code.is_some_and(|c| c != 0) => {
+            let s = session(e.session_id);
+            Some(Draft {
+                kind: NotificationKind::AgentError,
+                title: format!("{} exited unexpectedly", label(e.session_id, s.as_ref())),
+                body: format!("Exit code {}.", e.exit_code.unwrap_or_default()),
+                project_path: s.map(|s| s.project_path),
+                session_id: Some(e.session_id),
+            })
+        }
+        AppEvent::Git(e)
+            if rules.merge_conflict && matches!(e.event_type, GitEventType::ConflictDetected) =>
+        {
+            let files = e.conflict_files.clone().unwrap_or_default();
+            let mut body = files.iter().take(3).cloned().collect::<Vec<_>>().join(", ");
+            if files.len() > 3 {
+                body.push_str(&format!(" and {} more", files.len() - 3));
+            }
+            Some(Draft {
+                kind: NotificationKind::MergeConflict,
+                title: format!(
+                    "Conflict merging {} into {}",
+                    e.branch.as_deref().unwrap_or("branch"),
+                    e.base_branch.as_deref().unwrap_or("base")
+                ),
+                body,
+                project_path: Some(e.project_path.clone()),
+                session_id: e.session_id,
+            })
+        }
+        AppEvent::Review(e) if rules.review_ready && e.kind == ReviewEventKind::Created => {
+            Some(Draft {
+                kind: NotificationKind::ReviewReady,
+                title: format!("Review ready: {}", e.branch),
+                body: format!("Into {}.", e.base_branch),
+                project_path: Some(e.project_path.clone()),
+                session_id: Some(e.session_id),
+            })
+        }
+        AppEvent::LocalhostStatus(e)
+            if rules.agent_error
+                && e.status == LocalhostSessionStatus::Exited
+                && e.last_exit_code.is_some_and(|c| c != 0) =>
+        {
+            Some(Draft {
+                kind: NotificationKind::AgentError,
+                title: format!("Dev server {} crashed", e.id),
+                body: format!("Exit code {}.", e.last_exit_code.unwrap_or_default()),
+                project_path: Some(e.project_path.clone()),
+                session_id: None,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Desktop notification through the OS notifier; `synk serve` has no desktop to show it on.
+fn show_native(app: &AppContext, n: &Notification) {
+    if !app.is_desktop() {
+        return;
+    }
+    let Some(mut cmd) = native_command(&n.title, &n.body) else {
+        return;
+    };
+    if let Ok(mut child) = cmd.spawn() {
+        // Reap in the background so the notifier doesn't linger as a zombie.
+        std::thread::spawn(move || child.wait());
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn native_command(title: &str, body: &str) -> Option<Command> {
+    let mut cmd = Command::new("notify-send");
+    cmd.args(["--app-name=Synk", title, body]);
+    Some(cmd)
+}
+
+#[cfg(target_os = "macos")]
+fn native_command(title: &str, body: &str) -> Option<Command> {
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut cmd = Command::new("osascript");
+    cmd.arg("-e").arg(format!(
+        "display notification {} with title {}",
+        quote(body),
+        quote(title)
+    ));
+    Some(cmd)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn native_command(_title: &str, _body: &str) -> Option<Command> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{evaluate, NotificationCenter, NotificationKind, NotificationListArgs, SessionRef};
+    use crate::core::settings::NotificationsView;
+    use crate::core::test_fixtures::TempDir;
+    use crate::events::{AppEvent, SessionExitEvent};
+
+    fn exit(code: Option<i32>) -> AppEvent {
+        AppEvent::SessionExit(SessionExitEvent {
+            session_id: 2,
+            exit_code: code,
+        })
+    }
+
+    #[test]
+    fn rules_follow_the_toggles() {
+        let mut rules = NotificationsView::default();
+        let lookup = |_| {
+            Some(SessionRef {
+                label: "api (#2)".to_string(),
+                project_path: "/repo".to_string(),
+                session_key: "100-2".to_string(),
+            })
+        };
+        let draft = evaluate(&rules, &exit(Some(1)), &lookup).unwrap();
+        assert_eq!(draft.kind, NotificationKind::AgentError);
+        assert_eq!(draft.title, "api (#2) exited unexpectedly");
+        assert_eq!(draft.project_path.as_deref(), Some("/repo"));
+        assert!(evaluate(&rules, &exit(Some(0)), &lookup).is_none());
+        assert!(evaluate(&rules, &exit(None), &lookup).is_none());
+
+        rules.agent_error = false;
+        assert!(evaluate(&rules, &exit(Some(1)), &lookup).is_none());
+    }
+
+    #[test]
+    fn cost_alerts_once_per_session_across_restarts() {
+        let dir = TempDir::new("notify-cost");
+        let path = dir.join("notifications.json");
+        let session = |key: &str| SessionRef {
+            label: "Session #1".to_string(),
+            project_path: "/repo".to_string(),
+            session_key: key.to_string(),
+        };
+        let (first, later) = (session("100-1"), session("200-1"));
+        let mut center = NotificationCenter::open(path.clone());
+        let mut rules = NotificationsView::default();
+        assert!(center.check_cost(&rules, 1, &first, 50.0).is_none());
+        rules.cost_threshold = Some(5.0);
+        assert!(center.check_cost(&rules, 1, &first, 4.0).is_none());
+        let draft = center.check_cost(&rules, 1, &first, 6.5).unwrap();
+        assert_eq!(draft.title, "Session #1 passed $5.00");
+        assert!(center.check_cost(&rules, 1, &first, 9.0).is_none());
+
+        let mut reopened = NotificationCenter::open(path.clone());
+        assert!(reopened.check_cost(&rules, 1, &first, 9.0).is_none());
+        // A later run's session #1 is a different session.
+        assert!(reopened.check_cost(&rules, 1, &later, 9.0).is_some());
+    }
+
+    #[test]
+    fn history_persists_read_state() {
+        let dir = TempDir::new("notify-history");
+        let path = dir.join("notifications.json");
+        let lookup = |_| None;
+        let rules = NotificationsView::default();
+        let mut center = NotificationCenter::open(path.clone());
+        let first = center.push(evaluate(&rules, &exit(Some(1)), &lookup).unwrap());
+        center.push(evaluate(&rules, &exit(Some(2)), &lookup).unwrap());
+        assert_eq!(center.mark_read(Some(&[first.id])), 1);
+
+        let reopened = NotificationCenter::open(path);
+        let all = reopened.list(&NotificationListArgs::default());
+        assert_eq!(all.unread, 1);
+        assert_eq!(all.items[0].body, "Exit code 2.");
+        let unread = reopened.list(&NotificationListArgs {
+            unread_only: true,
+            limit: None,
+        });
+        assert_eq!(unread.items.len(), 1);
+    }
+}
@@ -5,12 +5,12 @@ use std::path::{Path, PathBuf};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tauri::path::BaseDirectory;
-use tauri::Manager;
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 
 use crate::core::agent_detection::AgentType;
+use crate::core::app_context::AppContext;
+use crate::core::session_labels::SessionLabels;
 use crate::core::session_manager::SessionInfo;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,13 +39,12 @@ fn now_rfc3339() -> Result<String> {
     Ok(OffsetDateTime::now_utc().format(&Rfc3339)?)
 }
 
-fn projects_file_path(app: &tauri::AppHandle) -> Result<PathBuf> {
-    app.path()
-        .resolve("synk/projects.json", BaseDirectory::Config)
+fn projects_file_path(app: &AppContext) -> Result<PathBuf> {
+    app.config_path("projects.json")
         .context("resolve config path for projects.json")
 }
 
-fn read_projects_file(app: &tauri::AppHandle) -> Result<ProjectsFileDisk> {
+fn read_projects_file(app: &AppContext) -> Result<ProjectsFileDisk> {
     let path = projects_file_path(app)?;
     let text = match fs::read_to_string(&path) {
         Ok(s) => s,
@@ -59,7 +58,7 @@ fn read_projects_file(app: &tauri::AppHandle) -> Result<ProjectsFileDisk> {
     Ok(parsed)
 }
 
-fn write_projects_file(app: &tauri::AppHandle, data: &ProjectsFileDisk) -> Result<()> {
+fn write_projects_file(app: &AppContext, data: &ProjectsFileDisk) -> Result<()> {
     let path = projects_file_path(app)?;
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
@@ -99,7 +98,7 @@ fn to_recent(p: ProjectDisk) -> RecentProject {
     }
 }
 
-pub fn list_recent_projects(app: &tauri::AppHandle) -> Result<Vec<RecentProject>> {
+pub fn list_recent_projects(app: &AppContext) -> Result<Vec<RecentProject>> {
     let mut file = read_projects_file(app)?;
     // RFC3339 sorts lexicographically, so this yields "most recent first".
     file.projects
@@ -107,7 +106,7 @@ pub fn list_recent_projects(app: &tauri::AppHandle) -> Result<Vec<RecentProject>
     Ok(file.projects.into_iter().map(to_recent).collect())
 }
 
-pub fn open_project(app: &tauri::AppHandle, project_path: &Path) -> Result<RecentProject> {
+pub fn open_project(app: &AppContext, project_path: &Path) -> Result<RecentProject> {
     ensure_synk_dir(project_path)?;
 
     let now = now_rfc3339()?;
@@ -172,6 +171,14 @@ pub struct SessionConfigDisk {
     pub skills: Vec<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub mcp_servers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -187,6 +194,32 @@ pub struct SessionConfigView {
     pub skills: Vec<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub mcp_servers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+}
+
+impl SessionConfigView {
+    pub fn labels(&self) -> SessionLabels {
+        SessionLabels {
+            name: self.name.clone(),
+            color: self.color.clone(),
+            tags: self.tags.clone(),
+            group: self.group.clone(),
+        }
+    }
+
+    pub fn set_labels(&mut self, labels: SessionLabels) {
+        self.name = labels.name;
+        self.color = labels.color;
+        self.tags = labels.tags;
+        self.group = labels.group;
+    }
 }
 
 impl From<SessionConfigDisk> for SessionConfigView {
@@ -197,6 +230,10 @@ impl From<SessionConfigDisk> for SessionConfigView {
             worktree_isolation: v.worktree_isolation,
             skills: v.skills,
             mcp_servers: v.mcp_servers,
+            name: v.name,
+            color: v.color,
+            tags: v.tags,
+            group: v.group,
         }
     }
 }
@@ -209,6 +246,10 @@ impl From<SessionConfigView> for SessionConfigDisk {
             worktree_isolation: v.worktree_isolation,
             skills: v.skills,
             mcp_servers: v.mcp_servers,
+            name: v.name,
+            color: v.color,
+            tags: v.tags,
+            group: v.group,
         }
     }
 }
@@ -356,6 +397,8 @@ pub struct SessionPaneSnapshot {
     pub mcp_servers: Vec<String>,
     #[serde(default)]
     pub env_overrides: HashMap<String, String>,
+    #[serde(default, flatten)]
+    pub labels: SessionLabels,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -385,10 +428,9 @@ pub struct SessionSnapshotMeta {
     pub layout: String,
 }
 
-fn sessions_dir(app: &tauri::AppHandle) -> Result<PathBuf> {
+fn sessions_dir(app: &AppContext) -> Result<PathBuf> {
     let path = app
-        .path()
-        .resolve("synk/sessions", BaseDirectory::Config)
+        .config_path("sessions")
         .context("resolve config path for sessions dir")?;
     fs::create_dir_all(&path).with_context(|| format!("create sessions dir {}", path.display()))?;
     Ok(path)
@@ -449,7 +491,7 @@ fn slugify_filename(name: &str) -> String {
     }
 }
 
-fn snapshot_path_named(app: &tauri::AppHandle, name: &str) -> Result<(String, PathBuf)> {
+fn snapshot_path_named(app: &AppContext, name: &str) -> Result<(String, PathBuf)> {
     let id = slugify_filename(name);
     let dir = sessions_dir(app)?;
     Ok((id.clone(), dir.join(format!("{id}.json"))))
@@ -460,10 +502,7 @@ fn snapshot_id_autosave(project_path: &Path) -> String {
     format!("{project}-autosave")
 }
 
-fn snapshot_path_autosave(
-    app: &tauri::AppHandle,
-    project_path: &Path,
-) -> Result<(String, PathBuf)> {
+fn snapshot_path_autosave(app: &AppContext, project_path: &Path) -> Result<(String, PathBuf)> {
     let id = snapshot_id_autosave(project_path);
     let dir = sessions_dir(app)?;
     Ok((id.clone(), dir.join(format!("{id}.json"))))
@@ -531,6 +570,11 @@ fn build_snapshot(
             .working_dir
             .clone()
             .unwrap_or_else(|| project_path.to_string_lossy().to_string());
+        let labels = if s.labels.is_empty() {
+            cfg.map(|c| c.labels()).unwrap_or_default()
+        } else {
+            s.labels.clone()
+        };
 
         panes.push(SessionPaneSnapshot {
             pane_index: s.pane_index,
@@ -541,6 +585,7 @@ fn build_snapshot(
             skills,
             mcp_servers,
             env_overrides: HashMap::new(),
+            labels,
         });
     }
 
@@ -560,7 +605,7 @@ fn build_snapshot(
 }
 
 pub fn session_snapshot_save_named(
-    app: &tauri::AppHandle,
+    app: &AppContext,
     project_path: &Path,
     name: &str,
     orchestration_mode: &str,
@@ -586,7 +631,7 @@ pub fn session_snapshot_save_named(
 }
 
 pub fn session_snapshot_save_autosave(
-    app: &tauri::AppHandle,
+    app: &AppContext,
     project_path: &Path,
     orchestration_mode: &str,
     sessions: Vec<SessionInfo>,
@@ -611,7 +656,7 @@ pub fn session_snapshot_save_autosave(
     Ok(snapshot_meta(id, "autosave", &snapshot, &path))
 }
 
-pub fn session_snapshot_load(app: &tauri::AppHandle, id: &str) -> Result<SessionSnapshot> {
+pub fn session_snapshot_load(app: &AppContext, id: &str) -> Result<SessionSnapshot> {
     let clean = slugify_filename(id);
     let dir = sessions_dir(app)?;
     let path = dir.join(format!("{clean}.json"));
@@ -619,7 +664,7 @@ pub fn session_snapshot_load(app: &tauri::AppHandle, id: &str) -> Result<Session
 }
 
 pub fn session_snapshot_list(
-    app: &tauri::AppHandle,
+    app: &AppContext,
     project_path: Option<&Path>,
 ) -> Result<Vec<SessionSnapshotMeta>> {
     let dir = sessions_dir(app)?;
@@ -673,7 +718,7 @@ pub fn session_snapshot_list(
 }
 
 pub fn session_snapshot_autosave_meta(
-    app: &tauri::AppHandle,
+    app: &AppContext,
     project_path: &Path,
 ) -> Result<Option<SessionSnapshotMeta>> {
     let (id, path) = snapshot_path_autosave(app, project_path)?;
@@ -50,6 +50,8 @@ impl Default for PoolConfig {
     }
 }
 
+/// Only the desktop app's debug commands read these.
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
 #[derive(Debug, Clone, Serialize)]
 pub struct PoolStats {
     pub idle: usize,
@@ -73,7 +75,31 @@ pub struct PtyHandle {
 
     master: Box<dyn MasterPty + Send>,
     writer: Box<dyn Write + Send>,
-    child: Box<dyn portable_pty::Child + Send + Sync>,
+    child: SharedChild,
+}
+
+type SharedChild = Arc<Mutex<Box<dyn portable_pty::Child + Send + Sync>>>;
+
+/// Reads a PTY child's exit status from another thread without owning its handle.
+#[derive(Clone)]
+pub struct ExitProbe(SharedChild);
+
+impl ExitProbe {
+    /// The child's exit code once it has exited, polling for up to `timeout`; `None` if it is
+    /// still running. A child killed by a signal reports 1.
+    pub fn wait(&self, timeout: Duration) -> Option<i32> {
+        let start = Instant::now();
+        loop {
+            let status = self.0.lock().expect("pty child mutex poisoned").try_wait();
+            match status {
+                Ok(Some(status)) => {
+                    return Some(i32::try_from(status.exit_code()).unwrap_or(i32::MAX))
+                }
+                Ok(None) if start.elapsed() < timeout => thread::sleep(Duration::from_millis(20)),
+                _ => return None,
+            }
+        }
+    }
 }
 
 impl PtyHandle {
@@ -108,6 +134,10 @@ impl PtyHandle {
         Ok(())
     }
 
+    pub fn exit_probe(&self) -> ExitProbe {
+        ExitProbe(self.child.clone())
+    }
+
     pub fn clone_reader(&mut self) -> Result<Box<dyn Read + Send>> {
         self.master.try_clone_reader().context("try_clone_reader")
     }
@@ -119,6 +149,7 @@ impl PtyHandle {
             .ok_or_else(|| anyhow!("MasterPty::as_raw_fd() not available"))
     }
 
+    #[cfg_attr(not(feature = "desktop"), allow(dead_code))]
     fn wait_for_marker(&mut self, marker: &str, timeout: Duration) -> Result<String> {
         #[cfg(not(unix))]
         {
@@ -287,6 +318,7 @@ impl PtyHandle {
         Ok(())
     }
 
+    #[cfg_attr(not(feature = "desktop"), allow(dead_code))]
     pub fn debug_roundtrip_echo(&mut self, timeout: Duration) -> Result<String> {
         let token = unique_token("echo");
         let marker = format!("__SYNK_ECHO__:{token}");
@@ -294,6 +326,13 @@ impl PtyHandle {
         self.wait_for_marker(&marker, timeout)
     }
 
+    fn try_wait_child(&self) -> std::io::Result<Option<portable_pty::ExitStatus>> {
+        self.child
+            .lock()
+            .expect("pty child mutex poisoned")
+            .try_wait()
+    }
+
     fn terminate(&mut self, grace: Duration) {
         #[cfg(unix)]
         if let Some(pid) = self.pid {
@@ -304,7 +343,7 @@ impl PtyHandle {
 
         let start = Instant::now();
         while start.elapsed() < grace {
-            match self.child.try_wait() {
+            match self.try_wait_child() {
                 Ok(Some(_)) => return,
                 Ok(None) => {}
                 Err(_) => break,
@@ -319,11 +358,11 @@ impl PtyHandle {
             }
         }
 
-        let _ = self.child.kill();
+        let _ = self.child.lock().expect("pty child mutex poisoned").kill();
 
         let start = Instant::now();
         while start.elapsed() < Duration::from_millis(500) {
-            if matches!(self.child.try_wait(), Ok(Some(_))) {
+            if matches!(self.try_wait_child(), Ok(Some(_))) {
                 return;
             }
             thread::sleep(Duration::from_millis(25));
@@ -351,6 +390,7 @@ impl ProcessPool {
         }
     }
 
+    #[cfg_attr(not(feature = "desktop"), allow(dead_code))]
     pub fn stats(&self) -> PoolStats {
         PoolStats {
             idle: self.idle_pool.len(),
@@ -593,6 +633,7 @@ impl ProcessPool {
         Ok(())
     }
 
+    #[cfg_attr(not(feature = "desktop"), allow(dead_code))]
     pub fn debug_roundtrip(pool: SharedProcessPool) -> Result<String> {
         let session_key = 9999usize;
         let mut handle = Self::claim(pool.clone(), session_key)?;
@@ -679,7 +720,7 @@ fn spawn_shell_pty(config: &PoolConfig) -> Result<PtyHandle> {
         state: PtyState::Warming,
         master: pair.master,
         writer,
-        child,
+        child: Arc::new(Mutex::new(child)),
     })
 }
 
@@ -706,7 +747,7 @@ fn tail_looks_like_prompt(captured: &str) -> bool {
     tail.ends_with("$ ") || tail.ends_with("# ") || tail.ends_with("% ") || tail.ends_with("> ")
 }
 
-fn strip_ansi(s: &str) -> String {
+pub(crate) fn strip_ansi(s: &str) -> String {
     // Minimal ANSI CSI stripping: ESC [ ... <final byte>.
     // This is intentionally conservative; it doesn't try to handle every sequence.
     let mut out = String::with_capacity(s.len());
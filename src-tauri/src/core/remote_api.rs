@@ -0,0 +1,770 @@
+use std::collections::HashMap;
+use std::io::{BufReader, Read, Write};
+use std::net::{IpAddr, Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::Role;
+use tungstenite::{Message, WebSocket};
+
+use crate::core::app_context::{AppContext, EventId};
+use crate::core::audit_log::AuditActor;
+use crate::core::control_socket::{
+    forward_events, CommandHandler, EventLine, Request, Response, SubscribeArgs, SUBSCRIBE_COMMAND,
+};
+use crate::core::llm_gateway::{read_request, ProxyRequest};
+use crate::core::secrets::{random_token, token_matches, write_private};
+
+const TOKEN_FILE: &str = "remote-api-token";
+/// Connections beyond this are answered with a 503 and closed.
+const MAX_CLIENTS: usize = 32;
+/// How long a client may take to send a complete HTTP request, in total.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// WebSocket reads give up this often so queued responses and events get written.
+const WS_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteApiStatus {
+    pub running: bool,
+    pub port: Option<u16>,
+    pub bind_lan: bool,
+    /// Base URL for clients, e.g. `http://127.0.0.1:4777`.
+    pub url: Option<String>,
+    /// Bearer token clients must send (`Authorization: Bearer ...`, or `?token=` on the
+    /// WebSocket upgrade).
+    pub token: String,
+    pub clients: usize,
+}
+
+/// The token lives next to settings.json with owner-only permissions; created on first use.
+pub fn load_or_create_token(app: &AppContext) -> Result<String> {
+    let path = token_path(app)?;
+    match std::fs::read_to_string(&path) {
+        Ok(t) if !t.trim().is_empty() => Ok(t.trim().to_string()),
+        Ok(_) => rotate_token_at(&path),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => rotate_token_at(&path),
+        Err(e) => Err(e).with_context(|| format!("read {}", path.display())),
+    }
+}
+
+#[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+pub fn rotate_token(app: &AppContext) -> Result<String> {
+    rotate_token_at(&token_path(app)?)
+}
+
+pub fn token_path(app: &AppContext) -> Result<PathBuf> {
+    app.config_path(TOKEN_FILE)
+        .context("resolve config path for the remote API token")
+}
+
+fn rotate_token_at(path: &Path) -> Result<String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    }
+    let token = random_token();
+    write_private(path, token.as_bytes())?;
+    Ok(token)
+}
+
+type Clients = Arc<Mutex<HashMap<u64, TcpStream>>>;
+
+/// Optional HTTP + WebSocket server exposing the same commands as the control socket:
+///
+/// - `GET /api/health` (no auth)
+/// - `POST /api/command/<name>` with the command's `args` as the JSON body; replies
+///   `{"ok":true,"result":...}` or `{"ok":false,"error":"..."}`
+/// - `GET /api/ws` upgrades to a WebSocket carrying the control socket protocol as text
+///   frames; `subscribe` may be sent any number of times and events arrive interleaved
+///   with responses.
+pub struct RemoteApi {
+    addr: Option<SocketAddr>,
+    bind_lan: bool,
+    stop: Arc<AtomicBool>,
+    accept_thread: Option<JoinHandle<()>>,
+    token: Arc<Mutex<String>>,
+    clients: Clients,
+}
+
+pub type SharedRemoteApi = Arc<Mutex<RemoteApi>>;
+
+impl Default for RemoteApi {
+    fn default() -> Self {
+        Self {
+            addr: None,
+            bind_lan: false,
+            stop: Arc::new(AtomicBool::new(false)),
+            accept_thread: None,
+            token: Arc::new(Mutex::new(String::new())),
+            clients: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl RemoteApi {
+    /// Starts listening on `127.0.0.1:port` (every interface with `bind_lan`). `app` is
+    /// needed for event subscriptions; `port = 0` picks a free port.
+    pub fn start(
+        &mut self,
+        app: Option<AppContext>,
+        handler: CommandHandler,
+        token: String,
+        port: u16,
+        bind_lan: bool,
+    ) -> Result<u16> {
+        if let Some(addr) = self.addr {
+            return Ok(addr.port());
+        }
+        let host = if bind_lan { "0.0.0.0" } else { "127.0.0.1" };
+        let listener = TcpListener::bind((host, port))
+            .with_context(|| format!("bind remote API on {host}:{port}"))?;
+        listener
+            .set_nonblocking(true)
+            .context("set remote API listener nonblocking")?;
+        let addr = listener.local_addr().context("remote API local addr")?;
+        *self.token.lock().expect("remote API token mutex poisoned") = token;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_accept = stop.clone();
+        let token = self.token.clone();
+        let clients = self.clients.clone();
+        let next_client = Arc::new(AtomicU64::new(1));
+        let accept_thread = std::thread::spawn(move || {
+            while !stop_accept.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((mut stream, _)) => {
+                        let client_id = next_client.fetch_add(1, Ordering::Relaxed);
+                        {
+                            let mut clients =
+                                clients.lock().expect("remote API clients mutex poisoned");
+                            if clients.len() >= MAX_CLIENTS {
+                                let _ = stream.set_nonblocking(false);
+                                write_error(
+                                    &mut stream,
+                                    503,
+                                    "Service Unavailable",
+                                    "too many connections",
+                                    None,
+                                );
+                                continue;
+                            }
+                            if let Ok(s) = stream.try_clone() {
+                                clients.insert(client_id, s);
+                            }
+                        }
+                        let app = app.clone();
+                        let handler = handler.clone();
+                        let token = token.clone();
+                        let clients = clients.clone();
+                        std::thread::spawn(move || {
+                            let conn = Connection {
+                                app: app.as_ref(),
+                                handler: &handler,
+                                token: &token,
+                            };
+                            if let Err(err) = conn.handle(stream) {
+                                eprintln!("remote api: {err:#}");
+                            }
+                            clients
+                                .lock()
+                                .expect("remote API clients mutex poisoned")
+                                .remove(&client_id);
+                        });
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        std::thread::sleep(Duration::from_millis(25));
+                    }
+                    Err(e) => {
+                        eprintln!("remote api: accept failed: {e}");
+                        std::thread::sleep(Duration::from_millis(250));
+                    }
+                }
+            }
+        });
+
+        self.addr = Some(addr);
+        self.bind_lan = bind_lan;
+        self.stop = stop;
+        self.accept_thread = Some(accept_thread);
+        Ok(addr.port())
+    }
+
+    /// Replaces the token and drops clients that connected with the old one.
+    #[cfg_attr(not(feature = "desktop"), allow(dead_code))]
+    pub fn set_token(&self, token: String) {
+        *self.token.lock().expect("remote API token mutex poisoned") = token;
+        self.disconnect_all();
+    }
+
+    fn disconnect_all(&self) {
+        for (_, stream) in self
+            .clients
+            .lock()
+            .expect("remote API clients mutex poisoned")
+            .drain()
+        {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    pub fn is_running_as(&self, port: u16, bind_lan: bool) -> bool {
+        self.addr
+            .is_some_and(|a| (port == 0 || a.port() == port) && self.bind_lan == bind_lan)
+    }
+
+    pub fn status(&self) -> RemoteApiStatus {
+        RemoteApiStatus {
+            running: self.addr.is_some(),
+            port: self.addr.map(|a| a.port()),
+            bind_lan: self.bind_lan,
+            url: self.addr.map(|a| format!("http://{a}")),
+            token: self
+                .token
+                .lock()
+                .expect("remote API token mutex poisoned")
+                .clone(),
+            clients: self
+                .clients
+                .lock()
+                .expect("remote API clients mutex poisoned")
+                .len(),
+        }
+    }
+
+    /// Stops accepting and disconnects every client.
+    pub fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(t) = self.accept_thread.take() {
+            let _ = t.join();
+        }
+        self.disconnect_all();
+        self.addr = None;
+    }
+}
+
+fn split_target(target: &str) -> (&str, HashMap<String, String>) {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let params = query
+        .split('&')
+        .filter_map(|pair| {
+            let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+            let v = urlencoding::decode(v).ok()?;
+            (!k.is_empty()).then(|| (k.to_string(), v.into_owned()))
+        })
+        .collect();
+    (path, params)
+}
+
+/// Host part of an `Origin` or `Host` header value, without scheme or port.
+fn host_of(value: &str) -> &str {
+    let rest = value.split_once("://").map_or(value, |(_, rest)| rest);
+    let authority = rest.split('/').next().unwrap_or(rest);
+    if let Some(v6) = authority.strip_prefix('[') {
+        return v6.split(']').next().unwrap_or(v6);
+    }
+    authority
+        .rsplit_once(':')
+        .map_or(authority, |(host, _)| host)
+}
+
+/// The browser UI is served by the dev server, on loopback or on the API's own host with
+/// another port; pages from any other origin get no CORS grant and can't open the WebSocket.
+fn allowed_origin(req: &ProxyRequest) -> Option<&str> {
+    let origin = req.header("origin")?;
+    let host = host_of(origin);
+    let same_host = req
+        .header("host")
+        .is_some_and(|h| host_of(h).eq_ignore_ascii_case(host));
+    let loopback = host.eq_ignore_ascii_case("localhost")
+        || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback());
+    (same_host || loopback).then_some(origin)
+}
+
+fn cors_headers(origin: Option<&str>) -> String {
+    origin.map_or_else(String::new, |origin| {
+        format!(
+            "Access-Control-Allow-Origin: {origin}\r\nVary: Origin\r\nAccess-Control-Allow-Headers: authorization, content-type\r\nAccess-Control-Allow-Methods: GET, POST, OPTIONS\r\n"
+        )
+    })
+}
+
+fn write_json(
+    stream: &mut TcpStream,
+    status: u16,
+    reason: &str,
+    body: &Value,
+    origin: Option<&str>,
+) {
+    let body = body.to_string();
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{body}",
+        body.len(),
+        cors_headers(origin)
+    );
+    let _ = stream.flush();
+}
+
+fn write_error(
+    stream: &mut TcpStream,
+    status: u16,
+    reason: &str,
+    message: &str,
+    origin: Option<&str>,
+) {
+    write_json(
+        stream,
+        status,
+        reason,
+        &json!({ "ok": false, "error": message }),
+        origin,
+    );
+}
+
+/// Read side of a connection that fails once `deadline` passes, so a client trickling bytes
+/// can't stretch a per-read timeout indefinitely.
+struct DeadlineStream {
+    stream: TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "request timed out",
+            ));
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        self.stream.read(buf)
+    }
+}
+
+struct Connection<'a> {
+    app: Option<&'a AppContext>,
+    handler: &'a CommandHandler,
+    token: &'a Mutex<String>,
+}
+
+impl Connection<'_> {
+    fn handle(&self, mut stream: TcpStream) -> Result<()> {
+        stream.set_nonblocking(false)?;
+        let mut reader = BufReader::new(DeadlineStream {
+            stream: stream.try_clone().context("clone remote API stream")?,
+            deadline: Instant::now() + REQUEST_TIMEOUT,
+        });
+        let req = match read_request(&mut reader) {
+            Ok(r) => r,
+            Err(err) => {
+                write_error(&mut stream, 400, "Bad Request", &format!("{err:#}"), None);
+                return Ok(());
+            }
+        };
+        let (path, query) = split_target(&req.target);
+        let origin = allowed_origin(&req);
+        let actor = AuditActor::Remote {
+            addr: stream
+                .peer_addr()
+                .map(|a| a.to_string())
+                .unwrap_or_default(),
+        };
+
+        if req.method == "OPTIONS" {
+            let _ = write!(
+                stream,
+                "HTTP/1.1 204 No Content\r\n{}Content-Length: 0\r\nConnection: close\r\n\r\n",
+                cors_headers(origin)
+            );
+            return Ok(());
+        }
+        if path == "/api/health" {
+            write_json(&mut stream, 200, "OK", &json!({ "ok": true }), origin);
+            return Ok(());
+        }
+
+        // Browsers can't set headers on a WebSocket, so only the upgrade takes the token
+        // from the query string; everywhere else it would end up in logs and history.
+        let given = req
+            .header("authorization")
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .or_else(|| {
+                (path == "/api/ws")
+                    .then(|| query.get("token").map(String::as_str))
+                    .flatten()
+            })
+            .unwrap_or("");
+        let expected = self
+            .token
+            .lock()
+            .expect("remote API token mutex poisoned")
+            .clone();
+        if !token_matches(given.trim(), &expected) {
+            write_error(
+                &mut stream,
+                401,
+                "Unauthorized",
+                "missing or invalid token",
+                origin,
+            );
+            return Ok(());
+        }
+
+        match (req.method.as_str(), path) {
+            ("POST", p) if p.starts_with("/api/command/") => {
+                let command = &p["/api/command/".len()..];
+                self.run_command(&mut stream, &actor, command, &req, origin);
+                Ok(())
+            }
+            ("GET", "/api/ws") => {
+                let Some(key) = req.header("sec-websocket-key") else {
+                    write_error(
+                        &mut stream,
+                        400,
+                        "Bad Request",
+                        "expected a WebSocket upgrade",
+                        origin,
+                    );
+                    return Ok(());
+                };
+                if req.header("origin").is_some() && origin.is_none() {
+                    write_error(&mut stream, 403, "Forbidden", "origin not allowed", None);
+                    return Ok(());
+                }
+                write!(
+                    stream,
+                    "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                    derive_accept_key(key.trim().as_bytes())
+                )
+                .context("websocket handshake")?;
+                // The client may have sent its first frames along with the upgrade request.
+                let buffered = reader.buffer().to_vec();
+                let ws = WebSocket::from_partially_read(stream, buffered, Role::Server, None);
+                self.serve_websocket(ws, &actor)
+            }
+            _ => {
+                write_error(
+                    &mut stream,
+                    404,
+                    "Not Found",
+                    &format!("no route for {path}"),
+                    origin,
+                );
+                Ok(())
+            }
+        }
+    }
+
+    fn run_command(
+        &self,
+        stream: &mut TcpStream,
+        actor: &AuditActor,
+        command: &str,
+        req: &ProxyRequest,
+        origin: Option<&str>,
+    ) {
+        let args = if req.body.iter().all(u8::is_ascii_whitespace) {
+            Value::Null
+        } else {
+            match serde_json::from_slice(&req.body) {
+                Ok(v) => v,
+                Err(err) => {
+                    write_error(
+                        stream,
+                        400,
+                        "Bad Request",
+                        &format!("invalid JSON: {err}"),
+                        origin,
+                    );
+                    return;
+                }
+            }
+        };
+        match (self.handler)(actor, command, args) {
+            Ok(result) => write_json(
+                stream,
+                200,
+                "OK",
+                &json!({ "ok": true, "result": result }),
+                origin,
+            ),
+            Err(err) => write_error(stream, 400, "Bad Request", &err, origin),
+        }
+    }
+
+    /// Requests are answered in order on a worker thread. This thread owns the socket and
+    /// writes responses and events as they're queued, so a slow command doesn't hold up
+    /// events; pings are answered by tungstenite.
+    fn serve_websocket(&self, mut ws: WebSocket<TcpStream>, actor: &AuditActor) -> Result<()> {
+        ws.get_mut()
+            .set_read_timeout(Some(WS_POLL_INTERVAL))
+            .context("set websocket read timeout")?;
+        let (out_tx, out_rx) = mpsc::channel::<String>();
+        let (req_tx, req_rx) = mpsc::channel::<String>();
+
+        std::thread::scope(|scope| {
+            let worker = scope.spawn(move || {
+                let mut listeners = Vec::new();
+                for text in req_rx {
+                    let response = match serde_json::from_str::<Request>(&text) {
+                        Ok(req) if req.command == SUBSCRIBE_COMMAND => {
+                            self.subscribe(req, &out_tx, &mut listeners)
+                        }
+                        Ok(req) => response(req.id, (self.handler)(actor, &req.command, req.args)),
+                        Err(err) => response(Value::Null, Err(format!("invalid request: {err}"))),
+                    };
+                    if let Ok(text) = serde_json::to_string(&response) {
+                        let _ = out_tx.send(text);
+                    }
+                }
+                listeners
+            });
+
+            let result = 'serve: loop {
+                match ws.read() {
+                    Ok(Message::Text(text)) => {
+                        let _ = req_tx.send(text.to_string());
+                    }
+                    Ok(Message::Close(_)) => break Ok(()),
+                    Ok(_) => {}
+                    Err(tungstenite::Error::Io(e))
+                        if matches!(
+                            e.kind(),
+                            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                        ) => {}
+                    Err(tungstenite::Error::ConnectionClosed) => break Ok(()),
+                    Err(err) => break Err(anyhow::Error::new(err).context("read websocket")),
+                }
+                while let Ok(text) = out_rx.try_recv() {
+                    if let Err(err) = ws.send(Message::text(text)) {
+                        break 'serve Err(anyhow::Error::new(err).context("write websocket"));
+                    }
+                }
+            };
+
+            drop(req_tx);
+            let listeners = worker.join().expect("remote API websocket worker panicked");
+            if let Some(app) = self.app {
+                for id in listeners {
+                    app.unlisten(id);
+                }
+            }
+            let _ = ws.close(None);
+            let _ = ws.flush();
+            result
+        })
+    }
+
+    fn subscribe(
+        &self,
+        req: Request,
+        tx: &mpsc::Sender<String>,
+        listeners: &mut Vec<EventId>,
+    ) -> Response {
+        let Some(app) = self.app else {
+            return response(req.id, Err("events are not available".into()));
+        };
+        let args: SubscribeArgs = match serde_json::from_value(req.args) {
+            Ok(a) => a,
+            Err(err) => return response(req.id, Err(format!("invalid args: {err}"))),
+        };
+        let tx = tx.clone();
+        listeners.extend(forward_events(
+            app,
+            &args,
+            Arc::new(move |line: EventLine| {
+                if let Ok(text) = serde_json::to_string(&line) {
+                    let _ = tx.send(text);
+                }
+            }),
+        ));
+        response(req.id, Ok(Value::Null))
+    }
+}
+
+fn response(id: Value, result: std::result::Result<Value, String>) -> Response {
+    match result {
+        Ok(result) => Response {
+            id,
+            ok: true,
+            result: Some(result),
+            error: None,
+        },
+        Err(error) => Response {
+            id,
+            ok: false,
+            result: None,
+            error: Some(error),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpStream;
+    use std::sync::Arc;
+
+    use serde_json::{json, Value};
+    use tungstenite::client::IntoClientRequest;
+    use tungstenite::Message;
+
+    use super::{host_of, split_target, token_matches, RemoteApi};
+    use crate::core::audit_log::AuditActor;
+    use crate::core::control_socket::CommandHandler;
+
+    fn echo_handler() -> CommandHandler {
+        Arc::new(
+            |actor: &AuditActor, command: &str, args: Value| match command {
+                "echo" => Ok(args),
+                "whoami" => Ok(serde_json::to_value(actor).unwrap()),
+                other => Err(format!("unknown command {other:?}")),
+            },
+        )
+    }
+
+    fn post(port: u16, path: &str, token: Option<&str>, body: &str) -> (u16, Value) {
+        let client = reqwest::blocking::Client::new();
+        let mut req = client
+            .post(format!("http://127.0.0.1:{port}{path}"))
+            .body(body.to_string());
+        if let Some(t) = token {
+            req = req.bearer_auth(t);
+        }
+        let res = req.send().unwrap();
+        let status = res.status().as_u16();
+        (status, res.json().unwrap())
+    }
+
+    #[test]
+    fn parses_query_and_compares_tokens() {
+        let (path, q) = split_target("/api/ws?token=a%2Bb&x");
+        assert_eq!(path, "/api/ws");
+        assert_eq!(q.get("token").map(String::as_str), Some("a+b"));
+        assert!(token_matches("abc", "abc"));
+        assert!(!token_matches("abd", "abc"));
+        assert!(!token_matches("", ""));
+    }
+
+    #[test]
+    fn runs_commands_with_a_valid_token() {
+        let mut api = RemoteApi::default();
+        let port = api
+            .start(None, echo_handler(), "secret".into(), 0, false)
+            .unwrap();
+
+        let (status, body) = post(port, "/api/command/echo", None, "{}");
+        assert_eq!(status, 401);
+        assert_eq!(body["ok"], false);
+
+        let (status, body) = post(port, "/api/command/echo", Some("secret"), r#"{"a":1}"#);
+        assert_eq!(status, 200);
+        assert_eq!(body, json!({ "ok": true, "result": { "a": 1 } }));
+
+        let (_, body) = post(port, "/api/command/whoami", Some("secret"), "");
+        assert_eq!(body["result"]["kind"], "remote");
+        assert!(body["result"]["addr"]
+            .as_str()
+            .unwrap()
+            .starts_with("127.0.0.1:"));
+
+        let (status, body) = post(port, "/api/command/nope", Some("secret"), "");
+        assert_eq!(status, 400);
+        assert!(body["error"].as_str().unwrap().contains("nope"));
+
+        api.shutdown();
+    }
+
+    #[test]
+    fn takes_the_query_token_only_on_the_websocket_upgrade() {
+        let mut api = RemoteApi::default();
+        let port = api
+            .start(None, echo_handler(), "secret".into(), 0, false)
+            .unwrap();
+
+        let (status, _) = post(port, "/api/command/echo?token=secret", None, "{}");
+        assert_eq!(status, 401);
+
+        let url = format!("ws://127.0.0.1:{port}/api/ws?token=secret");
+        let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        assert!(tungstenite::client(url.as_str(), stream).is_ok());
+
+        api.shutdown();
+    }
+
+    #[test]
+    fn grants_cors_only_to_same_host_or_loopback_origins() {
+        let mut api = RemoteApi::default();
+        let port = api
+            .start(None, echo_handler(), "secret".into(), 0, false)
+            .unwrap();
+        let health = |origin: &str| {
+            reqwest::blocking::Client::new()
+                .get(format!("http://127.0.0.1:{port}/api/health"))
+                .header("Origin", origin)
+                .send()
+                .unwrap()
+                .headers()
+                .get("access-control-allow-origin")
+                .map(|v| v.to_str().unwrap().to_string())
+        };
+        assert_eq!(
+            health("http://localhost:1420").as_deref(),
+            Some("http://localhost:1420")
+        );
+        assert_eq!(health("https://evil.example"), None);
+
+        let mut request = format!("ws://127.0.0.1:{port}/api/ws?token=secret")
+            .into_client_request()
+            .unwrap();
+        request
+            .headers_mut()
+            .insert("Origin", "https://evil.example".parse().unwrap());
+        let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        assert!(tungstenite::client(request, stream).is_err());
+
+        api.shutdown();
+    }
+
+    #[test]
+    fn strips_scheme_and_port_from_hosts() {
+        assert_eq!(host_of("http://localhost:1420"), "localhost");
+        assert_eq!(host_of("192.168.1.5:4777"), "192.168.1.5");
+        assert_eq!(host_of("http://[::1]:1420"), "::1");
+        assert_eq!(host_of("https://example.com"), "example.com");
+    }
+
+    #[test]
+    fn answers_requests_over_websocket() {
+        let mut api = RemoteApi::default();
+        let port = api
+            .start(None, echo_handler(), "secret".into(), 0, false)
+            .unwrap();
+
+        let url = format!("ws://127.0.0.1:{port}/api/ws?token=secret");
+        let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let (mut ws, _) = tungstenite::client(url.as_str(), stream).unwrap();
+
+        ws.send(Message::text(r#"{"id":7,"command":"echo","args":"hi"}"#))
+            .unwrap();
+        let reply: Value = serde_json::from_str(ws.read().unwrap().to_text().unwrap()).unwrap();
+        assert_eq!(reply, json!({ "id": 7, "ok": true, "result": "hi" }));
+
+        // Without an app there is nothing to subscribe to.
+        ws.send(Message::text(
+            r#"{"id":8,"command":"subscribe","args":{"events":["session:output"]}}"#,
+        ))
+        .unwrap();
+        let reply = ws.read().unwrap().into_text().unwrap();
+        assert!(reply.contains(r#""ok":false"#), "{reply}");
+
+        api.shutdown();
+    }
+}
@@ -1,13 +1,17 @@
+use std::collections::BTreeSet;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc;
 
 use anyhow::{Context, Result};
-use tauri::path::BaseDirectory;
-use tauri::Manager;
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 
-use crate::core::git_manager::{DiffLineType, FileDiff, GitManager, MergeStrategy};
+use crate::core::app_context::AppContext;
+use crate::core::event_bus::{publish, SharedEventBus};
+use crate::core::git_manager::{DiffLineType, FileDiff, GitManager, MergePreview, MergeStrategy};
+use crate::core::session_labels::SessionLabels;
+use crate::events::{AppEvent, ReviewEvent, ReviewEventKind, GIT_EVENT_NAME};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -17,9 +21,13 @@ pub enum ReviewStatus {
     Approved,
     Rejected,
     ChangesRequested,
+    /// Waiting in the project's merge queue.
+    Queued,
     Merging,
     Merged,
     MergeConflict,
+    /// The merge queue's check command failed on the merged result.
+    CheckFailed,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -48,6 +56,9 @@ pub struct ReviewItem {
     pub id: String,
     pub task_id: Option<String>,
     pub session_id: usize,
+    /// Labels of the session when the review was opened.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_labels: Option<SessionLabels>,
     pub branch: String,
     pub base_branch: String,
     pub status: ReviewStatus,
@@ -63,13 +74,17 @@ pub struct ReviewItem {
     pub comments: Vec<ReviewComment>,
     pub review_decision: Option<ReviewDecision>,
     pub merge_strategy: Option<MergeStrategy>,
+    /// Predicted merge outcome, recomputed whenever either branch tip moves. Stored in its own
+    /// file (see [`save_merge_preview`]) so a refresh never rewrites the rest of the review.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_preview: Option<MergePreview>,
 }
 
 fn now_rfc3339() -> Result<String> {
     Ok(OffsetDateTime::now_utc().format(&Rfc3339)?)
 }
 
-fn project_slug(project_path: &Path) -> String {
+pub(crate) fn project_slug(project_path: &Path) -> String {
     project_path
         .file_name()
         .and_then(|s| s.to_str())
@@ -82,15 +97,14 @@ fn project_slug(project_path: &Path) -> String {
         .to_string()
 }
 
-pub fn review_root_dir(app: &tauri::AppHandle, project_path: &Path) -> Result<PathBuf> {
+pub fn review_root_dir(app: &AppContext, project_path: &Path) -> Result<PathBuf> {
     let project = project_slug(project_path);
-    app.path()
-        .resolve(format!("synk/reviews/{project}"), BaseDirectory::Config)
+    app.config_path(format!("reviews/{project}"))
         .context("resolve reviews dir")
 }
 
 fn review_paths(
-    app: &tauri::AppHandle,
+    app: &AppContext,
     project_path: &Path,
     id: &str,
 ) -> Result<(PathBuf, PathBuf, PathBuf)> {
@@ -105,15 +119,21 @@ fn review_paths(
     ))
 }
 
-fn ensure_review_dirs(app: &tauri::AppHandle, project_path: &Path) -> Result<()> {
+fn preview_path(app: &AppContext, project_path: &Path, id: &str) -> Result<PathBuf> {
+    let root = review_root_dir(app, project_path)?;
+    Ok(root.join("previews").join(format!("{id}.json")))
+}
+
+fn ensure_review_dirs(app: &AppContext, project_path: &Path) -> Result<()> {
     let root = review_root_dir(app, project_path)?;
     fs::create_dir_all(root.join("reviews")).context("create reviews/ dir")?;
     fs::create_dir_all(root.join("comments")).context("create comments/ dir")?;
     fs::create_dir_all(root.join("diffs")).context("create diffs/ dir")?;
+    fs::create_dir_all(root.join("previews")).context("create previews/ dir")?;
     Ok(())
 }
 
-fn compute_stats(files: &[FileDiff]) -> (u32, u32, u32) {
+pub fn compute_stats(files: &[FileDiff]) -> (u32, u32, u32) {
     let files_changed = files.len() as u32;
     let mut additions = 0u32;
     let mut deletions = 0u32;
@@ -141,10 +161,11 @@ fn new_id() -> String {
 }
 
 pub fn review_create(
-    app: &tauri::AppHandle,
+    app: &AppContext,
     gm: &GitManager,
     project_path: &Path,
     session_id: usize,
+    session_labels: Option<SessionLabels>,
     branch: &str,
     base_branch: &str,
 ) -> Result<ReviewItem> {
@@ -159,6 +180,7 @@ pub fn review_create(
         id: id.clone(),
         task_id: None,
         session_id,
+        session_labels: session_labels.filter(|l| !l.is_empty()),
         branch: branch.to_string(),
         base_branch: base_branch.to_string(),
         status: ReviewStatus::Pending,
@@ -171,17 +193,115 @@ pub fn review_create(
         comments: Vec::new(),
         review_decision: None,
         merge_strategy: None,
+        merge_preview: gm.merge_preview(branch, base_branch).ok(),
     };
 
     review_save(app, project_path, &item)?;
+    if let Some(preview) = &item.merge_preview {
+        save_merge_preview(app, project_path, &item.id, preview)?;
+    }
+    publish(
+        app,
+        AppEvent::Review(ReviewEvent::new(
+            ReviewEventKind::Created,
+            project_path,
+            &item,
+        )),
+    );
     Ok(item)
 }
 
-pub fn review_save(app: &tauri::AppHandle, project_path: &Path, item: &ReviewItem) -> Result<()> {
+/// A fresh merge preview for `item` if either branch moved since its current one was taken;
+/// `None` when it is still current or the review is already merged.
+pub fn stale_merge_preview(gm: &GitManager, item: &ReviewItem) -> Result<Option<MergePreview>> {
+    if item.status == ReviewStatus::Merged {
+        return Ok(None);
+    }
+    if let Some(p) = &item.merge_preview {
+        if gm.resolve_commit(&item.branch)? == p.branch_commit
+            && gm.resolve_commit(&item.base_branch)? == p.base_commit
+        {
+            return Ok(None);
+        }
+    }
+    Ok(Some(gm.merge_preview(&item.branch, &item.base_branch)?))
+}
+
+/// Writes only the preview file, so concurrent status or comment updates are never lost.
+pub fn save_merge_preview(
+    app: &AppContext,
+    project_path: &Path,
+    id: &str,
+    preview: &MergePreview,
+) -> Result<()> {
+    ensure_review_dirs(app, project_path)?;
+    let path = preview_path(app, project_path, id)?;
+    let text = serde_json::to_string_pretty(preview).context("serialize MergePreview")?;
+    fs::write(&path, format!("{text}\n")).with_context(|| format!("write {}", path.display()))
+}
+
+/// Recomputes the merge previews of `project_path`'s reviews whose branches moved and
+/// publishes a review event for each. A branch that no longer resolves (e.g. deleted after a
+/// manual merge) keeps its last preview; a preview that fails to save doesn't stop the rest.
+pub fn refresh_merge_previews(app: &AppContext, project_path: &Path) -> Result<()> {
+    let gm = GitManager::for_project(app, project_path)?;
+    for item in review_list(app, project_path)? {
+        let Ok(Some(preview)) = stale_merge_preview(&gm, &item) else {
+            continue;
+        };
+        if let Err(err) = save_merge_preview(app, project_path, &item.id, &preview) {
+            eprintln!("review: merge preview {}: {err:#}", item.id);
+            continue;
+        }
+        publish(
+            app,
+            AppEvent::Review(ReviewEvent::new(
+                ReviewEventKind::MergePreviewChanged,
+                project_path,
+                &item,
+            )),
+        );
+    }
+    Ok(())
+}
+
+/// Refreshes merge previews whenever a project's refs change, so reads never have to.
+pub fn start_merge_preview_refresh(app: AppContext) {
+    let Some(bus) = app.try_state::<SharedEventBus>() else {
+        return;
+    };
+    let (tx, rx) = mpsc::channel::<String>();
+    bus.lock()
+        .expect("event bus mutex poisoned")
+        .subscribe(&[GIT_EVENT_NAME], move |event| {
+            if let AppEvent::Git(e) = event {
+                let _ = tx.send(e.project_path.clone());
+            }
+        });
+    std::thread::spawn(move || {
+        while let Ok(project) = rx.recv() {
+            // One refresh covers a burst of events for the same project.
+            let mut projects = BTreeSet::from([project]);
+            projects.extend(rx.try_iter());
+            for project in projects {
+                if let Err(err) = refresh_merge_previews(&app, Path::new(&project)) {
+                    eprintln!("review: merge preview {project}: {err:#}");
+                }
+            }
+        }
+    });
+}
+
+pub fn review_save(app: &AppContext, project_path: &Path, item: &ReviewItem) -> Result<()> {
     ensure_review_dirs(app, project_path)?;
     let (review_path, comments_path, _) = review_paths(app, project_path, &item.id)?;
 
-    let text = serde_json::to_string_pretty(item).context("serialize ReviewItem")?;
+    // The merge preview has its own file.
+    let stored = ReviewItem {
+        merge_preview: None,
+        ..item.clone()
+    };
+    let text = serde_json::to_string_pretty(&stored).context("serialize ReviewItem")?;
     fs::write(&review_path, format!("{text}\n"))
         .with_context(|| format!("write {}", review_path.display()))?;
 
@@ -194,7 +314,29 @@ pub fn review_save(app: &tauri::AppHandle, project_path: &Path, item: &ReviewIte
     Ok(())
 }
 
-pub fn review_get(app: &tauri::AppHandle, project_path: &Path, id: &str) -> Result<ReviewItem> {
+/// Sets `status` on a stored review and announces the change.
+pub fn review_set_status(
+    app: &AppContext,
+    project_path: &Path,
+    id: &str,
+    status: ReviewStatus,
+) -> Result<ReviewItem> {
+    let mut item = review_get(app, project_path, id)?;
+    item.status = status;
+    item.updated_at = now_rfc3339()?;
+    review_save(app, project_path, &item)?;
+    publish(
+        app,
+        AppEvent::Review(ReviewEvent::new(
+            ReviewEventKind::StatusChanged,
+            project_path,
+            &item,
+        )),
+    );
+    Ok(item)
+}
+
+pub fn review_get(app: &AppContext, project_path: &Path, id: &str) -> Result<ReviewItem> {
     let (review_path, comments_path, _) = review_paths(app, project_path, id)?;
     let text = fs::read_to_string(&review_path)
         .with_context(|| format!("read {}", review_path.display()))?;
@@ -205,11 +347,23 @@ pub fn review_get(app: &tauri::AppHandle, project_path: &Path, id: &str) -> Resu
             item.comments = comments;
         }
     }
+    load_merge_preview(app, project_path, &mut item);
 
     Ok(item)
 }
 
-pub fn review_list(app: &tauri::AppHandle, project_path: &Path) -> Result<Vec<ReviewItem>> {
+fn load_merge_preview(app: &AppContext, project_path: &Path, item: &mut ReviewItem) {
+    let Ok(path) = preview_path(app, project_path, &item.id) else {
+        return;
+    };
+    if let Ok(text) = fs::read_to_string(&path) {
+        if let Ok(preview) = serde_json::from_str::<MergePreview>(&text) {
+            item.merge_preview = Some(preview);
+        }
+    }
+}
+
+pub fn review_list(app: &AppContext, project_path: &Path) -> Result<Vec<ReviewItem>> {
     ensure_review_dirs(app, project_path)?;
     let root = review_root_dir(app, project_path)?;
     let reviews_dir = root.join("reviews");
@@ -230,10 +384,11 @@ pub fn review_list(app: &tauri::AppHandle, project_path: &Path) -> Result<Vec<Re
             Ok(v) => v,
             Err(_) => continue,
         };
-        let item: ReviewItem = match serde_json::from_str(&text) {
+        let mut item: ReviewItem = match serde_json::from_str(&text) {
             Ok(v) => v,
             Err(_) => continue,
         };
+        load_merge_preview(app, project_path, &mut item);
         out.push(item);
     }
 
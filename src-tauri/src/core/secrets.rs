@@ -0,0 +1,460 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+
+use crate::core::app_context::AppContext;
+
+// -----------------------------------------------------------------------------
+// Encrypted-at-rest store for provider API keys and other credentials.
+//
+// `synk/secrets.json` holds ChaCha20-Poly1305 sealed values. The key is either a random
+// local key file (`synk/secrets.key`, mode 0600) or, when the store was created with
+// `SYNK_SECRETS_PASSPHRASE` set, derived from that passphrase with Argon2id.
+// -----------------------------------------------------------------------------
+
+/// Env var holding the passphrase for passphrase-protected stores.
+pub const PASSPHRASE_ENV: &str = "SYNK_SECRETS_PASSPHRASE";
+
+/// Prefix for values (e.g. MCP server env in config files) that refer to a stored secret.
+pub const SECRET_REF_PREFIX: &str = "synk-secret:";
+
+/// What masked values start with; a settings value starting with this means "unchanged".
+pub const MASK_PREFIX: &str = "••••••••";
+
+const CHECK_NAME: &str = "__synk_check__";
+const CHECK_VALUE: &[u8] = b"synk";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum KdfDisk {
+    #[default]
+    KeyFile,
+    Argon2id,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct SealedDisk {
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+struct SecretsFileDisk {
+    version: u32,
+    kdf: KdfDisk,
+    salt: Option<String>,
+    check: Option<SealedDisk>,
+    entries: BTreeMap<String, SealedDisk>,
+}
+
+pub struct SecretStore {
+    path: PathBuf,
+    file: SecretsFileDisk,
+    cipher: ChaCha20Poly1305,
+}
+
+impl SecretStore {
+    pub fn open(app: &AppContext) -> Result<Self> {
+        let dir = app.config_dir().context("resolve config dir for secrets")?;
+        let passphrase = std::env::var(PASSPHRASE_ENV).ok().filter(|v| !v.is_empty());
+        Self::open_in(&dir, passphrase.as_deref())
+    }
+
+    pub fn open_in(dir: &Path, passphrase: Option<&str>) -> Result<Self> {
+        // Opening may create the key file and the check value.
+        let _writing = write_lock();
+        fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        let path = dir.join("secrets.json");
+        let existing = read_file(&path)?;
+
+        let mut file = existing.unwrap_or_else(|| SecretsFileDisk {
+            version: 1,
+            kdf: if passphrase.is_some() {
+                KdfDisk::Argon2id
+            } else {
+                KdfDisk::KeyFile
+            },
+            ..Default::default()
+        });
+
+        let key = match file.kdf {
+            KdfDisk::KeyFile => load_or_create_key_file(&dir.join("secrets.key"))?,
+            KdfDisk::Argon2id => {
+                let passphrase = passphrase.ok_or_else(|| {
+                    anyhow!("secrets store is passphrase-protected; set {PASSPHRASE_ENV}")
+                })?;
+                if file.salt.is_none() {
+                    let salt: [u8; 16] = random_bytes();
+                    file.salt = Some(STANDARD.encode(salt));
+                }
+                derive_key(passphrase, file.salt.as_deref().unwrap_or_default())?
+            }
+        };
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+
+        let mut store = Self { path, file, cipher };
+        match &store.file.check {
+            Some(check) => {
+                let ok = store.open_sealed(CHECK_NAME, check).ok();
+                if ok.as_deref() != Some(CHECK_VALUE) {
+                    return Err(anyhow!(
+                        "cannot unlock secrets store (wrong passphrase or key file)"
+                    ));
+                }
+            }
+            None => {
+                store.file.check = Some(store.seal(CHECK_NAME, CHECK_VALUE)?);
+                store.save()?;
+            }
+        }
+        Ok(store)
+    }
+
+    pub fn get(&self, name: &str) -> Result<Option<String>> {
+        let Some(sealed) = self.file.entries.get(name) else {
+            return Ok(None);
+        };
+        let plain = self.open_sealed(name, sealed)?;
+        String::from_utf8(plain)
+            .map(Some)
+            .map_err(|_| anyhow!("secret {name} is not valid UTF-8"))
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        let sealed = self.seal(name, value.as_bytes())?;
+        self.update(|entries| {
+            entries.insert(name.to_string(), sealed);
+            true
+        })?;
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<bool> {
+        self.update(|entries| entries.remove(name).is_some())
+    }
+
+    /// Applies `change` to the entries as they are on disk now, and saves if it made one. Other
+    /// handles may have written since this one was opened; re-reading under the write lock
+    /// keeps their entries.
+    fn update(
+        &mut self,
+        change: impl FnOnce(&mut BTreeMap<String, SealedDisk>) -> bool,
+    ) -> Result<bool> {
+        let _writing = write_lock();
+        if let Some(current) = read_file(&self.path)? {
+            self.file.entries = current.entries;
+        }
+        let changed = change(&mut self.file.entries);
+        if changed {
+            self.save()?;
+        }
+        Ok(changed)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.file.entries.keys().cloned().collect()
+    }
+
+    fn seal(&self, name: &str, plain: &[u8]) -> Result<SealedDisk> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        // The entry name is bound as associated data so values can't be swapped between names.
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plain,
+                    aad: name.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow!("encrypt secret {name}"))?;
+        Ok(SealedDisk {
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        })
+    }
+
+    fn open_sealed(&self, name: &str, sealed: &SealedDisk) -> Result<Vec<u8>> {
+        let nonce = STANDARD
+            .decode(&sealed.nonce)
+            .context("decode secret nonce")?;
+        if nonce.len() != 12 {
+            return Err(anyhow!("secret {name} has a malformed nonce"));
+        }
+        let ciphertext = STANDARD
+            .decode(&sealed.ciphertext)
+            .context("decode secret ciphertext")?;
+        self.cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: name.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow!("decrypt secret {name}"))
+    }
+
+    fn save(&self) -> Result<()> {
+        let text = serde_json::to_string_pretty(&self.file).context("serialize secrets.json")?;
+        write_private(&self.path, format!("{text}\n").as_bytes())
+    }
+}
+
+/// Serializes store writes within the process.
+fn write_lock() -> MutexGuard<'static, ()> {
+    static WRITES: Mutex<()> = Mutex::new(());
+    WRITES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn read_file(path: &Path) -> Result<Option<SecretsFileDisk>> {
+    match fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map(Some)
+            .with_context(|| format!("parse {}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("read {}", path.display())),
+    }
+}
+
+pub(crate) fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut out = [0u8; N];
+    OsRng.fill_bytes(&mut out);
+    out
+}
+
+/// 48 hex characters, for bearer tokens.
+pub(crate) fn random_token() -> String {
+    random_bytes::<24>()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+pub(crate) fn token_matches(given: &str, expected: &str) -> bool {
+    // Constant-time so a token can't be guessed byte by byte.
+    !expected.is_empty()
+        && given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+fn load_or_create_key_file(path: &Path) -> Result<[u8; 32]> {
+    match fs::read(path) {
+        Ok(bytes) => bytes
+            .try_into()
+            .map_err(|_| anyhow!("{} is not a 32-byte key", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let key: [u8; 32] = random_bytes();
+            write_private(path, &key)?;
+            Ok(key)
+        }
+        Err(e) => Err(e).with_context(|| format!("read {}", path.display())),
+    }
+}
+
+type DerivedKeyCache = Mutex<HashMap<(String, String), [u8; 32]>>;
+
+/// Argon2id is deliberately slow, so derived keys are cached per (salt, passphrase).
+fn derive_key(passphrase: &str, salt_b64: &str) -> Result<[u8; 32]> {
+    static CACHE: OnceLock<DerivedKeyCache> = OnceLock::new();
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    let cache_key = (salt_b64.to_string(), passphrase.to_string());
+    if let Some(k) = cache.lock().ok().and_then(|c| c.get(&cache_key).copied()) {
+        return Ok(k);
+    }
+
+    let salt = STANDARD.decode(salt_b64).context("decode secrets salt")?;
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| anyhow!("derive secrets key: {e}"))?;
+    if let Ok(mut c) = cache.lock() {
+        c.insert(cache_key, key);
+    }
+    Ok(key)
+}
+
+/// Replaces `path` atomically with a mode-0600 file. The temp name is unique so concurrent
+/// writers never share one.
+pub(crate) fn write_private(path: &Path, bytes: &[u8]) -> Result<()> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = path.with_file_name(format!(".{name}.{}.tmp", random_token()));
+    let mut opts = fs::OpenOptions::new();
+    opts.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+    }
+    let mut f = opts
+        .open(&tmp)
+        .with_context(|| format!("open {}", tmp.display()))?;
+    f.write_all(bytes)
+        .with_context(|| format!("write {}", tmp.display()))?;
+    drop(f);
+    fs::rename(&tmp, path)
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&tmp);
+        })
+        .with_context(|| format!("replace {}", path.display()))
+}
+
+/// Masked form shown to the frontend: a fixed prefix plus the last four characters.
+pub fn mask(value: &str) -> String {
+    let chars: Vec<char> = value.trim().chars().collect();
+    if chars.len() < 12 {
+        return MASK_PREFIX.to_string();
+    }
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{MASK_PREFIX}{tail}")
+}
+
+pub fn is_masked(value: &str) -> bool {
+    value.trim().starts_with(MASK_PREFIX)
+}
+
+pub fn has_refs(env: &HashMap<String, String>) -> bool {
+    env.values().any(|v| v.starts_with(SECRET_REF_PREFIX))
+}
+
+/// Replaces `synk-secret:<name>` values with the stored secret.
+pub fn resolve_refs(
+    store: &SecretStore,
+    env: &HashMap<String, String>,
+) -> Result<HashMap<String, String>> {
+    let mut out = HashMap::with_capacity(env.len());
+    for (k, v) in env {
+        let value = match v.strip_prefix(SECRET_REF_PREFIX) {
+            Some(name) => store
+                .get(name.trim())?
+                .ok_or_else(|| anyhow!("{k} refers to unknown secret {}", name.trim()))?,
+            None => v.clone(),
+        };
+        out.insert(k.clone(), value);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{has_refs, is_masked, mask, resolve_refs, SecretStore, SECRET_REF_PREFIX};
+    use crate::core::test_fixtures::TempDir;
+    use std::collections::HashMap;
+
+    #[test]
+    fn key_file_store_round_trips_and_encrypts() {
+        let dir = TempDir::new("secrets-keyfile");
+        let mut store = SecretStore::open_in(&dir, None).unwrap();
+        store
+            .set("provider.openai.api_key", "sk-live-123456789")
+            .unwrap();
+
+        let text = std::fs::read_to_string(dir.join("secrets.json")).unwrap();
+        assert!(!text.contains("sk-live"));
+
+        let reopened = SecretStore::open_in(&dir, None).unwrap();
+        assert_eq!(
+            reopened.get("provider.openai.api_key").unwrap().as_deref(),
+            Some("sk-live-123456789")
+        );
+        assert_eq!(reopened.get("missing").unwrap(), None);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(dir.join("secrets.key"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn passphrase_store_rejects_wrong_passphrase() {
+        let dir = TempDir::new("secrets-pass");
+        let mut store = SecretStore::open_in(&dir, Some("correct horse")).unwrap();
+        store.set("a", "value").unwrap();
+        assert!(!dir.join("secrets.key").exists());
+
+        assert!(SecretStore::open_in(&dir, Some("wrong")).is_err());
+        assert!(SecretStore::open_in(&dir, None).is_err());
+        let store = SecretStore::open_in(&dir, Some("correct horse")).unwrap();
+        assert_eq!(store.get("a").unwrap().as_deref(), Some("value"));
+    }
+
+    #[test]
+    fn concurrent_handles_keep_each_others_entries() {
+        let dir = TempDir::new("secrets-concurrent");
+        SecretStore::open_in(&dir, None).unwrap();
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let dir = dir.to_path_buf();
+                std::thread::spawn(move || {
+                    let mut store = SecretStore::open_in(&dir, None).unwrap();
+                    store
+                        .set(&format!("name{i}"), &format!("value{i}"))
+                        .unwrap();
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let store = SecretStore::open_in(&dir, None).unwrap();
+        for i in 0..8 {
+            assert_eq!(
+                store.get(&format!("name{i}")).unwrap(),
+                Some(format!("value{i}"))
+            );
+        }
+        let leftovers = std::fs::read_dir(&*dir)
+            .unwrap()
+            .filter(|e| {
+                e.as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .ends_with(".tmp")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn masks_and_resolves_refs() {
+        assert_eq!(mask("sk-abcdefghijkl1234"), "••••••••1234");
+        assert_eq!(mask("short"), "••••••••");
+        assert!(is_masked(&mask("sk-abcdefghijkl1234")));
+        assert!(!is_masked("sk-new"));
+
+        let dir = TempDir::new("secrets-refs");
+        let mut store = SecretStore::open_in(&dir, None).unwrap();
+        store.set("github", "ghp_secret").unwrap();
+        let env = HashMap::from([
+            ("TOKEN".to_string(), format!("{SECRET_REF_PREFIX}github")),
+            ("PLAIN".to_string(), "x".to_string()),
+        ]);
+        assert!(has_refs(&env));
+        let resolved = resolve_refs(&store, &env).unwrap();
+        assert_eq!(resolved["TOKEN"], "ghp_secret");
+        assert_eq!(resolved["PLAIN"], "x");
+
+        let bad = HashMap::from([("T".to_string(), format!("{SECRET_REF_PREFIX}nope"))]);
+        assert!(resolve_refs(&store, &bad).is_err());
+    }
+}
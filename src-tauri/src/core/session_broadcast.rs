@@ -0,0 +1,178 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::core::session_manager::SessionManager;
+use crate::events::now_rfc3339;
+
+pub const SESSION_BROADCAST_EVENT: &str = "session:broadcast";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcastArgs {
+    /// `@all`, `@idle`, `@active`, `@agent:<type>`, `@tag:<tag>`, `@group:<group>`, `@name`,
+    /// session ids, or a comma-separated mix of them.
+    pub target: String,
+    pub data: String,
+    /// Only sessions in this project are targeted when set.
+    #[serde(default)]
+    pub project_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcastDelivery {
+    pub session_id: usize,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcome of one broadcast. `session_broadcast` records it in the audit log (target and
+/// size, not the data) and emits it as `session:broadcast` for the UI.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcastResult {
+    pub target: String,
+    pub timestamp: String,
+    pub bytes: usize,
+    pub deliveries: Vec<BroadcastDelivery>,
+}
+
+/// Writes `data` to each session in turn. A failed write is reported for that session and
+/// does not stop the others.
+pub fn write_each(
+    manager: &mut SessionManager,
+    ids: Vec<usize>,
+    data: &str,
+) -> Vec<BroadcastDelivery> {
+    ids.into_iter()
+        .map(|session_id| match manager.write(session_id, data) {
+            Ok(()) => BroadcastDelivery {
+                session_id,
+                ok: true,
+                error: None,
+            },
+            Err(err) => BroadcastDelivery {
+                session_id,
+                ok: false,
+                error: Some(format!("{err:#}")),
+            },
+        })
+        .collect()
+}
+
+/// Writes `args.data` to every session matching `args.target`. The caller holds the manager
+/// lock throughout, so no other write can interleave with the broadcast.
+pub fn broadcast(manager: &mut SessionManager, args: &BroadcastArgs) -> Result<BroadcastResult> {
+    if args.data.is_empty() {
+        return Err(anyhow!("nothing to broadcast"));
+    }
+    let ids = manager.resolve_targets(&args.target, args.project_path.as_deref())?;
+    let deliveries = write_each(manager, ids, &args.data);
+    Ok(BroadcastResult {
+        target: args.target.trim().to_string(),
+        timestamp: now_rfc3339(),
+        bytes: args.data.len(),
+        deliveries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use super::{broadcast, write_each, BroadcastArgs};
+    use crate::core::agent_detection::{AgentRegistry, AgentType};
+    use crate::core::app_context::AppContext;
+    use crate::core::process_pool::{PoolConfig, ProcessPool};
+    use crate::core::session_labels::SessionLabels;
+    use crate::core::session_manager::{CreateSessionArgs, SessionManager};
+    use crate::core::test_fixtures::TempDir;
+
+    fn empty_manager() -> SessionManager {
+        SessionManager::new(
+            Arc::new(Mutex::new(ProcessPool::new(PoolConfig::default()))),
+            Arc::new(Mutex::new(AgentRegistry::detect())),
+        )
+    }
+
+    fn terminal(
+        app: &AppContext,
+        manager: &mut SessionManager,
+        project: &Path,
+        tag: &str,
+    ) -> usize {
+        manager
+            .create_session(
+                app.clone(),
+                CreateSessionArgs {
+                    agent_type: AgentType::Terminal,
+                    project_path: project.to_string_lossy().to_string(),
+                    branch: None,
+                    working_dir: None,
+                    model: None,
+                    codex_provider: None,
+                    provider_profile: None,
+                    env: None,
+                    labels: SessionLabels {
+                        tags: vec![tag.to_string()],
+                        ..Default::default()
+                    },
+                    initial_prompt: None,
+                },
+            )
+            .unwrap()
+            .session_id
+    }
+
+    #[test]
+    fn every_target_is_attempted() {
+        let mut manager = empty_manager();
+        let deliveries = write_each(&mut manager, vec![3, 5], "hi\r");
+        let ids: Vec<usize> = deliveries.iter().map(|d| d.session_id).collect();
+        assert_eq!(ids, vec![3, 5]);
+        assert!(deliveries
+            .iter()
+            .all(|d| !d.ok && d.error.as_deref().is_some_and(|e| e.contains("unknown"))));
+    }
+
+    #[test]
+    fn broadcasts_to_matching_sessions_and_typing_keeps_them_idle() {
+        let tmp = TempDir::new("broadcast");
+        let project = tmp.join("project");
+        std::fs::create_dir_all(&project).unwrap();
+        let app = AppContext::headless(tmp.join("config"));
+        let mut manager = empty_manager();
+        let a = terminal(&app, &mut manager, &project, "api");
+        let b = terminal(&app, &mut manager, &project, "web");
+
+        let args = |target: &str, data: &str| BroadcastArgs {
+            target: target.to_string(),
+            data: data.to_string(),
+            project_path: None,
+        };
+        let result = broadcast(&mut manager, &args("@tag:api", "true\r")).unwrap();
+        assert_eq!(result.target, "@tag:api");
+        assert_eq!(result.bytes, 5);
+        assert_eq!(result.deliveries.len(), 1);
+        assert!(result.deliveries[0].session_id == a && result.deliveries[0].ok);
+        assert!(broadcast(&mut manager, &args("@all", "")).is_err());
+
+        // The shell echoing unsubmitted typing is not a turn.
+        let typed = broadcast(&mut manager, &args(&b.to_string(), "abc")).unwrap();
+        assert!(typed.deliveries[0].ok);
+        std::thread::sleep(Duration::from_millis(500));
+        assert!(manager.resolve_targets("@idle", None).unwrap().contains(&b));
+
+        // Shells ignore SIGTERM, so let them exit rather than wait out the kill timeout.
+        let all = broadcast(&mut manager, &args("@all", "\x15exit\r")).unwrap();
+        let mut ids: Vec<usize> = all.deliveries.iter().map(|d| d.session_id).collect();
+        ids.sort();
+        assert_eq!(ids, vec![a, b]);
+        assert!(all.deliveries.iter().all(|d| d.ok));
+
+        manager.shutdown();
+    }
+}
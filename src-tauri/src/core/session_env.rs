@@ -0,0 +1,278 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::core::process_pool::PtyHandle;
+
+/// Replacement for secret values in session output.
+pub const REDACTED: &[u8] = b"[redacted]";
+
+/// Values shorter than this are too likely to collide with ordinary output to redact.
+const MIN_SECRET_LEN: usize = 6;
+
+/// Backstop removal for env files the shell never got to source (e.g. it died first).
+const ENV_FILE_TTL: Duration = Duration::from_secs(30);
+
+pub fn is_valid_env_var_name(name: &str) -> bool {
+    let mut it = name.chars();
+    let Some(first) = it.next() else {
+        return false;
+    };
+    if !(first.is_ascii_alphabetic() || first == '_') {
+        return false;
+    }
+    it.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Whether a user-supplied env var name looks like it carries a credential.
+pub fn looks_secret(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    [
+        "KEY",
+        "TOKEN",
+        "SECRET",
+        "PASSWORD",
+        "PASSWD",
+        "CREDENTIAL",
+        "AUTH",
+    ]
+    .iter()
+    .any(|needle| upper.contains(needle))
+}
+
+/// Escapes `s` for use inside a bash single-quoted string: `'` becomes `'\''`.
+pub fn shell_single_quote_escape(s: &str) -> String {
+    s.replace('\'', "'\\''")
+}
+
+/// Environment for a session shell, delivered through a private file instead of being typed
+/// into the PTY, so values never show up in the terminal echo, scrollback or shell history.
+#[derive(Debug, Default)]
+pub struct SessionEnv {
+    script: String,
+    secrets: Vec<String>,
+}
+
+impl SessionEnv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `None` unsets the variable.
+    pub fn set(&mut self, key: &str, value: Option<&str>) -> Result<()> {
+        if !is_valid_env_var_name(key) {
+            return Err(anyhow!("invalid env var name: {key}"));
+        }
+        match value {
+            Some(v) => self.script.push_str(&format!(
+                "export {}='{}'\n",
+                key,
+                shell_single_quote_escape(v)
+            )),
+            None => self.script.push_str(&format!("unset {key}\n")),
+        }
+        Ok(())
+    }
+
+    /// Like `set`, and redacts the value from the session's output.
+    pub fn set_secret(&mut self, key: &str, value: Option<&str>) -> Result<()> {
+        self.set(key, value)?;
+        if let Some(v) = value {
+            self.add_secret(v);
+        }
+        Ok(())
+    }
+
+    /// Appends a shell line verbatim (for cleanup logic that isn't a plain assignment).
+    pub fn raw(&mut self, line: &str) {
+        self.script.push_str(line);
+        self.script.push('\n');
+    }
+
+    pub fn add_secret(&mut self, value: &str) {
+        let v = value.trim();
+        if v.len() >= MIN_SECRET_LEN && !self.secrets.iter().any(|s| s == v) {
+            self.secrets.push(v.to_string());
+        }
+    }
+
+    pub fn secrets(&self) -> &[String] {
+        &self.secrets
+    }
+
+    #[cfg(test)]
+    fn script(&self) -> &str {
+        &self.script
+    }
+
+    /// Writes the env to a mode-0600 file that deletes itself once sourced, and sources it.
+    /// Only the file path is typed into the shell.
+    pub fn deliver(&self, handle: &mut PtyHandle, session_id: usize) -> Result<()> {
+        if self.script.is_empty() {
+            return Ok(());
+        }
+        let path = write_env_file(&std::env::temp_dir(), session_id, &self.script)?;
+        let quoted = shell_single_quote_escape(&path.to_string_lossy());
+        // Leading space keeps the line out of history for HISTCONTROL=ignorespace shells.
+        let res = handle.write_str(&format!(" . '{quoted}'\r\n"));
+
+        std::thread::spawn(move || {
+            std::thread::sleep(ENV_FILE_TTL);
+            let _ = fs::remove_file(&path);
+        });
+        res
+    }
+}
+
+fn write_env_file(dir: &Path, session_id: usize, script: &str) -> Result<PathBuf> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let path = dir.join(format!(
+        "synk-env-{}-{session_id}-{nanos}.sh",
+        std::process::id()
+    ));
+
+    let mut opts = fs::OpenOptions::new();
+    opts.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+    }
+    let mut file = opts
+        .open(&path)
+        .with_context(|| format!("create env file {}", path.display()))?;
+
+    let quoted = shell_single_quote_escape(&path.to_string_lossy());
+    let body = format!("{script}rm -f -- '{quoted}'\n");
+    file.write_all(body.as_bytes())
+        .with_context(|| format!("write env file {}", path.display()))?;
+    Ok(path)
+}
+
+/// Streaming replacement of known secret values. Output that could be the start of a secret
+/// is held back until the next chunk (or `flush`) decides it.
+#[derive(Debug, Default)]
+pub struct SecretRedactor {
+    secrets: Vec<Vec<u8>>,
+    pending: Vec<u8>,
+}
+
+impl SecretRedactor {
+    pub fn new(secrets: &[String]) -> Self {
+        Self {
+            secrets: secrets
+                .iter()
+                .filter(|s| s.len() >= MIN_SECRET_LEN)
+                .map(|s| s.as_bytes().to_vec())
+                .collect(),
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn feed(&mut self, input: &[u8], out: &mut Vec<u8>) {
+        if self.secrets.is_empty() {
+            out.extend_from_slice(input);
+            return;
+        }
+        self.pending.extend_from_slice(input);
+
+        let buf = std::mem::take(&mut self.pending);
+        let mut i = 0;
+        'scan: while i < buf.len() {
+            let rest = &buf[i..];
+            for secret in &self.secrets {
+                if rest.starts_with(secret) {
+                    out.extend_from_slice(REDACTED);
+                    i += secret.len();
+                    continue 'scan;
+                }
+            }
+            if self
+                .secrets
+                .iter()
+                .any(|s| rest.len() < s.len() && s.starts_with(rest))
+            {
+                self.pending = rest.to_vec();
+                return;
+            }
+            out.push(buf[i]);
+            i += 1;
+        }
+    }
+
+    /// Releases held-back bytes as-is; called when the stream goes idle.
+    pub fn flush(&mut self, out: &mut Vec<u8>) {
+        out.append(&mut self.pending);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{looks_secret, write_env_file, SecretRedactor, SessionEnv, REDACTED};
+
+    fn redact(secrets: &[&str], chunks: &[&str]) -> String {
+        let secrets: Vec<String> = secrets.iter().map(|s| s.to_string()).collect();
+        let mut r = SecretRedactor::new(&secrets);
+        let mut out = Vec::new();
+        for c in chunks {
+            r.feed(c.as_bytes(), &mut out);
+        }
+        r.flush(&mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn redacts_secret_split_across_chunks() {
+        let r = String::from_utf8(REDACTED.to_vec()).unwrap();
+        assert_eq!(
+            redact(&["sk-abc123"], &["key=sk-a", "bc123 done"]),
+            format!("key={r} done")
+        );
+        assert_eq!(
+            redact(&["sk-abc123"], &["sk-abc1", "xx"]),
+            "sk-abc1xx".to_string()
+        );
+        assert_eq!(redact(&[], &["sk-abc123"]), "sk-abc123");
+        // Too short to redact safely.
+        assert_eq!(redact(&["abc"], &["abc"]), "abc");
+    }
+
+    #[test]
+    fn env_script_quotes_and_tracks_secrets() {
+        let mut env = SessionEnv::new();
+        env.set("PLAIN", Some("it's")).unwrap();
+        env.set_secret("OPENAI_API_KEY", Some("sk-secret-1"))
+            .unwrap();
+        env.set("GONE", None).unwrap();
+        assert!(env.set("BAD-NAME", Some("x")).is_err());
+
+        assert_eq!(
+            env.script(),
+            "export PLAIN='it'\\''s'\nexport OPENAI_API_KEY='sk-secret-1'\nunset GONE\n"
+        );
+        assert_eq!(env.secrets(), ["sk-secret-1".to_string()]);
+        assert!(looks_secret("GITHUB_TOKEN"));
+        assert!(!looks_secret("RUST_LOG"));
+    }
+
+    #[test]
+    fn env_file_is_private_and_self_deleting() {
+        let dir = std::env::temp_dir();
+        let path = write_env_file(&dir, 7, "export A='1'\n").unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("export A='1'\nrm -f -- '"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,127 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::core::app_context::AppContext;
+use crate::core::git_manager::GitManager;
+use crate::core::persistence::{project_session_config_get, project_session_config_set};
+use crate::core::session_manager::{CreateSessionResponse, SharedSessionManager};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkSessionArgs {
+    pub session_id: usize,
+    /// New branch name; defaults to `<source branch>-fork` (suffixed until unused).
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// Overrides the source session's model for the fork.
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkSessionResponse {
+    #[serde(flatten)]
+    pub session: CreateSessionResponse,
+    pub forked_from: usize,
+    pub branch: String,
+    pub worktree_path: String,
+    /// Commit the fork's branch starts at (the source session's HEAD).
+    pub base_commit: String,
+}
+
+/// Launches a sibling of `args.session_id` with the same agent, model, provider, env, skills
+/// and MCP servers, on a new branch and worktree created from the source session's HEAD.
+/// The worktree is created before the session manager is locked to start the fork.
+pub fn fork_session(
+    sessions: &SharedSessionManager,
+    app: &AppContext,
+    args: ForkSessionArgs,
+) -> Result<ForkSessionResponse> {
+    let (source, mut create) = {
+        let manager = sessions.lock().expect("session manager mutex poisoned");
+        let source = manager
+            .get_session_info(args.session_id)
+            .ok_or_else(|| anyhow!("unknown session_id {}", args.session_id))?;
+        (source, manager.clone_args(args.session_id)?)
+    };
+
+    let project_path = PathBuf::from(&source.project_path);
+    let source_dir = source
+        .working_dir
+        .as_deref()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| project_path.clone());
+    let base_commit = GitManager::head_commit(&source_dir)
+        .with_context(|| format!("fork session {}", args.session_id))?;
+
+    let gm = GitManager::for_project(app, &project_path)?;
+    let branch = match args
+        .branch
+        .as_deref()
+        .map(str::trim)
+        .filter(|b| !b.is_empty())
+    {
+        Some(b) => {
+            let b = gm.normalize_branch(b)?;
+            if gm.unused_branch_name(&b)? != b {
+                return Err(anyhow!("branch {b} already exists"));
+            }
+            b
+        }
+        None => {
+            let stem = source
+                .branch
+                .clone()
+                .unwrap_or_else(|| format!("session-{}", args.session_id));
+            gm.unused_branch_name(&format!("{stem}-fork"))?
+        }
+    };
+    let (worktree, branch) = gm.create_worktree(&branch, &base_commit)?;
+    let worktree_path = worktree.to_string_lossy().to_string();
+
+    create.branch = Some(branch.clone());
+    create.working_dir = Some(worktree_path.clone());
+    if let Some(model) = args.model.filter(|m| !m.trim().is_empty()) {
+        create.model = Some(model);
+    }
+    if let Some(name) = create.labels.name.take() {
+        create.labels.name = Some(format!("{name} fork"));
+    }
+    let created = sessions
+        .lock()
+        .expect("session manager mutex poisoned")
+        .create_session(app.clone(), create);
+    let session = match created {
+        Ok(v) => v,
+        Err(err) => {
+            let _ = gm.remove_worktree(&branch);
+            return Err(err);
+        }
+    };
+
+    // Skills/MCP selections live in the project's pane config; copy them to the new pane.
+    match project_session_config_get(&project_path, source.pane_index) {
+        Ok(Some(mut cfg)) => {
+            cfg.branch = Some(branch.clone());
+            cfg.worktree_isolation = Some(true);
+            if let Err(err) =
+                project_session_config_set(&project_path, session.pane_index, cfg.into())
+            {
+                eprintln!("session_fork: copy pane config: {err:#}");
+            }
+        }
+        Ok(None) => {}
+        Err(err) => eprintln!("session_fork: read pane config: {err:#}"),
+    }
+
+    Ok(ForkSessionResponse {
+        session,
+        forked_from: args.session_id,
+        branch,
+        worktree_path,
+        base_commit,
+    })
+}
@@ -0,0 +1,398 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::core::agent_detection::AgentType;
+use crate::core::app_context::AppContext;
+use crate::core::git_manager::GitManager;
+use crate::core::process_pool::strip_ansi;
+use crate::core::review_store;
+use crate::core::session_manager::{CodexProvider, CreateSessionArgs, SharedSessionManager};
+
+/// Packets live here, relative to the target session's working dir.
+const HANDOFF_DIR: &str = ".synk/handoff";
+/// How much of the source session's output goes into the packet.
+const MAX_OUTPUT_LINES: usize = 200;
+/// Diffs beyond this are cut off; the target agent can run git itself for the rest.
+const MAX_DIFF_BYTES: usize = 200 * 1024;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandoffArgs {
+    pub source_session_id: usize,
+    /// Hand off to a running session...
+    #[serde(default)]
+    pub target_session_id: Option<usize>,
+    /// ...or launch a new one of this agent type in the source's working dir.
+    #[serde(default)]
+    pub agent_type: Option<AgentType>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub codex_provider: Option<CodexProvider>,
+    #[serde(default)]
+    pub provider_profile: Option<String>,
+    #[serde(default)]
+    pub task: Option<String>,
+    /// Defaults to the repo's default branch.
+    #[serde(default)]
+    pub base_branch: Option<String>,
+    /// Destroy the source session once the target has its prompt.
+    #[serde(default)]
+    pub close_source: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandoffResponse {
+    pub session_id: usize,
+    pub pane_index: usize,
+    pub agent_type: AgentType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    pub packet_path: String,
+}
+
+#[derive(Debug, Default)]
+struct HandoffPacket {
+    source_agent: String,
+    branch: Option<String>,
+    base_branch: Option<String>,
+    task: Option<String>,
+    recent_output: String,
+    committed_diff: Option<String>,
+    uncommitted_diff: Option<String>,
+    open_comments: Vec<String>,
+}
+
+/// Builds a context packet from the source session (recent output, diff against base, open
+/// review comments, task), writes it into the target's working dir and points the target
+/// agent at it by absolute path. A new target gets the pointer as its launch prompt. The
+/// session manager is only locked to read the sessions and to start or message the target,
+/// not while the packet is gathered.
+pub fn handoff_session(
+    sessions: &SharedSessionManager,
+    app: &AppContext,
+    args: HandoffArgs,
+) -> Result<HandoffResponse> {
+    if args.target_session_id == Some(args.source_session_id) {
+        return Err(anyhow!("cannot hand a session off to itself"));
+    }
+    if args.target_session_id.is_some() == args.agent_type.is_some() {
+        return Err(anyhow!(
+            "specify exactly one of targetSessionId or agentType"
+        ));
+    }
+
+    // A running target may sit in another worktree; a new one starts in the source's dir.
+    let (source, scrollback, target) = {
+        let manager = sessions.lock().expect("session manager mutex poisoned");
+        let source = manager
+            .get_session_info(args.source_session_id)
+            .ok_or_else(|| anyhow!("unknown session_id {}", args.source_session_id))?;
+        let scrollback = manager.scrollback_bytes(args.source_session_id)?;
+        let target = match args.target_session_id {
+            Some(id) => Some(
+                manager
+                    .get_session_info(id)
+                    .ok_or_else(|| anyhow!("unknown session_id {id}"))?,
+            ),
+            None => None,
+        };
+        (source, scrollback, target)
+    };
+    if target
+        .as_ref()
+        .is_some_and(|t| t.agent_type == AgentType::Terminal)
+    {
+        return Err(anyhow!("the target session is a plain terminal, not an agent"));
+    }
+
+    let project_path = PathBuf::from(&source.project_path);
+    let working_dir = source
+        .working_dir
+        .clone()
+        .unwrap_or_else(|| source.project_path.clone());
+    let mut packet = HandoffPacket {
+        source_agent: agent_label(source.agent_type, source.model.as_deref()),
+        branch: source.branch.clone(),
+        task: args
+            .task
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string),
+        recent_output: recent_output(&scrollback, MAX_OUTPUT_LINES),
+        ..Default::default()
+    };
+
+    // Git context is best-effort: a handoff in a non-git dir still carries output and task.
+    if let Ok(gm) = GitManager::for_project(app, &project_path) {
+        let base = match args.base_branch.as_deref().map(str::trim) {
+            Some(b) if !b.is_empty() => gm.normalize_base_branch(b),
+            _ => gm.default_base_branch(),
+        };
+        if let (Some(branch), Ok(base)) = (source.branch.as_deref(), base) {
+            match gm.raw_unified_diff(branch, &base) {
+                Ok(diff) => packet.committed_diff = Some(diff),
+                Err(err) => eprintln!("session_handoff: diff {branch}: {err:#}"),
+            }
+            packet.base_branch = Some(base);
+        }
+    }
+    packet.uncommitted_diff = GitManager::uncommitted_diff(Path::new(&working_dir)).ok();
+    if let Some(branch) = source.branch.as_deref() {
+        packet.open_comments = open_review_comments(app, &project_path, branch);
+    }
+
+    let target_dir = match &target {
+        Some(t) => t.working_dir.as_ref().unwrap_or(&t.project_path),
+        None => &working_dir,
+    };
+    let packet_path = write_packet(Path::new(target_dir), &render_packet(&packet))?;
+    let prompt = format!(
+        "You are taking over this task from a {} session. Read {} first: it has the task, \
+         the work so far and open review comments. Then continue the task.",
+        packet.source_agent,
+        packet_path.display()
+    );
+
+    let mut manager = sessions.lock().expect("session manager mutex poisoned");
+    let (session_id, pane_index, agent_type, warning) = match target {
+        Some(info) => {
+            manager.write(info.session_id, &format!("{prompt}\r"))?;
+            (info.session_id, info.pane_index, info.agent_type, None)
+        }
+        None => {
+            let created = manager.create_session(
+                app.clone(),
+                CreateSessionArgs {
+                    agent_type: args.agent_type.unwrap_or(AgentType::Terminal),
+                    project_path: source.project_path.clone(),
+                    branch: source.branch.clone(),
+                    working_dir: Some(working_dir.clone()),
+                    model: args.model,
+                    codex_provider: args.codex_provider,
+                    provider_profile: args.provider_profile,
+                    env: None,
+                    labels: source.labels.clone(),
+                    initial_prompt: Some(prompt),
+                },
+            )?;
+            (
+                created.session_id,
+                created.pane_index,
+                created.agent_type,
+                created.warning,
+            )
+        }
+    };
+
+    if args.close_source {
+        manager.destroy_session(app.clone(), args.source_session_id)?;
+    }
+
+    Ok(HandoffResponse {
+        session_id,
+        pane_index,
+        agent_type,
+        warning,
+        packet_path: packet_path.to_string_lossy().to_string(),
+    })
+}
+
+fn agent_label(agent_type: AgentType, model: Option<&str>) -> String {
+    let name = serde_json::to_value(agent_type)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_else(|| "terminal".to_string());
+    match model {
+        Some(m) => format!("{name} ({m})"),
+        None => name,
+    }
+}
+
+fn open_review_comments(app: &AppContext, project_path: &Path, branch: &str) -> Vec<String> {
+    let Ok(reviews) = review_store::review_list(app, project_path) else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for item in reviews.iter().filter(|r| r.branch == branch) {
+        for c in item.comments.iter().filter(|c| !c.resolved) {
+            out.push(format!(
+                "{}:{} ({}): {}",
+                c.file_path, c.line_number, c.author, c.body
+            ));
+        }
+    }
+    out
+}
+
+/// Writes the packet under `dir`'s `HANDOFF_DIR` (git-ignored) and returns its absolute path.
+fn write_packet(dir: &Path, text: &str) -> Result<PathBuf> {
+    let dir = fs::canonicalize(dir).with_context(|| format!("resolve {}", dir.display()))?;
+    let handoff_dir = dir.join(HANDOFF_DIR);
+    fs::create_dir_all(&handoff_dir)
+        .with_context(|| format!("create {}", handoff_dir.display()))?;
+    let ignore = handoff_dir.join(".gitignore");
+    if !ignore.exists() {
+        fs::write(&ignore, "*\n").with_context(|| format!("write {}", ignore.display()))?;
+    }
+
+    let stamp = OffsetDateTime::now_utc()
+        .format(&Rfc3339)?
+        .replace([':', '.'], "-");
+    let path = handoff_dir.join(format!("handoff-{stamp}.md"));
+    fs::write(&path, text).with_context(|| format!("write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Last `max_lines` lines of terminal output as plain text: escape sequences removed and
+/// carriage-return redraws collapsed to what ended up on screen.
+fn recent_output(bytes: &[u8], max_lines: usize) -> String {
+    let text = strip_ansi(&strip_osc(&String::from_utf8_lossy(bytes)));
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.split('\n') {
+        let raw = raw.trim_end_matches('\r');
+        let shown = raw.rsplit('\r').next().unwrap_or_default();
+        let line: String = shown
+            .chars()
+            .filter(|c| *c == '\t' || !c.is_control())
+            .collect();
+        let line = line.trim_end().to_string();
+        if line.is_empty() && lines.last().is_some_and(|l| l.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    let start = lines.len().saturating_sub(max_lines);
+    lines[start..].join("\n")
+}
+
+/// Drops OSC sequences (ESC ] ... BEL / ESC \), e.g. window titles and hyperlinks.
+fn strip_osc(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' && chars.peek() == Some(&']') {
+            let _ = chars.next();
+            while let Some(c) = chars.next() {
+                if c == '\x07' {
+                    break;
+                }
+                if c == '\x1b' && chars.peek() == Some(&'\\') {
+                    let _ = chars.next();
+                    break;
+                }
+            }
+            continue;
+        }
+        out.push(ch);
+    }
+    out
+}
+
+fn truncate_diff(diff: &str) -> String {
+    if diff.len() <= MAX_DIFF_BYTES {
+        return diff.to_string();
+    }
+    let mut end = MAX_DIFF_BYTES;
+    while !diff.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!(
+        "{}\n... (truncated; {} more bytes)",
+        &diff[..end],
+        diff.len() - end
+    )
+}
+
+fn render_packet(p: &HandoffPacket) -> String {
+    let mut out = String::new();
+    out.push_str("# Session handoff\n\n");
+    out.push_str(&format!("Handed off from: {}\n", p.source_agent));
+    if let Some(branch) = &p.branch {
+        out.push_str(&format!("Branch: {branch}\n"));
+    }
+    if let Some(base) = &p.base_branch {
+        out.push_str(&format!("Base branch: {base}\n"));
+    }
+
+    out.push_str("\n## Task\n\n");
+    out.push_str(p.task.as_deref().unwrap_or("(no task description given)"));
+    out.push('\n');
+
+    out.push_str("\n## Open review comments\n\n");
+    if p.open_comments.is_empty() {
+        out.push_str("(none)\n");
+    }
+    for c in &p.open_comments {
+        out.push_str(&format!("- {c}\n"));
+    }
+
+    for (title, diff) in [
+        ("Committed changes against base", &p.committed_diff),
+        ("Uncommitted changes", &p.uncommitted_diff),
+    ] {
+        out.push_str(&format!("\n## {title}\n\n"));
+        match diff.as_deref().filter(|d| !d.trim().is_empty()) {
+            Some(d) => out.push_str(&format!("```diff\n{}\n```\n", truncate_diff(d))),
+            None => out.push_str("(none)\n"),
+        }
+    }
+
+    out.push_str("\n## Recent output from the previous session\n\n");
+    out.push_str(&format!("```text\n{}\n```\n", p.recent_output));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{recent_output, render_packet, write_packet, HandoffPacket};
+    use crate::core::test_fixtures::TempDir;
+
+    #[test]
+    fn recent_output_strips_escapes_and_redraws() {
+        let raw =
+            b"\x1b]0;title\x07$ build\r\n\x1b[32mok\x1b[0m\r\n50%\r100%\r\n\r\n\r\n\r\ndone\r\n";
+        assert_eq!(recent_output(raw, 10), "$ build\nok\n100%\n\ndone");
+        assert_eq!(recent_output(raw, 2), "\ndone");
+    }
+
+    #[test]
+    fn packet_is_written_by_absolute_path() {
+        let dir = TempDir::new("handoff");
+        let path = write_packet(&dir, "hello").unwrap();
+        assert!(path.is_absolute());
+        assert!(path.starts_with(dir.canonicalize().unwrap().join(".synk/handoff")));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello");
+        assert!(dir.join(".synk/handoff/.gitignore").exists());
+    }
+
+    #[test]
+    fn packet_has_all_sections() {
+        let text = render_packet(&HandoffPacket {
+            source_agent: "claude_code".to_string(),
+            branch: Some("feat/x".to_string()),
+            base_branch: Some("main".to_string()),
+            task: Some("Add login".to_string()),
+            recent_output: "tests pass".to_string(),
+            committed_diff: Some("diff --git a/x b/x".to_string()),
+            uncommitted_diff: Some(String::new()),
+            open_comments: vec!["src/a.rs:3 (user): rename this".to_string()],
+        });
+        assert!(text.contains("Handed off from: claude_code\nBranch: feat/x\nBase branch: main\n"));
+        assert!(text.contains("## Task\n\nAdd login\n"));
+        assert!(text.contains("- src/a.rs:3 (user): rename this\n"));
+        assert!(text.contains("```diff\ndiff --git a/x b/x\n```"));
+        assert!(text.contains("## Uncommitted changes\n\n(none)\n"));
+        assert!(text.contains("```text\ntests pass\n```"));
+    }
+}
@@ -0,0 +1,345 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::core::agent_detection::AgentType;
+use crate::core::session_manager::SessionInfo;
+
+const MAX_LABEL_LEN: usize = 64;
+
+/// User-assigned metadata that makes sessions recognizable ("api-refactor") and targetable
+/// (`@tag:frontend`, `@group:squad-a`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionLabels {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// `#rgb` / `#rrggbb` or a color keyword like `teal`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+}
+
+fn clean_text(field: &str, v: Option<String>) -> Result<Option<String>> {
+    let Some(v) = v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty()) else {
+        return Ok(None);
+    };
+    if v.chars().count() > MAX_LABEL_LEN {
+        return Err(anyhow!("{field} is longer than {MAX_LABEL_LEN} characters"));
+    }
+    if v.chars().any(char::is_control) {
+        return Err(anyhow!("{field} contains control characters"));
+    }
+    Ok(Some(v))
+}
+
+/// Tags are matched case-insensitively and must survive `@tag:<tag>` parsing.
+fn clean_tag(tag: &str) -> Result<Option<String>> {
+    let t = tag.trim().to_lowercase();
+    if t.is_empty() {
+        return Ok(None);
+    }
+    if t.chars().count() > MAX_LABEL_LEN {
+        return Err(anyhow!(
+            "tag {t:?} is longer than {MAX_LABEL_LEN} characters"
+        ));
+    }
+    if !t
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'))
+    {
+        return Err(anyhow!(
+            "tag {t:?} may only contain letters, digits, '-', '_', '.' and '/'"
+        ));
+    }
+    Ok(Some(t))
+}
+
+fn valid_color(c: &str) -> bool {
+    match c.strip_prefix('#') {
+        Some(hex) => matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None => !c.is_empty() && c.len() <= 32 && c.chars().all(|c| c.is_ascii_alphabetic()),
+    }
+}
+
+impl SessionLabels {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Trims everything, lowercases and dedupes tags, and validates the color.
+    pub fn normalized(self) -> Result<Self> {
+        let color = clean_text("color", self.color)?.map(|c| c.to_lowercase());
+        if let Some(c) = &color {
+            if !valid_color(c) {
+                return Err(anyhow!(
+                    "invalid color {c:?} (use #rgb, #rrggbb or a color name)"
+                ));
+            }
+        }
+        let mut tags: Vec<String> = Vec::new();
+        for t in &self.tags {
+            if let Some(t) = clean_tag(t)? {
+                if !tags.contains(&t) {
+                    tags.push(t);
+                }
+            }
+        }
+        Ok(Self {
+            name: clean_text("name", self.name)?,
+            color,
+            tags,
+            group: clean_text("group", self.group)?,
+        })
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = tag.to_lowercase();
+        self.tags.contains(&tag)
+    }
+}
+
+/// Which sessions a write is aimed at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionSelector {
+    /// `7` or `#7`
+    Id(usize),
+    /// `@all`
+    All,
+    /// `@idle`: not producing output right now.
+    Idle,
+    /// `@active`: mid-turn.
+    Active,
+    /// `@agent:claude_code` (or `@agent:claude`)
+    Agent(AgentType),
+    /// `@tag:frontend`
+    Tag(String),
+    /// `@group:squad-a`
+    Group(String),
+    /// `@name:api` or just `@api`
+    Name(String),
+}
+
+pub(crate) fn parse_agent(v: &str) -> Option<AgentType> {
+    let v = v.to_lowercase().replace('-', "_");
+    serde_json::from_value(serde_json::Value::String(v.clone()))
+        .ok()
+        .or(match v.as_str() {
+            "claude" => Some(AgentType::ClaudeCode),
+            "gemini" => Some(AgentType::GeminiCli),
+            _ => None,
+        })
+}
+
+impl SessionSelector {
+    pub fn parse(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Ok(id) = s.strip_prefix('#').unwrap_or(s).parse::<usize>() {
+            return Ok(Self::Id(id));
+        }
+        let Some(rest) = s.strip_prefix('@') else {
+            return Err(anyhow!(
+                "invalid target {s:?} (use a session id, @all, @idle, @active, @name, \
+                 @agent:<type>, @tag:<tag> or @group:<group>)"
+            ));
+        };
+        match rest.trim() {
+            "all" => return Ok(Self::All),
+            "idle" => return Ok(Self::Idle),
+            "active" => return Ok(Self::Active),
+            _ => {}
+        }
+        let (kind, value) = match rest.split_once(':') {
+            Some((kind, value)) => (kind, value.trim()),
+            None => ("name", rest.trim()),
+        };
+        if value.is_empty() {
+            return Err(anyhow!("empty target {s:?}"));
+        }
+        match kind {
+            "agent" => parse_agent(value)
+                .map(Self::Agent)
+                .ok_or_else(|| anyhow!("unknown agent type {value:?} in {s:?}")),
+            "tag" => Ok(Self::Tag(value.to_lowercase())),
+            "group" => Ok(Self::Group(value.to_string())),
+            "name" => Ok(Self::Name(value.to_string())),
+            other => Err(anyhow!("unknown target kind {other:?} in {s:?}")),
+        }
+    }
+
+    /// Comma-separated selectors, e.g. `1,2,@tag:docs`; a session matching any of them is hit.
+    pub fn parse_list(s: &str) -> Result<Vec<Self>> {
+        let parts: Vec<&str> = s.split(',').filter(|p| !p.trim().is_empty()).collect();
+        if parts.is_empty() {
+            return Err(anyhow!("empty target"));
+        }
+        parts.into_iter().map(Self::parse).collect()
+    }
+
+    /// Ids of the `sessions` matching `target`, limited to `project_path` when given.
+    /// `is_busy` answers `@idle` / `@active`. Errors when nothing matches.
+    pub fn select(
+        target: &str,
+        sessions: &[SessionInfo],
+        project_path: Option<&str>,
+        is_busy: impl Fn(usize) -> bool,
+    ) -> Result<Vec<usize>> {
+        let selectors = Self::parse_list(target)?;
+        let ids: Vec<usize> = sessions
+            .iter()
+            .filter(|s| project_path.is_none_or(|p| s.project_path == p))
+            .filter(|s| {
+                let busy = is_busy(s.session_id);
+                selectors.iter().any(|sel| sel.matches(s, busy))
+            })
+            .map(|s| s.session_id)
+            .collect();
+        match project_path {
+            _ if !ids.is_empty() => Ok(ids),
+            Some(p) => Err(anyhow!("no session in {p} matches {}", target.trim())),
+            None => Err(anyhow!("no session matches {}", target.trim())),
+        }
+    }
+
+    /// `busy` is whether the session is mid-turn, for `@idle` / `@active`.
+    pub fn matches(&self, info: &SessionInfo, busy: bool) -> bool {
+        let labels = &info.labels;
+        match self {
+            Self::Id(id) => info.session_id == *id,
+            Self::All => true,
+            Self::Idle => !busy,
+            Self::Active => busy,
+            Self::Agent(agent) => info.agent_type == *agent,
+            Self::Tag(tag) => labels.has_tag(tag),
+            Self::Group(group) => labels
+                .group
+                .as_deref()
+                .is_some_and(|g| g.eq_ignore_ascii_case(group)),
+            Self::Name(name) => labels
+                .name
+                .as_deref()
+                .is_some_and(|n| n.eq_ignore_ascii_case(name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SessionLabels, SessionSelector};
+    use crate::core::agent_detection::AgentType;
+    use crate::core::session_manager::SessionInfo;
+
+    fn session(session_id: usize, project_path: &str, tag: &str) -> SessionInfo {
+        SessionInfo {
+            session_id,
+            session_key: format!("100-{session_id}"),
+            pane_index: session_id - 1,
+            agent_type: AgentType::ClaudeCode,
+            codex_provider: None,
+            provider_profile: None,
+            model: None,
+            project_path: project_path.to_string(),
+            branch: None,
+            working_dir: None,
+            labels: SessionLabels {
+                tags: vec![tag.to_string()],
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn labels_are_normalized() {
+        let labels = SessionLabels {
+            name: Some("  api refactor ".to_string()),
+            color: Some("#A0F".to_string()),
+            tags: vec![
+                "Frontend".into(),
+                "frontend".into(),
+                " ".into(),
+                "ui/web".into(),
+            ],
+            group: Some("".to_string()),
+        }
+        .normalized()
+        .unwrap();
+        assert_eq!(labels.name.as_deref(), Some("api refactor"));
+        assert_eq!(labels.color.as_deref(), Some("#a0f"));
+        assert_eq!(labels.tags, ["frontend", "ui/web"]);
+        assert_eq!(labels.group, None);
+
+        let bad = |l: SessionLabels| l.normalized().is_err();
+        assert!(bad(SessionLabels {
+            color: Some("#12345".into()),
+            ..Default::default()
+        }));
+        assert!(bad(SessionLabels {
+            tags: vec!["has space".into()],
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn parses_selectors() {
+        assert_eq!(SessionSelector::parse("7").unwrap(), SessionSelector::Id(7));
+        assert_eq!(
+            SessionSelector::parse("#7").unwrap(),
+            SessionSelector::Id(7)
+        );
+        assert_eq!(
+            SessionSelector::parse("@tag:Frontend").unwrap(),
+            SessionSelector::Tag("frontend".into())
+        );
+        assert_eq!(
+            SessionSelector::parse("@group:squad-a").unwrap(),
+            SessionSelector::Group("squad-a".into())
+        );
+        assert_eq!(
+            SessionSelector::parse("@api").unwrap(),
+            SessionSelector::Name("api".into())
+        );
+        assert_eq!(
+            SessionSelector::parse("@idle").unwrap(),
+            SessionSelector::Idle
+        );
+        assert_eq!(
+            SessionSelector::parse("@agent:claude").unwrap(),
+            SessionSelector::Agent(AgentType::ClaudeCode)
+        );
+        assert_eq!(
+            SessionSelector::parse("@agent:gemini_cli").unwrap(),
+            SessionSelector::Agent(AgentType::GeminiCli)
+        );
+        assert_eq!(
+            SessionSelector::parse_list("1, 2,@all").unwrap(),
+            [
+                SessionSelector::Id(1),
+                SessionSelector::Id(2),
+                SessionSelector::All
+            ]
+        );
+        assert!(SessionSelector::parse_list(" , ").is_err());
+        assert!(SessionSelector::parse("@agent:vim").is_err());
+        assert!(SessionSelector::parse("api").is_err());
+        assert!(SessionSelector::parse("@tag:").is_err());
+        assert!(SessionSelector::parse("@color:red").is_err());
+    }
+
+    #[test]
+    fn selects_within_a_project() {
+        let sessions = [
+            session(1, "/a", "docs"),
+            session(2, "/b", "docs"),
+            session(3, "/a", "ui"),
+        ];
+        let idle = |_| false;
+        let select = |target, project| SessionSelector::select(target, &sessions, project, idle);
+        assert_eq!(select("@tag:docs", None).unwrap(), [1, 2]);
+        assert_eq!(select("@tag:docs", Some("/a")).unwrap(), [1]);
+        assert_eq!(select("@idle", Some("/a")).unwrap(), [1, 3]);
+        assert!(select("2", Some("/a")).is_err());
+        assert!(SessionSelector::select("@active", &sessions, None, idle).is_err());
+    }
+}
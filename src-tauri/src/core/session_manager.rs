@@ -2,7 +2,7 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicU64, Ordering},
     Arc,
 };
 use std::thread::{self, JoinHandle};
@@ -10,12 +10,25 @@ use std::thread::{self, JoinHandle};
 use anyhow::{anyhow, Result};
 use base64::{engine::general_purpose::STANDARD, Engine as _};
 use serde::{Deserialize, Serialize};
-use tauri::path::BaseDirectory;
-use tauri::{Emitter, Manager};
 
-use crate::core::agent_detection::{AgentType, SharedAgentRegistry};
+use crate::core::agent_detection::{detect_auth, AgentType, AuthState, SharedAgentRegistry};
+use crate::core::app_context::AppContext;
+use crate::core::checkpoints::{self, TurnTracker};
+use crate::core::cost_tracker::{CostTracker, SharedCostTrackers};
+use crate::core::event_bus::publish;
+use crate::core::llm_gateway::{SharedLlmGateway, ANTHROPIC_ROUTE, OPENAI_ROUTE};
+use crate::core::persistence::project_session_config_get;
 use crate::core::process_pool::{ProcessPool, PtyHandle, SharedProcessPool};
-use crate::events::{SessionExitEvent, SessionOutputEvent};
+use crate::core::session_env::{
+    looks_secret, shell_single_quote_escape, SecretRedactor, SessionEnv,
+};
+use crate::core::session_labels::{SessionLabels, SessionSelector};
+use crate::core::settings::ProviderProfileView;
+use crate::core::simulated_agent::SIMULATED_AGENT_FLAG;
+use crate::events::{
+    AppEvent, SessionCheckpointEvent, SessionCostUpdatedEvent, SessionExitEvent, SessionIdleEvent,
+    SessionOutputEvent,
+};
 
 pub type SharedSessionManager = Arc<std::sync::Mutex<SessionManager>>;
 
@@ -38,7 +51,32 @@ pub struct CreateSessionArgs {
     pub model: Option<String>,
     #[serde(default)]
     pub codex_provider: Option<CodexProvider>,
+    /// Id of a `settings.ai_providers.profiles` entry (custom base URL / key / model).
+    #[serde(default)]
+    pub provider_profile: Option<String>,
     pub env: Option<HashMap<String, String>>,
+    /// Name/color/tags/group; when empty, the pane's saved labels are used.
+    #[serde(default, flatten)]
+    pub labels: SessionLabels,
+    /// First prompt for the agent. It goes on the agent's command line, so it can't reach the
+    /// shell before the agent is up; sessions that end up as plain terminals are refused.
+    #[serde(default)]
+    pub initial_prompt: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestartSessionArgs {
+    pub session_id: usize,
+    pub dir: String,
+    #[serde(default)]
+    pub branch: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub codex_provider: Option<CodexProvider>,
+    #[serde(default)]
+    pub provider_profile: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -55,15 +93,21 @@ pub struct CreateSessionResponse {
 #[serde(rename_all = "camelCase")]
 pub struct SessionInfo {
     pub session_id: usize,
+    /// Unique across app runs (ids restart at 1); names the session's checkpoint refs.
+    pub session_key: String,
     pub pane_index: usize,
     pub agent_type: AgentType,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub codex_provider: Option<CodexProvider>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_profile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
     pub project_path: String,
     pub branch: Option<String>,
     pub working_dir: Option<String>,
+    #[serde(flatten)]
+    pub labels: SessionLabels,
 }
 
 struct SessionRecord {
@@ -72,6 +116,11 @@ struct SessionRecord {
     stop: Arc<AtomicBool>,
     output_thread: JoinHandle<()>,
     scrollback: Arc<std::sync::Mutex<VecDeque<u8>>>,
+    /// Secret values delivered to the shell; redacted from output.
+    secrets: Vec<String>,
+    /// Extra env from `CreateSessionArgs::env`, kept so the session can be forked.
+    env: HashMap<String, String>,
+    turn: Arc<TurnFlags>,
 }
 
 type BuiltSession = (
@@ -79,8 +128,33 @@ type BuiltSession = (
     Arc<AtomicBool>,
     JoinHandle<()>,
     Arc<std::sync::Mutex<VecDeque<u8>>>,
+    Vec<String>,
+    Arc<TurnFlags>,
 );
 
+/// Shared between a session's writers and its output pump.
+#[derive(Default)]
+struct TurnFlags {
+    /// True while the session is mid-turn (replying to input).
+    busy: AtomicBool,
+    /// Set when a line of input is written; the pump takes it to start a turn.
+    submitted: AtomicBool,
+    /// Characters written since the pump last passed them to the cost tracker.
+    typed_chars: AtomicU64,
+}
+
+/// Turn tracking inputs for the output pump.
+struct TurnWatch {
+    turn: Arc<TurnFlags>,
+    /// Worktree to checkpoint at the end of each turn.
+    checkpoint_dir: Option<PathBuf>,
+    session_key: String,
+    /// Published with `session:idle` when a turn ends; plain terminals publish nothing.
+    agent_type: AgentType,
+    /// Starting model for the session's cost tracker.
+    model: Option<String>,
+}
+
 pub struct SessionManager {
     pool: SharedProcessPool,
     agents: SharedAgentRegistry,
@@ -88,17 +162,6 @@ pub struct SessionManager {
     sessions: HashMap<usize, SessionRecord>,
 }
 
-fn is_valid_env_var_name(name: &str) -> bool {
-    let mut it = name.chars();
-    let Some(first) = it.next() else {
-        return false;
-    };
-    if !(first.is_ascii_alphabetic() || first == '_') {
-        return false;
-    }
-    it.all(|c| c.is_ascii_alphanumeric() || c == '_')
-}
-
 impl SessionManager {
     pub fn new(pool: SharedProcessPool, agents: SharedAgentRegistry) -> Self {
         Self {
@@ -111,7 +174,7 @@ impl SessionManager {
 
     pub fn create_session(
         &mut self,
-        app: tauri::AppHandle,
+        app: AppContext,
         args: CreateSessionArgs,
     ) -> Result<CreateSessionResponse> {
         // Enforce the pool-configured max. The pool also enforces this, but doing it here
@@ -123,56 +186,86 @@ impl SessionManager {
 
         let session_id = self.alloc_session_id();
         let pane_index = self.alloc_pane_index(max_sessions)?;
+        let labels = if args.labels.is_empty() {
+            saved_labels(&args.project_path, pane_index)
+        } else {
+            args.labels.clone().normalized()?
+        };
+
+        let (effective_agent_type, warning) = self.resolve_agent(&args.agent_type);
+        let launch_cmd = self.launch_command(effective_agent_type);
+        let initial_prompt = args
+            .initial_prompt
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty());
+        if initial_prompt.is_some() && launch_cmd.is_none() {
+            return Err(match warning {
+                Some(w) => anyhow!("{w}; a terminal can't take the initial prompt"),
+                None => anyhow!("a terminal can't take the initial prompt"),
+            });
+        }
 
         let mut handle = ProcessPool::claim(self.pool.clone(), session_id)?;
 
-        let (effective_agent_type, warning) = self.resolve_agent(&args.agent_type);
+        let warning = match (warning, auth_warning(&app, effective_agent_type, &args)) {
+            (Some(a), Some(b)) => Some(format!("{a}; {b}")),
+            (a, b) => a.or(b),
+        };
 
         // If anything fails after we claim the PTY, return it to the pool so we don't leak.
         let built = (|| -> Result<BuiltSession> {
-            // Session bootstrap: cd + env exports.
+            // Session bootstrap: env (delivered out of band) + cd.
             let wd = args
                 .working_dir
                 .clone()
                 .unwrap_or_else(|| args.project_path.clone());
-            let launch_model = normalized_model(args.model.as_deref());
-
-            // Configure Codex provider env from Synk settings (OpenAI vs OpenRouter).
-            let codex_provider = match effective_agent_type {
-                AgentType::Codex => args.codex_provider,
-                AgentType::Openrouter => Some(CodexProvider::Openrouter),
+            let profile = resolve_provider_profile(
+                &app,
+                effective_agent_type,
+                args.provider_profile.as_deref(),
+            )?;
+            let launch_model = normalized_model(args.model.as_deref())
+                .or_else(|| profile.as_ref().and_then(|p| normalized_model(p.model.as_deref())));
+
+            // Configure provider env from Synk settings: a named profile wins; otherwise
+            // Codex picks OpenAI vs OpenRouter.
+            let codex_provider = match (effective_agent_type, &profile) {
+                (_, Some(_)) => None,
+                (AgentType::Codex, None) => args.codex_provider,
+                (AgentType::Openrouter, None) => Some(CodexProvider::Openrouter),
                 _ => None,
             };
-            let codex_uses_openrouter = apply_codex_provider_env(
-                &mut handle,
+            let mut env = SessionEnv::new();
+            let codex_uses_openrouter = apply_provider_env(
+                &mut env,
                 &app,
                 effective_agent_type,
                 codex_provider,
+                profile.as_ref(),
                 launch_model.as_deref(),
             )?;
+            apply_gateway_env(
+                &mut env,
+                &app,
+                session_id,
+                effective_agent_type,
+                profile.as_ref(),
+                codex_uses_openrouter,
+            )?;
 
-            if let Some(env) = &args.env {
-                for (k, v) in env {
-                    if !is_valid_env_var_name(k) {
-                        return Err(anyhow!("invalid env var name: {k}"));
-                    }
-                    handle.write_str(&format!(
-                        "export {}='{}'\r\n",
-                        k,
-                        shell_single_quote_escape(v)
-                    ))?;
-                }
+            if let Some(extra) = &args.env {
+                apply_extra_env(&mut env, extra)?;
             }
 
-            handle.write_str(&format!("export SYNK_SESSION_ID='{}'\r\n", session_id))?;
-            handle.write_str(&format!(
-                "export SYNK_AGENT_TYPE='{}'\r\n",
-                agent_type_to_env_value(effective_agent_type)
-            ))?;
-            handle.write_str(&format!(
-                "export SYNK_PROJECT_PATH='{}'\r\n",
-                shell_single_quote_escape(&args.project_path)
-            ))?;
+            set_session_identity_env(
+                &mut env,
+                session_id,
+                effective_agent_type,
+                &args.project_path,
+            )?;
+            env.deliver(&mut handle, session_id)?;
+            let secrets = env.secrets().to_vec();
 
             handle.write_str(&format!("cd '{}'\r\n", shell_single_quote_escape(&wd)))?;
 
@@ -189,24 +282,43 @@ impl SessionManager {
             let stop = Arc::new(AtomicBool::new(false));
             let scrollback: Arc<std::sync::Mutex<VecDeque<u8>>> =
                 Arc::new(std::sync::Mutex::new(VecDeque::new()));
+            let turn = Arc::new(TurnFlags::default());
+            if let Some(prompt) = initial_prompt {
+                turn.typed_chars
+                    .store(prompt.chars().count() as u64, Ordering::Relaxed);
+                turn.submitted.store(true, Ordering::Relaxed);
+            }
+            let session_key = new_session_key(session_id);
+            let checkpoint_dir = checkpoint_dir(&app, effective_agent_type, &wd);
             let output_thread = spawn_output_pump(
                 app.clone(),
                 session_id,
                 stop.clone(),
                 scrollback.clone(),
+                &secrets,
+                TurnWatch {
+                    turn: turn.clone(),
+                    checkpoint_dir,
+                    session_key: session_key.clone(),
+                    agent_type: effective_agent_type,
+                    model: launch_model.clone(),
+                },
                 &mut handle, // used only to clone fd/reader
             )?;
 
             // Launch the agent CLI inside the claimed shell.
             if effective_agent_type != AgentType::Terminal {
-                if let Some(cmd) = effective_agent_type.cli_command() {
-                    let full =
+                if let Some(cmd) = launch_cmd.as_deref() {
+                    let mut full =
                         agent_command_with_model(
                             effective_agent_type,
                             cmd,
                             launch_model.as_deref(),
                             codex_uses_openrouter,
                         );
+                    if let Some(prompt) = initial_prompt {
+                        full.push_str(&initial_prompt_arg(effective_agent_type, prompt));
+                    }
                     if let Err(err) = handle.write_str(&format!("{full}\r\n")) {
                         stop.store(true, Ordering::Relaxed);
                         let _ = output_thread.join();
@@ -217,19 +329,22 @@ impl SessionManager {
 
             let info = SessionInfo {
                 session_id,
+                session_key,
                 pane_index,
                 agent_type: effective_agent_type,
                 codex_provider,
+                provider_profile: profile.map(|p| p.id),
                 model: launch_model,
                 project_path: args.project_path,
                 branch: args.branch,
                 working_dir: Some(wd),
+                labels,
             };
 
-            Ok((info, stop, output_thread, scrollback))
+            Ok((info, stop, output_thread, scrollback, secrets, turn))
         })();
 
-        let (info, stop, output_thread, scrollback) = match built {
+        let (info, stop, output_thread, scrollback, secrets, turn) = match built {
             Ok(v) => v,
             Err(err) => {
                 let _ = ProcessPool::release(self.pool.clone(), session_id, handle);
@@ -245,6 +360,9 @@ impl SessionManager {
                 stop,
                 output_thread,
                 scrollback,
+                secrets,
+                env: args.env.unwrap_or_default(),
+                turn,
             },
         );
 
@@ -262,6 +380,12 @@ impl SessionManager {
             .get_mut(&session_id)
             .ok_or_else(|| anyhow!("unknown session_id {session_id}"))?;
         rec.handle.write_all(data.as_bytes())?;
+        rec.turn
+            .typed_chars
+            .fetch_add(data.chars().count() as u64, Ordering::Relaxed);
+        if data.contains(['\r', '\n']) {
+            rec.turn.submitted.store(true, Ordering::Relaxed);
+        }
         Ok(())
     }
 
@@ -274,7 +398,7 @@ impl SessionManager {
         Ok(())
     }
 
-    pub fn destroy_session(&mut self, app: tauri::AppHandle, session_id: usize) -> Result<()> {
+    pub fn destroy_session(&mut self, app: AppContext, session_id: usize) -> Result<()> {
         let rec = self
             .sessions
             .remove(&session_id)
@@ -285,6 +409,12 @@ impl SessionManager {
         let pool = self.pool.clone();
         let pool_config = ProcessPool::detach_active(pool.clone(), session_id);
 
+        if let Some(gateway) = app.try_state::<SharedLlmGateway>() {
+            if let Ok(gw) = gateway.inner().lock() {
+                gw.unregister_session(session_id);
+            }
+        }
+
         // Destroying a session can involve waiting for recycle/kill timeouts (seconds).
         // If we do that work on the Tauri command thread it can freeze the app UI.
         // Instead, remove the session from the manager immediately (above) and finish
@@ -299,12 +429,12 @@ impl SessionManager {
             }
 
             // Best-effort: if the frontend cares, it can mark the pane closed.
-            let _ = app.emit(
-                "session:exit",
-                SessionExitEvent {
+            publish(
+                &app,
+                AppEvent::SessionExit(SessionExitEvent {
                     session_id,
-                    exit_code: 0,
-                },
+                    exit_code: Some(0),
+                }),
             );
         });
 
@@ -313,13 +443,17 @@ impl SessionManager {
 
     pub fn restart_session(
         &mut self,
-        app: tauri::AppHandle,
-        session_id: usize,
-        dir: String,
-        branch: Option<String>,
-        model: Option<String>,
-        codex_provider: Option<CodexProvider>,
+        app: AppContext,
+        args: RestartSessionArgs,
     ) -> Result<SessionInfo> {
+        let RestartSessionArgs {
+            session_id,
+            dir,
+            branch,
+            model,
+            codex_provider,
+            provider_profile,
+        } = args;
         let dir = dir.trim();
         if dir.is_empty() {
             return Err(anyhow!("dir is empty"));
@@ -346,11 +480,24 @@ impl SessionManager {
                 // Restore accounting and resume output streaming on the existing handle.
                 let _ = ProcessPool::attach_active(pool.clone(), session_id, rec.handle.pid);
                 let stop = Arc::new(AtomicBool::new(false));
+                let checkpoint_dir = rec
+                    .info
+                    .working_dir
+                    .as_deref()
+                    .and_then(|wd| checkpoint_dir(&app, rec.info.agent_type, wd));
                 let output_thread = spawn_output_pump(
                     app.clone(),
                     session_id,
                     stop.clone(),
                     rec.scrollback.clone(),
+                    &rec.secrets,
+                    TurnWatch {
+                        turn: rec.turn.clone(),
+                        checkpoint_dir,
+                        session_key: rec.info.session_key.clone(),
+                        agent_type: rec.info.agent_type,
+                        model: rec.info.model.clone(),
+                    },
                     &mut rec.handle,
                 )?;
                 rec.stop = stop;
@@ -365,6 +512,10 @@ impl SessionManager {
         let codex_provider = codex_provider.or(rec.info.codex_provider);
         let launch_model = normalized_model(model.as_deref()).or(rec.info.model.clone());
         let project_path = rec.info.project_path.clone();
+        let provider_profile = provider_profile.or(rec.info.provider_profile.clone());
+        let extra_env = std::mem::take(&mut rec.env);
+        let labels = rec.info.labels.clone();
+        let session_key = rec.info.session_key.clone();
 
         // Hand old handle back to the pool in the background (recycle/kill may take time).
         std::thread::spawn(move || {
@@ -373,32 +524,38 @@ impl SessionManager {
             }
         });
 
-        // Bootstrap new session: env exports + cd.
-        handle.write_str(&format!("export SYNK_SESSION_ID='{}'\r\n", session_id))?;
-        handle.write_str(&format!(
-            "export SYNK_AGENT_TYPE='{}'\r\n",
-            agent_type_to_env_value(agent_type)
-        ))?;
-        handle.write_str(&format!(
-            "export SYNK_PROJECT_PATH='{}'\r\n",
-            shell_single_quote_escape(&project_path)
-        ))?;
-        // Re-apply Codex provider env for restarted sessions.
-        let codex_uses_openrouter = apply_codex_provider_env(
-            &mut handle,
+        // Bootstrap new session: env (delivered out of band) + cd.
+        let mut env = SessionEnv::new();
+        // Re-apply provider env for restarted sessions.
+        let profile = resolve_provider_profile(&app, agent_type, provider_profile.as_deref())?;
+        let codex_uses_openrouter = apply_provider_env(
+            &mut env,
             &app,
             agent_type,
             codex_provider,
+            profile.as_ref(),
             launch_model.as_deref(),
         )?;
+        apply_gateway_env(
+            &mut env,
+            &app,
+            session_id,
+            agent_type,
+            profile.as_ref(),
+            codex_uses_openrouter,
+        )?;
+        apply_extra_env(&mut env, &extra_env)?;
+        set_session_identity_env(&mut env, session_id, agent_type, &project_path)?;
+        env.deliver(&mut handle, session_id)?;
+        let secrets = env.secrets().to_vec();
         handle.write_str(&format!("cd '{}'\r\n", shell_single_quote_escape(dir)))?;
 
         // Relaunch agent CLI (if any).
         if agent_type != AgentType::Terminal {
-            if let Some(cmd) = agent_type.cli_command() {
+            if let Some(cmd) = self.launch_command(agent_type) {
                 let full = agent_command_with_model(
                     agent_type,
-                    cmd,
+                    &cmd,
                     launch_model.as_deref(),
                     codex_uses_openrouter,
                 );
@@ -410,23 +567,36 @@ impl SessionManager {
         let stop = Arc::new(AtomicBool::new(false));
         let scrollback: Arc<std::sync::Mutex<VecDeque<u8>>> =
             Arc::new(std::sync::Mutex::new(VecDeque::new()));
+        let turn = Arc::new(TurnFlags::default());
+        let checkpoint_dir = checkpoint_dir(&app, agent_type, dir);
         let output_thread = spawn_output_pump(
             app,
             session_id,
             stop.clone(),
             scrollback.clone(),
+            &secrets,
+            TurnWatch {
+                turn: turn.clone(),
+                checkpoint_dir,
+                session_key: session_key.clone(),
+                agent_type,
+                model: launch_model.clone(),
+            },
             &mut handle,
         )?;
 
         let info = SessionInfo {
             session_id,
+            session_key,
             pane_index,
             agent_type,
             codex_provider,
+            provider_profile: profile.map(|p| p.id),
             model: launch_model,
             project_path,
             branch,
             working_dir: Some(dir.to_string()),
+            labels,
         };
 
         self.sessions.insert(
@@ -437,6 +607,9 @@ impl SessionManager {
                 stop,
                 output_thread,
                 scrollback,
+                secrets,
+                env: extra_env,
+                turn,
             },
         );
 
@@ -453,6 +626,67 @@ impl SessionManager {
         self.sessions.get(&session_id).map(|r| r.info.clone())
     }
 
+    /// Arguments that recreate `session_id`'s configuration (agent, model, provider, env).
+    pub fn clone_args(&self, session_id: usize) -> Result<CreateSessionArgs> {
+        let rec = self
+            .sessions
+            .get(&session_id)
+            .ok_or_else(|| anyhow!("unknown session_id {session_id}"))?;
+        let info = &rec.info;
+        Ok(CreateSessionArgs {
+            agent_type: info.agent_type,
+            project_path: info.project_path.clone(),
+            branch: info.branch.clone(),
+            working_dir: info.working_dir.clone(),
+            model: info.model.clone(),
+            codex_provider: info.codex_provider,
+            provider_profile: info.provider_profile.clone(),
+            env: (!rec.env.is_empty()).then(|| rec.env.clone()),
+            labels: info.labels.clone(),
+            initial_prompt: None,
+        })
+    }
+
+    pub fn set_labels(&mut self, session_id: usize, labels: SessionLabels) -> Result<SessionInfo> {
+        let rec = self
+            .sessions
+            .get_mut(&session_id)
+            .ok_or_else(|| anyhow!("unknown session_id {session_id}"))?;
+        rec.info.labels = labels.normalized()?;
+        Ok(rec.info.clone())
+    }
+
+    /// Session id by shell pid, for attributing processes started inside a session.
+    pub fn shell_pids(&self) -> HashMap<u32, usize> {
+        self.sessions
+            .iter()
+            .filter_map(|(id, r)| r.handle.pid.map(|pid| (pid, *id)))
+            .collect()
+    }
+
+    /// Whether the session is mid-turn: it has been replying to submitted input within the
+    /// last few seconds. The echo of typing doesn't count (see `TurnTracker`).
+    pub fn is_busy(&self, session_id: usize) -> bool {
+        self.sessions
+            .get(&session_id)
+            .is_some_and(|r| r.turn.busy.load(Ordering::Relaxed))
+    }
+
+    /// Live sessions matching a target such as `7`, `@api`, `@idle`, `@tag:frontend` or a
+    /// comma-separated list of those, optionally only those in `project_path`.
+    pub fn resolve_targets(&self, target: &str, project_path: Option<&str>) -> Result<Vec<usize>> {
+        SessionSelector::select(target, &self.list_sessions(), project_path, |id| {
+            self.is_busy(id)
+        })
+    }
+
+    /// The live session working on `branch` in `project_path`, if any.
+    pub fn find_by_branch(&self, project_path: &str, branch: &str) -> Option<SessionInfo> {
+        self.list_sessions()
+            .into_iter()
+            .find(|s| s.project_path == project_path && s.branch.as_deref() == Some(branch))
+    }
+
     pub fn set_session_git_context(
         &mut self,
         session_id: usize,
@@ -469,6 +703,11 @@ impl SessionManager {
     }
 
     pub fn scrollback_b64(&self, session_id: usize) -> Result<String> {
+        Ok(STANDARD.encode(self.scrollback_bytes(session_id)?))
+    }
+
+    /// Raw (already redacted) output kept for the session, oldest first.
+    pub fn scrollback_bytes(&self, session_id: usize) -> Result<Vec<u8>> {
         let rec = self
             .sessions
             .get(&session_id)
@@ -478,7 +717,7 @@ impl SessionManager {
         let mut bytes = Vec::with_capacity(guard.len());
         bytes.extend_from_slice(a);
         bytes.extend_from_slice(b);
-        Ok(STANDARD.encode(bytes))
+        Ok(bytes)
     }
 
     pub fn shutdown(&mut self) {
@@ -523,7 +762,9 @@ impl SessionManager {
 
         let guard = self.agents.lock().expect("agent registry mutex poisoned");
         if guard.is_installed(*requested) {
-            return (*requested, None);
+            // Still launch an outdated CLI, but tell the user why flags may be rejected.
+            let warning = guard.get(*requested).and_then(|a| a.warning.clone());
+            return (*requested, warning);
         }
 
         let cmd = requested
@@ -538,13 +779,75 @@ impl SessionManager {
             )),
         )
     }
+
+    /// Command used to launch an agent CLI, honoring binary path overrides from settings.
+    fn launch_command(&self, agent_type: AgentType) -> Option<String> {
+        if agent_type == AgentType::Terminal {
+            return None;
+        }
+        let guard = self.agents.lock().expect("agent registry mutex poisoned");
+        guard
+            .get(agent_type)
+            .map(|a| a.launch_command())
+            .or_else(|| agent_type.cli_command().map(|c| c.to_string()))
+    }
+}
+
+/// Warns when the agent looks signed out, so the pane doesn't silently land on a login prompt.
+/// Provider profiles bring their own credentials and are not checked.
+fn auth_warning(app: &AppContext, agent: AgentType, args: &CreateSessionArgs) -> Option<String> {
+    if args
+        .provider_profile
+        .as_deref()
+        .is_some_and(|p| !p.trim().is_empty())
+    {
+        return None;
+    }
+    let mut probe = crate::core::settings::auth_probe(app);
+    match args.codex_provider {
+        Some(CodexProvider::Openrouter) => probe.codex_uses_openrouter = true,
+        Some(CodexProvider::Openai) => probe.codex_uses_openrouter = false,
+        None => {}
+    }
+    let auth = detect_auth(agent, &probe)?;
+    (auth.state == AuthState::Missing).then(|| {
+        format!(
+            "{} does not appear to be signed in ({}); it may stop at a login prompt",
+            agent.display_name(),
+            auth.detail
+        )
+    })
+}
+
+/// Labels saved in the project's config for `pane_index` (best-effort).
+fn saved_labels(project_path: &str, pane_index: usize) -> SessionLabels {
+    project_session_config_get(std::path::Path::new(project_path), pane_index)
+        .ok()
+        .flatten()
+        .map(|cfg| cfg.labels())
+        .and_then(|l| l.normalized().ok())
+        .unwrap_or_default()
+}
+
+/// Where turn-end checkpoints go for a session, if they are enabled for it.
+fn checkpoint_dir(app: &AppContext, agent_type: AgentType, dir: &str) -> Option<PathBuf> {
+    if agent_type == AgentType::Terminal {
+        return None;
+    }
+    let enabled = crate::core::settings::settings_get(app)
+        .map(|s| s.git.checkpoint_on_idle)
+        .unwrap_or(true);
+    let dir = PathBuf::from(dir);
+    (enabled && checkpoints::is_work_tree(&dir)).then_some(dir)
 }
 
 fn spawn_output_pump(
-    app: tauri::AppHandle,
+    app: AppContext,
     session_id: usize,
     stop: Arc<AtomicBool>,
     scrollback: Arc<std::sync::Mutex<VecDeque<u8>>>,
+    secrets: &[String],
+    watch: TurnWatch,
     handle: &mut PtyHandle,
 ) -> Result<JoinHandle<()>> {
     #[cfg(not(unix))]
@@ -552,6 +855,8 @@ fn spawn_output_pump(
         let _ = app;
         let _ = session_id;
         let _ = stop;
+        let _ = secrets;
+        let _ = watch;
         let _ = handle;
         return Err(anyhow!(
             "session output streaming is only implemented for unix targets"
@@ -562,6 +867,8 @@ fn spawn_output_pump(
     {
         let fd = handle.master_fd()?;
         let mut reader = handle.clone_reader()?;
+        let exit = handle.exit_probe();
+        let secrets = secrets.to_vec();
 
         // Minimal filter for terminal Device Status Report queries.
         // Some TUIs (including Codex CLI via crossterm) query cursor position via
@@ -749,6 +1056,9 @@ fn spawn_output_pump(
             const SCROLLBACK_CAP_BYTES: usize = 512 * 1024;
             let mut buf = [0u8; 16 * 1024];
             let mut dsr = DsrFilter::new();
+            let mut redactor = SecretRedactor::new(&secrets);
+            let mut turns = TurnTracker::default();
+            let checkpoint_running = Arc::new(AtomicBool::new(false));
 
             while !stop.load(Ordering::Relaxed) {
                 let mut pfd = libc::pollfd {
@@ -761,53 +1071,89 @@ fn spawn_output_pump(
                 if rc < 0 {
                     break;
                 }
-                if rc == 0 {
-                    continue;
-                }
-                if (pfd.revents & libc::POLLIN) == 0 {
+                let filtered = if rc == 0 {
+                    // Idle: release output held back as a possible secret prefix.
+                    let mut tail = Vec::new();
+                    redactor.flush(&mut tail);
+                    tail
+                } else if (pfd.revents & libc::POLLIN) == 0 {
                     continue;
+                } else {
+                    match reader.read(&mut buf) {
+                        Ok(0) => break,
+                        Ok(n) => {
+                            let mut unfiltered: Vec<u8> = Vec::with_capacity(n);
+                            dsr.feed(fd, &buf[..n], &mut unfiltered);
+                            let mut filtered: Vec<u8> = Vec::with_capacity(unfiltered.len());
+                            redactor.feed(&unfiltered, &mut filtered);
+                            filtered
+                        }
+                        Err(_) => break,
+                    }
+                };
+                let now = std::time::Instant::now();
+                if watch.turn.submitted.swap(false, Ordering::Relaxed) {
+                    turns.submitted(now);
                 }
-
-                match reader.read(&mut buf) {
-                    Ok(0) => break,
-                    Ok(n) => {
-                        let mut filtered: Vec<u8> = Vec::with_capacity(n);
-                        dsr.feed(fd, &buf[..n], &mut filtered);
-                        if filtered.is_empty() {
-                            continue;
+                if filtered.is_empty() {
+                    if turns.turn_ended(now) {
+                        watch.turn.busy.store(false, Ordering::Relaxed);
+                        if watch.agent_type != AgentType::Terminal {
+                            publish(
+                                &app,
+                                AppEvent::SessionIdle(SessionIdleEvent {
+                                    session_id,
+                                    agent_type: watch.agent_type,
+                                }),
+                            );
                         }
-
-                        // Keep a bounded in-memory scrollback so the UI can restore content
-                        // after React unmounts/remounts (e.g. Home -> Workspace navigation).
-                        if let Ok(mut sb) = scrollback.lock() {
-                            for &b in &filtered {
-                                sb.push_back(b);
-                            }
-                            while sb.len() > SCROLLBACK_CAP_BYTES {
-                                sb.pop_front();
+                        if let Some(dir) = &watch.checkpoint_dir {
+                            if !checkpoint_running.swap(true, Ordering::SeqCst) {
+                                spawn_checkpoint(
+                                    app.clone(),
+                                    session_id,
+                                    watch.session_key.clone(),
+                                    dir.clone(),
+                                    checkpoint_running.clone(),
+                                );
                             }
                         }
+                    }
+                    continue;
+                }
+                if turns.output(now) {
+                    watch.turn.busy.store(true, Ordering::Relaxed);
+                }
+                track_output_cost(&app, session_id, &watch, &filtered);
 
-                        let data_b64 = STANDARD.encode(&filtered);
-                        let _ = app.emit(
-                            "session:output",
-                            SessionOutputEvent {
-                                session_id,
-                                data_b64,
-                            },
-                        );
+                // Keep a bounded in-memory scrollback so the UI can restore content
+                // after React unmounts/remounts (e.g. Home -> Workspace navigation).
+                if let Ok(mut sb) = scrollback.lock() {
+                    for &b in &filtered {
+                        sb.push_back(b);
+                    }
+                    while sb.len() > SCROLLBACK_CAP_BYTES {
+                        sb.pop_front();
                     }
-                    Err(_) => break,
                 }
+
+                let data_b64 = STANDARD.encode(&filtered);
+                publish(
+                    &app,
+                    AppEvent::SessionOutput(SessionOutputEvent {
+                        session_id,
+                        data_b64,
+                    }),
+                );
             }
 
             if !stop.load(Ordering::Relaxed) {
-                let _ = app.emit(
-                    "session:exit",
-                    SessionExitEvent {
+                publish(
+                    &app,
+                    AppEvent::SessionExit(SessionExitEvent {
                         session_id,
-                        exit_code: -1,
-                    },
+                        exit_code: exit.wait(std::time::Duration::from_secs(2)),
+                    }),
                 );
             }
         });
@@ -816,9 +1162,65 @@ fn spawn_output_pump(
     }
 }
 
-fn shell_single_quote_escape(s: &str) -> String {
-    // Bash-safe single-quote escaping: ' -> '\''.
-    s.replace('\'', "'\\''")
+/// Feeds a chunk of output, and the input typed since the last one, to the session's cost
+/// tracker. Usage the agent prints counts until the LLM gateway meters the session.
+#[cfg(unix)]
+fn track_output_cost(app: &AppContext, session_id: usize, watch: &TurnWatch, output: &[u8]) {
+    if watch.agent_type == AgentType::Terminal {
+        return;
+    }
+    let Some(trackers) = app.try_state::<SharedCostTrackers>() else {
+        return;
+    };
+    let typed = watch.turn.typed_chars.swap(0, Ordering::Relaxed);
+    let cost = trackers
+        .lock()
+        .expect("cost tracker mutex poisoned")
+        .ingest_output(session_id, typed, output, || {
+            CostTracker::new(watch.agent_type, watch.model.clone(), app)
+        });
+    if let Some(cost) = cost {
+        publish(
+            app,
+            AppEvent::CostUpdated(SessionCostUpdatedEvent { session_id, cost }),
+        );
+    }
+}
+
+/// Snapshots the session's worktree off the pump thread; `running` guards against overlap.
+#[cfg(unix)]
+fn spawn_checkpoint(
+    app: AppContext,
+    session_id: usize,
+    session_key: String,
+    dir: PathBuf,
+    running: Arc<AtomicBool>,
+) {
+    thread::spawn(move || {
+        match checkpoints::create_checkpoint(&dir, &session_key) {
+            Ok(Some(checkpoint)) => {
+                publish(
+                    &app,
+                    AppEvent::SessionCheckpoint(SessionCheckpointEvent {
+                        session_id,
+                        checkpoint,
+                    }),
+                );
+            }
+            Ok(None) => {}
+            Err(err) => eprintln!("checkpoint session {session_id}: {err:#}"),
+        }
+        running.store(false, Ordering::SeqCst);
+    });
+}
+
+/// Start time plus the run-local id, so keys never repeat across runs.
+fn new_session_key(session_id: usize) -> String {
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    format!("{millis}-{session_id}")
 }
 
 fn normalized_model(model: Option<&str>) -> Option<String> {
@@ -834,6 +1236,7 @@ fn agent_type_to_env_value(t: AgentType) -> &'static str {
         AgentType::GeminiCli => "gemini_cli",
         AgentType::Codex => "codex",
         AgentType::Openrouter => "openrouter",
+        AgentType::Simulated => "simulated",
         AgentType::Terminal => "terminal",
     }
 }
@@ -876,38 +1279,66 @@ fn agent_command_with_model(
             }
             cmd
         }
+        // The model picker doubles as the scenario selector (builtin name or JSON path).
+        AgentType::Simulated => {
+            let mut cmd = format!("{base_cmd} {SIMULATED_AGENT_FLAG}");
+            if let Some(scenario) = model.map(str::trim).filter(|s| !s.is_empty()) {
+                let s = shell_single_quote_escape(scenario);
+                cmd.push_str(&format!(" --scenario '{s}'"));
+            }
+            cmd
+        }
         AgentType::Terminal => base_cmd.to_string(),
     }
 }
 
-fn openrouter_codex_home(app: &tauri::AppHandle) -> Result<PathBuf> {
+/// Command-line suffix that starts an interactive agent on `prompt`.
+fn initial_prompt_arg(agent: AgentType, prompt: &str) -> String {
+    let p = shell_single_quote_escape(prompt);
+    match agent {
+        AgentType::GeminiCli => format!(" --prompt-interactive '{p}'"),
+        AgentType::Simulated => format!(" --prompt '{p}'"),
+        // Claude Code and Codex take the prompt as a positional argument.
+        AgentType::ClaudeCode | AgentType::Codex | AgentType::Openrouter => format!(" -- '{p}'"),
+        AgentType::Terminal => String::new(),
+    }
+}
+
+fn openrouter_codex_home(app: &AppContext) -> Result<PathBuf> {
     let dir = app
-        .path()
-        .resolve("synk/codex-openrouter", BaseDirectory::Config)
+        .config_path("codex-openrouter")
         .map_err(|e| anyhow!("resolve config path for openrouter codex home: {e}"))?;
     fs::create_dir_all(&dir)
         .map_err(|e| anyhow!("create openrouter codex home {}: {e}", dir.display()))?;
     Ok(dir)
 }
 
-fn set_or_unset_env(handle: &mut PtyHandle, key: &str, value: Option<&str>) -> Result<()> {
-    if !is_valid_env_var_name(key) {
-        return Err(anyhow!("invalid env var name: {key}"));
-    }
-    match value {
-        Some(v) => handle.write_str(&format!(
-            "export {}='{}'\r\n",
-            key,
-            shell_single_quote_escape(v)
-        ))?,
-        None => handle.write_str(&format!("unset {}\r\n", key))?,
+/// The caller's `env` for a session; values under secret-looking names are redacted from output.
+fn apply_extra_env(env: &mut SessionEnv, extra: &HashMap<String, String>) -> Result<()> {
+    for (k, v) in extra {
+        if looks_secret(k) {
+            env.set_secret(k, Some(v))?;
+        } else {
+            env.set(k, Some(v))?;
+        }
     }
     Ok(())
 }
 
+fn set_session_identity_env(
+    env: &mut SessionEnv,
+    session_id: usize,
+    agent: AgentType,
+    project_path: &str,
+) -> Result<()> {
+    env.set("SYNK_SESSION_ID", Some(&session_id.to_string()))?;
+    env.set("SYNK_AGENT_TYPE", Some(agent_type_to_env_value(agent)))?;
+    env.set("SYNK_PROJECT_PATH", Some(project_path))
+}
+
 fn apply_codex_provider_env(
-    handle: &mut PtyHandle,
-    app: &tauri::AppHandle,
+    env: &mut SessionEnv,
+    app: &AppContext,
     agent: AgentType,
     codex_provider: Option<CodexProvider>,
     model: Option<&str>,
@@ -916,7 +1347,7 @@ fn apply_codex_provider_env(
         return Ok(false);
     }
 
-    let settings = match crate::core::settings::settings_get(app) {
+    let settings = match crate::core::settings::settings_get_with_secrets(app) {
         Ok(v) => v,
         Err(_) => return Ok(false),
     };
@@ -940,45 +1371,247 @@ fn apply_codex_provider_env(
         let key = settings.ai_providers.openrouter.api_key.unwrap_or_default();
         let key = key.trim();
         let codex_home = openrouter_codex_home(app)?;
-        set_or_unset_env(
-            handle,
-            "OPENAI_BASE_URL",
-            Some("https://openrouter.ai/api/v1"),
-        )?;
-        set_or_unset_env(
-            handle,
-            "OPENAI_API_KEY",
-            if key.is_empty() { None } else { Some(key) },
-        )?;
-        set_or_unset_env(
-            handle,
-            "OPENROUTER_API_KEY",
-            if key.is_empty() { None } else { Some(key) },
-        )?;
-        set_or_unset_env(
-            handle,
-            "CODEX_HOME",
-            Some(codex_home.to_string_lossy().as_ref()),
-        )?;
+        let key = if key.is_empty() { None } else { Some(key) };
+        env.set("OPENAI_BASE_URL", Some("https://openrouter.ai/api/v1"))?;
+        env.set_secret("OPENAI_API_KEY", key)?;
+        env.set_secret("OPENROUTER_API_KEY", key)?;
+        env.set("CODEX_HOME", Some(codex_home.to_string_lossy().as_ref()))?;
     } else {
         let key = settings.ai_providers.openai.api_key.unwrap_or_default();
         let key = key.trim();
-        set_or_unset_env(handle, "OPENAI_BASE_URL", None)?;
-        set_or_unset_env(
-            handle,
-            "OPENAI_API_KEY",
-            if key.is_empty() { None } else { Some(key) },
-        )?;
-        set_or_unset_env(handle, "OPENROUTER_API_KEY", None)?;
-        set_or_unset_env(handle, "CODEX_HOME", None)?;
+        env.set("OPENAI_BASE_URL", None)?;
+        env.set_secret("OPENAI_API_KEY", if key.is_empty() { None } else { Some(key) })?;
+        env.set("OPENROUTER_API_KEY", None)?;
+        env.set("CODEX_HOME", None)?;
     }
 
     Ok(use_openrouter)
 }
 
+fn resolve_provider_profile(
+    app: &AppContext,
+    agent: AgentType,
+    profile_id: Option<&str>,
+) -> Result<Option<ProviderProfileView>> {
+    let Some(id) = profile_id.map(str::trim).filter(|s| !s.is_empty()) else {
+        return Ok(None);
+    };
+    // The agent fell back to a plain shell (CLI missing); there is nothing to configure.
+    if agent == AgentType::Terminal {
+        return Ok(None);
+    }
+
+    let settings = crate::core::settings::settings_get_with_secrets(app)?;
+    let profile = crate::core::settings::find_provider_profile(&settings, id)
+        .ok_or_else(|| anyhow!("unknown provider profile: {id}"))?;
+
+    let same_family = match agent {
+        AgentType::Codex | AgentType::Openrouter => matches!(
+            profile.agent_type,
+            AgentType::Codex | AgentType::Openrouter
+        ),
+        other => profile.agent_type == other,
+    };
+    if !same_family || profile.api_provider().is_none() {
+        return Err(anyhow!(
+            "provider profile '{}' is for {}, not {}",
+            profile.name,
+            profile.agent_type.display_name(),
+            agent.display_name()
+        ));
+    }
+    Ok(Some(profile))
+}
+
+fn profile_codex_home(app: &AppContext, profile_id: &str) -> Result<PathBuf> {
+    let slug: String = profile_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let dir = app
+        .config_path(format!("codex-profiles/{slug}"))
+        .map_err(|e| anyhow!("resolve config path for codex profile home: {e}"))?;
+    fs::create_dir_all(&dir)
+        .map_err(|e| anyhow!("create codex profile home {}: {e}", dir.display()))?;
+    Ok(dir)
+}
+
+/// Env assignments for a provider profile (`None` = unset). Variables Synk sets are recorded
+/// in `SYNK_PROVIDER_ENV_KEYS` so a recycled shell can drop them for the next session.
+fn provider_profile_env(
+    profile: &ProviderProfileView,
+    codex_home: Option<&str>,
+) -> Vec<(String, Option<String>)> {
+    let codex = profile.api_provider() == Some("openai");
+    let base_var = if codex {
+        "OPENAI_BASE_URL"
+    } else {
+        "ANTHROPIC_BASE_URL"
+    };
+    let key_var = profile.key_env_var();
+
+    let mut out: Vec<(String, Option<String>)> = Vec::new();
+    let mut owned: Vec<String> = vec![base_var.to_string()];
+    out.push((
+        base_var.to_string(),
+        profile.custom_base_url().map(|s| s.to_string()),
+    ));
+
+    // Without a stored key, leave the variable alone so a key from the user's shell is used.
+    if let Some(key) = profile
+        .api_key
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        out.push((key_var.clone(), Some(key.to_string())));
+        owned.push(key_var);
+    }
+
+    if codex {
+        out.push(("OPENROUTER_API_KEY".to_string(), None));
+        out.push(("CODEX_HOME".to_string(), codex_home.map(|s| s.to_string())));
+        owned.push("CODEX_HOME".to_string());
+    }
+
+    out.push(("SYNK_PROVIDER_PROFILE".to_string(), Some(profile.id.clone())));
+    out.push(("SYNK_PROVIDER_ENV_KEYS".to_string(), Some(owned.join(" "))));
+    out
+}
+
+fn clear_provider_profile_env(env: &mut SessionEnv) {
+    env.raw(
+        "if [ -n \"${SYNK_PROVIDER_ENV_KEYS:-}\" ]; then for k in $(printf '%s' \"$SYNK_PROVIDER_ENV_KEYS\"); do unset \"$k\"; done; unset SYNK_PROVIDER_ENV_KEYS SYNK_PROVIDER_PROFILE; fi",
+    );
+    env.raw(
+        "if [ -n \"${SYNK_GATEWAY_ENV_KEYS:-}\" ]; then for k in $(printf '%s' \"$SYNK_GATEWAY_ENV_KEYS\"); do unset \"$k\"; done; unset SYNK_GATEWAY_ENV_KEYS; fi",
+    );
+}
+
+fn apply_provider_env(
+    env: &mut SessionEnv,
+    app: &AppContext,
+    agent: AgentType,
+    codex_provider: Option<CodexProvider>,
+    profile: Option<&ProviderProfileView>,
+    model: Option<&str>,
+) -> Result<bool> {
+    // Drop env left behind by a previous session in a recycled shell.
+    clear_provider_profile_env(env);
+    let Some(profile) = profile else {
+        return apply_codex_provider_env(env, app, agent, codex_provider, model);
+    };
+
+    let codex = profile.api_provider() == Some("openai");
+    let codex_home = if codex {
+        Some(profile_codex_home(app, &profile.id)?)
+    } else {
+        None
+    };
+    let codex_home = codex_home.as_ref().map(|p| p.to_string_lossy().to_string());
+    let key_var = profile.key_env_var();
+    for (key, value) in provider_profile_env(profile, codex_home.as_deref()) {
+        if key == key_var {
+            env.set_secret(&key, value.as_deref())?;
+        } else {
+            env.set(&key, value.as_deref())?;
+        }
+    }
+
+    // Codex must use the API key rather than a cached ChatGPT login for custom endpoints.
+    Ok(codex)
+}
+
+/// Upstream the gateway forwards to for a session: what the agent would have used directly.
+fn gateway_upstream(
+    route: &str,
+    profile: Option<&ProviderProfileView>,
+    codex_uses_openrouter: bool,
+    shell_base_url: Option<&str>,
+) -> String {
+    let custom = match profile {
+        Some(p) => p.custom_base_url(),
+        // Without a profile, Codex's base URL is set explicitly; Claude Code keeps the user's.
+        None if route == ANTHROPIC_ROUTE => shell_base_url.map(str::trim).filter(|s| !s.is_empty()),
+        None => None,
+    };
+    if let Some(base) = custom {
+        return base.to_string();
+    }
+    match route {
+        OPENAI_ROUTE if codex_uses_openrouter => "https://openrouter.ai/api/v1".to_string(),
+        OPENAI_ROUTE => "https://api.openai.com/v1".to_string(),
+        _ => "https://api.anthropic.com".to_string(),
+    }
+}
+
+/// When the LLM gateway is enabled, points the agent's base URL at it and registers the real
+/// upstream for this session. Best-effort: if the gateway can't start, the agent talks to the
+/// provider directly.
+fn apply_gateway_env(
+    env: &mut SessionEnv,
+    app: &AppContext,
+    session_id: usize,
+    agent: AgentType,
+    profile: Option<&ProviderProfileView>,
+    codex_uses_openrouter: bool,
+) -> Result<()> {
+    let (route, var) = match agent {
+        AgentType::Codex | AgentType::Openrouter => (OPENAI_ROUTE, "OPENAI_BASE_URL"),
+        AgentType::ClaudeCode => (ANTHROPIC_ROUTE, "ANTHROPIC_BASE_URL"),
+        _ => return Ok(()),
+    };
+    let Ok(settings) = crate::core::settings::settings_get(app) else {
+        return Ok(());
+    };
+    if !settings.gateway.enabled {
+        return Ok(());
+    }
+    let Some(gateway) = app.try_state::<SharedLlmGateway>() else {
+        return Ok(());
+    };
+
+    let shell_base_url = std::env::var("ANTHROPIC_BASE_URL").ok();
+    let upstream = gateway_upstream(
+        route,
+        profile,
+        codex_uses_openrouter,
+        shell_base_url.as_deref(),
+    );
+    let base = {
+        let mut gw = gateway
+            .inner()
+            .lock()
+            .map_err(|_| anyhow!("gateway mutex poisoned"))?;
+        if let Err(err) = gw.ensure_started(Some(app.clone()), settings.gateway.port) {
+            eprintln!("llm gateway unavailable, session {session_id} goes direct: {err:#}");
+            return Ok(());
+        }
+        gw.set_default_rate_cap(settings.gateway.max_requests_per_minute);
+        gw.register_session(session_id, vec![(route.to_string(), upstream)]);
+        gw.base_url(session_id, route)
+            .zip(gw.session_token(session_id))
+    };
+    let Some((base, token)) = base else {
+        return Ok(());
+    };
+
+    env.set(var, Some(&base))?;
+    // The token authenticates the session to the gateway; keep it out of output and scrollback.
+    env.add_secret(&token);
+    env.set("SYNK_GATEWAY_ENV_KEYS", Some(var))?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{agent_command_with_model, is_valid_env_var_name, AgentType};
+    use std::collections::HashMap;
+
+    use super::{
+        agent_command_with_model, apply_extra_env, gateway_upstream, initial_prompt_arg,
+        provider_profile_env, AgentType, ProviderProfileView, ANTHROPIC_ROUTE, OPENAI_ROUTE,
+    };
+    use crate::core::session_env::{is_valid_env_var_name, SessionEnv};
 
     #[test]
     fn env_var_name_validation() {
@@ -999,6 +1632,23 @@ mod tests {
         assert!(cmd.contains("-c 'model_reasoning_effort=\"high\"'"));
     }
 
+    #[test]
+    fn initial_prompt_is_quoted_on_the_command_line() {
+        assert_eq!(
+            initial_prompt_arg(AgentType::ClaudeCode, "don't stop"),
+            " -- 'don'\\''t stop'"
+        );
+        assert_eq!(
+            initial_prompt_arg(AgentType::GeminiCli, "go"),
+            " --prompt-interactive 'go'"
+        );
+        assert_eq!(
+            initial_prompt_arg(AgentType::Simulated, "go"),
+            " --prompt 'go'"
+        );
+        assert_eq!(initial_prompt_arg(AgentType::Terminal, "go"), "");
+    }
+
     #[test]
     fn codex_command_adds_model_override() {
         let cmd = agent_command_with_model(
@@ -1015,4 +1665,100 @@ mod tests {
         let cmd = agent_command_with_model(AgentType::Codex, "codex", None, true);
         assert!(cmd.contains("-c 'forced_login_method=\"api\"'"));
     }
+
+    #[test]
+    fn codex_profile_env_sets_base_url_and_key() {
+        let profile = ProviderProfileView {
+            id: "gw".to_string(),
+            name: "Gateway".to_string(),
+            agent_type: AgentType::Codex,
+            base_url: "http://localhost:11434/v1/".to_string(),
+            api_key: Some("sk-test".to_string()),
+            api_key_env: None,
+            model: Some("qwen2.5-coder".to_string()),
+        };
+        let env = provider_profile_env(&profile, Some("/tmp/codex-gw"));
+        let get = |k: &str| env.iter().find(|(n, _)| n == k).map(|(_, v)| v.clone());
+        assert_eq!(
+            get("OPENAI_BASE_URL"),
+            Some(Some("http://localhost:11434/v1/".to_string()))
+        );
+        assert_eq!(get("OPENAI_API_KEY"), Some(Some("sk-test".to_string())));
+        assert_eq!(get("OPENROUTER_API_KEY"), Some(None));
+        assert_eq!(get("CODEX_HOME"), Some(Some("/tmp/codex-gw".to_string())));
+        assert_eq!(
+            get("SYNK_PROVIDER_ENV_KEYS"),
+            Some(Some("OPENAI_BASE_URL OPENAI_API_KEY CODEX_HOME".to_string()))
+        );
+    }
+
+    #[test]
+    fn claude_profile_env_uses_custom_key_var_and_keeps_shell_key() {
+        let mut profile = ProviderProfileView {
+            id: "proxy".to_string(),
+            name: "Proxy".to_string(),
+            agent_type: AgentType::ClaudeCode,
+            base_url: "https://llm-gateway.internal".to_string(),
+            api_key: Some("tok".to_string()),
+            api_key_env: Some("ANTHROPIC_AUTH_TOKEN".to_string()),
+            model: None,
+        };
+        let env = provider_profile_env(&profile, None);
+        assert!(env.contains(&(
+            "ANTHROPIC_BASE_URL".to_string(),
+            Some("https://llm-gateway.internal".to_string())
+        )));
+        assert!(env.contains(&("ANTHROPIC_AUTH_TOKEN".to_string(), Some("tok".to_string()))));
+        assert!(!env.iter().any(|(k, _)| k == "CODEX_HOME"));
+
+        profile.api_key = None;
+        let env = provider_profile_env(&profile, None);
+        assert!(!env.iter().any(|(k, _)| k == "ANTHROPIC_AUTH_TOKEN"));
+    }
+
+    #[test]
+    fn gateway_upstream_matches_direct_endpoint() {
+        let profile = ProviderProfileView {
+            id: "local".to_string(),
+            agent_type: AgentType::ClaudeCode,
+            base_url: "http://10.0.0.2:4000".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            gateway_upstream(
+                ANTHROPIC_ROUTE,
+                Some(&profile),
+                false,
+                Some("http://ignored")
+            ),
+            "http://10.0.0.2:4000"
+        );
+        assert_eq!(
+            gateway_upstream(ANTHROPIC_ROUTE, None, false, Some("http://corp-proxy")),
+            "http://corp-proxy"
+        );
+        assert_eq!(
+            gateway_upstream(ANTHROPIC_ROUTE, None, false, None),
+            "https://api.anthropic.com"
+        );
+        assert_eq!(
+            gateway_upstream(OPENAI_ROUTE, None, true, None),
+            "https://openrouter.ai/api/v1"
+        );
+        assert_eq!(
+            gateway_upstream(OPENAI_ROUTE, None, false, None),
+            "https://api.openai.com/v1"
+        );
+    }
+
+    #[test]
+    fn extra_env_secrets_are_redacted() {
+        let extra = HashMap::from([
+            ("GITHUB_TOKEN".to_string(), "ghp_123456789".to_string()),
+            ("MODE".to_string(), "fast-mode".to_string()),
+        ]);
+        let mut env = SessionEnv::new();
+        apply_extra_env(&mut env, &extra).unwrap();
+        assert_eq!(env.secrets(), ["ghp_123456789"]);
+    }
 }
@@ -1,13 +1,15 @@
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 use std::time::Duration;
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use tauri::path::BaseDirectory;
-use tauri::Manager;
 
+use crate::core::agent_detection::{AgentType, AuthProbe};
+use crate::core::app_context::AppContext;
 use crate::core::process_pool::PoolConfig;
+use crate::core::secrets::{is_masked, mask, SecretStore, MASK_PREFIX};
 
 // -----------------------------------------------------------------------------
 // Disk schema (snake_case) matches `~/.config/synk/settings.json` spec.
@@ -50,7 +52,10 @@ impl From<AuthModeView> for AuthModeDisk {
 #[serde(rename_all = "snake_case", default)]
 pub struct ProviderAuthDisk {
     pub auth_mode: Option<AuthModeDisk>,
+    // Legacy plaintext key; moved into the secrets store on load.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub api_key: Option<String>,
+    pub api_key_ref: Option<String>, // name in the encrypted secrets store
     pub oauth_connected: bool,
     pub oauth_email: Option<String>,
     pub default_model: String,
@@ -72,6 +77,37 @@ impl Default for OllamaDisk {
     }
 }
 
+/// Named endpoint for an agent CLI: Codex against an OpenAI-compatible server (Ollama,
+/// vLLM, an internal gateway) or Claude Code against an `ANTHROPIC_BASE_URL` proxy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct ProviderProfileDisk {
+    pub id: String,
+    pub name: String,
+    pub agent_type: AgentType,
+    pub base_url: String, // empty = the provider's public API
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>, // legacy plaintext, see ProviderAuthDisk
+    pub api_key_ref: Option<String>,
+    pub api_key_env: Option<String>, // defaults to OPENAI_API_KEY / ANTHROPIC_API_KEY
+    pub model: Option<String>,
+}
+
+impl Default for ProviderProfileDisk {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            name: String::new(),
+            agent_type: AgentType::Codex,
+            base_url: String::new(),
+            api_key: None,
+            api_key_ref: None,
+            api_key_env: None,
+            model: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", default)]
 pub struct AiProvidersDisk {
@@ -81,6 +117,7 @@ pub struct AiProvidersDisk {
     pub openai: ProviderAuthDisk,
     pub openrouter: ProviderAuthDisk,
     pub ollama: OllamaDisk,
+    pub profiles: Vec<ProviderProfileDisk>,
 }
 
 impl Default for AiProvidersDisk {
@@ -90,6 +127,7 @@ impl Default for AiProvidersDisk {
             anthropic: ProviderAuthDisk {
                 auth_mode: Some(AuthModeDisk::ApiKey),
                 api_key: None,
+                api_key_ref: None,
                 oauth_connected: false,
                 oauth_email: None,
                 default_model: "claude-opus-4-6".to_string(),
@@ -97,6 +135,7 @@ impl Default for AiProvidersDisk {
             google: ProviderAuthDisk {
                 auth_mode: Some(AuthModeDisk::ApiKey),
                 api_key: None,
+                api_key_ref: None,
                 oauth_connected: false,
                 oauth_email: None,
                 default_model: "gemini-2.0-flash".to_string(),
@@ -104,6 +143,7 @@ impl Default for AiProvidersDisk {
             openai: ProviderAuthDisk {
                 auth_mode: None,
                 api_key: None,
+                api_key_ref: None,
                 oauth_connected: false,
                 oauth_email: None,
                 // Used for Codex panes today (Codex CLI) and as the OpenAI default generally.
@@ -112,11 +152,13 @@ impl Default for AiProvidersDisk {
             openrouter: ProviderAuthDisk {
                 auth_mode: Some(AuthModeDisk::ApiKey),
                 api_key: None,
+                api_key_ref: None,
                 oauth_connected: false,
                 oauth_email: None,
                 default_model: "openrouter/auto".to_string(),
             },
             ollama: OllamaDisk::default(),
+            profiles: Vec::new(),
         }
     }
 }
@@ -227,6 +269,13 @@ pub struct GitDisk {
     pub auto_delegate_conflicts: bool,
     pub worktree_base_path: String,
     pub branch_prefix: String,
+    /// Snapshot agent worktrees into checkpoint refs at the end of each turn.
+    pub checkpoint_on_idle: bool,
+    /// Keep conflicted merges in progress in an integration worktree instead of aborting.
+    pub interactive_conflicts: bool,
+    /// Run in the merge queue's worktree on each merged result before the base branch moves.
+    pub queue_check_command: Option<String>,
+    pub queue_check_timeout_secs: u64,
 }
 
 impl Default for GitDisk {
@@ -236,6 +285,10 @@ impl Default for GitDisk {
             auto_delegate_conflicts: true,
             worktree_base_path: "~/.synk/worktrees".to_string(),
             branch_prefix: "feat/".to_string(),
+            checkpoint_on_idle: true,
+            interactive_conflicts: false,
+            queue_check_command: None,
+            queue_check_timeout_secs: 1800,
         }
     }
 }
@@ -274,6 +327,82 @@ impl Default for GastownDisk {
     }
 }
 
+/// Binary path overrides for agent CLIs installed outside `PATH` (nvm, volta, ...).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case", default)]
+pub struct AgentsDisk {
+    pub claude_code_path: Option<String>,
+    pub gemini_cli_path: Option<String>,
+    /// Also used for OpenRouter sessions (they run Codex).
+    pub codex_path: Option<String>,
+}
+
+/// Local metering proxy for agent API traffic (see `core::llm_gateway`).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case", default)]
+pub struct GatewayDisk {
+    pub enabled: bool,
+    /// 0 = pick a free port at startup.
+    pub port: u16,
+    /// Default per-session cap; 0 = unlimited.
+    pub max_requests_per_minute: u32,
+}
+
+/// HTTP + WebSocket API for browsers and scripts (see `core::remote_api`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct RemoteApiDisk {
+    pub enabled: bool,
+    pub port: u16,
+    /// Listen on every interface instead of 127.0.0.1 (e.g. for a tablet on the LAN).
+    pub bind_lan: bool,
+}
+
+impl Default for RemoteApiDisk {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 4777,
+            bind_lan: false,
+        }
+    }
+}
+
+/// Most tries a hook gets per delivery; saved `max_attempts` values are clamped to this.
+pub const MAX_HOOK_ATTEMPTS: u32 = 10;
+
+/// Outbound webhook or local command fired on selected events (see `core::hooks`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct HookDisk {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    /// `review_ready`, `merge_completed`, `conflict_detected`, `session_crashed`,
+    /// `budget_exceeded`.
+    pub events: Vec<String>,
+    /// POST the payload as JSON to this URL...
+    pub url: Option<String>,
+    /// ...or run this through the shell with the payload on stdin.
+    pub command: Option<String>,
+    /// Including the first try.
+    pub max_attempts: u32,
+}
+
+impl Default for HookDisk {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            name: String::new(),
+            enabled: true,
+            events: Vec::new(),
+            url: None,
+            command: None,
+            max_attempts: 3,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", default)]
 pub struct SettingsDisk {
@@ -286,12 +415,16 @@ pub struct SettingsDisk {
     pub git: GitDisk,
     pub session: SessionDisk,
     pub gastown: GastownDisk,
+    pub gateway: GatewayDisk,
+    pub remote_api: RemoteApiDisk,
+    pub agents: AgentsDisk,
+    pub hooks: Vec<HookDisk>,
 }
 
 impl Default for SettingsDisk {
     fn default() -> Self {
         Self {
-            version: 4,
+            version: 5,
             ai_providers: AiProvidersDisk::default(),
             performance: PerformanceDisk::default(),
             keyboard: KeyboardDisk::default(),
@@ -300,6 +433,10 @@ impl Default for SettingsDisk {
             git: GitDisk::default(),
             session: SessionDisk::default(),
             gastown: GastownDisk::default(),
+            gateway: GatewayDisk::default(),
+            remote_api: RemoteApiDisk::default(),
+            agents: AgentsDisk::default(),
+            hooks: Vec::new(),
         }
     }
 }
@@ -334,6 +471,76 @@ impl Default for OllamaView {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProviderProfileView {
+    pub id: String,
+    pub name: String,
+    pub agent_type: AgentType,
+    pub base_url: String,
+    pub api_key: Option<String>,
+    pub api_key_env: Option<String>,
+    pub model: Option<String>,
+}
+
+impl Default for ProviderProfileView {
+    fn default() -> Self {
+        ProviderProfileDisk::default().into()
+    }
+}
+
+impl ProviderProfileView {
+    /// Provider name understood by `validate_provider_key` / `list_provider_models`.
+    pub fn api_provider(&self) -> Option<&'static str> {
+        match self.agent_type {
+            AgentType::Codex | AgentType::Openrouter => Some("openai"),
+            AgentType::ClaudeCode => Some("anthropic"),
+            AgentType::GeminiCli | AgentType::Simulated | AgentType::Terminal => None,
+        }
+    }
+
+    pub fn custom_base_url(&self) -> Option<&str> {
+        let v = self.base_url.trim();
+        if v.is_empty() {
+            None
+        } else {
+            Some(v)
+        }
+    }
+
+    /// Environment variable the agent reads its key from.
+    pub fn key_env_var(&self) -> String {
+        if let Some(v) = self
+            .api_key_env
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        {
+            return v.to_string();
+        }
+        match self.api_provider() {
+            Some("anthropic") => "ANTHROPIC_API_KEY".to_string(),
+            _ => "OPENAI_API_KEY".to_string(),
+        }
+    }
+
+    /// Stored key, falling back to the key env var in Synk's own environment.
+    pub fn resolve_api_key(&self) -> Option<String> {
+        if let Some(v) = self
+            .api_key
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        {
+            return Some(v.to_string());
+        }
+        std::env::var(self.key_env_var())
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", default)]
 pub struct AiProvidersView {
@@ -343,6 +550,7 @@ pub struct AiProvidersView {
     pub openai: ProviderAuthView,
     pub openrouter: ProviderAuthView,
     pub ollama: OllamaView,
+    pub profiles: Vec<ProviderProfileView>,
 }
 
 impl Default for AiProvidersView {
@@ -425,6 +633,10 @@ pub struct GitView {
     pub auto_delegate_conflicts: bool,
     pub worktree_base_path: String,
     pub branch_prefix: String,
+    pub checkpoint_on_idle: bool,
+    pub interactive_conflicts: bool,
+    pub queue_check_command: Option<String>,
+    pub queue_check_timeout_secs: u64,
 }
 
 impl Default for GitView {
@@ -460,6 +672,60 @@ impl Default for GastownView {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AgentsView {
+    pub claude_code_path: Option<String>,
+    pub gemini_cli_path: Option<String>,
+    pub codex_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GatewayView {
+    pub enabled: bool,
+    pub port: u16,
+    pub max_requests_per_minute: u32,
+}
+
+impl Default for GatewayView {
+    fn default() -> Self {
+        GatewayDisk::default().into()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RemoteApiView {
+    pub enabled: bool,
+    pub port: u16,
+    pub bind_lan: bool,
+}
+
+impl Default for RemoteApiView {
+    fn default() -> Self {
+        RemoteApiDisk::default().into()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HookView {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub events: Vec<String>,
+    pub url: Option<String>,
+    pub command: Option<String>,
+    pub max_attempts: u32,
+}
+
+impl Default for HookView {
+    fn default() -> Self {
+        HookDisk::default().into()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", default)]
 pub struct SettingsView {
@@ -472,6 +738,10 @@ pub struct SettingsView {
     pub git: GitView,
     pub session: SessionView,
     pub gastown: GastownView,
+    pub gateway: GatewayView,
+    pub remote_api: RemoteApiView,
+    pub agents: AgentsView,
+    pub hooks: Vec<HookView>,
 }
 
 impl Default for SettingsView {
@@ -497,6 +767,7 @@ impl From<ProviderAuthView> for ProviderAuthDisk {
         Self {
             auth_mode: v.auth_mode.map(AuthModeDisk::from),
             api_key: v.api_key,
+            api_key_ref: None,
             oauth_connected: v.oauth_connected,
             oauth_email: v.oauth_email,
             default_model: v.default_model,
@@ -531,6 +802,7 @@ impl From<AiProvidersDisk> for AiProvidersView {
             openai: v.openai.into(),
             openrouter: v.openrouter.into(),
             ollama: v.ollama.into(),
+            profiles: v.profiles.into_iter().map(Into::into).collect(),
         }
     }
 }
@@ -544,6 +816,7 @@ impl From<AiProvidersView> for AiProvidersDisk {
             openai: v.openai.into(),
             openrouter: v.openrouter.into(),
             ollama: v.ollama.into(),
+            profiles: v.profiles.into_iter().map(Into::into).collect(),
         }
     }
 }
@@ -563,6 +836,34 @@ macro_rules! trivial_from {
     };
 }
 
+impl From<ProviderProfileDisk> for ProviderProfileView {
+    fn from(v: ProviderProfileDisk) -> Self {
+        Self {
+            id: v.id,
+            name: v.name,
+            agent_type: v.agent_type,
+            base_url: v.base_url,
+            api_key: v.api_key,
+            api_key_env: v.api_key_env,
+            model: v.model,
+        }
+    }
+}
+
+impl From<ProviderProfileView> for ProviderProfileDisk {
+    fn from(v: ProviderProfileView) -> Self {
+        Self {
+            id: v.id,
+            name: v.name,
+            agent_type: v.agent_type,
+            base_url: v.base_url,
+            api_key: v.api_key,
+            api_key_ref: None,
+            api_key_env: v.api_key_env,
+            model: v.model,
+        }
+    }
+}
 trivial_from!(PerformanceDisk, PerformanceView, {
     initial_pool_size,
     max_pool_size,
@@ -595,9 +896,17 @@ trivial_from!(GitDisk, GitView, {
     auto_delegate_conflicts,
     worktree_base_path,
     branch_prefix,
+    checkpoint_on_idle,
+    interactive_conflicts,
+    queue_check_command,
+    queue_check_timeout_secs,
 });
 trivial_from!(SessionDisk, SessionView, { auto_save, auto_save_interval_seconds });
 trivial_from!(GastownDisk, GastownView, { cli_path, workspace_path, pinned_version });
+trivial_from!(GatewayDisk, GatewayView, { enabled, port, max_requests_per_minute });
+trivial_from!(RemoteApiDisk, RemoteApiView, { enabled, port, bind_lan });
+trivial_from!(AgentsDisk, AgentsView, { claude_code_path, gemini_cli_path, codex_path });
+trivial_from!(HookDisk, HookView, { id, name, enabled, events, url, command, max_attempts });
 
 impl From<SettingsDisk> for SettingsView {
     fn from(v: SettingsDisk) -> Self {
@@ -611,6 +920,10 @@ impl From<SettingsDisk> for SettingsView {
             git: v.git.into(),
             session: v.session.into(),
             gastown: v.gastown.into(),
+            gateway: v.gateway.into(),
+            remote_api: v.remote_api.into(),
+            agents: v.agents.into(),
+            hooks: v.hooks.into_iter().map(Into::into).collect(),
         }
     }
 }
@@ -627,21 +940,24 @@ impl From<SettingsView> for SettingsDisk {
             git: v.git.into(),
             session: v.session.into(),
             gastown: v.gastown.into(),
+            gateway: v.gateway.into(),
+            remote_api: v.remote_api.into(),
+            agents: v.agents.into(),
+            hooks: v.hooks.into_iter().map(Into::into).collect(),
         }
     }
 }
 
-fn settings_path(app: &tauri::AppHandle) -> Result<PathBuf> {
-    app.path()
-        .resolve("synk/settings.json", BaseDirectory::Config)
+fn settings_path(app: &AppContext) -> Result<PathBuf> {
+    app.config_path("settings.json")
         .context("resolve config path for settings.json")
 }
 
-pub fn settings_get(app: &tauri::AppHandle) -> Result<SettingsView> {
+fn read_settings_disk(app: &AppContext) -> Result<SettingsDisk> {
     let path = settings_path(app)?;
     let text = match fs::read_to_string(&path) {
         Ok(s) => s,
-        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(SettingsView::default()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(SettingsDisk::default()),
         Err(e) => return Err(e).with_context(|| format!("read {}", path.display())),
     };
 
@@ -681,6 +997,17 @@ pub fn settings_get(app: &tauri::AppHandle) -> Result<SettingsView> {
         disk.version = 4;
         changed = true;
     }
+    // Keys used to be stored in plaintext; move any we find into the secrets store.
+    if has_plaintext_keys(&disk) {
+        match migrate_plaintext_keys(app, &mut disk) {
+            Ok(()) => changed = true,
+            Err(err) => eprintln!("settings: leaving API keys in plaintext: {err:#}"),
+        }
+    }
+    if disk.version < 5 && !has_plaintext_keys(&disk) {
+        disk.version = 5;
+        changed = true;
+    }
 
     if changed {
         // Best-effort persist so next launch sees the migrated defaults.
@@ -692,28 +1019,202 @@ pub fn settings_get(app: &tauri::AppHandle) -> Result<SettingsView> {
         }
     }
 
-    Ok(SettingsView::from(disk))
+    Ok(disk)
+}
+
+/// Settings for the frontend: API keys are masked.
+pub fn settings_get(app: &AppContext) -> Result<SettingsView> {
+    let disk = read_settings_disk(app)?;
+    Ok(disk_to_view(app, disk, false))
+}
+
+/// Settings with API keys decrypted, for backend use (session env, key validation).
+/// Never return this over IPC.
+pub fn settings_get_with_secrets(app: &AppContext) -> Result<SettingsView> {
+    let disk = read_settings_disk(app)?;
+    Ok(disk_to_view(app, disk, true))
 }
 
-pub fn settings_set(app: &tauri::AppHandle, view: SettingsView) -> Result<SettingsView> {
+pub fn settings_set(app: &AppContext, view: SettingsView) -> Result<SettingsView> {
     let path = settings_path(app)?;
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
             .with_context(|| format!("create config dir {}", parent.display()))?;
     }
+    let previous = read_settings_disk(app).unwrap_or_default();
 
     // Normalize via disk schema so missing fields get defaults.
     let mut disk = SettingsDisk::from(view);
-    if disk.version == 0 {
-        disk.version = 4;
+    if disk.version < 5 {
+        disk.version = 5;
     }
-    if disk.version < 4 {
-        disk.version = 4;
+    for hook in &mut disk.hooks {
+        hook.max_attempts = hook.max_attempts.clamp(1, MAX_HOOK_ATTEMPTS);
     }
+    store_submitted_keys(app, &previous, &mut disk)?;
 
     let text = serde_json::to_string_pretty(&disk).context("serialize settings.json")?;
     fs::write(&path, format!("{text}\n")).with_context(|| format!("write {}", path.display()))?;
-    Ok(SettingsView::from(disk))
+    Ok(disk_to_view(app, disk, false))
+}
+
+// -----------------------------------------------------------------------------
+// API keys live in the encrypted secrets store; settings.json only holds references.
+// -----------------------------------------------------------------------------
+
+/// (secret name, plaintext key, store reference) for every key-bearing settings entry.
+fn key_slots(p: &mut AiProvidersDisk) -> Vec<(String, &mut Option<String>, &mut Option<String>)> {
+    let mut out = Vec::new();
+    for (provider, auth) in [
+        ("anthropic", &mut p.anthropic),
+        ("google", &mut p.google),
+        ("openai", &mut p.openai),
+        ("openrouter", &mut p.openrouter),
+    ] {
+        out.push((
+            format!("provider.{provider}.api_key"),
+            &mut auth.api_key,
+            &mut auth.api_key_ref,
+        ));
+    }
+    for profile in p.profiles.iter_mut() {
+        out.push((
+            format!("profile.{}.api_key", profile.id),
+            &mut profile.api_key,
+            &mut profile.api_key_ref,
+        ));
+    }
+    out
+}
+
+fn has_plaintext_keys(disk: &SettingsDisk) -> bool {
+    let mut providers = disk.ai_providers.clone();
+    key_slots(&mut providers)
+        .into_iter()
+        .any(|(_, key, _)| key.as_deref().is_some_and(|k| !k.trim().is_empty()))
+}
+
+fn migrate_plaintext_keys(app: &AppContext, disk: &mut SettingsDisk) -> Result<()> {
+    let mut store = SecretStore::open(app)?;
+    for (name, key, key_ref) in key_slots(&mut disk.ai_providers) {
+        let Some(value) = key.take() else {
+            continue;
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        store.set(&name, value)?;
+        *key_ref = Some(name);
+    }
+    Ok(())
+}
+
+/// Replaces store references with the decrypted key (`reveal`) or its masked form.
+fn disk_to_view(app: &AppContext, mut disk: SettingsDisk, reveal: bool) -> SettingsView {
+    let mut store: Option<Result<SecretStore>> = None;
+    for (_, key, key_ref) in key_slots(&mut disk.ai_providers) {
+        if let Some(name) = key_ref.as_deref() {
+            let store = store.get_or_insert_with(|| SecretStore::open(app));
+            *key = match store {
+                Ok(s) => s.get(name).unwrap_or_else(|err| {
+                    eprintln!("settings: {err:#}");
+                    None
+                }),
+                Err(_) => None,
+            };
+            // Keep showing that a key is configured even when the store is locked.
+            if key.is_none() && !reveal {
+                *key = Some(MASK_PREFIX.to_string());
+            }
+        }
+        if !reveal {
+            if let Some(k) = key.as_deref().filter(|k| !is_masked(k)) {
+                *key = Some(mask(k));
+            }
+        }
+    }
+    if let Some(Err(err)) = &store {
+        eprintln!("settings: secrets store unavailable: {err:#}");
+    }
+    SettingsView::from(disk)
+}
+
+/// Keys coming back from the frontend: masked = unchanged, empty = removed, otherwise new.
+fn store_submitted_keys(
+    app: &AppContext,
+    previous: &SettingsDisk,
+    disk: &mut SettingsDisk,
+) -> Result<()> {
+    let mut prev = previous.ai_providers.clone();
+    let previous_refs: HashSet<String> = key_slots(&mut prev)
+        .into_iter()
+        .filter_map(|(_, _, r)| r.clone())
+        .collect();
+
+    let mut writes: Vec<(String, String)> = Vec::new();
+    let mut live: HashSet<String> = HashSet::new();
+    for (name, key, key_ref) in key_slots(&mut disk.ai_providers) {
+        let value = key.take().unwrap_or_default();
+        let value = value.trim();
+        if value.is_empty() {
+            *key_ref = None;
+        } else if is_masked(value) {
+            *key_ref = previous_refs.contains(&name).then(|| name.clone());
+        } else {
+            writes.push((name.clone(), value.to_string()));
+            *key_ref = Some(name.clone());
+        }
+        if key_ref.is_some() {
+            live.insert(name);
+        }
+    }
+
+    // Saving UI or git settings must not need the store (or its passphrase).
+    if writes.is_empty() && previous_refs.is_subset(&live) {
+        return Ok(());
+    }
+    let mut store = SecretStore::open(app)?;
+    for (name, value) in &writes {
+        store.set(name, value)?;
+    }
+
+    // Drop keys for cleared providers and deleted profiles.
+    for name in store.names() {
+        let owned = name.starts_with("provider.") || name.starts_with("profile.");
+        if owned && !live.contains(&name) {
+            store.remove(&name)?;
+        }
+    }
+    Ok(())
+}
+
+/// Keys typed in the settings UI come back masked when unchanged; swap in the stored key.
+pub fn unmask_provider_key(app: &AppContext, provider: &str, key: &str) -> Result<String> {
+    if !is_masked(key) {
+        return Ok(key.to_string());
+    }
+    let settings = settings_get_with_secrets(app)?;
+    let p = &settings.ai_providers;
+    let stored = match provider.trim().to_ascii_lowercase().as_str() {
+        "anthropic" => p.anthropic.api_key.clone(),
+        "google" => p.google.api_key.clone(),
+        "openai" => p.openai.api_key.clone(),
+        "openrouter" => p.openrouter.api_key.clone(),
+        _ => None,
+    };
+    Ok(stored.unwrap_or_default())
+}
+
+pub fn unmask_profile_key(
+    app: &AppContext,
+    mut profile: ProviderProfileView,
+) -> Result<ProviderProfileView> {
+    if profile.api_key.as_deref().is_some_and(is_masked) {
+        let settings = settings_get_with_secrets(app)?;
+        profile.api_key = find_provider_profile(&settings, &profile.id).and_then(|p| p.api_key);
+    }
+    Ok(profile)
 }
 
 pub fn pool_config_from_settings(view: &SettingsView) -> PoolConfig {
@@ -729,6 +1230,38 @@ pub fn pool_config_from_settings(view: &SettingsView) -> PoolConfig {
     cfg
 }
 
+pub fn agent_path_overrides(view: &SettingsView) -> HashMap<AgentType, String> {
+    let a = &view.agents;
+    let mut out = HashMap::new();
+    for (agent_type, path) in [
+        (AgentType::ClaudeCode, &a.claude_code_path),
+        (AgentType::GeminiCli, &a.gemini_cli_path),
+        (AgentType::Codex, &a.codex_path),
+        (AgentType::Openrouter, &a.codex_path),
+    ] {
+        if let Some(p) = path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+            out.insert(agent_type, p.to_string());
+        }
+    }
+    out
+}
+
+/// Local auth inputs for agent detection, including the keys Synk exports into sessions.
+/// Keys are read from the secrets store rather than the masked view: a locked store shows a
+/// placeholder there, but sessions wouldn't get a key.
+pub fn auth_probe(app: &AppContext) -> AuthProbe {
+    let view = settings_get_with_secrets(app).unwrap_or_default();
+    let mut probe = AuthProbe::from_process();
+    let p = &view.ai_providers;
+    for (name, key) in [("openai", &p.openai.api_key), ("openrouter", &p.openrouter.api_key)] {
+        if key.as_deref().is_some_and(|k| !k.trim().is_empty()) {
+            probe.stored_keys.insert(name.to_string());
+        }
+    }
+    probe.codex_uses_openrouter = p.default.trim().eq_ignore_ascii_case("openrouter");
+    probe
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProviderKeyValidationResult {
@@ -756,7 +1289,76 @@ pub struct OllamaPullResult {
     pub message: String,
 }
 
-pub fn validate_provider_key(provider: &str, key: &str) -> Result<ProviderKeyValidationResult> {
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderProfileValidationResult {
+    pub ok: bool,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<ProviderKeyValidationResult>,
+    pub models: ProviderModelsResult,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_available: Option<bool>,
+}
+
+fn normalize_custom_base_url(base_url: Option<&str>) -> Option<String> {
+    base_url
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.trim_end_matches('/').to_string())
+}
+
+/// Model-list endpoint for a provider. A custom base URL follows the same convention as the
+/// agent CLIs: OpenAI-style bases include `/v1`, Anthropic-style bases do not.
+fn models_endpoint(provider: &str, key: &str, base_url: Option<&str>) -> Option<String> {
+    let custom = normalize_custom_base_url(base_url);
+    match provider {
+        "anthropic" => Some(format!(
+            "{}/v1/models",
+            custom.unwrap_or_else(|| "https://api.anthropic.com".to_string())
+        )),
+        "openai" => Some(format!(
+            "{}/models",
+            custom.unwrap_or_else(|| "https://api.openai.com/v1".to_string())
+        )),
+        "openrouter" => Some(format!(
+            "{}/models",
+            custom.unwrap_or_else(|| "https://openrouter.ai/api/v1".to_string())
+        )),
+        "google" | "gemini" => Some(format!(
+            "https://generativelanguage.googleapis.com/v1beta/models?key={}",
+            urlencoding::encode(key)
+        )),
+        _ => None,
+    }
+}
+
+fn models_request(
+    client: &reqwest::blocking::Client,
+    provider: &str,
+    key: &str,
+    base_url: Option<&str>,
+) -> Option<reqwest::blocking::RequestBuilder> {
+    let url = models_endpoint(provider, key, base_url)?;
+    let req = client.get(url);
+    // Local OpenAI-compatible servers (Ollama, vLLM) often run without auth.
+    if key.is_empty() {
+        return Some(req);
+    }
+    Some(match provider {
+        "anthropic" => req
+            .header("x-api-key", key)
+            .header("anthropic-version", "2023-06-01"),
+        "google" | "gemini" => req,
+        _ => req.bearer_auth(key),
+    })
+}
+
+pub fn validate_provider_key(
+    provider: &str,
+    key: &str,
+    base_url: Option<&str>,
+) -> Result<ProviderKeyValidationResult> {
     let provider = provider.to_ascii_lowercase();
     let key = key.trim();
     if key.is_empty() {
@@ -773,36 +1375,15 @@ pub fn validate_provider_key(provider: &str, key: &str) -> Result<ProviderKeyVal
         .build()
         .context("build http client")?;
 
-    let resp = match provider.as_str() {
-        "anthropic" => client
-            .get("https://api.anthropic.com/v1/models")
-            .header("x-api-key", key)
-            .header("anthropic-version", "2023-06-01")
-            .send(),
-        "openai" => client
-            .get("https://api.openai.com/v1/models")
-            .bearer_auth(key)
-            .send(),
-        "openrouter" => client
-            .get("https://openrouter.ai/api/v1/models")
-            .bearer_auth(key)
-            .send(),
-        "google" | "gemini" => client
-            .get(format!(
-                "https://generativelanguage.googleapis.com/v1beta/models?key={}",
-                urlencoding::encode(key)
-            ))
-            .send(),
-        _ => {
-            return Ok(ProviderKeyValidationResult {
-                ok: false,
-                message: format!("Unknown provider: {provider}"),
-                status_code: None,
-            })
-        }
+    let Some(req) = models_request(&client, &provider, key, base_url) else {
+        return Ok(ProviderKeyValidationResult {
+            ok: false,
+            message: format!("Unknown provider: {provider}"),
+            status_code: None,
+        });
     };
 
-    let resp = match resp {
+    let resp = match req.send() {
         Ok(r) => r,
         Err(e) => {
             return Ok(ProviderKeyValidationResult {
@@ -935,7 +1516,9 @@ pub fn list_provider_models(
         });
     }
 
-    if key.is_empty() {
+    // A custom endpoint (local proxy / gateway) may not require a key at all.
+    let custom_base = normalize_custom_base_url(base_url);
+    if key.is_empty() && custom_base.is_none() {
         return Ok(ProviderModelsResult {
             ok: false,
             models: Vec::new(),
@@ -949,35 +1532,15 @@ pub fn list_provider_models(
         .build()
         .context("build http client")?;
 
-    let resp = match provider.as_str() {
-        "anthropic" => client
-            .get("https://api.anthropic.com/v1/models")
-            .header("x-api-key", key)
-            .header("anthropic-version", "2023-06-01")
-            .send(),
-        "openai" => client
-            .get("https://api.openai.com/v1/models")
-            .bearer_auth(key)
-            .send(),
-        "openrouter" => client
-            .get("https://openrouter.ai/api/v1/models")
-            .bearer_auth(key)
-            .send(),
-        "google" | "gemini" => client
-            .get(format!(
-                "https://generativelanguage.googleapis.com/v1beta/models?key={}",
-                urlencoding::encode(key)
-            ))
-            .send(),
-        _ => {
-            return Ok(ProviderModelsResult {
-                ok: false,
-                models: Vec::new(),
-                message: format!("Unknown provider: {provider}"),
-                status_code: None,
-            })
-        }
+    let Some(req) = models_request(&client, &provider, key, custom_base.as_deref()) else {
+        return Ok(ProviderModelsResult {
+            ok: false,
+            models: Vec::new(),
+            message: format!("Unknown provider: {provider}"),
+            status_code: None,
+        });
     };
+    let resp = req.send();
 
     let resp = match resp {
         Ok(r) => r,
@@ -1011,6 +1574,77 @@ pub fn list_provider_models(
     })
 }
 
+pub fn find_provider_profile(view: &SettingsView, id: &str) -> Option<ProviderProfileView> {
+    let id = id.trim();
+    view.ai_providers
+        .profiles
+        .iter()
+        .find(|p| p.id == id)
+        .cloned()
+}
+
+/// Checks a profile against its endpoint using the same requests as the provider key/model
+/// checks. Profiles without a key (e.g. local Ollama) are validated by listing models only.
+pub fn validate_provider_profile(
+    profile: &ProviderProfileView,
+) -> Result<ProviderProfileValidationResult> {
+    let Some(provider) = profile.api_provider() else {
+        return Ok(ProviderProfileValidationResult {
+            ok: false,
+            message: format!(
+                "Provider profiles are not supported for {}",
+                profile.agent_type.display_name()
+            ),
+            key: None,
+            models: ProviderModelsResult {
+                ok: false,
+                models: Vec::new(),
+                message: "Skipped".to_string(),
+                status_code: None,
+            },
+            model_available: None,
+        });
+    };
+
+    let base_url = profile.custom_base_url();
+    let api_key = profile.resolve_api_key();
+    let key = match api_key.as_deref() {
+        Some(k) => Some(validate_provider_key(provider, k, base_url)?),
+        None => None,
+    };
+    let models = list_provider_models(provider, api_key.as_deref().unwrap_or(""), base_url)?;
+
+    let model_available = profile
+        .model
+        .as_deref()
+        .map(str::trim)
+        .filter(|m| !m.is_empty() && models.ok)
+        .map(|m| models.models.iter().any(|x| x == m));
+
+    let key_ok = key.as_ref().map(|k| k.ok).unwrap_or(true);
+    let ok = key_ok && models.ok && model_available != Some(false);
+    let message = if !key_ok {
+        format!("API key rejected by {}", base_url.unwrap_or(provider))
+    } else if !models.ok {
+        format!("Could not list models: {}", models.message)
+    } else if model_available == Some(false) {
+        format!(
+            "Model '{}' is not offered by this endpoint",
+            profile.model.as_deref().unwrap_or("").trim()
+        )
+    } else {
+        "OK".to_string()
+    };
+
+    Ok(ProviderProfileValidationResult {
+        ok,
+        message,
+        key,
+        models,
+        model_available,
+    })
+}
+
 pub fn pull_ollama_model(model: &str, base_url: Option<&str>) -> Result<OllamaPullResult> {
     let model = model.trim();
     if model.is_empty() {
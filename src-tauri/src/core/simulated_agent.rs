@@ -0,0 +1,446 @@
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+
+/// Flag that turns the Synk binary into the scripted agent instead of launching the app.
+pub const SIMULATED_AGENT_FLAG: &str = "--simulated-agent";
+pub const DEFAULT_SCENARIO: &str = "demo";
+
+/// Which agent's cost/usage line format to print, so `CostTracker` sees realistic output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CostFormat {
+    #[default]
+    ClaudeCode,
+    Codex,
+    GeminiCli,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScenarioStep {
+    Print {
+        text: String,
+    },
+    Sleep {
+        ms: u64,
+    },
+    /// Spinner frames on one line, like an agent waiting on the model.
+    Thinking {
+        #[serde(default)]
+        label: Option<String>,
+        ms: u64,
+    },
+    /// Usage for one turn. Claude Code prints running totals; Codex/Gemini print per turn.
+    Cost {
+        input_tokens: u64,
+        output_tokens: u64,
+        #[serde(default)]
+        cost: Option<f64>,
+    },
+    /// Waits for a line on stdin. Anything other than y/yes denies.
+    Prompt {
+        text: String,
+        #[serde(default)]
+        deny_exit_code: Option<i32>,
+    },
+    WriteFile {
+        path: String,
+        content: String,
+    },
+    Commit {
+        message: String,
+    },
+    Exit {
+        code: i32,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub format: CostFormat,
+    #[serde(default)]
+    pub model: Option<String>,
+    pub steps: Vec<ScenarioStep>,
+}
+
+const BUILTIN_SCENARIOS: &[(&str, &str)] = &[
+    (
+        "demo",
+        r##"{
+  "name": "demo",
+  "description": "Claude Code style turn: plan, ask to edit, write a file, commit, report cost.",
+  "format": "claude_code",
+  "model": "claude-sim-1",
+  "steps": [
+    { "type": "print", "text": "\u001b[1m✻ Welcome to the Synk simulated agent\u001b[0m" },
+    { "type": "thinking", "label": "Reading the project", "ms": 800 },
+    { "type": "print", "text": "● I'll add a short note to SIMULATED.md and commit it." },
+    { "type": "prompt", "text": "Allow Write(SIMULATED.md)? (y/n) " },
+    { "type": "write_file", "path": "SIMULATED.md", "content": "# Simulated change\n\nWritten by the Synk simulated agent.\n" },
+    { "type": "print", "text": "● Wrote SIMULATED.md" },
+    { "type": "commit", "message": "docs: add simulated agent note" },
+    { "type": "cost", "input_tokens": 1840, "output_tokens": 312, "cost": 0.0102 },
+    { "type": "print", "text": "✻ Done." },
+    { "type": "exit", "code": 0 }
+  ]
+}"##,
+    ),
+    (
+        "codex",
+        r##"{
+  "name": "codex",
+  "description": "Codex style: two turns with per-turn usage lines and a commit.",
+  "format": "codex",
+  "model": "gpt-sim-codex",
+  "steps": [
+    { "type": "print", "text": ">_ OpenAI Codex (simulated)" },
+    { "type": "thinking", "label": "Working", "ms": 600 },
+    { "type": "cost", "input_tokens": 1200, "output_tokens": 150 },
+    { "type": "write_file", "path": "simulated_codex.txt", "content": "codex was here\n" },
+    { "type": "commit", "message": "chore: simulated codex change" },
+    { "type": "thinking", "label": "Working", "ms": 400 },
+    { "type": "cost", "input_tokens": 900, "output_tokens": 80 },
+    { "type": "exit", "code": 0 }
+  ]
+}"##,
+    ),
+    (
+        "gemini",
+        r##"{
+  "name": "gemini",
+  "description": "Gemini CLI style response-level usage.",
+  "format": "gemini_cli",
+  "model": "gemini-sim",
+  "steps": [
+    { "type": "print", "text": "Gemini CLI (simulated)" },
+    { "type": "thinking", "label": "Thinking", "ms": 500 },
+    { "type": "print", "text": "✦ Nothing to change; the project looks good." },
+    { "type": "cost", "input_tokens": 640, "output_tokens": 44 },
+    { "type": "exit", "code": 0 }
+  ]
+}"##,
+    ),
+    (
+        "error",
+        r##"{
+  "name": "error",
+  "description": "Fails mid-turn with a non-zero exit code.",
+  "format": "claude_code",
+  "steps": [
+    { "type": "thinking", "label": "Calling the API", "ms": 400 },
+    { "type": "print", "text": "\u001b[31mAPI Error: 529 overloaded_error\u001b[0m" },
+    { "type": "exit", "code": 1 }
+  ]
+}"##,
+    ),
+];
+
+pub fn builtin_scenario_names() -> Vec<&'static str> {
+    BUILTIN_SCENARIOS.iter().map(|(name, _)| *name).collect()
+}
+
+/// Resolves a built-in scenario name or a path to a scenario JSON file.
+pub fn load_scenario(name_or_path: &str) -> Result<Scenario> {
+    let key = name_or_path.trim();
+    if let Some((_, text)) = BUILTIN_SCENARIOS.iter().find(|(name, _)| *name == key) {
+        return serde_json::from_str(text).with_context(|| format!("parse builtin scenario {key}"));
+    }
+    let path = Path::new(key);
+    if path.is_file() {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("read scenario {}", path.display()))?;
+        return serde_json::from_str(&text)
+            .with_context(|| format!("parse scenario {}", path.display()));
+    }
+    Err(anyhow!(
+        "unknown scenario '{key}' (builtins: {})",
+        builtin_scenario_names().join(", ")
+    ))
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunOptions {
+    /// Skip sleeps/spinners (tests).
+    pub instant: bool,
+}
+
+/// Plays a scenario against `out`/`input` in `cwd`. Returns the exit code.
+pub fn run_scenario(
+    scenario: &Scenario,
+    out: &mut impl Write,
+    input: &mut impl BufRead,
+    cwd: &Path,
+    opts: RunOptions,
+) -> Result<i32> {
+    let mut total_in = 0u64;
+    let mut total_out = 0u64;
+    let mut total_cost = 0f64;
+
+    if let Some(model) = &scenario.model {
+        writeln!(out, "Using model: {model}")?;
+    }
+
+    for step in &scenario.steps {
+        match step {
+            ScenarioStep::Print { text } => writeln!(out, "{text}")?,
+            ScenarioStep::Sleep { ms } => pause(*ms, opts),
+            ScenarioStep::Thinking { label, ms } => {
+                let label = label.as_deref().unwrap_or("Thinking");
+                let frames = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+                let ticks = if opts.instant { 1 } else { (*ms / 80).max(1) };
+                for i in 0..ticks {
+                    write!(out, "\r{} {label}…", frames[i as usize % frames.len()])?;
+                    out.flush()?;
+                    pause(80, opts);
+                }
+                write!(out, "\r\x1b[2K")?;
+            }
+            ScenarioStep::Cost {
+                input_tokens,
+                output_tokens,
+                cost,
+            } => {
+                total_in += input_tokens;
+                total_out += output_tokens;
+                total_cost += cost.unwrap_or(0.0);
+                for line in cost_lines(
+                    scenario.format,
+                    (*input_tokens, *output_tokens),
+                    (total_in, total_out),
+                    cost.map(|_| total_cost),
+                ) {
+                    writeln!(out, "{line}")?;
+                }
+            }
+            ScenarioStep::Prompt {
+                text,
+                deny_exit_code,
+            } => {
+                write!(out, "{text}")?;
+                out.flush()?;
+                let mut answer = String::new();
+                input.read_line(&mut answer)?;
+                let allowed = matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes");
+                if !allowed {
+                    writeln!(out, "Denied.")?;
+                    if let Some(code) = deny_exit_code {
+                        return Ok(*code);
+                    }
+                }
+            }
+            ScenarioStep::WriteFile { path, content } => {
+                let target = cwd.join(path);
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent)
+                        .with_context(|| format!("create {}", parent.display()))?;
+                }
+                std::fs::write(&target, content)
+                    .with_context(|| format!("write {}", target.display()))?;
+            }
+            ScenarioStep::Commit { message } => match commit_all(cwd, message) {
+                Ok(hash) => writeln!(out, "Committed {hash}: {message}")?,
+                Err(err) => writeln!(out, "Commit failed: {err:#}")?,
+            },
+            ScenarioStep::Exit { code } => return Ok(*code),
+        }
+        out.flush()?;
+    }
+    Ok(0)
+}
+
+fn pause(ms: u64, opts: RunOptions) {
+    if !opts.instant {
+        std::thread::sleep(Duration::from_millis(ms));
+    }
+}
+
+/// Usage lines in the format each agent prints (and `CostTracker` parses).
+fn cost_lines(
+    format: CostFormat,
+    turn: (u64, u64),
+    totals: (u64, u64),
+    total_cost: Option<f64>,
+) -> Vec<String> {
+    match format {
+        CostFormat::ClaudeCode => {
+            let mut lines = Vec::new();
+            if let Some(cost) = total_cost {
+                lines.push(format!("Total cost: ${cost:.4}"));
+            }
+            lines.push(format!(
+                "Total tokens: {} input, {} output",
+                totals.0, totals.1
+            ));
+            lines
+        }
+        CostFormat::Codex => vec![format!(
+            "Usage: {} prompt + {} completion = {} total tokens",
+            turn.0,
+            turn.1,
+            turn.0 + turn.1
+        )],
+        CostFormat::GeminiCli => vec![format!(
+            "[{} input tokens, {} output tokens]",
+            turn.0, turn.1
+        )],
+    }
+}
+
+fn commit_all(cwd: &Path, message: &str) -> Result<String> {
+    let git = |args: &[&str]| -> Result<String> {
+        let out = Command::new("git")
+            .args([
+                "-c",
+                "user.name=Synk Simulator",
+                "-c",
+                "user.email=simulator@synk.local",
+            ])
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .context("run git")?;
+        if !out.status.success() {
+            return Err(anyhow!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&out.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+    };
+    git(&["add", "-A"])?;
+    git(&["commit", "-m", message])?;
+    git(&["rev-parse", "--short", "HEAD"])
+}
+
+/// Entry point for `synk --simulated-agent [--scenario <name|path>] [--prompt <text>]`.
+pub fn main_from_args(args: &[String]) -> i32 {
+    if args.iter().any(|a| a == "--list-scenarios") {
+        for (_, json) in BUILTIN_SCENARIOS {
+            if let Ok(s) = serde_json::from_str::<Scenario>(json) {
+                println!("{:<10} {}", s.name, s.description);
+            }
+        }
+        return 0;
+    }
+    let scenario_arg = args
+        .iter()
+        .position(|a| a == "--scenario")
+        .and_then(|i| args.get(i + 1))
+        .map(|s| s.as_str())
+        .unwrap_or(DEFAULT_SCENARIO);
+    let instant = std::env::var("SYNK_SIM_INSTANT").is_ok_and(|v| v == "1");
+
+    let scenario = match load_scenario(scenario_arg) {
+        Ok(s) => s,
+        Err(err) => {
+            eprintln!("synk simulated agent: {err:#}");
+            return 2;
+        }
+    };
+    // Scenarios are scripted; the initial prompt is only echoed, as a real agent would show it.
+    if let Some(prompt) = args
+        .iter()
+        .position(|a| a == "--prompt")
+        .and_then(|i| args.get(i + 1))
+    {
+        println!("> {prompt}");
+    }
+    let cwd = std::env::current_dir().unwrap_or_else(|_| ".".into());
+    let stdout = std::io::stdout();
+    let stdin = std::io::stdin();
+    match run_scenario(
+        &scenario,
+        &mut stdout.lock(),
+        &mut stdin.lock(),
+        &cwd,
+        RunOptions { instant },
+    ) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("synk simulated agent: {err:#}");
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_fixtures::TempDir;
+
+    fn run(name: &str, answers: &str, cwd: &Path) -> (i32, String) {
+        let scenario = load_scenario(name).unwrap();
+        let mut out = Vec::new();
+        let mut input = std::io::Cursor::new(answers.as_bytes().to_vec());
+        let code = run_scenario(
+            &scenario,
+            &mut out,
+            &mut input,
+            cwd,
+            RunOptions { instant: true },
+        )
+        .unwrap();
+        (code, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn builtin_scenarios_parse() {
+        for name in builtin_scenario_names() {
+            load_scenario(name).unwrap();
+        }
+        assert!(load_scenario("no-such-scenario").is_err());
+    }
+
+    #[test]
+    fn codex_scenario_prints_per_turn_usage() {
+        let dir = TempDir::new("sim-codex");
+        let (code, out) = run("codex", "", &dir);
+        assert_eq!(code, 0);
+        assert!(out.contains("Usage: 1200 prompt + 150 completion = 1350 total tokens"));
+        assert!(out.contains("Usage: 900 prompt + 80 completion = 980 total tokens"));
+        assert!(dir.join("simulated_codex.txt").is_file());
+    }
+
+    #[test]
+    fn denied_prompt_can_exit() {
+        let scenario: Scenario = serde_json::from_str(
+            r#"{"name":"t","steps":[
+                {"type":"prompt","text":"ok? ","deny_exit_code":3},
+                {"type":"print","text":"unreachable"}
+            ]}"#,
+        )
+        .unwrap();
+        let mut out = Vec::new();
+        let mut input = std::io::Cursor::new(b"n\n".to_vec());
+        let code = run_scenario(
+            &scenario,
+            &mut out,
+            &mut input,
+            Path::new("."),
+            RunOptions { instant: true },
+        )
+        .unwrap();
+        assert_eq!(code, 3);
+        assert!(!String::from_utf8(out).unwrap().contains("unreachable"));
+    }
+
+    #[test]
+    fn claude_cost_lines_are_cumulative() {
+        let lines = cost_lines(CostFormat::ClaudeCode, (10, 5), (30, 15), Some(0.25));
+        assert_eq!(
+            lines,
+            vec!["Total cost: $0.2500", "Total tokens: 30 input, 15 output"]
+        );
+    }
+}
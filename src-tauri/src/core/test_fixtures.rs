@@ -0,0 +1,70 @@
+//! Temp dirs and git repos for unit tests.
+
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+
+/// A fresh directory under the system temp dir, removed with its contents when dropped, so
+/// tests clean up after themselves even when an assertion fails.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates `synk-<name>-<pid>-<nanos>`.
+    pub fn new(name: &str) -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let path = std::env::temp_dir().join(format!("synk-{name}-{}-{nanos}", std::process::id()));
+        fs::create_dir_all(&path).expect("create temp dir");
+        Self(path)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Runs git in `dir` and returns its stdout, trimmed.
+pub fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let out = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .with_context(|| format!("run git {}", args.join(" ")))?;
+    if !out.status.success() {
+        bail!(
+            "git {} failed\nstdout: {}\nstderr: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&out.stdout),
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+/// A repo on `main` with a committer identity and one commit adding `README.md`.
+pub fn init_repo(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir).context("create tmp repo dir")?;
+    git(dir, &["init", "-b", "main"])?;
+    git(dir, &["config", "user.name", "synk"])?;
+    git(dir, &["config", "user.email", "synk@example.com"])?;
+    fs::write(dir.join("README.md"), "hello\n").context("write README")?;
+    git(dir, &["add", "."])?;
+    git(dir, &["commit", "-m", "init"])?;
+    Ok(())
+}
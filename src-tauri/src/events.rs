@@ -1,8 +1,23 @@
+use std::path::Path;
+
 use serde::Serialize;
+use serde_json::Value;
 
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 
+use crate::core::agent_detection::AgentType;
+use crate::core::cost_tracker::SessionCostSnapshot;
+use crate::core::notifications::Notification;
+use crate::core::review_store::{ReviewItem, ReviewStatus};
+use crate::core::session_broadcast::{BroadcastResult, SESSION_BROADCAST_EVENT};
+use crate::core::session_labels::SessionLabels;
+
+pub const SESSION_OUTPUT_EVENT_NAME: &str = "session:output";
+pub const SESSION_EXIT_EVENT_NAME: &str = "session:exit";
+pub const SESSION_CHECKPOINT_EVENT_NAME: &str = "session:checkpoint";
+pub const SESSION_IDLE_EVENT_NAME: &str = "session:idle";
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionOutputEvent {
@@ -14,7 +29,23 @@ pub struct SessionOutputEvent {
 #[serde(rename_all = "camelCase")]
 pub struct SessionExitEvent {
     pub session_id: usize,
-    pub exit_code: i32,
+    /// The shell's exit status; `None` if it was still running when its terminal closed.
+    pub exit_code: Option<i32>,
+}
+
+/// An agent session went quiet after producing output, i.e. finished its turn.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionIdleEvent {
+    pub session_id: usize,
+    pub agent_type: AgentType,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionCheckpointEvent {
+    pub session_id: usize,
+    pub checkpoint: crate::core::checkpoints::Checkpoint,
 }
 
 // -----------------------------------------------------------------------------
@@ -44,6 +75,9 @@ pub struct GitEvent {
     // Common optional context
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_id: Option<usize>,
+    /// Name/tags/group of `session_id` at the time of the event, for attribution.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_labels: Option<SessionLabels>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub branch: Option<String>,
 
@@ -102,3 +136,176 @@ pub struct LocalhostSessionStatusEvent {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_exit_code: Option<i32>,
 }
+
+// -----------------------------------------------------------------------------
+// LLM gateway
+// -----------------------------------------------------------------------------
+
+pub const GATEWAY_REQUEST_EVENT_NAME: &str = "gateway:request";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayRequestEvent {
+    pub session_id: usize,
+    pub route: String, // "openai" | "anthropic"
+    pub status: u16,
+    pub latency_ms: u64,
+    pub input_tokens: u64,
+    pub cached_input_tokens: u64,
+    pub output_tokens: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub timestamp: String, // RFC3339
+}
+
+pub const COST_UPDATED_EVENT_NAME: &str = "cost:updated";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionCostUpdatedEvent {
+    pub session_id: usize,
+    pub cost: SessionCostSnapshot,
+}
+
+// -----------------------------------------------------------------------------
+// Reviews and notifications
+// -----------------------------------------------------------------------------
+
+pub const REVIEW_EVENT_NAME: &str = "review:event";
+pub const NOTIFICATION_EVENT_NAME: &str = "notification:new";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewEventKind {
+    Created,
+    StatusChanged,
+    /// Either branch moved and the review's merge preview was recomputed.
+    MergePreviewChanged,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewEvent {
+    pub kind: ReviewEventKind,
+    pub project_path: String,
+    pub id: String,
+    pub session_id: usize,
+    pub branch: String,
+    pub base_branch: String,
+    pub status: ReviewStatus,
+}
+
+impl ReviewEvent {
+    pub fn new(kind: ReviewEventKind, project_path: &Path, item: &ReviewItem) -> Self {
+        Self {
+            kind,
+            project_path: project_path.to_string_lossy().to_string(),
+            id: item.id.clone(),
+            session_id: item.session_id,
+            branch: item.branch.clone(),
+            base_branch: item.base_branch.clone(),
+            status: item.status,
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Merge queue
+// -----------------------------------------------------------------------------
+
+pub const MERGE_QUEUE_EVENT_NAME: &str = "merge_queue:event";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeQueueEventKind {
+    Enqueued,
+    Removed,
+    Started,
+    Merged,
+    Failed,
+    Paused,
+    Resumed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeQueueEvent {
+    pub kind: MergeQueueEventKind,
+    pub project_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// New base commit, for `merged`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// Why the entry failed or the queue paused.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Entries left in the queue, including one being merged.
+    pub queued: usize,
+    pub timestamp: String, // RFC3339
+}
+
+// -----------------------------------------------------------------------------
+// Event bus
+// -----------------------------------------------------------------------------
+
+/// Everything published through `core::event_bus`, one variant per topic.
+#[derive(Debug, Clone)]
+pub enum AppEvent {
+    SessionOutput(SessionOutputEvent),
+    SessionExit(SessionExitEvent),
+    SessionCheckpoint(SessionCheckpointEvent),
+    SessionIdle(SessionIdleEvent),
+    SessionBroadcast(BroadcastResult),
+    Git(Box<GitEvent>),
+    LocalhostLog(LocalhostSessionLogEvent),
+    LocalhostStatus(LocalhostSessionStatusEvent),
+    GatewayRequest(GatewayRequestEvent),
+    CostUpdated(SessionCostUpdatedEvent),
+    Review(ReviewEvent),
+    Notification(Notification),
+    MergeQueue(MergeQueueEvent),
+}
+
+impl AppEvent {
+    /// The event name listeners subscribe to.
+    pub fn topic(&self) -> &'static str {
+        match self {
+            Self::SessionOutput(_) => SESSION_OUTPUT_EVENT_NAME,
+            Self::SessionExit(_) => SESSION_EXIT_EVENT_NAME,
+            Self::SessionCheckpoint(_) => SESSION_CHECKPOINT_EVENT_NAME,
+            Self::SessionIdle(_) => SESSION_IDLE_EVENT_NAME,
+            Self::SessionBroadcast(_) => SESSION_BROADCAST_EVENT,
+            Self::Git(_) => GIT_EVENT_NAME,
+            Self::LocalhostLog(_) => LOCALHOST_LOG_EVENT_NAME,
+            Self::LocalhostStatus(_) => LOCALHOST_STATUS_EVENT_NAME,
+            Self::GatewayRequest(_) => GATEWAY_REQUEST_EVENT_NAME,
+            Self::CostUpdated(_) => COST_UPDATED_EVENT_NAME,
+            Self::Review(_) => REVIEW_EVENT_NAME,
+            Self::Notification(_) => NOTIFICATION_EVENT_NAME,
+            Self::MergeQueue(_) => MERGE_QUEUE_EVENT_NAME,
+        }
+    }
+
+    pub fn payload(&self) -> serde_json::Result<Value> {
+        match self {
+            Self::SessionOutput(e) => serde_json::to_value(e),
+            Self::SessionExit(e) => serde_json::to_value(e),
+            Self::SessionCheckpoint(e) => serde_json::to_value(e),
+            Self::SessionIdle(e) => serde_json::to_value(e),
+            Self::SessionBroadcast(e) => serde_json::to_value(e),
+            Self::Git(e) => serde_json::to_value(e),
+            Self::LocalhostLog(e) => serde_json::to_value(e),
+            Self::LocalhostStatus(e) => serde_json::to_value(e),
+            Self::GatewayRequest(e) => serde_json::to_value(e),
+            Self::CostUpdated(e) => serde_json::to_value(e),
+            Self::Review(e) => serde_json::to_value(e),
+            Self::Notification(e) => serde_json::to_value(e),
+            Self::MergeQueue(e) => serde_json::to_value(e),
+        }
+    }
+}
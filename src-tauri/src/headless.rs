@@ -0,0 +1,170 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::core::app_context::{default_headless_config_dir, AppContext};
+use crate::core::control_socket::socket_path;
+use crate::core::remote_api::{self, SharedRemoteApi};
+use crate::core::settings as core_settings;
+
+pub const SERVE_COMMAND: &str = "serve";
+
+const USAGE: &str = "\
+usage: synk serve [--port <port>] [--lan] [--config-dir <dir>]
+
+Runs sessions, the git watcher, localhost runtime and review store without a window. Drive it
+with the synk CLI over the control socket, or over the remote API.
+
+  --port <port>       enable the remote API on this port (saved to settings)
+  --lan               listen on every interface instead of 127.0.0.1 (saved to settings)
+  --config-dir <dir>  config dir (default: $XDG_CONFIG_HOME/synk, else ~/.config/synk)
+";
+
+static STOP: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ServeArgs {
+    port: Option<u16>,
+    lan: bool,
+    config_dir: Option<PathBuf>,
+    help: bool,
+}
+
+fn parse_args(args: &[String]) -> Result<ServeArgs> {
+    let mut out = ServeArgs::default();
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--port" => {
+                let v = it.next().ok_or_else(|| anyhow!("--port needs a value"))?;
+                out.port = Some(v.parse().with_context(|| format!("invalid port {v:?}"))?);
+            }
+            "--lan" => out.lan = true,
+            "--config-dir" => {
+                let v = it
+                    .next()
+                    .ok_or_else(|| anyhow!("--config-dir needs a value"))?;
+                out.config_dir = Some(PathBuf::from(v));
+            }
+            "-h" | "--help" => out.help = true,
+            other => bail!("unknown option {other:?}"),
+        }
+    }
+    Ok(out)
+}
+
+pub fn main_from_args(args: &[String]) -> i32 {
+    match serve(args) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("synk serve: {err:#}");
+            1
+        }
+    }
+}
+
+fn serve(args: &[String]) -> Result<i32> {
+    let args = parse_args(args)?;
+    if args.help {
+        print!("{USAGE}");
+        return Ok(0);
+    }
+    let config_dir = match args.config_dir {
+        Some(dir) => dir,
+        None => default_headless_config_dir()?,
+    };
+    std::fs::create_dir_all(&config_dir)
+        .with_context(|| format!("create {}", config_dir.display()))?;
+
+    let ctx = AppContext::headless(config_dir.clone());
+    crate::Services::new().manage(&ctx);
+
+    // Save command-line overrides so a later settings_set from a client keeps the API up.
+    if args.port.is_some() || args.lan {
+        let mut view = core_settings::settings_get(&ctx)?;
+        view.remote_api.enabled = true;
+        view.remote_api.port = args.port.unwrap_or(view.remote_api.port);
+        view.remote_api.bind_lan = view.remote_api.bind_lan || args.lan;
+        core_settings::settings_set(&ctx, view)?;
+    }
+
+    crate::start_services(&ctx);
+    crate::warm_pool(&ctx);
+    install_signal_handlers();
+
+    eprintln!("synk serve: config {}", config_dir.display());
+    eprintln!("synk serve: control socket {}", socket_path().display());
+    let status = ctx
+        .state::<SharedRemoteApi>()
+        .lock()
+        .expect("remote API mutex poisoned")
+        .status();
+    match status.url {
+        Some(url) => {
+            eprintln!("synk serve: remote API {url}");
+            // The token itself stays out of logs; clients read it from the owner-only file.
+            match remote_api::token_path(&ctx) {
+                Ok(path) => eprintln!("synk serve: token in {}", path.display()),
+                Err(err) => eprintln!("synk serve: {err:#}"),
+            }
+        }
+        None => eprintln!("synk serve: remote API disabled (pass --port to enable it)"),
+    }
+
+    while !STOP.load(Ordering::Relaxed) {
+        std::thread::sleep(Duration::from_millis(200));
+    }
+    eprintln!("synk serve: shutting down");
+    crate::shutdown_services(&ctx);
+    Ok(0)
+}
+
+#[cfg(unix)]
+fn install_signal_handlers() {
+    extern "C" fn on_signal(_: libc::c_int) {
+        STOP.store(true, Ordering::Relaxed);
+    }
+    let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {
+        libc::signal(libc::SIGINT, handler);
+        libc::signal(libc::SIGTERM, handler);
+    }
+}
+
+#[cfg(not(unix))]
+fn install_signal_handlers() {}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{parse_args, ServeArgs};
+
+    fn args(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_serve_flags() {
+        assert_eq!(
+            parse_args(&args(&[
+                "--port",
+                "4800",
+                "--lan",
+                "--config-dir",
+                "/srv/synk"
+            ]))
+            .unwrap(),
+            ServeArgs {
+                port: Some(4800),
+                lan: true,
+                config_dir: Some(PathBuf::from("/srv/synk")),
+                help: false,
+            }
+        );
+        assert!(parse_args(&args(&["--port", "x"])).is_err());
+        assert!(parse_args(&args(&["--bogus"])).is_err());
+    }
+}
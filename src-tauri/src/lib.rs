@@ -1,107 +1,344 @@
 mod commands;
 mod core;
 mod events;
+mod headless;
 
-use crate::commands::agents::agents_list;
+#[cfg(feature = "desktop")]
+use crate::commands::agents::{agents_list, agents_refresh};
+#[cfg(feature = "desktop")]
+use crate::commands::attempts::{attempts_compare, attempts_list, attempts_promote, attempts_start};
+#[cfg(feature = "desktop")]
+use crate::commands::audit::audit_query;
+#[cfg(feature = "desktop")]
+use crate::commands::checkpoints::{
+    checkpoint_create, checkpoint_diff, checkpoint_list, checkpoint_restore,
+};
+#[cfg(feature = "desktop")]
+use crate::commands::command_bar::{command_complete, command_execute};
+#[cfg(feature = "desktop")]
+use crate::commands::events::events_since;
+#[cfg(feature = "desktop")]
+use crate::commands::gateway::{gateway_set_rate_cap, gateway_status, gateway_usage};
+#[cfg(feature = "desktop")]
 use crate::commands::git::{
-    git_branches, git_cleanup_orphans, git_create_worktree, git_delete_worktree,
-    git_detect_orphans, git_ensure_worktree, git_list_worktrees, git_remove_worktree,
+    git_branches, git_cleanup_orphans, git_commit_files, git_commit_log, git_create_worktree,
+    git_delete_worktree, git_detect_orphans, git_ensure_worktree, git_list_worktrees,
+    git_refs_for_commit, git_remove_worktree,
+};
+#[cfg(feature = "desktop")]
+use crate::commands::hooks::{hooks_deliveries, hooks_test};
+#[cfg(feature = "desktop")]
+use crate::commands::integration::{
+    integration_abort, integration_continue, integration_get, integration_list, integration_resolve,
 };
+#[cfg(feature = "desktop")]
 use crate::commands::localhost::{
     localhost_session_delete, localhost_session_list, localhost_session_logs,
     localhost_session_restart, localhost_session_start, localhost_session_stop,
     localhost_session_upsert,
 };
+#[cfg(feature = "desktop")]
 use crate::commands::mcp::{mcp_discover, mcp_set_enabled};
+#[cfg(feature = "desktop")]
+use crate::commands::merge_queue::{
+    merge_queue_enqueue, merge_queue_get, merge_queue_pause, merge_queue_remove, merge_queue_resume,
+};
+#[cfg(feature = "desktop")]
+use crate::commands::notifications::{
+    notifications_clear, notifications_list, notifications_mark_read,
+};
+#[cfg(feature = "desktop")]
 use crate::commands::onboarding::{
     onboarding_initialize, onboarding_is_first_run, onboarding_scan,
 };
+#[cfg(feature = "desktop")]
 use crate::commands::persistence::{list_recent_projects, open_project};
+#[cfg(feature = "desktop")]
+use crate::commands::remote::{remote_api_rotate_token, remote_api_status};
+#[cfg(feature = "desktop")]
 use crate::commands::persistence::{
     project_config_get, project_session_config_get, project_session_config_set,
 };
+#[cfg(feature = "desktop")]
 use crate::commands::persistence::{
     session_snapshot_autosave_meta, session_snapshot_list, session_snapshot_load,
     session_snapshot_save_autosave, session_snapshot_save_named,
 };
+#[cfg(feature = "desktop")]
 use crate::commands::review::{
-    git_diff, git_merge, review_add_comment, review_create, review_get, review_list,
-    review_resolve_comment, review_set_decision, review_set_merge_strategy, review_set_status,
+    git_diff, git_merge, git_merge_preview, review_add_comment, review_create, review_get,
+    review_list, review_resolve_comment, review_set_decision, review_set_merge_strategy,
+    review_set_status,
 };
+#[cfg(feature = "desktop")]
+use crate::commands::secrets::{secrets_delete, secrets_list, secrets_set};
+#[cfg(feature = "desktop")]
 use crate::commands::session::{
-    session_cd, session_create, session_destroy, session_list, session_resize, session_restart,
-    session_scrollback, session_write,
+    session_broadcast, session_cd, session_create, session_destroy, session_fork, session_handoff,
+    session_list, session_resize, session_restart, session_scrollback, session_set_labels,
+    session_write,
 };
+#[cfg(feature = "desktop")]
 use crate::commands::settings::{
     settings_get, settings_list_provider_models, settings_ollama_pull_model, settings_set,
-    settings_validate_provider_key,
+    settings_validate_provider_key, settings_validate_provider_profile,
 };
+#[cfg(feature = "desktop")]
 use crate::commands::skills::{skills_discover, skills_set_enabled};
 use crate::core::agent_detection::{AgentRegistry, SharedAgentRegistry};
+use crate::core::app_context::AppContext;
+#[cfg(feature = "desktop")]
+use crate::core::app_context::Managed;
+use crate::core::control_socket::{ControlSocket, SharedControlSocket};
+use crate::core::cost_tracker::{CostTrackers, SharedCostTrackers};
+use crate::core::event_bus::{EventBus, SharedEventBus};
 use crate::core::git_events::{GitEventWatcher, SharedGitEventWatcher};
+use crate::core::hooks;
+use crate::core::llm_gateway::{LlmGateway, SharedLlmGateway};
 use crate::core::localhost_runtime::{LocalhostRuntime, SharedLocalhostRuntime};
+use crate::core::mcp_discovery;
 use crate::core::mcp_server::{McpRuntime, SharedMcpRuntime};
+use crate::core::merge_queue::{self, MergeQueues, SharedMergeQueues};
+use crate::core::notifications::{NotificationCenter, SharedNotificationCenter};
 use crate::core::process_pool::{PoolConfig, ProcessPool, SharedProcessPool};
+use crate::core::remote_api::{RemoteApi, SharedRemoteApi};
+use crate::core::review_store;
 use crate::core::session_manager::{SessionManager, SharedSessionManager};
 use crate::core::settings as core_settings;
-use tauri::Manager;
 
-#[tauri::command]
-fn debug_pool_stats(pool: tauri::State<'_, SharedProcessPool>) -> core::process_pool::PoolStats {
+#[cfg(feature = "desktop")]
+#[cfg_attr(feature = "desktop", tauri::command)]
+fn debug_pool_stats(pool: Managed<SharedProcessPool>) -> core::process_pool::PoolStats {
     let guard = pool.inner().lock().expect("pool mutex poisoned");
     guard.stats()
 }
 
-#[tauri::command]
-fn debug_pool_roundtrip(pool: tauri::State<'_, SharedProcessPool>) -> Result<String, String> {
+#[cfg(feature = "desktop")]
+#[cfg_attr(feature = "desktop", tauri::command)]
+fn debug_pool_roundtrip(pool: Managed<SharedProcessPool>) -> Result<String, String> {
     ProcessPool::debug_roundtrip(pool.inner().clone()).map_err(|e| format!("{e:#}"))
 }
 
+/// When invoked as `synk --simulated-agent ...`, plays a scripted agent scenario instead of
+/// starting the app. Returns the exit code in that case.
+pub fn run_simulated_agent_if_requested() -> Option<i32> {
+    let args: Vec<String> = std::env::args().collect();
+    if !args
+        .iter()
+        .any(|a| a == core::simulated_agent::SIMULATED_AGENT_FLAG)
+    {
+        return None;
+    }
+    Some(core::simulated_agent::main_from_args(&args))
+}
+
+/// When invoked as `synk <subcommand>` (`synk ls`, `synk send 3 ...`), talks to the running
+/// app over its control socket instead of starting another one. Returns the exit code then.
+pub fn run_cli_if_requested() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !core::cli::is_subcommand(args.first()?) {
+        return None;
+    }
+    Some(core::cli::main_from_args(&args))
+}
+
+/// When invoked as `synk serve`, runs the core headless (no window) until interrupted, for
+/// driving a remote machine over the control socket and remote API. Returns the exit code then.
+pub fn run_headless_if_requested() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first()? != headless::SERVE_COMMAND {
+        return None;
+    }
+    Some(headless::main_from_args(&args[1..]))
+}
+
+/// Long-lived state shared by every command; managed by the desktop app and by `synk serve`.
+struct Services {
+    pool: SharedProcessPool,
+    agents: SharedAgentRegistry,
+    mcp_runtime: SharedMcpRuntime,
+    localhost_runtime: SharedLocalhostRuntime,
+    llm_gateway: SharedLlmGateway,
+    cost_trackers: SharedCostTrackers,
+    session_manager: SharedSessionManager,
+    git_watcher: SharedGitEventWatcher,
+    control_socket: SharedControlSocket,
+    remote_api: SharedRemoteApi,
+    event_bus: SharedEventBus,
+    notifications: SharedNotificationCenter,
+    merge_queues: SharedMergeQueues,
+}
+
+impl Services {
+    fn new() -> Self {
+        let pool: SharedProcessPool = std::sync::Arc::new(std::sync::Mutex::new(ProcessPool::new(
+            PoolConfig::default(),
+        )));
+        let agents: SharedAgentRegistry =
+            std::sync::Arc::new(std::sync::Mutex::new(AgentRegistry::detect()));
+        let session_manager: SharedSessionManager = std::sync::Arc::new(std::sync::Mutex::new(
+            SessionManager::new(pool.clone(), agents.clone()),
+        ));
+        Self {
+            pool,
+            agents,
+            mcp_runtime: std::sync::Arc::new(std::sync::Mutex::new(McpRuntime::default())),
+            localhost_runtime: std::sync::Arc::new(std::sync::Mutex::new(
+                LocalhostRuntime::default(),
+            )),
+            llm_gateway: std::sync::Arc::new(std::sync::Mutex::new(LlmGateway::new())),
+            cost_trackers: std::sync::Arc::new(std::sync::Mutex::new(CostTrackers::default())),
+            session_manager,
+            git_watcher: std::sync::Arc::new(std::sync::Mutex::new(GitEventWatcher::new())),
+            control_socket: std::sync::Arc::new(std::sync::Mutex::new(ControlSocket::default())),
+            remote_api: std::sync::Arc::new(std::sync::Mutex::new(RemoteApi::default())),
+            event_bus: std::sync::Arc::new(std::sync::Mutex::new(EventBus::new())),
+            notifications: std::sync::Arc::new(
+                std::sync::Mutex::new(NotificationCenter::default()),
+            ),
+            merge_queues: std::sync::Arc::new(std::sync::Mutex::new(MergeQueues::default())),
+        }
+    }
+
+    #[cfg(feature = "desktop")]
+    fn manage_in(self, builder: tauri::Builder<tauri::Wry>) -> tauri::Builder<tauri::Wry> {
+        builder
+            .manage(self.pool)
+            .manage(self.agents)
+            .manage(self.mcp_runtime)
+            .manage(self.localhost_runtime)
+            .manage(self.llm_gateway)
+            .manage(self.cost_trackers)
+            .manage(self.session_manager)
+            .manage(self.git_watcher)
+            .manage(self.control_socket)
+            .manage(self.remote_api)
+            .manage(self.event_bus)
+            .manage(self.notifications)
+            .manage(self.merge_queues)
+    }
+
+    fn manage(self, ctx: &AppContext) {
+        ctx.manage(self.pool);
+        ctx.manage(self.agents);
+        ctx.manage(self.mcp_runtime);
+        ctx.manage(self.localhost_runtime);
+        ctx.manage(self.llm_gateway);
+        ctx.manage(self.cost_trackers);
+        ctx.manage(self.session_manager);
+        ctx.manage(self.git_watcher);
+        ctx.manage(self.control_socket);
+        ctx.manage(self.remote_api);
+        ctx.manage(self.event_bus);
+        ctx.manage(self.notifications);
+        ctx.manage(self.merge_queues);
+    }
+}
+
+/// Starts the background pieces once [`Services`] are managed: agent re-detection, the git
+/// watcher and its subscribers, waiting merge queues, the control socket and (if enabled) the
+/// remote API.
+fn start_services(ctx: &AppContext) {
+    // Startup detection only sees PATH; re-run it if settings point at custom binaries.
+    if let Ok(settings) = core_settings::settings_get(ctx) {
+        let overrides = core_settings::agent_path_overrides(&settings);
+        if !overrides.is_empty() {
+            let fresh = AgentRegistry::detect_with(&overrides);
+            *ctx.state::<SharedAgentRegistry>()
+                .lock()
+                .expect("agent registry mutex poisoned") = fresh;
+        }
+    }
+    GitEventWatcher::start(
+        ctx.state::<SharedGitEventWatcher>().inner().clone(),
+        ctx.clone(),
+        ctx.state::<SharedSessionManager>().inner().clone(),
+    );
+    NotificationCenter::start(
+        ctx.state::<SharedNotificationCenter>().inner().clone(),
+        ctx.clone(),
+    );
+    hooks::start(ctx.clone());
+    match mcp_discovery::migrate_global_env_secrets(ctx) {
+        Ok(0) => {}
+        Ok(n) => eprintln!("mcp: moved {n} env values into the secrets store"),
+        Err(err) => eprintln!("mcp: leaving env values in plaintext: {err:#}"),
+    }
+    review_store::start_merge_preview_refresh(ctx.clone());
+    merge_queue::resume_all(&ctx.state::<SharedMergeQueues>(), ctx);
+    let started = ctx
+        .state::<SharedControlSocket>()
+        .lock()
+        .expect("control socket mutex poisoned")
+        .start(ctx.clone(), commands::remote::handler(ctx));
+    if let Err(err) = started {
+        eprintln!("control socket: {err:#}");
+    }
+    if let Ok(settings) = core_settings::settings_get(ctx) {
+        commands::remote::remote_api_apply(ctx, &settings.remote_api);
+    }
+}
+
+/// Applies the user's pool config, then warms the pool up in the background.
+fn warm_pool(ctx: &AppContext) {
+    let pool = ctx.state::<SharedProcessPool>().inner().clone();
+    if let Ok(settings) = core_settings::settings_get(ctx) {
+        let cfg = core_settings::pool_config_from_settings(&settings);
+        ProcessPool::reconfigure(pool.clone(), cfg);
+    }
+    ProcessPool::warmup_in_background(pool);
+}
+
+/// Tears down child processes, servers and watchers.
+fn shutdown_services(ctx: &AppContext) {
+    if let Ok(mut mgr) = ctx.state::<SharedSessionManager>().try_lock() {
+        mgr.shutdown();
+    }
+
+    let _ = ProcessPool::shutdown(ctx.state::<SharedProcessPool>().inner().clone());
+
+    if let Ok(mut rt) = ctx.state::<SharedMcpRuntime>().try_lock() {
+        rt.shutdown_all();
+    }
+
+    if let Ok(mut rt) = ctx.state::<SharedLocalhostRuntime>().try_lock() {
+        rt.shutdown_all(ctx.clone());
+    }
+
+    if let Ok(mut gw) = ctx.state::<SharedLlmGateway>().try_lock() {
+        gw.shutdown();
+    }
+
+    if let Ok(mut gw) = ctx.state::<SharedGitEventWatcher>().try_lock() {
+        gw.shutdown();
+    }
+
+    if let Ok(mut socket) = ctx.state::<SharedControlSocket>().try_lock() {
+        socket.shutdown();
+    }
+
+    if let Ok(mut api) = ctx.state::<SharedRemoteApi>().try_lock() {
+        api.shutdown();
+    }
+}
+
+/// The desktop app: webview, IPC commands and the window lifecycle.
+#[cfg(feature = "desktop")]
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let pool: SharedProcessPool = std::sync::Arc::new(std::sync::Mutex::new(ProcessPool::new(
-        PoolConfig::default(),
-    )));
-
-    let agents: SharedAgentRegistry =
-        std::sync::Arc::new(std::sync::Mutex::new(AgentRegistry::detect()));
-
-    let mcp_runtime: SharedMcpRuntime =
-        std::sync::Arc::new(std::sync::Mutex::new(McpRuntime::default()));
-
-    let localhost_runtime: SharedLocalhostRuntime =
-        std::sync::Arc::new(std::sync::Mutex::new(LocalhostRuntime::default()));
-
-    let session_manager: SharedSessionManager = std::sync::Arc::new(std::sync::Mutex::new(
-        SessionManager::new(pool.clone(), agents.clone()),
-    ));
-
-    let git_watcher: SharedGitEventWatcher =
-        std::sync::Arc::new(std::sync::Mutex::new(GitEventWatcher::new()));
-    let git_watcher_setup = git_watcher.clone();
-    let session_manager_setup = session_manager.clone();
-
-    let app = tauri::Builder::default()
-        .manage(pool.clone())
-        .manage(agents)
-        .manage(mcp_runtime.clone())
-        .manage(localhost_runtime.clone())
-        .manage(session_manager)
-        .manage(git_watcher)
+    let app = Services::new()
+        .manage_in(tauri::Builder::default())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
-        .setup(move |app| {
-            GitEventWatcher::start(
-                git_watcher_setup.clone(),
-                app.handle().clone(),
-                session_manager_setup.clone(),
-            );
+        .setup(|app| {
+            start_services(&AppContext::from(app.handle()));
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             debug_pool_stats,
             debug_pool_roundtrip,
             agents_list,
+            agents_refresh,
             onboarding_is_first_run,
             onboarding_initialize,
             onboarding_scan,
@@ -118,6 +355,7 @@ pub fn run() {
             settings_get,
             settings_set,
             settings_validate_provider_key,
+            settings_validate_provider_profile,
             settings_list_provider_models,
             settings_ollama_pull_model,
             skills_discover,
@@ -132,8 +370,12 @@ pub fn run() {
             git_detect_orphans,
             git_cleanup_orphans,
             git_branches,
+            git_commit_log,
+            git_commit_files,
+            git_refs_for_commit,
             git_diff,
             git_merge,
+            git_merge_preview,
             review_create,
             review_list,
             review_get,
@@ -149,11 +391,50 @@ pub fn run() {
             localhost_session_stop,
             localhost_session_restart,
             localhost_session_logs,
+            gateway_status,
+            gateway_usage,
+            gateway_set_rate_cap,
+            remote_api_status,
+            remote_api_rotate_token,
+            secrets_list,
+            secrets_set,
+            secrets_delete,
             session_create,
             session_destroy,
             session_write,
             session_cd,
             session_restart,
+            session_fork,
+            session_handoff,
+            session_broadcast,
+            command_complete,
+            command_execute,
+            events_since,
+            audit_query,
+            notifications_list,
+            notifications_mark_read,
+            notifications_clear,
+            hooks_deliveries,
+            hooks_test,
+            integration_list,
+            integration_get,
+            integration_resolve,
+            integration_continue,
+            integration_abort,
+            merge_queue_get,
+            merge_queue_enqueue,
+            merge_queue_remove,
+            merge_queue_pause,
+            merge_queue_resume,
+            session_set_labels,
+            checkpoint_list,
+            checkpoint_create,
+            checkpoint_diff,
+            checkpoint_restore,
+            attempts_start,
+            attempts_list,
+            attempts_compare,
+            attempts_promote,
             session_resize,
             session_scrollback,
             session_list
@@ -162,11 +443,7 @@ pub fn run() {
         .expect("error while building tauri application");
 
     // Load settings and apply pool config before warmup so warmup uses the user's config.
-    if let Ok(settings) = core_settings::settings_get(app.handle()) {
-        let cfg = core_settings::pool_config_from_settings(&settings);
-        ProcessPool::reconfigure(pool.clone(), cfg);
-    }
-    ProcessPool::warmup_in_background(pool.clone());
+    warm_pool(&AppContext::from(app.handle()));
 
     // Ensure we tear down child processes on exit (especially important during dev).
     let did_shutdown = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
@@ -182,44 +459,6 @@ pub fn run() {
         if did_shutdown_2.swap(true, std::sync::atomic::Ordering::SeqCst) {
             return;
         }
-
-        if let Ok(mut mgr) = app_handle
-            .state::<SharedSessionManager>()
-            .inner()
-            .as_ref()
-            .try_lock()
-        {
-            mgr.shutdown();
-        }
-
-        let pool = app_handle.state::<SharedProcessPool>().inner().clone();
-        let _ = ProcessPool::shutdown(pool);
-
-        if let Ok(mut rt) = app_handle
-            .state::<SharedMcpRuntime>()
-            .inner()
-            .as_ref()
-            .try_lock()
-        {
-            rt.shutdown_all();
-        }
-
-        if let Ok(mut rt) = app_handle
-            .state::<SharedLocalhostRuntime>()
-            .inner()
-            .as_ref()
-            .try_lock()
-        {
-            rt.shutdown_all(app_handle.clone());
-        }
-
-        if let Ok(mut gw) = app_handle
-            .state::<SharedGitEventWatcher>()
-            .inner()
-            .as_ref()
-            .try_lock()
-        {
-            gw.shutdown();
-        }
+        shutdown_services(&AppContext::from(app_handle));
     });
 }
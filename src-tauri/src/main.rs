@@ -2,5 +2,20 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
-    synk_lib::run()
+    if let Some(code) = synk_lib::run_headless_if_requested() {
+        std::process::exit(code);
+    }
+    if let Some(code) = synk_lib::run_cli_if_requested() {
+        std::process::exit(code);
+    }
+    if let Some(code) = synk_lib::run_simulated_agent_if_requested() {
+        std::process::exit(code);
+    }
+    #[cfg(feature = "desktop")]
+    synk_lib::run();
+    #[cfg(not(feature = "desktop"))]
+    {
+        eprintln!("synk: built without the desktop app; run `synk serve` or a `synk` subcommand");
+        std::process::exit(2);
+    }
 }
@@ -1,10 +1,11 @@
 import { useEffect, useMemo, useState } from "react";
-import { isTauri } from "@tauri-apps/api/core";
+import { backendAvailable } from "./lib/transport";
 
 import { HomeScreen } from "./components/home/HomeScreen";
 import { Workspace } from "./components/workspace/Workspace";
 import { Settings } from "./components/shared/Settings";
 import { ErrorBoundary } from "./components/shared/ErrorBoundary";
+import { Toasts } from "./components/shared/Toasts";
 import { OnboardingWizard } from "./components/onboarding/OnboardingWizard";
 import { useAppStore } from "./lib/store";
 import { onboardingIsFirstRun, settingsGet } from "./lib/tauri-api";
@@ -19,7 +20,7 @@ function App() {
   const onboardingOpen = useAppStore((s) => s.onboardingOpen);
   const setOnboardingOpen = useAppStore((s) => s.setOnboardingOpen);
 
-  const tauriAvailable = useMemo(() => isTauri(), []);
+  const tauriAvailable = useMemo(() => backendAvailable(), []);
   const [firstRun, setFirstRun] = useState<boolean | null>(null);
 
   useEffect(() => {
@@ -106,6 +107,7 @@ function App() {
         tauriAvailable={tauriAvailable}
         onClose={() => setSettingsOpen(false)}
       />
+      <Toasts tauriAvailable={tauriAvailable} />
     </ErrorBoundary>
   );
 }
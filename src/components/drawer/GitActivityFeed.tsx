@@ -1,6 +1,6 @@
 import { useCallback, useEffect, useMemo, useRef, useState } from "react";
 
-import { onGitEvent } from "../../lib/tauri-api";
+import { eventsSince, onGitEvent } from "../../lib/tauri-api";
 import type { GitEvent, GitEventType } from "../../lib/types";
 import { useAppStore } from "../../lib/store";
 
@@ -118,6 +118,18 @@ export function GitActivityFeed(props: { tauriAvailable: boolean; projectPath: s
       }
     });
 
+    // Catch up on events published before this panel mounted (e.g. after a reload). The store
+    // dedupes by id, so overlap with the live listener is harmless.
+    eventsSince(0, ["git:event"])
+      .then((res) => {
+        if (disposed) return;
+        for (const rec of res.events) {
+          const ev = rec.payload as GitEvent;
+          if (ev.projectPath === projectPath) appendGitEvent(projectPath, ev);
+        }
+      })
+      .catch(() => {});
+
     return () => {
       disposed = true;
       unlisten?.();
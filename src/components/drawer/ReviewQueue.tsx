@@ -1,8 +1,21 @@
 import { useEffect, useMemo, useState } from "react";
 
-import { gitBranches, reviewCreate, reviewGet, reviewList, reviewSetStatus, sessionList } from "../../lib/tauri-api";
-import type { ReviewItem, ReviewStatus, SessionInfo } from "../../lib/types";
-import { ReviewPanel } from "../review/ReviewPanel";
+import {
+  gitBranches,
+  mergeQueueGet,
+  mergeQueuePause,
+  mergeQueueRemove,
+  mergeQueueResume,
+  onGitEvent,
+  onMergeQueueEvent,
+  reviewCreate,
+  reviewGet,
+  reviewList,
+  reviewSetStatus,
+  sessionList,
+} from "../../lib/tauri-api";
+import type { MergeQueue, ReviewItem, ReviewStatus, SessionInfo } from "../../lib/types";
+import { mergeablePill, ReviewPanel } from "../review/ReviewPanel";
 
 function pillForStatus(status: ReviewStatus) {
   switch (status) {
@@ -22,6 +35,10 @@ function pillForStatus(status: ReviewStatus) {
       return { text: "MERGED", cls: "border-accent-green/40 bg-accent-green/10 text-accent-green" };
     case "merge_conflict":
       return { text: "CONFLICT", cls: "border-accent-red/40 bg-accent-red/10 text-accent-red" };
+    case "queued":
+      return { text: "QUEUED", cls: "border-accent-blue/40 bg-accent-blue/10 text-accent-blue" };
+    case "check_failed":
+      return { text: "CHECK FAILED", cls: "border-accent-red/40 bg-accent-red/10 text-accent-red" };
     default:
       return { text: status, cls: "border-border bg-bg-primary/40 text-text-secondary" };
   }
@@ -50,6 +67,10 @@ export function ReviewQueue(props: { tauriAvailable: boolean; projectPath: strin
   const [createBranch, setCreateBranch] = useState("");
   const [createBaseBranch, setCreateBaseBranch] = useState("main");
   const [creating, setCreating] = useState(false);
+  // Bumped when a branch moves in this project; reads recompute stale merge previews.
+  const [gitTick, setGitTick] = useState(0);
+  const [queue, setQueue] = useState<MergeQueue | null>(null);
+  const [queueBusy, setQueueBusy] = useState(false);
 
   const refresh = async (opts?: { keepSelection?: boolean }) => {
     if (!tauriAvailable) return;
@@ -79,6 +100,81 @@ export function ReviewQueue(props: { tauriAvailable: boolean; projectPath: strin
     refresh();
   }, [tauriAvailable, projectPath]);
 
+  useEffect(() => {
+    if (!tauriAvailable) return;
+    if (!projectPath) return;
+    let unlisten: (() => void) | null = null;
+    let disposed = false;
+    let timer: number | undefined;
+    onGitEvent((ev) => {
+      if (ev.projectPath !== projectPath) return;
+      window.clearTimeout(timer);
+      timer = window.setTimeout(() => setGitTick((n) => n + 1), 500);
+    }).then((fn) => {
+      if (disposed) {
+        fn();
+      } else {
+        unlisten = fn;
+      }
+    });
+    return () => {
+      disposed = true;
+      window.clearTimeout(timer);
+      unlisten?.();
+    };
+  }, [tauriAvailable, projectPath]);
+
+  useEffect(() => {
+    setQueue(null);
+    if (!tauriAvailable) return;
+    if (!projectPath) return;
+    mergeQueueGet(projectPath)
+      .then(setQueue)
+      .catch(() => setQueue(null));
+    let unlisten: (() => void) | null = null;
+    let disposed = false;
+    onMergeQueueEvent((ev) => {
+      if (ev.projectPath !== projectPath) return;
+      mergeQueueGet(projectPath)
+        .then(setQueue)
+        .catch(() => {});
+      // Entries change review status as they move through the queue.
+      setGitTick((n) => n + 1);
+    }).then((fn) => {
+      if (disposed) {
+        fn();
+      } else {
+        unlisten = fn;
+      }
+    });
+    return () => {
+      disposed = true;
+      unlisten?.();
+    };
+  }, [tauriAvailable, projectPath]);
+
+  const queueAction = async (fn: () => Promise<MergeQueue>) => {
+    setQueueBusy(true);
+    setError(null);
+    try {
+      setQueue(await fn());
+      setGitTick((n) => n + 1);
+    } catch (e) {
+      setError(e instanceof Error ? e.message : String(e));
+    } finally {
+      setQueueBusy(false);
+    }
+  };
+
+  useEffect(() => {
+    if (gitTick === 0 || !projectPath) return;
+    refresh({ keepSelection: true });
+    if (!selectedId) return;
+    reviewGet(projectPath, selectedId)
+      .then((it) => setSelected((cur) => (cur?.id === it.id ? it : cur)))
+      .catch(() => {});
+  }, [gitTick]);
+
   useEffect(() => {
     if (!tauriAvailable) return;
     if (!projectPath) return;
@@ -155,7 +251,7 @@ export function ReviewQueue(props: { tauriAvailable: boolean; projectPath: strin
 
   const onItemUpdated = (it: ReviewItem) => {
     setSelected(it);
-    setItems((prev) => prev.map((p) => (p.id === it.id ? { ...p, status: it.status, updatedAt: it.updatedAt, additions: it.additions, deletions: it.deletions, filesChanged: it.filesChanged } : p)));
+    setItems((prev) => prev.map((p) => (p.id === it.id ? { ...p, status: it.status, updatedAt: it.updatedAt, additions: it.additions, deletions: it.deletions, filesChanged: it.filesChanged, mergePreview: it.mergePreview } : p)));
   };
 
   const selectedSummary = useMemo(() => items.find((i) => i.id === selectedId) ?? null, [items, selectedId]);
@@ -308,6 +404,66 @@ export function ReviewQueue(props: { tauriAvailable: boolean; projectPath: strin
         </div>
       ) : null}
 
+      {queue && (queue.entries.length > 0 || queue.paused || queue.lastFailure) ? (
+        <div className="rounded-xl border border-border bg-bg-primary/30 p-3">
+          <div className="flex items-center justify-between gap-2">
+            <div className="text-xs font-semibold tracking-tight text-text-secondary">
+              Merge queue ({queue.entries.length})
+              {queue.paused ? <span className="ml-2 font-mono text-[11px] text-accent-orange">paused</span> : null}
+            </div>
+            <button
+              type="button"
+              className="rounded-lg border border-border bg-bg-secondary px-2 py-1 text-[11px] font-semibold text-text-secondary hover:bg-bg-hover hover:text-text-primary disabled:opacity-60"
+              disabled={queueBusy}
+              onClick={() => void queueAction(() => (queue.paused ? mergeQueueResume(projectPath) : mergeQueuePause(projectPath)))}
+            >
+              {queue.paused ? "Resume" : "Pause"}
+            </button>
+          </div>
+
+          {queue.lastFailure ? (
+            <div className="mt-2 rounded-lg border border-accent-red/40 bg-accent-red/10 p-2 text-[11px] text-accent-red">
+              <div className="font-semibold">
+                {queue.lastFailure.branch} failed · {fmtTime(queue.lastFailure.at)}
+              </div>
+              <pre className="mt-1 max-h-32 overflow-auto whitespace-pre-wrap font-mono text-[11px] text-accent-red/90">
+                {queue.lastFailure.reason}
+              </pre>
+            </div>
+          ) : null}
+
+          {queue.entries.length ? (
+            <ol className="mt-2 space-y-1">
+              {queue.entries.map((e, i) => (
+                <li key={e.reviewId} className="flex items-center gap-2 text-[11px]">
+                  <span className="w-4 font-mono text-text-secondary">{i + 1}</span>
+                  <button
+                    type="button"
+                    className="min-w-0 flex-1 truncate text-left font-mono text-text-primary hover:underline"
+                    onClick={() => setSelectedId(e.reviewId)}
+                  >
+                    {e.branch} → {e.baseBranch}
+                  </button>
+                  <span className="font-mono text-text-secondary">{e.strategy}</span>
+                  {e.state === "running" ? (
+                    <span className="font-mono text-accent-blue">merging…</span>
+                  ) : (
+                    <button
+                      type="button"
+                      className="rounded-md border border-border px-1.5 py-0.5 text-text-secondary hover:bg-bg-hover hover:text-text-primary disabled:opacity-60"
+                      disabled={queueBusy}
+                      onClick={() => void queueAction(() => mergeQueueRemove(projectPath, e.reviewId))}
+                    >
+                      Remove
+                    </button>
+                  )}
+                </li>
+              ))}
+            </ol>
+          ) : null}
+        </div>
+      ) : null}
+
       <div className="flex min-h-0 flex-1 gap-3">
         <div className="w-[320px] min-w-[260px] max-w-[380px] overflow-auto rounded-xl border border-border bg-bg-primary/30">
           {items.length === 0 ? (
@@ -322,6 +478,7 @@ export function ReviewQueue(props: { tauriAvailable: boolean; projectPath: strin
               {items.map((it) => {
                 const selected = it.id === selectedId;
                 const pill = pillForStatus(it.status);
+                const mergeable = it.status === "merged" ? null : mergeablePill(it.mergePreview);
                 return (
                   <li key={it.id}>
                     <button
@@ -349,6 +506,11 @@ export function ReviewQueue(props: { tauriAvailable: boolean; projectPath: strin
                         </span>
                         <span className="text-accent-green">+{it.additions}</span>
                         <span className="text-accent-red">−{it.deletions}</span>
+                        {mergeable ? (
+                          <span className={["ml-auto rounded-full border px-1.5 py-0.5 font-mono text-[9px]", mergeable.cls].join(" ")} title={mergeable.title}>
+                            {mergeable.text}
+                          </span>
+                        ) : null}
                       </div>
                     </button>
                   </li>
@@ -1,6 +1,6 @@
 import { useEffect, useMemo, useState } from "react";
 import { open } from "@tauri-apps/plugin-dialog";
-import { isTauri } from "@tauri-apps/api/core";
+import { backendAvailable } from "../../lib/transport";
 
 import type { RecentProject } from "../../lib/types";
 import type { SessionSnapshotMeta } from "../../lib/types";
@@ -36,7 +36,7 @@ export function HomeScreen() {
   const setPendingSessionRestoreId = useAppStore((s) => s.setPendingSessionRestoreId);
   const autoSaveEnabled = useAppStore((s) => s.settings?.session?.autoSave ?? true);
 
-  const tauriAvailable = useMemo(() => isTauri(), []);
+  const tauriAvailable = useMemo(() => backendAvailable(), []);
   const [recentProjects, setRecentProjects] = useState<RecentProject[]>([]);
   const [busy, setBusy] = useState(false);
   const [error, setError] = useState<string | null>(null);
@@ -17,6 +17,8 @@ function agentLabel(t: DetectedAgent["agentType"]): string {
       return "OpenAI Codex";
     case "openrouter":
       return "OpenRouter";
+    case "simulated":
+      return "Simulated Agent";
     case "terminal":
       return "Terminal";
     default:
@@ -3,6 +3,7 @@ import { useMemo, useState } from "react";
 import type { AiProviderId, AppSettings, ProviderKeyValidationResult, ProviderModelsResult } from "../../lib/types";
 import { settingsListProviderModels, settingsValidateProviderKey } from "../../lib/tauri-api";
 import { KNOWN_MODELS, mergeModelLists } from "../../lib/known-models";
+import { editMaskedKey } from "../../lib/masked-key";
 
 type ProviderSetupProps = {
   settings: AppSettings;
@@ -244,7 +245,7 @@ export function ProviderSetup(props: ProviderSetupProps) {
               placeholder="paste key..."
               value={key}
               onChange={(e) => {
-                const nextKey = e.target.value;
+                const nextKey = editMaskedKey(key, e.target.value);
                 props.onChange({
                   ...s,
                   aiProviders: {
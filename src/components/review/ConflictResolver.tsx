@@ -0,0 +1,191 @@
+import { useCallback, useEffect, useState } from "react";
+
+import { integrationAbort, integrationContinue, integrationGet, integrationResolve } from "../../lib/tauri-api";
+import type { ConflictFile, Integration, IntegrationDetail, Resolution } from "../../lib/types";
+
+type Editing = { path: string; hunk: number | null; content: string };
+
+function SideBlock(props: { label: string; text: string | null; cls: string }) {
+  return (
+    <div className="min-w-0">
+      <div className={["text-[10px] font-semibold tracking-[0.14em]", props.cls].join(" ")}>{props.label}</div>
+      <pre className="mt-1 max-h-40 overflow-auto rounded-lg border border-border bg-bg-primary/40 p-2 font-mono text-[11px] text-text-primary">
+        {props.text ?? "(deleted)"}
+      </pre>
+    </div>
+  );
+}
+
+/**
+ * Resolves a conflicted merge kept in progress in an integration worktree: pick a side per file
+ * or per hunk (or edit it), then continue to move the base branch, or abort.
+ */
+export function ConflictResolver(props: {
+  tauriAvailable: boolean;
+  projectPath: string;
+  integration: Integration;
+  /** Called after continue (`merged`) or abort. */
+  onDone: (merged: boolean) => void;
+}) {
+  const { tauriAvailable, projectPath, integration, onDone } = props;
+  const [detail, setDetail] = useState<IntegrationDetail | null>(null);
+  const [editing, setEditing] = useState<Editing | null>(null);
+  const [busy, setBusy] = useState(false);
+  const [err, setErr] = useState<string | null>(null);
+
+  const refresh = useCallback(() => {
+    if (!tauriAvailable) return;
+    integrationGet(projectPath, integration.id)
+      .then(setDetail)
+      .catch((e) => setErr(String(e)));
+  }, [tauriAvailable, projectPath, integration.id]);
+
+  useEffect(() => {
+    refresh();
+  }, [refresh]);
+
+  const run = async (fn: () => Promise<void>) => {
+    setBusy(true);
+    setErr(null);
+    try {
+      await fn();
+    } catch (e) {
+      setErr(e instanceof Error ? e.message : String(e));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const resolve = (path: string, resolution: Resolution, hunk: number | null) =>
+    run(async () => {
+      setDetail(await integrationResolve(projectPath, integration.id, path, resolution, hunk));
+      setEditing(null);
+    });
+
+  const pickButtons = (f: ConflictFile, hunk: number | null, seed: string) => (
+    <div className="flex flex-wrap items-center gap-1.5">
+      {(["ours", "theirs", "both"] as const).map((kind) => (
+        <button
+          key={kind}
+          type="button"
+          className="h-7 rounded-lg border border-border bg-bg-tertiary px-2 text-[11px] hover:bg-bg-hover disabled:opacity-50"
+          disabled={busy || (kind === "both" && f.hunks.length === 0)}
+          onClick={() => void resolve(f.path, { kind }, hunk)}
+        >
+          {kind === "ours" ? `Keep ${integration.baseBranch}` : kind === "theirs" ? `Take ${integration.branch}` : "Both"}
+        </button>
+      ))}
+      <button
+        type="button"
+        className="h-7 rounded-lg border border-border bg-bg-tertiary px-2 text-[11px] hover:bg-bg-hover disabled:opacity-50"
+        disabled={busy || f.binary}
+        onClick={() => setEditing({ path: f.path, hunk, content: seed })}
+      >
+        Edit
+      </button>
+    </div>
+  );
+
+  const open = detail?.conflicts ?? [];
+
+  return (
+    <div className="mt-3 rounded-xl border border-accent-orange/40 bg-accent-orange/5 p-3">
+      <div className="flex flex-wrap items-center justify-between gap-2">
+        <div className="min-w-0 text-[12px] text-text-primary">
+          Merge in progress in <span className="font-mono">{integration.worktreePath}</span>
+          <div className="text-[11px] text-text-secondary">
+            {open.length} unresolved · {detail?.resolved.length ?? 0} resolved
+          </div>
+        </div>
+        <div className="flex items-center gap-2">
+          <button
+            type="button"
+            className="rounded-xl border border-accent-green/45 bg-accent-green/10 px-3 py-1.5 text-[11px] font-semibold text-accent-green hover:bg-accent-green/15 disabled:opacity-60"
+            disabled={!tauriAvailable || busy || !detail || open.length > 0}
+            onClick={() =>
+              void run(async () => {
+                await integrationContinue(projectPath, integration.id);
+                onDone(true);
+              })
+            }
+          >
+            Continue merge
+          </button>
+          <button
+            type="button"
+            className="rounded-xl border border-accent-red/45 bg-accent-red/10 px-3 py-1.5 text-[11px] font-semibold text-accent-red hover:bg-accent-red/15 disabled:opacity-60"
+            disabled={!tauriAvailable || busy}
+            onClick={() =>
+              void run(async () => {
+                await integrationAbort(projectPath, integration.id);
+                onDone(false);
+              })
+            }
+          >
+            Abort
+          </button>
+        </div>
+      </div>
+
+      {err ? <div className="mt-2 text-xs text-accent-red">{err}</div> : null}
+
+      <div className="mt-3 max-h-[420px] space-y-3 overflow-auto">
+        {open.map((f) => (
+          <div key={f.path} className="rounded-lg border border-border bg-bg-secondary p-2">
+            <div className="flex flex-wrap items-center justify-between gap-2">
+              <div className="truncate font-mono text-[12px] text-text-primary">
+                {f.path}
+                {f.binary ? <span className="ml-2 text-text-secondary">(binary)</span> : null}
+              </div>
+              {pickButtons(f, null, f.ours ?? f.theirs ?? "")}
+            </div>
+
+            {f.hunks.map((h) => (
+              <div key={h.index} className="mt-2 rounded-lg border border-border/60 p-2">
+                <div className="flex items-center justify-between gap-2">
+                  <span className="font-mono text-[11px] text-text-secondary">line {h.startLine}</span>
+                  {pickButtons(f, h.index, h.ours + h.theirs)}
+                </div>
+                <div className="mt-2 grid grid-cols-1 gap-2 md:grid-cols-3">
+                  <SideBlock label={integration.baseBranch.toUpperCase()} text={h.ours} cls="text-accent-blue" />
+                  <SideBlock label="BASE" text={h.base} cls="text-text-secondary" />
+                  <SideBlock label={integration.branch.toUpperCase()} text={h.theirs} cls="text-accent-green" />
+                </div>
+              </div>
+            ))}
+
+            {editing && editing.path === f.path ? (
+              <div className="mt-2">
+                <textarea
+                  className="h-40 w-full rounded-lg border border-border bg-bg-tertiary p-2 font-mono text-[11px] text-text-primary"
+                  value={editing.content}
+                  onChange={(e) => setEditing({ ...editing, content: e.target.value })}
+                />
+                <div className="mt-1 flex justify-end gap-2">
+                  <button
+                    type="button"
+                    className="h-7 rounded-lg border border-border px-2 text-[11px] hover:bg-bg-hover"
+                    onClick={() => setEditing(null)}
+                  >
+                    Cancel
+                  </button>
+                  <button
+                    type="button"
+                    className="h-7 rounded-lg border border-accent-blue/45 bg-accent-blue/10 px-2 text-[11px] text-accent-blue disabled:opacity-50"
+                    disabled={busy}
+                    onClick={() => void resolve(f.path, { kind: "edited", content: editing.content }, editing.hunk)}
+                  >
+                    {editing.hunk == null ? "Save file" : `Save hunk ${editing.hunk + 1}`}
+                  </button>
+                </div>
+              </div>
+            ) : null}
+          </div>
+        ))}
+        {detail && open.length === 0 ? (
+          <div className="text-xs text-text-secondary">All conflicts resolved. Continue to update {integration.baseBranch}.</div>
+        ) : null}
+      </div>
+    </div>
+  );
+}
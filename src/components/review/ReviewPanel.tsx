@@ -1,9 +1,10 @@
 import { useEffect, useMemo, useState } from "react";
 
-import { gitMerge, reviewSetDecision, reviewSetMergeStrategy, reviewSetStatus } from "../../lib/tauri-api";
+import { gitMerge, mergeQueueEnqueue, reviewGet, reviewSetDecision, reviewSetMergeStrategy, reviewSetStatus } from "../../lib/tauri-api";
 import { useAppStore } from "../../lib/store";
-import type { FileDiff, MergeStrategy, ReviewDecision, ReviewItem } from "../../lib/types";
+import type { FileDiff, Integration, MergePreview, MergeStrategy, ReviewDecision, ReviewItem } from "../../lib/types";
 import { CommentThread } from "./CommentThread";
+import { ConflictResolver } from "./ConflictResolver";
 import { DiffViewer } from "./DiffViewer";
 
 function strategyLabel(s: MergeStrategy) {
@@ -50,11 +51,28 @@ function statusPill(status: ReviewItem["status"]) {
       return { text: "MERGED", cls: "border-accent-green/40 bg-accent-green/10 text-accent-green" };
     case "merge_conflict":
       return { text: "CONFLICT", cls: "border-accent-red/40 bg-accent-red/10 text-accent-red" };
+    case "queued":
+      return { text: "QUEUED", cls: "border-accent-blue/40 bg-accent-blue/10 text-accent-blue" };
+    case "check_failed":
+      return { text: "CHECK FAILED", cls: "border-accent-red/40 bg-accent-red/10 text-accent-red" };
     default:
       return { text: status, cls: "border-border bg-bg-primary/40 text-text-secondary" };
   }
 }
 
+export function mergeablePill(preview: MergePreview | null | undefined) {
+  if (!preview) return null;
+  if (preview.mergeable) {
+    return { text: "MERGEABLE", cls: "border-accent-green/40 bg-accent-green/10 text-accent-green", title: "Merges cleanly" };
+  }
+  const n = preview.conflictFiles.length;
+  return {
+    text: `CONFLICTS ${n}`,
+    cls: "border-accent-red/40 bg-accent-red/10 text-accent-red",
+    title: `Would conflict in ${n} file${n === 1 ? "" : "s"}:\n${preview.conflictFiles.join("\n")}`,
+  };
+}
+
 function fileLabel(f: FileDiff) {
   const s =
     f.status === "added"
@@ -83,6 +101,7 @@ export function ReviewPanel(props: {
   const [busy, setBusy] = useState(false);
   const [err, setErr] = useState<string | null>(null);
   const [conflicts, setConflicts] = useState<string[] | null>(null);
+  const [integration, setIntegration] = useState<Integration | null>(null);
 
   const mergeStrategy: MergeStrategy = (review.mergeStrategy ?? defaultStrategy) as MergeStrategy;
 
@@ -92,14 +111,17 @@ export function ReviewPanel(props: {
     setSelectedLine(null);
     setErr(null);
     setConflicts(null);
+    setIntegration(null);
   }, [review.id]);
 
   const file = useMemo(() => (selectedFile ? review.files.find((f) => f.path === selectedFile) ?? null : null), [review.files, selectedFile]);
 
   const topStatus = statusPill(review.status);
   const topDecision = decisionPill(review.reviewDecision ?? null);
+  const topMergeable = review.status === "merged" ? null : mergeablePill(review.mergePreview);
 
-  const canAct = tauriAvailable && !busy && review.status !== "merged" && review.status !== "merging";
+  const canAct =
+    tauriAvailable && !busy && review.status !== "merged" && review.status !== "merging" && review.status !== "queued";
 
   return (
     <div className="flex h-full min-h-0 flex-col">
@@ -120,6 +142,14 @@ export function ReviewPanel(props: {
           </div>
 
           <div className="flex items-center gap-2">
+            {topMergeable ? (
+              <span
+                className={["rounded-full border px-2 py-1 font-mono text-[10px]", topMergeable.cls].join(" ")}
+                title={topMergeable.title}
+              >
+                {topMergeable.text}
+              </span>
+            ) : null}
             <span className={["rounded-full border px-2 py-1 font-mono text-[10px]", topStatus.cls].join(" ")}>
               {topStatus.text}
             </span>
@@ -168,6 +198,7 @@ export function ReviewPanel(props: {
                 setBusy(true);
                 setErr(null);
                 setConflicts(null);
+                setIntegration(null);
                 try {
                   const decided = await reviewSetDecision(projectPath, review.id, "approved");
                   onReviewUpdated(decided);
@@ -183,6 +214,7 @@ export function ReviewPanel(props: {
                     const conflict = await reviewSetStatus(projectPath, review.id, "merge_conflict");
                     onReviewUpdated(conflict);
                     setConflicts(res.conflictFiles ?? null);
+                    setIntegration(res.integration ?? null);
                   }
                 } catch (e2) {
                   setErr(e2 instanceof Error ? e2.message : String(e2));
@@ -194,6 +226,33 @@ export function ReviewPanel(props: {
               Approve
             </button>
 
+            <button
+              type="button"
+              className="rounded-xl border border-accent-blue/45 bg-accent-blue/10 px-3 py-2 text-[11px] font-semibold text-accent-blue hover:bg-accent-blue/15 disabled:opacity-60"
+              disabled={!canAct}
+              title="Approve and merge after the reviews already queued for this project"
+              onClick={async () => {
+                if (!tauriAvailable) return;
+                setBusy(true);
+                setErr(null);
+                setConflicts(null);
+                setIntegration(null);
+                try {
+                  if (review.reviewDecision !== "approved") {
+                    onReviewUpdated(await reviewSetDecision(projectPath, review.id, "approved"));
+                  }
+                  await mergeQueueEnqueue(projectPath, review.id);
+                  onReviewUpdated(await reviewGet(projectPath, review.id));
+                } catch (e2) {
+                  setErr(e2 instanceof Error ? e2.message : String(e2));
+                } finally {
+                  setBusy(false);
+                }
+              }}
+            >
+              Queue
+            </button>
+
             <button
               type="button"
               className="rounded-xl border border-accent-red/45 bg-accent-red/10 px-3 py-2 text-[11px] font-semibold text-accent-red hover:bg-accent-red/15 disabled:opacity-60"
@@ -256,6 +315,24 @@ export function ReviewPanel(props: {
             </ul>
           </div>
         ) : null}
+
+        {integration ? (
+          <ConflictResolver
+            tauriAvailable={tauriAvailable}
+            projectPath={projectPath}
+            integration={integration}
+            onDone={async (merged) => {
+              setIntegration(null);
+              setConflicts(null);
+              if (!merged) return;
+              try {
+                onReviewUpdated(await reviewSetStatus(projectPath, review.id, "merged"));
+              } catch (e2) {
+                setErr(e2 instanceof Error ? e2.message : String(e2));
+              }
+            }}
+          />
+        ) : null}
       </div>
 
       <div className="min-h-0 flex-1">
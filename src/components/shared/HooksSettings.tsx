@@ -0,0 +1,214 @@
+import { useCallback, useEffect, useState } from "react";
+
+import type { HookDelivery, HookEvent, HookSettings } from "../../lib/types";
+import { hooksDeliveries } from "../../lib/tauri-api";
+
+const HOOK_EVENTS: ReadonlyArray<{ id: HookEvent; label: string }> = [
+  { id: "review_ready", label: "Review ready" },
+  { id: "merge_completed", label: "Merge completed" },
+  { id: "conflict_detected", label: "Conflict detected" },
+  { id: "session_crashed", label: "Session crashed" },
+  { id: "budget_exceeded", label: "Budget exceeded" },
+];
+
+type HooksSettingsProps = {
+  hooks: HookSettings[];
+  tauriAvailable: boolean;
+  onChange: (hooks: HookSettings[]) => void;
+  /** Saves pending settings, then sends a test payload. */
+  onTest: (hookId: string) => Promise<HookDelivery>;
+};
+
+function newHook(): HookSettings {
+  return {
+    id: `hook-${Date.now().toString(36)}`,
+    name: "New hook",
+    enabled: true,
+    events: ["review_ready"],
+    url: "",
+    command: null,
+    maxAttempts: 3,
+  };
+}
+
+export function HooksSettings(props: HooksSettingsProps) {
+  const { hooks, tauriAvailable, onChange, onTest } = props;
+  const [deliveries, setDeliveries] = useState<HookDelivery[]>([]);
+  const [testing, setTesting] = useState<string | null>(null);
+  const [testError, setTestError] = useState<string | null>(null);
+
+  const refresh = useCallback(() => {
+    if (!tauriAvailable) return;
+    hooksDeliveries(null, 10)
+      .then(setDeliveries)
+      .catch(() => {});
+  }, [tauriAvailable]);
+
+  useEffect(() => {
+    refresh();
+  }, [refresh]);
+
+  const update = (id: string, patch: Partial<HookSettings>) =>
+    onChange(hooks.map((h) => (h.id === id ? { ...h, ...patch } : h)));
+
+  const test = async (id: string) => {
+    setTesting(id);
+    setTestError(null);
+    try {
+      await onTest(id);
+    } catch (e) {
+      setTestError(String(e));
+    } finally {
+      setTesting(null);
+      refresh();
+    }
+  };
+
+  const inputClass =
+    "mt-1 h-9 w-full rounded-lg border border-border bg-bg-tertiary px-2 text-xs text-text-primary";
+
+  return (
+    <div className="rounded-2xl border border-border bg-bg-secondary p-4 lg:col-span-2">
+      <div className="flex items-center justify-between gap-2">
+        <div>
+          <div className="text-sm font-semibold">Hooks</div>
+          <div className="mt-0.5 text-[11px] text-text-secondary">
+            POST a JSON payload to a URL or run a command with it on stdin. Failures are retried with backoff.
+          </div>
+        </div>
+        <button
+          type="button"
+          className="h-8 rounded-lg border border-border bg-bg-tertiary px-3 text-xs hover:bg-bg-hover"
+          onClick={() => onChange([...hooks, newHook()])}
+        >
+          Add hook
+        </button>
+      </div>
+
+      <div className="mt-3 space-y-3">
+        {hooks.map((h) => {
+          const mode = h.command != null ? "command" : "url";
+          return (
+            <div key={h.id} className="rounded-xl border border-border bg-bg-tertiary p-3">
+              <div className="flex items-center gap-2">
+                <input
+                  type="checkbox"
+                  checked={h.enabled}
+                  onChange={(e) => update(h.id, { enabled: e.target.checked })}
+                  aria-label="Enabled"
+                />
+                <input
+                  className="h-8 min-w-0 flex-1 rounded-lg border border-border bg-bg-secondary px-2 text-xs text-text-primary"
+                  value={h.name}
+                  onChange={(e) => update(h.id, { name: e.target.value })}
+                />
+                <button
+                  type="button"
+                  className="h-8 rounded-lg border border-border px-3 text-xs hover:bg-bg-hover disabled:opacity-50"
+                  disabled={!tauriAvailable || testing === h.id}
+                  onClick={() => void test(h.id)}
+                >
+                  {testing === h.id ? "Sending..." : "Test"}
+                </button>
+                <button
+                  type="button"
+                  className="h-8 rounded-lg border border-border px-3 text-xs text-accent-red hover:bg-bg-hover"
+                  onClick={() => onChange(hooks.filter((x) => x.id !== h.id))}
+                >
+                  Remove
+                </button>
+              </div>
+
+              <div className="mt-3 flex flex-wrap gap-3">
+                {HOOK_EVENTS.map(({ id, label }) => (
+                  <label key={id} className="flex cursor-pointer items-center gap-1.5 text-xs text-text-primary">
+                    <input
+                      type="checkbox"
+                      checked={h.events.includes(id)}
+                      onChange={(e) =>
+                        update(h.id, {
+                          events: e.target.checked ? [...h.events, id] : h.events.filter((x) => x !== id),
+                        })
+                      }
+                    />
+                    {label}
+                  </label>
+                ))}
+              </div>
+
+              <div className="mt-3 grid grid-cols-1 gap-3 md:grid-cols-[140px_1fr_110px]">
+                <label className="block">
+                  <div className="text-[10px] font-semibold tracking-[0.14em] text-text-secondary">TARGET</div>
+                  <select
+                    className={inputClass}
+                    value={mode}
+                    onChange={(e) =>
+                      update(
+                        h.id,
+                        e.target.value === "command" ? { url: null, command: "" } : { url: "", command: null },
+                      )
+                    }
+                  >
+                    <option value="url">Webhook URL</option>
+                    <option value="command">Command</option>
+                  </select>
+                </label>
+                <label className="block">
+                  <div className="text-[10px] font-semibold tracking-[0.14em] text-text-secondary">
+                    {mode === "command" ? "COMMAND" : "URL"}
+                  </div>
+                  <input
+                    className={`${inputClass} font-mono`}
+                    placeholder={mode === "command" ? "~/bin/on-synk-event.sh" : "https://hooks.example.com/..."}
+                    value={(mode === "command" ? h.command : h.url) ?? ""}
+                    onChange={(e) =>
+                      update(h.id, mode === "command" ? { command: e.target.value } : { url: e.target.value })
+                    }
+                  />
+                </label>
+                <label className="block">
+                  <div className="text-[10px] font-semibold tracking-[0.14em] text-text-secondary">ATTEMPTS</div>
+                  <input
+                    className={inputClass}
+                    type="number"
+                    min={1}
+                    max={10}
+                    value={h.maxAttempts}
+                    onChange={(e) => update(h.id, { maxAttempts: Math.min(10, Math.max(1, Number(e.target.value))) })}
+                  />
+                </label>
+              </div>
+            </div>
+          );
+        })}
+        {hooks.length === 0 ? <div className="text-xs text-text-secondary">No hooks configured.</div> : null}
+      </div>
+
+      {testError ? <div className="mt-3 text-xs text-accent-red">{testError}</div> : null}
+
+      {deliveries.length > 0 ? (
+        <div className="mt-4">
+          <div className="text-[10px] font-semibold tracking-[0.14em] text-text-secondary">RECENT DELIVERIES</div>
+          <div className="mt-1 space-y-1">
+            {deliveries.map((d, i) => (
+              <div
+                key={`${d.timestamp}-${d.hookId}-${i}`}
+                className="flex items-center gap-2 rounded-lg border border-border bg-bg-tertiary px-2 py-1.5 text-[11px]"
+              >
+                <span className={d.ok ? "text-accent-green" : "text-accent-red"}>{d.ok ? "ok" : "failed"}</span>
+                <span className="text-text-primary">{d.hookName || d.hookId}</span>
+                <span className="text-text-secondary">{d.event}</span>
+                <span className="min-w-0 flex-1 truncate text-text-secondary" title={d.error ?? undefined}>
+                  {d.error ?? (d.status != null ? `HTTP ${d.status}` : "")}
+                </span>
+                <span className="text-text-secondary">
+                  {d.attempts}x · {d.durationMs} ms
+                </span>
+              </div>
+            ))}
+          </div>
+        </div>
+      ) : null}
+    </div>
+  );
+}
@@ -7,10 +7,14 @@ import type {
   NotificationsSettings,
   ProviderKeyValidationResult,
   ProviderModelsResult,
+  RemoteApiStatus,
   TerminalExitMethod,
   ToastPosition,
 } from "../../lib/types";
 import {
+  hooksTest,
+  remoteApiRotateToken,
+  remoteApiStatus,
   settingsGet,
   settingsListProviderModels,
   settingsOllamaPullModel,
@@ -20,6 +24,8 @@ import {
 import { useAppStore } from "../../lib/store";
 import { defaultAppSettings } from "../../lib/default-settings";
 import { KNOWN_MODELS, mergeModelLists } from "../../lib/known-models";
+import { editMaskedKey } from "../../lib/masked-key";
+import { HooksSettings } from "./HooksSettings";
 
 type SettingsProps = {
   open: boolean;
@@ -71,6 +77,7 @@ function ensureSettingsShape(input: AppSettings): AppSettings {
   return {
     ...input,
     aiProviders: ensureAiProvidersShape((input as { aiProviders?: unknown }).aiProviders),
+    hooks: input.hooks ?? [],
   };
 }
 
@@ -191,6 +198,7 @@ export function Settings(props: SettingsProps) {
   const [ollamaInstallModel, setOllamaInstallModel] = useState("");
   const [ollamaInstallBusy, setOllamaInstallBusy] = useState(false);
   const [ollamaInstallMessage, setOllamaInstallMessage] = useState<string | null>(null);
+  const [remoteApi, setRemoteApi] = useState<RemoteApiStatus | null>(null);
   const saveTimerRef = useRef<number | null>(null);
   const statusTimerRef = useRef<number | null>(null);
   const validateTimersRef = useRef<Record<string, number | null>>({});
@@ -202,6 +210,13 @@ export function Settings(props: SettingsProps) {
     [],
   );
 
+  const refreshRemoteApi = useCallback(() => {
+    if (!tauriAvailable) return;
+    remoteApiStatus()
+      .then(setRemoteApi)
+      .catch(() => setRemoteApi(null));
+  }, [tauriAvailable]);
+
   const saveNow = async (next: AppSettings) => {
     if (!tauriAvailable) {
       setError("Browser preview mode: settings persistence requires `npm run tauri dev`.");
@@ -215,6 +230,7 @@ export function Settings(props: SettingsProps) {
       const saved = await settingsSet(normalized);
       setSettings(saved);
       setDraft(saved);
+      refreshRemoteApi();
       setStatus("Saved");
       if (statusTimerRef.current !== null) {
         window.clearTimeout(statusTimerRef.current);
@@ -301,6 +317,10 @@ export function Settings(props: SettingsProps) {
     }
   }, [open, settings, setSettings, tauriAvailable]);
 
+  useEffect(() => {
+    if (open) refreshRemoteApi();
+  }, [open, refreshRemoteApi]);
+
   useEffect(() => {
     return () => {
       if (saveTimerRef.current !== null) {
@@ -687,7 +707,7 @@ export function Settings(props: SettingsProps) {
               placeholder="paste key..."
               value={key}
               onChange={(e) => {
-                const nextKey = e.target.value;
+                const nextKey = editMaskedKey(key, e.target.value);
                 setDraftAndSave({
                   ...s,
                   aiProviders: {
@@ -1210,7 +1230,9 @@ export function Settings(props: SettingsProps) {
               {tab === "notifications" ? (
                 <div>
                   <div className="text-lg font-semibold tracking-tight">Notifications</div>
-                  <div className="mt-1 text-sm text-text-secondary">Toast defaults and per-type toggles.</div>
+                  <div className="mt-1 text-sm text-text-secondary">
+                    Toast defaults, per-type toggles, and hooks for chat or scripts.
+                  </div>
 
                   <div className="mt-5 grid grid-cols-1 gap-4 lg:grid-cols-2">
                     <div className="rounded-2xl border border-border bg-bg-secondary p-4">
@@ -1283,9 +1305,19 @@ export function Settings(props: SettingsProps) {
                         </label>
                       </div>
                       <div className="mt-3 rounded-xl border border-border bg-bg-tertiary px-3 py-3 text-[11px] text-text-secondary">
-                        Toast UI ships later; these values are persisted now.
+                        Toasts stay until dismissed or the duration elapses; unread ones come back after a reload.
                       </div>
                     </div>
+
+                    <HooksSettings
+                      hooks={s.hooks}
+                      tauriAvailable={tauriAvailable}
+                      onChange={(hooks) => setDraftAndSave({ ...s, hooks })}
+                      onTest={async (hookId) => {
+                        await settingsSet(s);
+                        return hooksTest(hookId);
+                      }}
+                    />
                   </div>
                 </div>
               ) : null}
@@ -1330,6 +1362,59 @@ export function Settings(props: SettingsProps) {
                             }
                           />
                         </label>
+                        <label className="flex cursor-pointer items-start justify-between gap-2 rounded-xl border border-border bg-bg-tertiary px-3 py-3 text-xs hover:bg-bg-hover">
+                          <span className="min-w-0 break-words text-text-primary">Checkpoint after each agent turn</span>
+                          <input
+                            type="checkbox"
+                            checked={s.git.checkpointOnIdle}
+                            onChange={(e) =>
+                              setDraftAndSave({ ...s, git: { ...s.git, checkpointOnIdle: e.target.checked } })
+                            }
+                          />
+                        </label>
+                        <label className="flex cursor-pointer items-start justify-between gap-2 rounded-xl border border-border bg-bg-tertiary px-3 py-3 text-xs hover:bg-bg-hover">
+                          <span className="min-w-0 break-words text-text-primary">Resolve conflicts interactively</span>
+                          <input
+                            type="checkbox"
+                            checked={s.git.interactiveConflicts}
+                            onChange={(e) =>
+                              setDraftAndSave({ ...s, git: { ...s.git, interactiveConflicts: e.target.checked } })
+                            }
+                          />
+                        </label>
+                        <label className="block md:col-span-2">
+                          <div className="text-[10px] font-semibold tracking-[0.14em] text-text-secondary">
+                            MERGE QUEUE CHECK
+                          </div>
+                          <input
+                            className="mt-1 h-9 w-full rounded-lg border border-border bg-bg-tertiary px-2 font-mono text-[12px] text-text-primary"
+                            value={s.git.queueCheckCommand ?? ""}
+                            placeholder="cargo test (optional)"
+                            onChange={(e) =>
+                              setDraftAndSave({
+                                ...s,
+                                git: { ...s.git, queueCheckCommand: e.target.value.trim() ? e.target.value : null },
+                              })
+                            }
+                          />
+                        </label>
+                        <label className="block">
+                          <div className="text-[10px] font-semibold tracking-[0.14em] text-text-secondary">
+                            CHECK TIMEOUT (S)
+                          </div>
+                          <input
+                            type="number"
+                            min={1}
+                            className="mt-1 h-9 w-full rounded-lg border border-border bg-bg-tertiary px-2 font-mono text-[12px] text-text-primary"
+                            value={s.git.queueCheckTimeoutSecs}
+                            onChange={(e) =>
+                              setDraftAndSave({
+                                ...s,
+                                git: { ...s.git, queueCheckTimeoutSecs: Math.max(1, Number(e.target.value) || 1) },
+                              })
+                            }
+                          />
+                        </label>
                       </div>
                       <div className="mt-3 rounded-xl border border-border bg-bg-tertiary px-3 py-3 text-[11px] text-text-secondary">
                         Worktree engine ships in Phase 3; these values are persisted now.
@@ -1419,6 +1504,76 @@ export function Settings(props: SettingsProps) {
                         Snapshot files live in <span className="break-all font-mono">~/.config/synk/sessions/</span>.
                       </div>
                     </div>
+
+                    <div className="rounded-2xl border border-border bg-bg-secondary p-4">
+                      <div className="text-sm font-semibold">Remote API</div>
+                      <div className="mt-3 flex items-start justify-between gap-2 rounded-xl border border-border bg-bg-tertiary px-3 py-3">
+                        <div className="min-w-0">
+                          <div className="text-[10px] font-semibold tracking-[0.14em] text-text-secondary">
+                            ENABLED
+                          </div>
+                          <div className="mt-1 break-words text-[11px] text-text-secondary">
+                            HTTP + WebSocket access to sessions for browsers and scripts.
+                          </div>
+                        </div>
+                        <input
+                          type="checkbox"
+                          checked={s.remoteApi.enabled}
+                          onChange={(e) =>
+                            setDraftAndSave({ ...s, remoteApi: { ...s.remoteApi, enabled: e.target.checked } })
+                          }
+                        />
+                      </div>
+                      <div className="mt-3 grid grid-cols-2 gap-3">
+                        <label className="block">
+                          <div className="text-[10px] font-semibold tracking-[0.14em] text-text-secondary">PORT</div>
+                          <input
+                            className="mt-1 h-9 w-full rounded-lg border border-border bg-bg-tertiary px-2 text-xs text-text-primary"
+                            type="number"
+                            min={0}
+                            max={65535}
+                            value={s.remoteApi.port}
+                            onChange={(e) =>
+                              setDraftAndSave({
+                                ...s,
+                                remoteApi: { ...s.remoteApi, port: clampInt(Number(e.target.value), 0, 65535) },
+                              })
+                            }
+                          />
+                        </label>
+                        <label className="flex items-end justify-between gap-2 text-xs">
+                          <span className="min-w-0 break-words text-text-primary">Allow LAN devices</span>
+                          <input
+                            type="checkbox"
+                            checked={s.remoteApi.bindLan}
+                            onChange={(e) =>
+                              setDraftAndSave({ ...s, remoteApi: { ...s.remoteApi, bindLan: e.target.checked } })
+                            }
+                          />
+                        </label>
+                      </div>
+                      {remoteApi?.running ? (
+                        <div className="mt-3 rounded-xl border border-border bg-bg-tertiary px-3 py-3 text-[11px] text-text-secondary">
+                          <div>
+                            Listening on <span className="break-all font-mono">{remoteApi.url}</span> ·{" "}
+                            {remoteApi.clients} connected
+                          </div>
+                          <div className="mt-1 flex items-center justify-between gap-2">
+                            <span className="min-w-0 break-all font-mono">token {remoteApi.token}</span>
+                            <button
+                              className="shrink-0 rounded-md border border-border bg-bg-primary px-2 py-1 text-[11px] font-medium text-text-secondary hover:bg-bg-hover"
+                              onClick={() => {
+                                remoteApiRotateToken()
+                                  .then(setRemoteApi)
+                                  .catch((e) => setError(String(e)));
+                              }}
+                            >
+                              Rotate
+                            </button>
+                          </div>
+                        </div>
+                      ) : null}
+                    </div>
                   </div>
                 </div>
               ) : null}
@@ -0,0 +1,127 @@
+import { useCallback, useEffect, useRef, useState } from "react";
+
+import { notificationsList, notificationsMarkRead, onNotification } from "../../lib/tauri-api";
+import type { AppNotification, NotificationKind, ToastPosition } from "../../lib/types";
+import { useAppStore } from "../../lib/store";
+
+const MAX_VISIBLE = 5;
+
+const POSITION_CLASS: Record<ToastPosition, string> = {
+  "top-right": "top-4 right-4",
+  "top-left": "top-4 left-4",
+  "bottom-right": "bottom-4 right-4 flex-col-reverse",
+  "bottom-left": "bottom-4 left-4 flex-col-reverse",
+};
+
+function accentFor(kind: NotificationKind) {
+  switch (kind) {
+    case "agent_error":
+    case "merge_conflict":
+      return "border-accent-red";
+    case "cost_threshold":
+      return "border-accent-orange";
+    case "review_ready":
+      return "border-accent-blue";
+    default:
+      return "border-accent-green";
+  }
+}
+
+/**
+ * Shows `notification:new` events as toasts. Unread notifications are loaded on mount, so a
+ * toast that was still up when the UI reloaded comes back; dismissing (or timing out) marks it
+ * read in the backend history.
+ */
+export function Toasts(props: { tauriAvailable: boolean }) {
+  const { tauriAvailable } = props;
+  const prefs = useAppStore((s) => s.settings?.notifications);
+  const position = prefs?.position ?? "top-right";
+  const durationMs = prefs?.durationMs ?? 5000;
+
+  const [toasts, setToasts] = useState<AppNotification[]>([]);
+  const timers = useRef(new Map<number, number>());
+
+  const dismiss = useCallback((id: number) => {
+    const t = timers.current.get(id);
+    if (t !== undefined) window.clearTimeout(t);
+    timers.current.delete(id);
+    setToasts((list) => list.filter((n) => n.id !== id));
+    notificationsMarkRead([id]).catch(() => {});
+  }, []);
+
+  const show = useCallback((items: AppNotification[]) => {
+    setToasts((list) => {
+      const known = new Set(list.map((n) => n.id));
+      const next = [...list, ...items.filter((n) => !known.has(n.id))];
+      return next.slice(-MAX_VISIBLE);
+    });
+  }, []);
+
+  useEffect(() => {
+    if (!tauriAvailable) return;
+    let unlisten: (() => void) | null = null;
+    let disposed = false;
+    onNotification((n) => show([n])).then((fn) => {
+      if (disposed) {
+        fn();
+      } else {
+        unlisten = fn;
+      }
+    });
+    notificationsList(true, MAX_VISIBLE)
+      .then((res) => {
+        if (!disposed) show([...res.items].reverse());
+      })
+      .catch(() => {});
+    return () => {
+      disposed = true;
+      unlisten?.();
+    };
+  }, [tauriAvailable, show]);
+
+  useEffect(() => {
+    if (durationMs <= 0) return;
+    for (const n of toasts) {
+      if (timers.current.has(n.id)) continue;
+      timers.current.set(
+        n.id,
+        window.setTimeout(() => dismiss(n.id), durationMs),
+      );
+    }
+  }, [toasts, durationMs, dismiss]);
+
+  useEffect(() => {
+    const pending = timers.current;
+    return () => {
+      for (const t of pending.values()) window.clearTimeout(t);
+    };
+  }, []);
+
+  if (toasts.length === 0) return null;
+
+  return (
+    <div className={`pointer-events-none fixed z-[70] flex w-80 flex-col gap-2 ${POSITION_CLASS[position]}`}>
+      {toasts.map((n) => (
+        <div
+          key={n.id}
+          className={`pointer-events-auto rounded-xl border border-l-4 border-border bg-bg-secondary px-3 py-2 shadow-[0_14px_40px_rgba(0,0,0,0.35)] ${accentFor(n.kind)}`}
+        >
+          <div className="flex items-start gap-2">
+            <div className="min-w-0 flex-1">
+              <div className="truncate text-xs font-semibold text-text-primary">{n.title}</div>
+              {n.body ? <div className="mt-0.5 text-[11px] text-text-secondary">{n.body}</div> : null}
+            </div>
+            <button
+              type="button"
+              className="text-xs text-text-secondary hover:text-text-primary"
+              onClick={() => dismiss(n.id)}
+              aria-label="Dismiss notification"
+            >
+              ×
+            </button>
+          </div>
+        </div>
+      ))}
+    </div>
+  );
+}
@@ -16,6 +16,8 @@ function agentBadge(agentType: AgentType): { label: string; className: string }
       return { label: "Codex", className: "border-accent-green/40 bg-accent-green/10 text-accent-green" };
     case "openrouter":
       return { label: "Router", className: "border-accent-orange/40 bg-accent-orange/10 text-accent-orange" };
+    case "simulated":
+      return { label: "Sim", className: "border-border bg-bg-tertiary text-text-secondary" };
     case "terminal":
       return { label: "Term", className: "border-border bg-bg-tertiary text-text-secondary" };
   }
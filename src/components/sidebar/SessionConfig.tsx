@@ -44,6 +44,7 @@ const AGENTS: { id: AgentType; label: string }[] = [
   { id: "gemini_cli", label: "Gemini CLI" },
   { id: "codex", label: "OpenAI Codex" },
   { id: "openrouter", label: "OpenRouter" },
+  { id: "simulated", label: "Simulated Agent" },
   { id: "terminal", label: "Terminal" },
 ];
 
@@ -16,6 +16,8 @@ function agentBadge(agentType: SessionInfo["agentType"]) {
       return { icon: "CX", label: "Codex", color: "bg-accent-green/15 text-accent-green border-accent-green/40" };
     case "openrouter":
       return { icon: "OR", label: "OpenRouter", color: "bg-accent-orange/15 text-accent-orange border-accent-orange/40" };
+    case "simulated":
+      return { icon: "SM", label: "Simulated", color: "bg-bg-tertiary text-text-secondary border-border" };
     case "terminal":
     default:
       return { icon: ">>", label: "Terminal", color: "bg-bg-primary text-text-secondary border-border" };
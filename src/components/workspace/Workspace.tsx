@@ -1,6 +1,6 @@
 import { useCallback, useEffect, useMemo, useRef, useState } from "react";
 import { open } from "@tauri-apps/plugin-dialog";
-import { isTauri } from "@tauri-apps/api/core";
+import { backendAvailable } from "../../lib/transport";
 
 import {
   agentsList,
@@ -27,6 +27,7 @@ import type {
   SessionExitEvent,
   SessionId,
   SessionInfo,
+  SessionLabels,
   SessionOutputEvent,
 } from "../../lib/types";
 import { useAppStore } from "../../lib/store";
@@ -67,7 +68,7 @@ export function Workspace() {
   const [error, setError] = useState<string | null>(null);
   const [notice, setNotice] = useState<string | null>(null);
 
-  const tauriAvailable = useMemo(() => isTauri(), []);
+  const tauriAvailable = useMemo(() => backendAvailable(), []);
   const orchestrationMode: OrchestrationMode = "manual";
 
   const outputHandlersRef = useRef<Map<number, OutputHandler>>(new Map());
@@ -116,14 +117,16 @@ export function Workspace() {
   }, []);
 
   const sessionCreateCompat = useCallback(
-    async (args: {
-      agentType: AgentType;
-      projectPath: string;
-      branch?: string;
-      workingDir?: string;
-      model?: string;
-      codexProvider?: CodexProvider;
-    }) => {
+    async (
+      args: {
+        agentType: AgentType;
+        projectPath: string;
+        branch?: string;
+        workingDir?: string;
+        model?: string;
+        codexProvider?: CodexProvider;
+      } & SessionLabels,
+    ) => {
       try {
         return await sessionCreate(args);
       } catch (e) {
@@ -316,6 +319,10 @@ export function Workspace() {
           model: modelForAgent(p.agentType),
           codexProvider:
             p.agentType === "codex" ? "openai" : p.agentType === "openrouter" ? "openrouter" : undefined,
+          name: p.name,
+          color: p.color,
+          tags: p.tags,
+          group: p.group,
         });
 
         // Persist per-pane config so SessionConfig panel reflects restored overrides.
@@ -325,6 +332,10 @@ export function Workspace() {
           worktreeIsolation: p.worktreeEnabled,
           skills: p.skills ?? [],
           mcpServers: p.mcpServers ?? [],
+          name: p.name ?? "",
+          color: p.color ?? "",
+          tags: p.tags ?? [],
+          group: p.group ?? "",
         });
       }
 
@@ -473,7 +484,7 @@ export function Workspace() {
       refreshSessions().catch(() => {});
       // Also allow panes to show a local marker if they want.
       const h = outputHandlersRef.current.get(payload.sessionId);
-      if (h) h(btoa(`\r\n[session exited: ${payload.exitCode}]\r\n`));
+      if (h) h(btoa(`\r\n[session exited: ${payload.exitCode ?? "unknown"}]\r\n`));
     }).then((fn) => {
       if (disposed) {
         fn();
@@ -692,6 +703,7 @@ export function Workspace() {
                   ? " (needs codex CLI)"
                   : ""}
               </option>
+              <option value="simulated">simulated</option>
             </select>
           </label>
         <button
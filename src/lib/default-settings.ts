@@ -2,7 +2,7 @@ import type { AppSettings } from "./types";
 
 export function defaultAppSettings(): AppSettings {
   return {
-    version: 4,
+    version: 5,
     aiProviders: {
       default: "anthropic",
       anthropic: {
@@ -38,6 +38,7 @@ export function defaultAppSettings(): AppSettings {
         baseUrl: "http://localhost:11434",
         defaultModel: "llama3.1",
       },
+      profiles: [],
     },
     performance: {
       initialPoolSize: 2,
@@ -75,6 +76,10 @@ export function defaultAppSettings(): AppSettings {
       autoDelegateConflicts: true,
       worktreeBasePath: "~/.synk/worktrees",
       branchPrefix: "feat/",
+      checkpointOnIdle: true,
+      interactiveConflicts: false,
+      queueCheckCommand: null,
+      queueCheckTimeoutSecs: 1800,
     },
     session: {
       autoSave: true,
@@ -85,5 +90,21 @@ export function defaultAppSettings(): AppSettings {
       workspacePath: "~/gt/",
       pinnedVersion: "0.3.x",
     },
+    gateway: {
+      enabled: false,
+      port: 0,
+      maxRequestsPerMinute: 0,
+    },
+    remoteApi: {
+      enabled: false,
+      port: 4777,
+      bindLan: false,
+    },
+    agents: {
+      claudeCodePath: null,
+      geminiCliPath: null,
+      codexPath: null,
+    },
+    hooks: [],
   };
 }
@@ -0,0 +1,15 @@
+// API keys come back from the backend masked (see src-tauri/src/core/secrets.rs).
+// Sending the masked value back unchanged keeps the stored key.
+export const MASKED_KEY_PREFIX = "••••••••";
+
+export function isMaskedKey(value: string | null | undefined): boolean {
+  return (value ?? "").startsWith(MASKED_KEY_PREFIX);
+}
+
+/** Editing a masked key replaces it: typed/pasted text becomes the new key, deleting clears it. */
+export function editMaskedKey(previous: string, raw: string): string {
+  if (!isMaskedKey(previous)) return raw;
+  if (raw.startsWith(previous)) return raw.slice(previous.length);
+  if (isMaskedKey(raw) || previous.startsWith(raw)) return "";
+  return raw;
+}
@@ -1,7 +1,8 @@
-import { invoke } from "@tauri-apps/api/core";
-import { listen } from "@tauri-apps/api/event";
+import { invoke, listen } from "./transport";
 
 import type {
+  BroadcastDelivery,
+  BroadcastResult,
   DetectedAgent,
   CodexProvider,
   McpDiscoveryResult,
@@ -10,9 +11,13 @@ import type {
   SessionConfigDisk,
   SessionCreateArgs,
   SessionCreateResponse,
+  SessionForkResponse,
+  SessionHandoffArgs,
+  SessionHandoffResponse,
   SessionExitEvent,
   SessionId,
   SessionInfo,
+  SessionLabels,
   SessionOutputEvent,
   SessionCostUpdatedEvent,
   SessionScrollbackResponse,
@@ -21,6 +26,8 @@ import type {
   AppSettings,
   ProviderKeyValidationResult,
   ProviderModelsResult,
+  ProviderProfileSettings,
+  ProviderProfileValidationResult,
   OllamaPullResult,
   SkillsDiscoveryResult,
   OnboardingScanResult,
@@ -30,8 +37,23 @@ import type {
   GitCleanupOrphansResponse,
   FileDiff,
   GitMergeResult,
+  MergePreview,
+  CommitFileChange,
+  CommitLogPage,
+  CommitRefs,
   MergeStrategy,
   GitEvent,
+  AuditEntry,
+  AuditQuery,
+  HookDelivery,
+  Integration,
+  IntegrationDetail,
+  MergeQueue,
+  MergeQueueEvent,
+  Resolution,
+  AppNotification,
+  NotificationList,
+  EventsSinceResult,
   ReviewItem,
   ReviewStatus,
   ReviewDecision,
@@ -39,12 +61,29 @@ import type {
   LocalhostSessionSpec,
   LocalhostSessionStatusEvent,
   LocalhostSessionView,
+  GatewayRequestEvent,
+  GatewaySessionUsage,
+  GatewayStatus,
+  RemoteApiStatus,
+  SecretEntry,
+  Checkpoint,
+  SessionCheckpointEvent,
+  AttemptComparison,
+  AttemptGroup,
+  AttemptSpec,
+  CommandOutcome,
+  CompletionResult,
+  OrchestrationMode,
 } from "./types";
 
 export function agentsList() {
   return invoke<DetectedAgent[]>("agents_list");
 }
 
+export function agentsRefresh() {
+  return invoke<DetectedAgent[]>("agents_refresh");
+}
+
 export function persistenceListRecentProjects() {
   return invoke<RecentProject[]>("list_recent_projects");
 }
@@ -73,6 +112,11 @@ export function projectSessionConfigSet(projectPath: string, sessionId: number,
       worktreeIsolation: cfg.worktreeIsolation ?? null,
       skills: cfg.skills ?? [],
       mcpServers: cfg.mcpServers ?? [],
+      // Omitted label fields keep their saved values.
+      name: cfg.name,
+      color: cfg.color,
+      tags: cfg.tags,
+      group: cfg.group,
     },
   });
 }
@@ -114,7 +158,31 @@ export function sessionDestroy(sessionId: SessionId) {
 }
 
 export function sessionWrite(sessionId: SessionId, data: string) {
-  return invoke<void>("session_write", { args: { sessionId, data } });
+  return invoke<BroadcastDelivery[]>("session_write", { args: { sessionId, data } });
+}
+
+/**
+ * Writes to every session matching `target` (`@name`, `@tag:<tag>`, `@group:<group>`); each
+ * session gets its own entry, failed or not.
+ */
+export function sessionWriteTarget(target: string, data: string) {
+  return invoke<BroadcastDelivery[]>("session_write", { args: { target, data } });
+}
+
+/**
+ * Writes `data` to every session matching `target`: `@all`, `@idle`, `@active`,
+ * `@agent:<type>`, `@tag:<tag>`, `@group:<group>`, `@name`, ids, or a comma-separated mix.
+ */
+export function sessionBroadcast(target: string, data: string) {
+  return invoke<BroadcastResult>("session_broadcast", { args: { target, data } });
+}
+
+export async function onSessionBroadcast(handler: (payload: BroadcastResult) => void) {
+  return listen<BroadcastResult>("session:broadcast", (event) => handler(event.payload));
+}
+
+export function sessionSetLabels(sessionId: SessionId, labels: SessionLabels) {
+  return invoke<SessionInfo>("session_set_labels", { args: { sessionId, ...labels } });
 }
 
 export function sessionCd(sessionId: SessionId, dir: string, branch?: string | null) {
@@ -127,6 +195,7 @@ export function sessionRestart(
   branch?: string | null,
   model?: string | null,
   codexProvider?: CodexProvider | null,
+  providerProfile?: string | null,
 ) {
   return invoke<SessionInfo>("session_restart", {
     args: {
@@ -135,10 +204,21 @@ export function sessionRestart(
       branch: branch ?? null,
       model: model ?? null,
       codexProvider: codexProvider ?? null,
+      providerProfile: providerProfile ?? null,
     },
   });
 }
 
+export function sessionFork(sessionId: SessionId, branch?: string | null, model?: string | null) {
+  return invoke<SessionForkResponse>("session_fork", {
+    args: { sessionId, branch: branch ?? null, model: model ?? null },
+  });
+}
+
+export function sessionHandoff(args: SessionHandoffArgs) {
+  return invoke<SessionHandoffResponse>("session_handoff", { args });
+}
+
 export function sessionResize(sessionId: SessionId, cols: number, rows: number) {
   return invoke<void>("session_resize", { args: { sessionId, cols, rows } });
 }
@@ -200,20 +280,129 @@ export function gitBranches(projectPath: string) {
   return invoke<string[]>("git_branches", { args: { projectPath } });
 }
 
+/** One page of history; `branch` null means every branch, remote and tag. */
+export function gitCommitLog(
+  projectPath: string,
+  opts: { branch?: string | null; cursor?: string | null; limit?: number | null } = {},
+) {
+  return invoke<CommitLogPage>("git_commit_log", {
+    args: { projectPath, branch: opts.branch ?? null, cursor: opts.cursor ?? null, limit: opts.limit ?? null },
+  });
+}
+
+export function gitCommitFiles(projectPath: string, commit: string) {
+  return invoke<CommitFileChange[]>("git_commit_files", { args: { projectPath, commit } });
+}
+
+export function gitRefsForCommit(projectPath: string, commit: string) {
+  return invoke<CommitRefs>("git_refs_for_commit", { args: { projectPath, commit } });
+}
+
 export function gitDiff(projectPath: string, branch: string, baseBranch: string) {
   return invoke<FileDiff[]>("git_diff", { args: { projectPath, branch, baseBranch } });
 }
 
-export function gitMerge(projectPath: string, branch: string, baseBranch: string, strategy: MergeStrategy) {
+export function gitMerge(
+  projectPath: string,
+  branch: string,
+  baseBranch: string,
+  strategy: MergeStrategy,
+  interactive?: boolean | null,
+) {
   return invoke<GitMergeResult>("git_merge", {
-    args: { projectPath, branch, baseBranch, strategy },
+    args: { projectPath, branch, baseBranch, strategy, interactive: interactive ?? null },
+  });
+}
+
+/** Predicts whether `branch` merges cleanly into `baseBranch` without touching any worktree. */
+export function gitMergePreview(projectPath: string, branch: string, baseBranch: string) {
+  return invoke<MergePreview>("git_merge_preview", { args: { projectPath, branch, baseBranch } });
+}
+
+export function integrationList(projectPath: string) {
+  return invoke<Integration[]>("integration_list", { args: { projectPath } });
+}
+
+export function integrationGet(projectPath: string, id: string) {
+  return invoke<IntegrationDetail>("integration_get", { args: { projectPath, id } });
+}
+
+/** Resolves a whole conflicted file, or one hunk of it when `hunk` is given. */
+export function integrationResolve(
+  projectPath: string,
+  id: string,
+  path: string,
+  resolution: Resolution,
+  hunk?: number | null,
+) {
+  return invoke<IntegrationDetail>("integration_resolve", {
+    args: { projectPath, id, path, hunk: hunk ?? null, resolution },
   });
 }
 
+/** Commits the resolved merge and moves the base branch; resolves to the new base commit. */
+export function integrationContinue(projectPath: string, id: string) {
+  return invoke<string>("integration_continue", { args: { projectPath, id } });
+}
+
+export function integrationAbort(projectPath: string, id: string) {
+  return invoke<void>("integration_abort", { args: { projectPath, id } });
+}
+
+/** Also restarts a queue left waiting by an app restart. */
+export function mergeQueueGet(projectPath: string) {
+  return invoke<MergeQueue>("merge_queue_get", { args: { projectPath } });
+}
+
+/** Queues an approved review; it merges with its own strategy once the entries ahead have landed. */
+export function mergeQueueEnqueue(projectPath: string, reviewId: string) {
+  return invoke<MergeQueue>("merge_queue_enqueue", { args: { projectPath, reviewId } });
+}
+
+export function mergeQueueRemove(projectPath: string, reviewId: string) {
+  return invoke<MergeQueue>("merge_queue_remove", { args: { projectPath, reviewId } });
+}
+
+export function mergeQueuePause(projectPath: string) {
+  return invoke<MergeQueue>("merge_queue_pause", { args: { projectPath } });
+}
+
+/** Clears the last failure and continues with the next entry. */
+export function mergeQueueResume(projectPath: string) {
+  return invoke<MergeQueue>("merge_queue_resume", { args: { projectPath } });
+}
+
+export async function onMergeQueueEvent(handler: (payload: MergeQueueEvent) => void) {
+  return listen<MergeQueueEvent>("merge_queue:event", (event) => handler(event.payload));
+}
+
 export function reviewCreate(projectPath: string, sessionId: number, branch: string, baseBranch: string) {
   return invoke<ReviewItem>("review_create", { args: { projectPath, sessionId, branch, baseBranch } });
 }
 
+export function attemptsStart(
+  projectPath: string,
+  prompt: string,
+  attempts: AttemptSpec[],
+  baseBranch?: string | null,
+) {
+  return invoke<AttemptGroup>("attempts_start", {
+    args: { projectPath, prompt, attempts, baseBranch: baseBranch ?? null },
+  });
+}
+
+export function attemptsList(projectPath: string) {
+  return invoke<AttemptGroup[]>("attempts_list", { args: { projectPath } });
+}
+
+export function attemptsCompare(projectPath: string, groupId: string) {
+  return invoke<AttemptComparison>("attempts_compare", { args: { projectPath, groupId } });
+}
+
+export function attemptsPromote(projectPath: string, groupId: string, index: number) {
+  return invoke<ReviewItem>("attempts_promote", { args: { projectPath, groupId, index } });
+}
+
 export function reviewList(projectPath: string) {
   return invoke<ReviewItem[]>("review_list", { args: { projectPath } });
 }
@@ -255,6 +444,26 @@ export async function onSessionOutput(
   return listen<SessionOutputEvent>("session:output", (event) => handler(event.payload));
 }
 
+export async function onSessionCheckpoint(handler: (payload: SessionCheckpointEvent) => void) {
+  return listen<SessionCheckpointEvent>("session:checkpoint", (event) => handler(event.payload));
+}
+
+export function checkpointList(sessionId: SessionId) {
+  return invoke<Checkpoint[]>("checkpoint_list", { args: { sessionId } });
+}
+
+export function checkpointCreate(sessionId: SessionId) {
+  return invoke<Checkpoint | null>("checkpoint_create", { args: { sessionId } });
+}
+
+export function checkpointDiff(sessionId: SessionId, index: number, against?: number | null) {
+  return invoke<FileDiff[]>("checkpoint_diff", { args: { sessionId, index, against: against ?? null } });
+}
+
+export function checkpointRestore(sessionId: SessionId, index: number) {
+  return invoke<Checkpoint | null>("checkpoint_restore", { args: { sessionId, index } });
+}
+
 export async function onSessionCostUpdated(
   handler: (payload: SessionCostUpdatedEvent) => void,
 ) {
@@ -269,6 +478,42 @@ export async function onGitEvent(handler: (payload: GitEvent) => void) {
   return listen<GitEvent>("git:event", (event) => handler(event.payload));
 }
 
+/** Buffered events published after `seq`, oldest first; lets a view catch up after mounting. */
+export function eventsSince(seq: number, topics?: string[] | null, limit?: number | null) {
+  return invoke<EventsSinceResult>("events_since", { args: { seq, topics: topics ?? [], limit: limit ?? null } });
+}
+
+/** Audit log entries, newest first. */
+export function auditQuery(query: AuditQuery = {}) {
+  return invoke<AuditEntry[]>("audit_query", { args: query });
+}
+
+export function notificationsList(unreadOnly = false, limit?: number | null) {
+  return invoke<NotificationList>("notifications_list", { args: { unreadOnly, limit: limit ?? null } });
+}
+
+/** Marks `ids` read, or every notification when omitted. Resolves to how many changed. */
+export function notificationsMarkRead(ids?: number[] | null) {
+  return invoke<number>("notifications_mark_read", { args: { ids: ids ?? null } });
+}
+
+export function notificationsClear() {
+  return invoke<void>("notifications_clear");
+}
+
+export async function onNotification(handler: (payload: AppNotification) => void) {
+  return listen<AppNotification>("notification:new", (event) => handler(event.payload));
+}
+
+export function hooksDeliveries(hookId?: string | null, limit?: number | null) {
+  return invoke<HookDelivery[]>("hooks_deliveries", { args: { hookId: hookId ?? null, limit: limit ?? null } });
+}
+
+/** Sends a test payload to a saved hook; a failed delivery resolves with `ok: false`. */
+export function hooksTest(hookId: string) {
+  return invoke<HookDelivery>("hooks_test", { args: { hookId } });
+}
+
 // -----------------------------------------------------------------------------
 // Localhost sessions (Phase 4)
 // -----------------------------------------------------------------------------
@@ -311,6 +556,46 @@ export async function onLocalhostSessionStatus(handler: (payload: LocalhostSessi
   return listen<LocalhostSessionStatusEvent>("localhost:status", (event) => handler(event.payload));
 }
 
+export function gatewayStatus() {
+  return invoke<GatewayStatus>("gateway_status");
+}
+
+export function gatewayUsage(sessionId?: SessionId | null) {
+  return invoke<GatewaySessionUsage[]>("gateway_usage", {
+    args: { sessionId: sessionId ?? null },
+  });
+}
+
+export function remoteApiStatus() {
+  return invoke<RemoteApiStatus>("remote_api_status");
+}
+
+export function remoteApiRotateToken() {
+  return invoke<RemoteApiStatus>("remote_api_rotate_token");
+}
+
+export function gatewaySetRateCap(sessionId: SessionId, maxRequestsPerMinute: number | null) {
+  return invoke<GatewaySessionUsage>("gateway_set_rate_cap", {
+    args: { sessionId, maxRequestsPerMinute },
+  });
+}
+
+export async function onGatewayRequest(handler: (payload: GatewayRequestEvent) => void) {
+  return listen<GatewayRequestEvent>("gateway:request", (event) => handler(event.payload));
+}
+
+export function secretsList() {
+  return invoke<SecretEntry[]>("secrets_list");
+}
+
+export function secretsSet(name: string, value: string) {
+  return invoke<SecretEntry>("secrets_set", { args: { name, value } });
+}
+
+export function secretsDelete(name: string) {
+  return invoke<boolean>("secrets_delete", { args: { name } });
+}
+
 export function skillsDiscover(projectPath?: string | null) {
   return invoke<SkillsDiscoveryResult>("skills_discover", {
     args: { projectPath: projectPath ?? null },
@@ -389,9 +674,15 @@ export function settingsSet(settings: AppSettings) {
   return invoke<AppSettings>("settings_set", { args: { settings } });
 }
 
-export function settingsValidateProviderKey(provider: string, apiKey: string) {
+export function settingsValidateProviderKey(provider: string, apiKey: string, baseUrl?: string | null) {
   return invoke<ProviderKeyValidationResult>("settings_validate_provider_key", {
-    args: { provider, apiKey },
+    args: { provider, apiKey, baseUrl: baseUrl ?? null },
+  });
+}
+
+export function settingsValidateProviderProfile(profile: ProviderProfileSettings) {
+  return invoke<ProviderProfileValidationResult>("settings_validate_provider_profile", {
+    args: { profile },
   });
 }
 
@@ -422,3 +713,17 @@ export function onboardingInitialize() {
 export function onboardingScan() {
   return invoke<OnboardingScanResult>("onboarding_scan");
 }
+
+// -----------------------------------------------------------------------------
+// Command bar (Task 6A.1)
+// -----------------------------------------------------------------------------
+
+export function commandComplete(projectPath: string, input: string) {
+  return invoke<CompletionResult>("command_complete", { args: { projectPath, input } });
+}
+
+export function commandExecute(projectPath: string, input: string, orchestrationMode?: OrchestrationMode) {
+  return invoke<CommandOutcome>("command_execute", {
+    args: { projectPath, input, orchestrationMode: orchestrationMode ?? null },
+  });
+}
@@ -0,0 +1,110 @@
+import { invoke as tauriInvoke, isTauri } from "@tauri-apps/api/core";
+import { listen as tauriListen } from "@tauri-apps/api/event";
+import type { EventCallback, UnlistenFn } from "@tauri-apps/api/event";
+
+// Outside the Tauri webview (`npm run dev` in a browser, a tablet on the LAN), commands and
+// events go through the remote API instead. Open the UI once with
+// `?synkApi=http://host:4777&synkToken=<token>`; the connection is remembered.
+
+type RemoteConfig = { url: string; token: string };
+
+type EventLine = { event: string; payload: unknown };
+type ResponseLine = { id: number; ok: boolean; result?: unknown; error?: string };
+
+const STORAGE_KEY = "synk.remoteApi";
+const RECONNECT_MS = 2000;
+
+let cachedConfig: RemoteConfig | null | undefined;
+
+function remoteConfig(): RemoteConfig | null {
+  if (cachedConfig !== undefined) return cachedConfig;
+  cachedConfig = null;
+  if (typeof window === "undefined") return null;
+  const params = new URLSearchParams(window.location.search);
+  const url = params.get("synkApi");
+  const token = params.get("synkToken");
+  if (url && token) {
+    cachedConfig = { url: url.replace(/\/+$/, ""), token };
+    window.localStorage.setItem(STORAGE_KEY, JSON.stringify(cachedConfig));
+    return cachedConfig;
+  }
+  try {
+    const saved = JSON.parse(window.localStorage.getItem(STORAGE_KEY) ?? "null") as RemoteConfig | null;
+    if (saved?.url && saved.token) cachedConfig = saved;
+  } catch {
+    // Ignore a corrupt entry; the user can pass the query params again.
+  }
+  return cachedConfig;
+}
+
+/** True inside Tauri, or in a browser that has been pointed at a remote API. */
+export function backendAvailable(): boolean {
+  return isTauri() || remoteConfig() !== null;
+}
+
+export async function invoke<T>(command: string, payload?: { args?: unknown }): Promise<T> {
+  if (isTauri()) return tauriInvoke<T>(command, payload);
+  const remote = remoteConfig();
+  if (!remote) throw `no Synk backend for ${command}`;
+  const res = await fetch(`${remote.url}/api/command/${command}`, {
+    method: "POST",
+    headers: { Authorization: `Bearer ${remote.token}`, "Content-Type": "application/json" },
+    body: JSON.stringify(payload?.args ?? null),
+  });
+  const body = (await res.json()) as { ok: boolean; result?: T; error?: string };
+  // Tauri rejects with the command's error string; keep that shape for callers.
+  if (!body.ok) throw body.error ?? `HTTP ${res.status}`;
+  return body.result as T;
+}
+
+// One WebSocket per page, shared by every `listen` call.
+const handlers = new Map<string, Set<(payload: unknown) => void>>();
+let socket: WebSocket | null = null;
+let nextId = 1;
+
+function subscribe(events: string[]) {
+  if (socket?.readyState !== WebSocket.OPEN || events.length === 0) return;
+  socket.send(JSON.stringify({ id: nextId++, command: "subscribe", args: { events } }));
+}
+
+function connect(remote: RemoteConfig) {
+  const wsUrl = `${remote.url.replace(/^http/, "ws")}/api/ws?token=${encodeURIComponent(remote.token)}`;
+  const ws = new WebSocket(wsUrl);
+  socket = ws;
+  ws.onopen = () => subscribe([...handlers.keys()]);
+  ws.onmessage = (msg) => {
+    const line = JSON.parse(String(msg.data)) as EventLine | ResponseLine;
+    if ("event" in line) {
+      handlers.get(line.event)?.forEach((h) => h(line.payload));
+    } else if (!line.ok) {
+      console.warn("synk remote api:", line.error);
+    }
+  };
+  ws.onclose = () => {
+    if (socket !== ws) return;
+    socket = null;
+    window.setTimeout(() => {
+      if (!socket && handlers.size > 0) connect(remote);
+    }, RECONNECT_MS);
+  };
+}
+
+export async function listen<T>(event: string, handler: EventCallback<T>): Promise<UnlistenFn> {
+  if (isTauri()) return tauriListen<T>(event, handler);
+  const remote = remoteConfig();
+  if (!remote) return () => {};
+
+  const wrapped = (payload: unknown) => handler({ event, id: 0, payload: payload as T });
+  let set = handlers.get(event);
+  if (!set) {
+    set = new Set();
+    handlers.set(event, set);
+    subscribe([event]);
+  }
+  set.add(wrapped);
+  if (!socket) connect(remote);
+
+  return () => {
+    handlers.get(event)?.delete(wrapped);
+  };
+}
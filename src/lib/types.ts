@@ -1,4 +1,4 @@
-export type AgentType = "claude_code" | "gemini_cli" | "codex" | "openrouter" | "terminal";
+export type AgentType = "claude_code" | "gemini_cli" | "codex" | "openrouter" | "simulated" | "terminal";
 export type CodexProvider = "openai" | "openrouter";
 
 export type SessionId = number;
@@ -9,6 +9,19 @@ export interface DetectedAgent {
   found: boolean;
   path?: string | null;
   version?: string | null;
+  customPath: boolean;
+  minVersion?: string | null;
+  versionOk?: boolean | null;
+  warning?: string | null;
+  auth?: AgentAuthStatus | null;
+}
+
+export type AgentAuthState = "authenticated" | "missing";
+
+export interface AgentAuthStatus {
+  state: AgentAuthState;
+  method?: "oauth" | "auth_file" | "api_key_env" | "synk_settings" | "cloud_provider" | null;
+  detail: string;
 }
 
 // Keep this aligned with the sidebar UI. Backend persistence currently defaults to "manual".
@@ -28,7 +41,22 @@ export interface SessionCreateArgs {
   workingDir?: string;
   model?: string;
   codexProvider?: CodexProvider;
+  providerProfile?: string;
   env?: Record<string, string>;
+  name?: string;
+  color?: string;
+  tags?: string[];
+  group?: string;
+  /** Passed on the agent CLI's command line; rejected for plain terminals. */
+  initialPrompt?: string;
+}
+
+/** User-assigned session metadata; `@name`, `@tag:<tag>` and `@group:<group>` target it. */
+export interface SessionLabels {
+  name?: string;
+  color?: string;
+  tags?: string[];
+  group?: string;
 }
 
 export interface SessionCreateResponse {
@@ -38,11 +66,37 @@ export interface SessionCreateResponse {
   warning?: string | null;
 }
 
-export interface SessionInfo {
+export interface SessionForkResponse extends SessionCreateResponse {
+  forkedFrom: SessionId;
+  branch: string;
+  worktreePath: string;
+  baseCommit: string;
+}
+
+export interface SessionHandoffArgs {
+  sourceSessionId: SessionId;
+  targetSessionId?: SessionId | null;
+  agentType?: AgentType | null;
+  model?: string | null;
+  codexProvider?: CodexProvider | null;
+  providerProfile?: string | null;
+  task?: string | null;
+  baseBranch?: string | null;
+  closeSource?: boolean;
+}
+
+export interface SessionHandoffResponse extends SessionCreateResponse {
+  packetPath: string;
+}
+
+export interface SessionInfo extends SessionLabels {
   sessionId: SessionId;
+  /** Unique across app runs; session ids restart at 1. */
+  sessionKey: string;
   paneIndex: number;
   agentType: AgentType;
   codexProvider?: CodexProvider;
+  providerProfile?: string;
   model?: string;
   projectPath: string;
   branch?: string;
@@ -50,7 +104,7 @@ export interface SessionInfo {
   cost?: SessionCostSnapshot | null;
 }
 
-export type SessionCostSource = "mcp" | "output_parsed" | "heuristic";
+export type SessionCostSource = "gateway" | "mcp" | "output_parsed" | "heuristic";
 
 export interface SessionCostSnapshot {
   inputTokens: number;
@@ -70,9 +124,37 @@ export interface SessionOutputEvent {
   dataB64: string;
 }
 
+export interface Checkpoint {
+  index: number;
+  refName: string;
+  commit: string;
+  tree: string;
+  head: string;
+  createdAt: string;
+}
+
+export interface SessionCheckpointEvent {
+  sessionId: SessionId;
+  checkpoint: Checkpoint;
+}
+
+export interface BroadcastDelivery {
+  sessionId: SessionId;
+  ok: boolean;
+  error?: string;
+}
+
+/** Result of `session_broadcast`, also emitted as `session:broadcast`. */
+export interface BroadcastResult {
+  target: string;
+  timestamp: string;
+  bytes: number;
+  deliveries: BroadcastDelivery[];
+}
+
 export interface SessionExitEvent {
   sessionId: SessionId;
-  exitCode: number;
+  exitCode: number | null;
 }
 
 export interface SessionScrollbackResponse {
@@ -125,7 +207,7 @@ export interface McpDiscoveryResult {
   runningProcesses: McpRunningProcess[];
 }
 
-export interface SessionConfigDisk {
+export interface SessionConfigDisk extends SessionLabels {
   agentType?: AgentType | null;
   branch?: string | null;
   worktreeIsolation?: boolean | null;
@@ -186,6 +268,56 @@ export interface LocalhostSessionStatusEvent {
   lastExitCode?: number | null;
 }
 
+// -----------------------------------------------------------------------------
+// LLM gateway
+// -----------------------------------------------------------------------------
+
+export interface GatewayStatus {
+  running: boolean;
+  port?: number | null;
+  defaultMaxRequestsPerMinute: number;
+  sessions: number;
+}
+
+export interface GatewaySessionUsage {
+  sessionId: SessionId;
+  requests: number;
+  errors: number;
+  rateLimited: number;
+  inputTokens: number;
+  cachedInputTokens: number;
+  outputTokens: number;
+  totalLatencyMs: number;
+  lastLatencyMs?: number | null;
+  lastModel?: string | null;
+  lastError?: string | null;
+  maxRequestsPerMinute?: number | null;
+}
+
+export interface GatewayRequestEvent {
+  sessionId: SessionId;
+  route: "openai" | "anthropic";
+  status: number;
+  latencyMs: number;
+  inputTokens: number;
+  cachedInputTokens: number;
+  outputTokens: number;
+  model?: string | null;
+  error?: string | null;
+  timestamp: string;
+}
+
+// -----------------------------------------------------------------------------
+// Secrets store
+// -----------------------------------------------------------------------------
+
+export interface SecretEntry {
+  name: string;
+  masked: string;
+  /** Value to use in MCP server env, e.g. "synk-secret:github". */
+  reference: string;
+}
+
 // -----------------------------------------------------------------------------
 // Git (Phase 3A)
 // -----------------------------------------------------------------------------
@@ -213,6 +345,7 @@ export interface OrphanWorktree {
 export interface GitCleanupOrphansResponse {
   removed: string[];
   failed: string[];
+  deletedBranches: string[];
 }
 
 export type GitEventType =
@@ -228,6 +361,7 @@ export interface GitEvent {
   timestamp: string; // RFC3339
   projectPath: string;
   sessionId?: number | null;
+  sessionLabels?: SessionLabels | null;
   branch?: string | null;
   hash?: string | null;
   message?: string | null;
@@ -237,6 +371,50 @@ export interface GitEvent {
   conflictFiles?: string[] | null;
 }
 
+export interface RecordedEvent<T = unknown> {
+  seq: number;
+  topic: string;
+  timestamp: string; // RFC3339
+  payload: T;
+}
+
+export interface EventsSinceResult {
+  events: RecordedEvent[];
+  latestSeq: number;
+  /** Some matching events after the requested seq were already dropped from the buffer. */
+  truncated: boolean;
+}
+
+export type AuditActor =
+  | { kind: "user" }
+  | { kind: "session"; sessionId: number }
+  | { kind: "rule"; name: string }
+  | { kind: "scheduler"; name: string }
+  | { kind: "remote"; addr: string };
+
+export interface AuditEntry {
+  timestamp: string; // RFC3339
+  action: string;
+  actor: AuditActor;
+  projectPath?: string;
+  args: unknown;
+  ok: boolean;
+  result?: unknown;
+  error?: string;
+}
+
+export interface AuditQuery {
+  /** Omit for the app-wide log (settings changes, broadcasts). */
+  projectPath?: string | null;
+  action?: string | null;
+  actor?: AuditActor["kind"] | null;
+  sessionId?: number | null;
+  since?: string | null;
+  /** Substring of args/result/error, e.g. a branch name. */
+  text?: string | null;
+  limit?: number | null;
+}
+
 export type FileDiffStatus = "added" | "modified" | "deleted" | "renamed";
 export type DiffLineType = "context" | "addition" | "deletion";
 
@@ -264,6 +442,105 @@ export interface FileDiff {
 export interface GitMergeResult {
   success: boolean;
   conflictFiles?: string[] | null;
+  /** Set when the conflicted merge was kept in progress for interactive resolution. */
+  integration?: Integration | null;
+}
+
+/** A merge in progress in its own worktree. "Ours" is the base branch, "theirs" the merged branch. */
+export interface Integration {
+  id: string;
+  projectPath: string;
+  branch: string;
+  baseBranch: string;
+  strategy: MergeStrategy;
+  baseCommit: string;
+  worktreePath: string;
+  conflictFiles: string[];
+  createdAt: string;
+}
+
+export interface ConflictHunk {
+  index: number;
+  startLine: number;
+  ours: string;
+  base: string | null;
+  theirs: string;
+}
+
+export interface ConflictFile {
+  path: string;
+  base: string | null;
+  ours: string | null;
+  theirs: string | null;
+  binary: boolean;
+  hunks: ConflictHunk[];
+}
+
+export interface IntegrationDetail {
+  integration: Integration;
+  conflicts: ConflictFile[];
+  resolved: string[];
+}
+
+export type Resolution =
+  | { kind: "ours" }
+  | { kind: "theirs" }
+  | { kind: "both" }
+  | { kind: "edited"; content: string };
+
+export type GitRefKind = "head" | "branch" | "remote" | "tag" | "other";
+
+export interface GitRef {
+  /** Short name, e.g. `main`, `origin/main`, `v1.2`. */
+  name: string;
+  fullName: string;
+  kind: GitRefKind;
+}
+
+/** Columns are 0-based; lines run from one row to the next. */
+export interface GraphRow {
+  lane: number;
+  /** Columns whose line from the row above ends at this commit. */
+  incoming: number[];
+  /** Column each parent continues in below this row, in parent order. */
+  parentLanes: number[];
+  passthrough: number[];
+  width: number;
+}
+
+export interface CommitNode {
+  hash: string;
+  parents: string[];
+  authorName: string;
+  authorEmail: string;
+  authorDate: string;
+  commitDate: string;
+  subject: string;
+  refs: GitRef[];
+  graph: GraphRow;
+}
+
+export interface CommitLogPage {
+  commits: CommitNode[];
+  /** Pass back to continue the graph; null at the end of history. */
+  nextCursor?: string | null;
+}
+
+export type CommitFileStatus = "added" | "modified" | "deleted" | "renamed" | "copied" | "type_changed";
+
+export interface CommitFileChange {
+  path: string;
+  status: CommitFileStatus;
+  oldPath?: string;
+  similarity?: number;
+  /** Null for binary files. */
+  additions: number | null;
+  deletions: number | null;
+}
+
+export interface CommitRefs {
+  at: GitRef[];
+  containing: GitRef[];
 }
 
 export type ReviewStatus =
@@ -272,9 +549,11 @@ export type ReviewStatus =
   | "approved"
   | "rejected"
   | "changes_requested"
+  | "queued"
   | "merging"
   | "merged"
-  | "merge_conflict";
+  | "merge_conflict"
+  | "check_failed";
 
 export type ReviewDecision = "approved" | "rejected" | "changes_requested";
 
@@ -292,6 +571,7 @@ export interface ReviewItem {
   id: string;
   taskId?: string | null;
   sessionId: number;
+  sessionLabels?: SessionLabels | null;
   branch: string;
   baseBranch: string;
   status: ReviewStatus;
@@ -304,6 +584,109 @@ export interface ReviewItem {
   comments: ReviewComment[];
   reviewDecision?: ReviewDecision | null;
   mergeStrategy?: MergeStrategy | null;
+  /** Predicted merge outcome; the backend recomputes it when either branch moves. */
+  mergePreview?: MergePreview | null;
+}
+
+export interface MergePreview {
+  baseCommit: string;
+  branchCommit: string;
+  mergeable: boolean;
+  conflictFiles: string[];
+  filesChanged: number;
+  additions: number;
+  deletions: number;
+}
+
+export interface QueueEntry {
+  reviewId: string;
+  branch: string;
+  baseBranch: string;
+  strategy: MergeStrategy;
+  state: "queued" | "running";
+  enqueuedAt: string;
+}
+
+export interface QueueFailure {
+  reviewId: string;
+  branch: string;
+  reason: string;
+  conflictFiles?: string[];
+  at: string;
+}
+
+export interface MergeQueue {
+  entries: QueueEntry[];
+  /** Set after a failure; the rest of the queue waits until it is resumed. */
+  paused: boolean;
+  lastFailure?: QueueFailure | null;
+}
+
+export type MergeQueueEventKind = "enqueued" | "removed" | "started" | "merged" | "failed" | "paused" | "resumed";
+
+export interface MergeQueueEvent {
+  kind: MergeQueueEventKind;
+  projectPath: string;
+  reviewId?: string | null;
+  branch?: string | null;
+  commit?: string | null;
+  reason?: string | null;
+  queued: number;
+  timestamp: string;
+}
+
+export interface AttemptSpec {
+  agentType: AgentType;
+  model?: string | null;
+  codexProvider?: CodexProvider | null;
+  providerProfile?: string | null;
+}
+
+export interface Attempt {
+  index: number;
+  sessionId: SessionId;
+  paneIndex: number;
+  agentType: AgentType;
+  model?: string;
+  branch: string;
+  worktreePath: string;
+  warning?: string;
+}
+
+export interface AttemptGroup {
+  id: string;
+  projectPath: string;
+  prompt: string;
+  baseBranch: string;
+  baseCommit: string;
+  createdAt: string;
+  attempts: Attempt[];
+  winner?: number;
+  reviewId?: string;
+}
+
+export interface AttemptResult {
+  index: number;
+  sessionId: SessionId;
+  agentType: AgentType;
+  model?: string;
+  branch: string;
+  filesChanged: number;
+  additions: number;
+  deletions: number;
+  files: string[];
+  /** Counted above, but not committed to `branch`, so a review of it won't include them. */
+  uncommitted: boolean;
+  cost?: SessionCostSnapshot;
+  error?: string;
+}
+
+export interface AttemptComparison {
+  groupId: string;
+  baseCommit: string;
+  attempts: AttemptResult[];
+  unionFiles: string[];
+  commonFiles: string[];
 }
 
 export type SessionSnapshotKind = "named" | "autosave";
@@ -313,7 +696,7 @@ export interface GridLayoutSnapshot {
   layout: string; // e.g. "2x2"
 }
 
-export interface SessionPaneSnapshot {
+export interface SessionPaneSnapshot extends SessionLabels {
   paneIndex: number;
   agentType: AgentType;
   branch?: string | null;
@@ -367,6 +750,16 @@ export interface OllamaSettings {
   defaultModel: string;
 }
 
+export interface ProviderProfileSettings {
+  id: string;
+  name: string;
+  agentType: AgentType;
+  baseUrl: string;
+  apiKey?: string | null;
+  apiKeyEnv?: string | null;
+  model?: string | null;
+}
+
 export interface AiProvidersSettings {
   default: AiProviderId;
   anthropic: ProviderAuthSettings;
@@ -374,6 +767,7 @@ export interface AiProvidersSettings {
   openai: ProviderAuthSettings;
   openrouter: ProviderAuthSettings;
   ollama: OllamaSettings;
+  profiles: ProviderProfileSettings[];
 }
 
 export interface PerformanceSettings {
@@ -415,6 +809,30 @@ export interface NotificationsSettings {
   durationMs: number;
 }
 
+export type NotificationKind =
+  | "task_completed"
+  | "agent_error"
+  | "merge_conflict"
+  | "review_ready"
+  | "cost_threshold";
+
+/** A `notification:new` payload / history entry. Named to avoid the DOM `Notification`. */
+export interface AppNotification {
+  id: number;
+  kind: NotificationKind;
+  title: string;
+  body: string;
+  timestamp: string; // RFC3339
+  projectPath?: string;
+  sessionId?: number;
+  read: boolean;
+}
+
+export interface NotificationList {
+  items: AppNotification[]; // newest first
+  unread: number;
+}
+
 export type MergeStrategy = "merge" | "squash" | "rebase";
 
 export interface GitSettings {
@@ -422,6 +840,11 @@ export interface GitSettings {
   autoDelegateConflicts: boolean;
   worktreeBasePath: string;
   branchPrefix: string;
+  checkpointOnIdle: boolean;
+  interactiveConflicts: boolean;
+  /** Run in the merged tree before the merge queue advances the base branch. */
+  queueCheckCommand?: string | null;
+  queueCheckTimeoutSecs: number;
 }
 
 export interface SessionSettings {
@@ -435,6 +858,64 @@ export interface GastownSettings {
   pinnedVersion: string;
 }
 
+export interface AgentsSettings {
+  claudeCodePath?: string | null;
+  geminiCliPath?: string | null;
+  codexPath?: string | null;
+}
+
+export interface GatewaySettings {
+  enabled: boolean;
+  port: number;
+  maxRequestsPerMinute: number;
+}
+
+export interface RemoteApiSettings {
+  enabled: boolean;
+  port: number;
+  bindLan: boolean;
+}
+
+export interface RemoteApiStatus {
+  running: boolean;
+  port?: number | null;
+  bindLan: boolean;
+  url?: string | null;
+  token: string;
+  clients: number;
+}
+
+export type HookEvent =
+  | "review_ready"
+  | "merge_completed"
+  | "conflict_detected"
+  | "session_crashed"
+  | "budget_exceeded";
+
+/** Set exactly one of `url` (JSON POST) or `command` (payload on stdin). */
+export interface HookSettings {
+  id: string;
+  name: string;
+  enabled: boolean;
+  events: HookEvent[];
+  url?: string | null;
+  command?: string | null;
+  maxAttempts: number;
+}
+
+export interface HookDelivery {
+  timestamp: string;
+  hookId: string;
+  hookName: string;
+  event: HookEvent;
+  target: string;
+  attempts: number;
+  ok: boolean;
+  status?: number;
+  error?: string;
+  durationMs: number;
+}
+
 export interface AppSettings {
   version: number;
   aiProviders: AiProvidersSettings;
@@ -445,6 +926,10 @@ export interface AppSettings {
   git: GitSettings;
   session: SessionSettings;
   gastown: GastownSettings;
+  gateway: GatewaySettings;
+  remoteApi: RemoteApiSettings;
+  agents: AgentsSettings;
+  hooks: HookSettings[];
 }
 
 export interface ProviderKeyValidationResult {
@@ -460,6 +945,14 @@ export interface ProviderModelsResult {
   statusCode?: number | null;
 }
 
+export interface ProviderProfileValidationResult {
+  ok: boolean;
+  message: string;
+  key?: ProviderKeyValidationResult | null;
+  models: ProviderModelsResult;
+  modelAvailable?: boolean | null;
+}
+
 export interface OllamaPullResult {
   ok: boolean;
   model: string;
@@ -477,3 +970,41 @@ export interface OnboardingScanResult {
   gastownWorkspacePath: string;
   gastownWorkspaceFound: boolean;
 }
+
+// -----------------------------------------------------------------------------
+// Command bar (Task 6A.1)
+// -----------------------------------------------------------------------------
+
+export type CompletionKind =
+  | "command"
+  | "agent"
+  | "branch"
+  | "target"
+  | "strategy"
+  | "keyword"
+  | "snapshot"
+  | "localhost";
+
+export interface Completion {
+  value: string;
+  kind: CompletionKind;
+  detail?: string;
+}
+
+export interface CompletionResult {
+  /** Offset in the input where the completed word starts. */
+  replaceFrom: number;
+  items: Completion[];
+}
+
+export type CommandOutcome =
+  | { kind: "session_created"; session: SessionCreateResponse }
+  | { kind: "sent"; result: BroadcastResult }
+  | {
+      kind: "closed";
+      result: { sessionIds: SessionId[]; failed: { sessionId: SessionId; error: string }[] };
+    }
+  | { kind: "merged"; result: GitMergeResult }
+  | { kind: "snapshot_saved"; snapshot: SessionSnapshotMeta }
+  | { kind: "snapshots"; snapshots: SessionSnapshotMeta[] }
+  | { kind: "localhost"; session: LocalhostSessionView };